@@ -17,12 +17,16 @@ use toml::Value;
 //
 // # Default: "data.json"
 // data_file = "dev-data.json"
+//
+// # Default: "style.css"
+// style_file = "dev-style.css"
 
 #[derive(Debug)]
 struct ResourceConfig {
     log_file: String,
     settings_file: String,
     data_file: String,
+    style_file: String,
 }
 
 impl Default for ResourceConfig {
@@ -31,6 +35,7 @@ impl Default for ResourceConfig {
             log_file: "log.toml".to_string(),
             settings_file: "settings.json".to_string(),
             data_file: "data.json".to_string(),
+            style_file: "style.css".to_string(),
         }
     }
 }
@@ -44,6 +49,7 @@ fn main() {
     println!("cargo:rustc-env=RESOURCE_LOG_FILE={}", config.log_file);
     println!("cargo:rustc-env=RESOURCE_SETTINGS_FILE={}", config.settings_file);
     println!("cargo:rustc-env=RESOURCE_DATA_FILE={}", config.data_file);
+    println!("cargo:rustc-env=RESOURCE_STYLE_FILE={}", config.style_file);
 }
 
 fn load_resource_config() -> ResourceConfig {
@@ -92,5 +98,9 @@ fn load_resource_config() -> ResourceConfig {
         config.data_file = data_file.to_string();
     }
 
+    if let Some(style_file) = resources.get("style_file").and_then(|v| v.as_str()) {
+        config.style_file = style_file.to_string();
+    }
+
     config
 }
\ No newline at end of file
@@ -0,0 +1,87 @@
+/// Desktop appearance (dark or light mode), read from the freedesktop settings portal
+
+use anyhow::Result;
+use gtk4::{gio, glib, prelude::*};
+
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const SETTINGS_INTERFACE: &str = "org.freedesktop.portal.Settings";
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+const PORTAL_TIMEOUT_MS: i32 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSchemePreference {
+    NoPreference,
+    Dark,
+    Light,
+}
+
+impl ColorSchemePreference {
+    /// Value of the portal `color-scheme` setting: 1 prefers dark, 2 prefers light
+    pub fn from_portal_value(value: u32) -> Self {
+        match value {
+            1 => ColorSchemePreference::Dark,
+            2 => ColorSchemePreference::Light,
+            _ => ColorSchemePreference::NoPreference,
+        }
+    }
+
+    /// GTK_THEME with a variant, e.g. "Adwaita:dark"
+    pub fn from_gtk_theme(theme: &str) -> Self {
+        match theme.rsplit_once(':').map(|(_, variant)| variant.to_lowercase()) {
+            Some(variant) if variant == "dark" => ColorSchemePreference::Dark,
+            Some(variant) if variant == "light" => ColorSchemePreference::Light,
+            _ => ColorSchemePreference::NoPreference,
+        }
+    }
+}
+
+/// Current dark/light preference of the desktop, GTK_THEME is used when the portal is not available
+pub fn color_scheme_preference() -> ColorSchemePreference {
+    match read_portal_color_scheme() {
+        Ok(value) => ColorSchemePreference::from_portal_value(value),
+        Err(e) => {
+            log::debug!("Appearance portal not available: {}", e);
+            std::env::var("GTK_THEME")
+                .map(|theme| ColorSchemePreference::from_gtk_theme(&theme))
+                .unwrap_or(ColorSchemePreference::NoPreference)
+        }
+    }
+}
+
+fn read_portal_color_scheme() -> Result<u32> {
+    let connection = gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE)?;
+    let parameters = (APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY).to_variant();
+
+    // ReadOne (portal version 2) returns the value, the deprecated Read wraps it in another variant
+    let reply = ["ReadOne", "Read"].iter()
+        .map(|method| connection.call_sync(
+            Some(PORTAL_BUS_NAME), PORTAL_OBJECT_PATH, SETTINGS_INTERFACE, method,
+            Some(&parameters), None, gio::DBusCallFlags::NONE, PORTAL_TIMEOUT_MS, gio::Cancellable::NONE,
+        ))
+        .find_map(Result::ok)
+        .ok_or_else(|| anyhow::anyhow!("Failed to read {} {}", APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY))?;
+
+    let mut value: glib::Variant = reply.child_value(0);
+    while let Some(inner) = value.as_variant() {
+        value = inner;
+    }
+    value.get::<u32>()
+        .ok_or_else(|| anyhow::anyhow!("Unexpected {} value: {}", COLOR_SCHEME_KEY, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_scheme_preference() {
+        assert_eq!(ColorSchemePreference::from_portal_value(0), ColorSchemePreference::NoPreference);
+        assert_eq!(ColorSchemePreference::from_portal_value(1), ColorSchemePreference::Dark);
+        assert_eq!(ColorSchemePreference::from_portal_value(2), ColorSchemePreference::Light);
+
+        assert_eq!(ColorSchemePreference::from_gtk_theme("Adwaita:dark"), ColorSchemePreference::Dark);
+        assert_eq!(ColorSchemePreference::from_gtk_theme("Adwaita"), ColorSchemePreference::NoPreference);
+    }
+}
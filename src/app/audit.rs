@@ -0,0 +1,99 @@
+/// Append-only audit log of executed actions as JSON lines, to reconstruct what HotKeys typed
+/// into a window. Unlike the json_log records it includes the action payloads, except of sensitive pads
+
+use crate::core::{Action, ModifierState};
+use anyhow::Result;
+use serde::Serialize;
+use super::json_log;
+
+/// Record of one executed action
+#[derive(Serialize, Debug)]
+struct ActionRecord<'a> {
+    timestamp: String,
+    profile: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    board: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pad: Option<u8>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    modifiers: String,
+    action: Action,
+    result: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Audit log of the actions of one pad, or of the actions given to `exec` and `batch`
+#[derive(Clone, Debug)]
+pub struct AuditLog {
+    path: String,
+    profile: String,
+    pad: Option<(String, u8, ModifierState)>,
+    redact: bool,
+}
+
+impl AuditLog {
+    pub fn new(path: &str, profile: &str) -> Self {
+        Self { path: path.to_string(), profile: profile.to_string(), pad: None, redact: false }
+    }
+
+    /// Pad whose actions are executed
+    pub fn with_pad(mut self, board: &str, pad: u8, modifiers: &ModifierState) -> Self {
+        self.pad = Some((board.to_string(), pad, modifiers.clone()));
+        self
+    }
+
+    /// Record only the length of the typed texts, commands and URLs
+    pub fn with_redact(mut self, redact: bool) -> Self {
+        self.redact = redact;
+        self
+    }
+
+    fn action_record(&self, action: &Action, result: &Result<()>) -> ActionRecord<'_> {
+        let mut action = action.clone();
+        if let Some(text) = action.text_mut().filter(|_| self.redact) {
+            *text = format!("<{} characters>", text.chars().count());
+        }
+        ActionRecord {
+            timestamp: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+            profile: &self.profile,
+            board: self.pad.as_ref().map(|(board, _, _)| board.as_str()),
+            pad: self.pad.as_ref().map(|(_, pad, _)| *pad),
+            modifiers: self.pad.as_ref().map(|(_, _, modifiers)| modifiers.to_string()).unwrap_or_default(),
+            action,
+            result: if result.is_ok() { "ok" } else { "error" },
+            error: result.as_ref().err().map(|e| e.to_string()),
+        }
+    }
+
+    /// Append the executed action, failures are only logged
+    pub fn record(&self, action: &Action, result: &Result<()>) {
+        if let Err(e) = json_log::write(&self.path, &self.action_record(action, result)) {
+            log::warn!("Failed to write audit log record to {}: {}", self.path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_records() {
+        let ctrl = ModifierState { ctrl: true, ..Default::default() };
+        let audit = AuditLog::new("audit.jsonl", "default").with_pad("code", 5, &ctrl);
+        let record = serde_json::to_value(audit.action_record(&Action::Line("git push".to_string()), &Ok(()))).unwrap();
+        assert_eq!(record["board"], "code");
+        assert_eq!(record["pad"], 5);
+        assert_eq!(record["modifiers"], "Ctrl");
+        assert_eq!(record["action"]["Line"], "git push");
+        assert_eq!(record["result"], "ok");
+
+        let audit = AuditLog::new("audit.jsonl", "default").with_redact(true);
+        let record = serde_json::to_value(audit.action_record(&Action::Text("hunter2".to_string()), &Err(anyhow::anyhow!("no uinput")))).unwrap();
+        assert!(record.get("board").is_none() && record.get("pad").is_none());
+        assert_eq!(record["action"]["Text"], "<7 characters>");
+        assert_eq!(record["result"], "error");
+        assert_eq!(record["error"], "no uinput");
+    }
+}
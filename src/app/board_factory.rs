@@ -1,9 +1,13 @@
 use std::{collections::HashMap, sync::{Arc, Mutex}};
 use anyhow::Result;
 
-use crate::core::{Board, PadSet, ColorScheme, TextStyle, DataRepository, Pad};
+use crate::core::{Board, PadSet, ColorScheme, TextStyle, DataRepository, ModifierState, Pad, repository::unix_now};
 use crate::components::boards::{StaticBoard, HomeBoard};
+use super::appearance::{self, ColorSchemePreference};
 use super::config::{AppSettings, BoardConfig, BoardKind, PadConfig};
+use super::usage::UsageStats;
+use super::counters;
+use super::dynamic_text;
 
 pub struct BoardFactory {
     settings: AppSettings,
@@ -26,19 +30,81 @@ impl BoardFactory {
         self
     }
 
-    pub fn create_board(&self, board_config: &BoardConfig) -> Result<Box<dyn Board>> {
+    pub fn create_board(&self, board_config: &BoardConfig) -> Result<Arc<dyn Board>> {
         let color_scheme = self.resolve_color_scheme(board_config);
         let text_style = self.resolve_text_style(board_config);
 
         match board_config.kind {
-            BoardKind::Static => Ok(Box::new(self.create_static_board(board_config, color_scheme, text_style)?)),
+            BoardKind::Static => Ok(Arc::new(self.create_static_board(board_config, color_scheme, text_style)?)),
             BoardKind::Home => {
                 let Some(ref repo) = self.repository else {
                     return Err(anyhow::anyhow!("Repository required for Home board"));
                 };
-                Ok(Box::new(HomeBoard::new(color_scheme, text_style, self.profile.clone(), repo.clone(), self.settings.file_path().to_string())))
+                Ok(Arc::new(HomeBoard::new(color_scheme, text_style, self.profile.clone(), repo.clone(), self.settings.file_path().to_string())))
+            },
+            BoardKind::Favorites => Ok(Arc::new(self.create_favorites_board(board_config, color_scheme, text_style)?)),
+        }
+    }
+
+    /// Pad `pad_id` (1-9) of a padset, resolved as a pad of the board
+    pub fn create_pad(&self, board_name: &str, padset_name: &str, pad_id: usize) -> Result<Pad> {
+        let pad_config = self.settings.get_padset_config(padset_name)
+            .ok_or_else(|| anyhow::anyhow!("PadSet '{}' not found", padset_name))?
+            .items.get(pad_id.wrapping_sub(1))
+            .ok_or_else(|| anyhow::anyhow!("Pad {} not found in PadSet '{}'", pad_id, padset_name))?;
+        Ok(self.resolve_pad(pad_config, self.command_text(board_name, pad_config)))
+    }
+
+    /// Static board with the nine most used pads of the static boards, in the order of their usage
+    fn create_favorites_board(
+        &self,
+        board_config: &BoardConfig,
+        color_scheme: ColorScheme,
+        text_style: TextStyle,
+    ) -> Result<StaticBoard> {
+        let Some(ref repo) = self.repository else {
+            return Err(anyhow::anyhow!("Repository required for Favorites board"));
+        };
+        let stats = {
+            let repo = repo.lock().map_err(|e| anyhow::anyhow!("Failed to acquire repository lock: {}", e))?;
+            UsageStats::load(&*repo, &self.profile)
+        };
+
+        let mut boards: HashMap<&str, StaticBoard> = HashMap::new();
+        let mut pads: Vec<Pad> = Vec::new();
+        for usage in stats.ranked(unix_now()) {
+            if pads.len() == 9 {
+                break;
+            }
+            // Pads of favorites boards are copies, only the pads of static boards are ranked
+            let Some(config) = self.settings.board_configs.iter().find(|config| config.name == usage.board && config.kind.is_static()) else {
+                continue;
+            };
+            if !boards.contains_key(config.name.as_str()) {
+                let board = self.create_static_board(config, self.resolve_color_scheme(config), self.resolve_text_style(config))?;
+                boards.insert(&config.name, board);
+            }
+            let board = &boards[config.name.as_str()];
+            let pad = board.pads(Some(&usage.modifiers)).get((usage.pad as usize).wrapping_sub(1))
+                .filter(|pad| !pad.actions.is_empty())
+                .cloned();
+            if let Some(mut pad) = pad {
+                if pad.header.is_empty() {
+                    pad.header = board.title().to_string();
+                }
+                pads.push(pad);
             }
         }
+        pads.resize_with(9, Pad::default);
+
+        Ok(StaticBoard::new(
+            board_config.title.clone().unwrap_or_else(|| board_config.name.clone()),
+            board_config.icon.clone(),
+            color_scheme,
+            text_style,
+            Arc::new(pads),
+            HashMap::new(),
+        ).with_background_image(board_config.background_image.clone()))
     }
 
     fn create_static_board(
@@ -49,6 +115,9 @@ impl BoardFactory {
     ) -> Result<StaticBoard> {
         let base_pads = self.resolve_base_pads(board_config)?;
         let modifier_pads = self.resolve_modifier_pads(board_config)?;
+        let double_pads = board_config.double_pads.as_deref()
+            .map(|padset_name| self.resolve_padset(board_config, padset_name))
+            .transpose()?;
 
         Ok(StaticBoard::new(
             board_config.title.clone().unwrap_or_else(|| board_config.name.clone()), // if there is no 'title', use 'name' for title instead
@@ -57,7 +126,7 @@ impl BoardFactory {
             text_style,
             base_pads,
             modifier_pads,
-        ))
+        ).with_background_image(board_config.background_image.clone()).with_double_pads(double_pads))
     }
 
     fn resolve_color_scheme(&self, board_config: &BoardConfig) -> ColorScheme {
@@ -66,7 +135,17 @@ impl BoardFactory {
                 .cloned()
                 .unwrap_or_default();
 
-        match &board_config.color_scheme {
+        // dark/light variants follow the desktop appearance, the portal is only asked when a board has them
+        let themed_scheme = match (&board_config.color_scheme_dark, &board_config.color_scheme_light) {
+            (None, None) => None,
+            (dark, light) => match appearance::color_scheme_preference() {
+                ColorSchemePreference::Dark => dark.as_ref(),
+                ColorSchemePreference::Light => light.as_ref(),
+                ColorSchemePreference::NoPreference => None,
+            },
+        };
+
+        match themed_scheme.or(board_config.color_scheme.as_ref()) {
             None => default_scheme,
             Some(scheme_name) => self.settings.get_color_scheme(scheme_name)
                 .cloned()
@@ -88,7 +167,14 @@ impl BoardFactory {
         }
     }
 
-    fn resolve_pad(&self, pad_config: &PadConfig) -> Pad {
+    /// Text of the `text_command` of a pad, None without a command or when it produced no text
+    fn command_text(&self, board_name: &str, pad_config: &PadConfig) -> Option<String> {
+        pad_config.text_command.as_ref()
+            .and_then(|command| dynamic_text::command_text(self.repository.as_deref(), &self.profile, board_name, command, pad_config.text_cache))
+    }
+
+    /// Resolve a pad, `command_text` is the output of its `text_command`, see [Self::command_text]
+    fn resolve_pad(&self, pad_config: &PadConfig, command_text: Option<String>) -> Pad {
         let pad_color_scheme = pad_config.color_scheme
             .as_ref()
             .and_then(|name| self.settings.get_color_scheme(name))
@@ -99,43 +185,65 @@ impl BoardFactory {
             .and_then(|name| self.settings.get_text_style(name))
             .cloned();
 
+        // Counter values are read when the board is created, a board shown again shows the new value
+        let count = pad_config.counter.as_ref().zip(self.repository.as_ref())
+            .and_then(|(counter, repo)| repo.lock().ok().map(|repo| counters::value(&*repo, &self.profile, counter)));
+        let show_count = |text: &String| match count {
+            Some(count) => counters::show_count(text, count),
+            None => text.clone(),
+        };
+
+        let text = command_text.unwrap_or_else(|| pad_config.text.clone());
+
         Pad {
-            header: pad_config.header.clone(),
-            text: pad_config.text.clone(),
+            header: show_count(&pad_config.header),
+            text: show_count(&text),
             icon: pad_config.icon.clone(),
             actions: pad_config.actions.clone(),
+            long_actions: pad_config.long_actions.clone(),
             board: pad_config.board.clone(),
             color_scheme: pad_color_scheme,
             text_style: pad_text_style,
+            opacity: pad_config.opacity,
+            fill_background: pad_config.fill_background,
+            icon_size: pad_config.icon_size,
+            icon_placement: pad_config.icon_placement,
+            svg_style: pad_config.svg_style.clone(),
+            counter: pad_config.counter.clone(),
+            sensitive: pad_config.sensitive,
         }
     }
 
-    fn resolve_base_pads(&self, board_config: &BoardConfig) -> Result<Box<dyn PadSet>> {
+    fn resolve_padset(&self, board_config: &BoardConfig, padset_name: &str) -> Result<Arc<dyn PadSet>> {
+        let padset_config = self.settings.get_padset_config(padset_name)
+            .ok_or_else(|| anyhow::anyhow!("PadSet '{}' not found", padset_name))?;
+        // Text commands of the padset run in parallel
+        let commands: Vec<Option<(&str, u64)>> = padset_config.items
+            .iter()
+            .map(|pad_config| pad_config.text_command.as_deref().map(|command| (command, pad_config.text_cache)))
+            .collect();
+        let texts = dynamic_text::command_texts(self.repository.as_deref(), &self.profile, &board_config.name, &commands);
+        let resolved_pads: Vec<Pad> = padset_config.items
+            .iter()
+            .zip(texts)
+            .map(|(pad_config, text)| self.resolve_pad(pad_config, text))
+            .collect();
+        Ok(Arc::new(resolved_pads))
+    }
+
+    fn resolve_base_pads(&self, board_config: &BoardConfig) -> Result<Arc<dyn PadSet>> {
         match &board_config.base_pads {
-            Some(padset_name) => {
-                let padset_config = self.settings.get_padset_config(padset_name)
-                    .ok_or_else(|| anyhow::anyhow!("PadSet '{}' not found", padset_name))?;
-                let resolved_pads: Vec<Pad> = padset_config.items
-                    .iter()
-                    .map(|pad_config| self.resolve_pad(pad_config))
-                    .collect();
-                Ok(Box::new(resolved_pads))
-            },
-            None => Ok(Box::new(Vec::new())),
+            Some(padset_name) => self.resolve_padset(board_config, padset_name),
+            None => Ok(Arc::new(Vec::new())),
         }
     }
 
-    fn resolve_modifier_pads(&self, board_config: &BoardConfig) -> Result<HashMap<String, Box<dyn PadSet>>> {
+    fn resolve_modifier_pads(&self, board_config: &BoardConfig) -> Result<HashMap<ModifierState, Arc<dyn PadSet>>> {
         let mut modifier_pads = HashMap::new();
 
-        for (modifier, padset_name) in &board_config.modifier_pads {
-            let padset_config = self.settings.get_padset_config(padset_name)
-                .ok_or_else(|| anyhow::anyhow!("PadSet '{}' not found", padset_name))?;
-            let resolved_pads: Vec<Pad> = padset_config.items
-                .iter()
-                .map(|pad_config| self.resolve_pad(pad_config))
-                .collect();
-            modifier_pads.insert(modifier.clone(), Box::new(resolved_pads) as Box<dyn PadSet>);
+        // Keys that bind nothing are reported by the validation
+        for (modifier, padset_name) in board_config.modifier_bindings() {
+            modifier_pads.insert(modifier, self.resolve_padset(board_config, padset_name)?);
         }
 
         Ok(modifier_pads)
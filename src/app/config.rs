@@ -1,10 +1,24 @@
-use std::{collections::{BTreeMap, HashMap, HashSet}, fs, path::PathBuf};
-use serde::{Deserialize, Serialize, Serializer};
+use std::{collections::{BTreeMap, HashMap, HashSet}, fs, path::{Path, PathBuf}, str::FromStr};
+use serde::{de::DeserializeOwned, Deserialize, Serialize, Serializer};
 use anyhow::Result;
-use crate::core::{ActionList, ColorScheme, TextStyle, Resources};
+use crate::core::{perf, Action, ActionList, BackgroundImage, ColorScheme, ColorTransform, CountdownStyle, GradientDirection, IconPlacement, ModifierState, TextStyle, IncludePattern, Resources, WindowPlacement};
+use crate::input::{evdev::KeyChord, keys::{ckey, xkb::{self, XkbNames}}, script, secret};
+use super::{interpolation, migration, settings_cache, validation::{Finding, ValidationReport}};
 
 const DEFAULT_SCHEME: &str = "default";
 const DEFAULT_KEYBOARD_LAYOUT: &str = "default";
+/// Profile default returning to the board shown last
+const LAST_USED_BOARD: &str = "last_used";
+/// Profiles the profile chooser shows, one per pad
+const MAX_CHOOSER_PROFILES: usize = 9;
+const DEFAULT_PASTE_SHORTCUT: &str = "Ctrl V";
+/// Settings without boards for the safe mode board, which stays open until closed
+const SAFE_MODE_SETTINGS: &str = r#"{
+    "timeout": 0, "feedback": 1, "delay": 100,
+    "color_schemes": [], "text_styles": [],
+    "keyboard_layout": "default", "keyboard_layouts": [],
+    "boards": [], "padsets": []
+}"#;
 const DEFAULT_OPACITY: f64 = 0.75;
 const DEFAULT_BACKGROUND: &str = "#00007f";
 const DEFAULT_FOREGROUND1: &str = "#5454a9";
@@ -16,6 +30,11 @@ const DEFAULT_FONT_PAD_TITLE: &str = "Consolas 9";
 const DEFAULT_FONT_PAD_DESCRIPTION: &str = "Arial Bold 12";
 const DEFAULT_FONT_PAD_ID: &str = "Impact Bold 10";
 
+/// For use with serde's [skip_serializing_if] attribute
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
 /// For use with serde's [serialize_with] attribute
 fn ordered_map<S, K: Ord + Serialize, V: Serialize>(
     value: &HashMap<K, V>,
@@ -34,7 +53,28 @@ pub struct KeyboardLayout {
     pub name: String,
     #[serde(default)]
     #[serde(serialize_with = "ordered_map")]
-    pub mappings: HashMap<String, String>
+    pub mappings: HashMap<String, String>,
+    /// xkb keymap used for characters without a mapping, detected from the desktop when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xkb: Option<XkbNames>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub unmapped_characters: UnmappedCharacters,
+    /// Shortcut pasting the clipboard when unmapped characters are pasted, "Ctrl V" by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paste_shortcut: Option<String>,
+}
+
+/// How Text and Line actions type characters the keyboard layout cannot produce
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnmappedCharacters {
+    /// Enter the code point with Ctrl+Shift+U (IBus and GTK unicode input)
+    #[default]
+    Unicode,
+    /// Leave the characters out
+    Skip,
+    /// Paste the whole text from the clipboard
+    Clipboard,
 }
 
 impl Default for KeyboardLayout {
@@ -42,10 +82,26 @@ impl Default for KeyboardLayout {
         Self {
             name: DEFAULT_KEYBOARD_LAYOUT.to_owned(),
             mappings: HashMap::new(),
+            xkb: None,
+            unmapped_characters: UnmappedCharacters::default(),
+            paste_shortcut: None,
         }
     }
 }
 
+impl KeyboardLayout {
+    /// Character key mapper for typing text with this layout
+    pub fn character_keys(&self) -> ckey::WithLayout {
+        ckey::with_layout(self.mappings.clone())
+            .with_xkb(xkb::keymap(self.xkb.as_ref()))
+            .with_unicode_fallback(self.unmapped_characters == UnmappedCharacters::Unicode)
+    }
+
+    pub fn paste_shortcut(&self) -> String {
+        self.paste_shortcut.clone().unwrap_or_else(|| DEFAULT_PASTE_SHORTCUT.to_owned())
+    }
+}
+
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -83,7 +139,9 @@ impl Detection {
 #[serde(rename_all = "lowercase")]
 pub enum BoardKind {
     Static,
-    Home
+    Home,
+    /// The most used pads of the other boards, ranked by the recorded usage
+    Favorites,
 }
 
 impl Default for BoardKind {
@@ -133,9 +191,21 @@ pub struct BoardConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub color_scheme: Option<String>,
 
+    /// Color scheme used instead of `color_scheme` while the desktop prefers dark mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_scheme_dark: Option<String>,
+
+    /// Color scheme used instead of `color_scheme` while the desktop prefers light mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_scheme_light: Option<String>,
+
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub text_style: Option<String>,
 
+    /// Image drawn behind the pads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_image: Option<BackgroundImage>,
+
     #[serde(default)]
     pub detection: Detection,
 
@@ -143,7 +213,49 @@ pub struct BoardConfig {
     pub base_pads: Option<String>,
 
     #[serde(default, skip_serializing_if = "HashMap::is_empty", serialize_with = "ordered_map")]
-    pub modifier_pads: HashMap<String, String>
+    pub modifier_pads: HashMap<String, String>,
+
+    /// Padset selected by tapping a number key twice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub double_pads: Option<String>,
+
+    /// Seconds between re-creating the pads while the board is shown, so dynamic texts stay current (0 never)
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub refresh: u64,
+
+    /// Hostnames the board is loaded on, all hosts when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only_on_hosts: Vec<String>,
+
+    /// Hostnames the board is left out on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub except_hosts: Vec<String>,
+}
+
+impl BoardConfig {
+    /// Name of the padset shown while the modifiers are held, the base pads without matching modifier pads
+    pub fn padset_name(&self, modifiers: &ModifierState) -> Option<&str> {
+        modifiers.find_binding(self.modifier_bindings())
+            .map(|(_, padset)| padset)
+            .or(self.base_pads.as_deref())
+    }
+
+    /// Modifier pads by their modifiers, in any order and case ("shift + ctrl" binds Ctrl+Shift).
+    /// Of keys naming the same modifiers the first in sorted order binds them, invalid keys bind nothing
+    pub fn modifier_bindings(&self) -> Vec<(ModifierState, &str)> {
+        let mut keys: Vec<&String> = self.modifier_pads.keys().collect();
+        keys.sort();
+
+        let mut bindings: Vec<(ModifierState, &str)> = Vec::new();
+        let parsed = keys.into_iter()
+            .filter_map(|key| key.parse::<ModifierState>().ok().map(|modifiers| (modifiers, self.modifier_pads[key].as_str())));
+        for (modifiers, padset) in parsed {
+            if !bindings.iter().any(|(bound, _)| *bound == modifiers) {
+                bindings.push((modifiers, padset));
+            }
+        }
+        bindings
+    }
 }
 
 /// Configuration-level pad structure (internal)
@@ -161,6 +273,10 @@ pub struct PadConfig {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub actions: Vec<crate::core::Action>,
 
+    /// Actions run instead of the actions when the pad key is held past the long press time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub long_actions: Vec<crate::core::Action>,
+
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub board: Option<String>,
 
@@ -169,6 +285,60 @@ pub struct PadConfig {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub text_style: Option<String>,
+
+    /// Opacity of the pad background, the color scheme opacity when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
+
+    /// Always paint the pad background, e.g. for highlighted pads
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fill_background: bool,
+
+    /// Icon size in pixels, overrides the text style icon size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_size: Option<f64>,
+
+    /// Icon placement relative to the text, overrides the text style icon placement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_placement: Option<IconPlacement>,
+
+    /// CSS for SVG icons of the pad, applied after the text style CSS
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub svg_style: String,
+
+    /// Counter incremented each time the pad is selected, its value replaces `{count}` in the header and text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counter: Option<String>,
+
+    /// Shell command whose output replaces the text when the board is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_command: Option<String>,
+
+    /// Seconds the output of the text command is reused for (0 runs the command every time)
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub text_cache: u64,
+
+    /// Never write the typed texts, commands and URLs of the pad to the log, even with privacy off
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sensitive: bool,
+}
+
+impl PadConfig {
+    /// Whether no field of the pad is set
+    pub fn is_empty(&self) -> bool {
+        serde_json::to_value(self).is_ok_and(|value| value.as_object().is_some_and(|fields| fields.is_empty()))
+    }
+
+    /// Action lists of the pad with their field names, the actions and the long press actions
+    pub fn action_lists(&self) -> [(&'static str, &Vec<Action>); 2] {
+        [("actions", &self.actions), ("long_actions", &self.long_actions)]
+    }
+
+    /// Actions of all action lists with their field names and indexes, e.g. `("long_actions", 0, action)`
+    pub fn indexed_actions(&self) -> impl Iterator<Item = (&'static str, usize, &Action)> {
+        self.action_lists().into_iter()
+            .flat_map(|(field, actions)| actions.iter().enumerate().map(move |(index, action)| (field, index, action)))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
@@ -180,6 +350,41 @@ pub struct PadSetConfig {
 
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub items: Vec<PadConfig>,
+
+    /// Padset whose pads are reused, see [AppSettings::resolve_padset_bases]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+
+    /// Pads replacing the base pads, by pad id (1-9)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: BTreeMap<usize, PadConfig>,
+}
+
+impl PadSetConfig {
+    /// Pad `id` (1-9) as written in the padset, the override of derived padsets
+    pub fn pad(&self, id: usize) -> PadConfig {
+        match self.base {
+            Some(_) => self.overrides.get(&id).cloned(),
+            None => self.items.get(id - 1).cloned(),
+        }.unwrap_or_default()
+    }
+
+    /// Replace pad `id` (1-9). Derived padsets keep empty pads as overrides clearing the pad of the base,
+    /// empty pads at the end of the items are left out
+    pub fn set_pad(&mut self, id: usize, pad: PadConfig) {
+        if self.base.is_some() {
+            self.overrides.insert(id, pad);
+            return;
+        }
+
+        if self.items.len() < id {
+            self.items.resize_with(id, PadConfig::default);
+        }
+        self.items[id - 1] = pad;
+        while self.items.last().is_some_and(PadConfig::is_empty) {
+            self.items.pop();
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -187,23 +392,159 @@ pub struct Profile {
     pub name: String,
     pub boards: Vec<String>,
     pub default: String,
+
+    /// Keyboard layout used while this profile is active, overrides the global `keyboard_layout`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyboard_layout: Option<String>,
+
+    /// Sounds played while this profile is active
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub sounds: SoundSettings,
+
+    /// Hostnames the profile is loaded on, all hosts when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only_on_hosts: Vec<String>,
+
+    /// Hostnames the profile is left out on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub except_hosts: Vec<String>,
+}
+
+impl Profile {
+    /// Whether the default board is the board shown last (`"default": "last_used"`),
+    /// the first board of the profile until a board was shown
+    pub fn default_is_last_used(&self) -> bool {
+        self.default == LAST_USED_BOARD
+    }
+}
+
+/// Sound effects: freedesktop sound theme names (e.g. "bell") or sound files under sounds/
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SoundSettings {
+    /// Played when a board is shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open: Option<String>,
+
+    /// Played when a pad is selected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select: Option<String>,
+
+    /// Played when a board closes because its timeout ran out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+}
+
+impl SoundSettings {
+    /// Configured sounds with their field names
+    pub fn sounds(&self) -> impl Iterator<Item = (&'static str, &String)> {
+        [("open", &self.open), ("select", &self.select), ("timeout", &self.timeout)].into_iter()
+            .filter_map(|(event, sound)| sound.as_ref().map(|sound| (event, sound)))
+    }
+
+    fn sounds_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        [&mut self.open, &mut self.select, &mut self.timeout].into_iter().flatten()
+    }
+}
+
+/// Trigger key chord that opens a board while running in daemon mode
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TriggerConfig {
+    pub keys: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board: Option<String>,
+}
+
+/// evdev listener used by daemon mode
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ListenerSettings {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<String>,
+
+    #[serde(default)]
+    pub triggers: Vec<TriggerConfig>,
+}
+
+/// How components of included files are combined with the ones loaded before them
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IncludeMode {
+    /// Components are appended, equal names are an error
+    #[default]
+    Append,
+    /// A component replaces an earlier one with the same name
+    Override,
+}
+
+/// What the 0 key does on the boards
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ZeroKey {
+    /// Nothing, the key is ignored
+    #[default]
+    None,
+    /// Show the board navigated from, the board stays when it is the first one
+    Back,
+    /// Show the default board of the profile
+    Home,
+    /// Select a pad of a padset (pad ids 1-9) as if it was a pad of the board
+    Pad { padset: String, pad: usize },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LayoutSettings {
     pub width: i32,
     pub height: i32,
-    pub window_style: String, // "Window" | "Taskbar"
+    pub window_style: String, // "Window" | "Taskbar" | "Overlay"
+    /// Fade the board window in and out over this many milliseconds, 0 shows and closes it at once
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub fade: u64,
+    /// Board window position on X11, left to the window manager when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<WindowPlacement>,
+    /// Keep the board window above other windows (X11)
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub keep_above: bool,
+    /// Leave the board window out of the taskbar and pager (X11)
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub skip_taskbar: bool,
+    /// Grab the keyboard while the board is open (X11)
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub grab_keyboard: bool,
+    /// Show the board without taking the focus, its keys are read from the keyboards (evdev)
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub no_focus: bool,
+    /// Latch a modifier tapped alone (pressed and released), for modifier pads without holding the modifier
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub sticky_modifiers: bool,
+    /// Milliseconds a pad key is held to run the long press actions of the pad instead of its actions, 500 when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub long_press: Option<u64>,
+    /// Milliseconds a second tap of a number key selects from the double tap pads, 300 when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub double_tap: Option<u64>,
 }
 
 /// Main application settings structure
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AppSettings {
+    #[serde(default)]
+    version: u64,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     includes: Vec<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    include_mode: IncludeMode,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    variables: BTreeMap<String, String>,
     timeout: u64,
     feedback: u64,
     delay: u64,
+    /// Milliseconds after a pad selection in which summoning a board again is ignored
+    #[serde(default, skip_serializing_if = "is_default")]
+    debounce: u64,
     color_schemes: Vec<ColorScheme>,
     text_styles: Vec<TextStyle>,
     keyboard_layout: String,
@@ -221,18 +562,60 @@ pub struct AppSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     layout: Option<LayoutSettings>,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    listener: Option<ListenerSettings>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    tray: bool,
+
+    /// Pick the profile on a board when no `--profile` is given, instead of using "default"
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    profile_chooser: bool,
+
+    /// Write nothing to disk: repository data is kept in memory and the settings are not cached
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    ephemeral: bool,
+
+    /// Keep typed texts, commands and URLs out of the log, on when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    privacy: Option<bool>,
+
+    /// File the pad executions are written to as JSON lines, "stderr" for the standard error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    json_log: Option<String>,
+
+    /// File every executed action is appended to as a JSON line, with its payload and result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    audit_log: Option<String>,
+
+    /// What the 0 key does on the boards
+    #[serde(default, skip_serializing_if = "is_default")]
+    zero_key: ZeroKey,
+
     #[serde(default, skip_serializing)]
     file_path: String,
+
+    /// Files loaded through includes
+    #[serde(skip)]
+    included_files: Vec<PathBuf>,
+
+    /// Included file of each component, by collection and name (components of the settings file are not listed)
+    #[serde(skip)]
+    origins: HashMap<(&'static str, String), PathBuf>,
 }
 
 impl Default for ColorScheme {
     fn default() -> Self {
         Self {
             name: DEFAULT_SCHEME.to_owned(),
-            opacity: DEFAULT_OPACITY,
+            base: None,
+            opacity: Some(DEFAULT_OPACITY),
             background: DEFAULT_BACKGROUND.to_owned(),
             foreground1: DEFAULT_FOREGROUND1.to_owned(),
             foreground2: DEFAULT_FOREGROUND2.to_owned(),
+            background2: String::new(),
+            gradient: GradientDirection::default(),
+            transform: ColorTransform::default(),
         }
     }
 }
@@ -246,6 +629,12 @@ impl Default for TextStyle {
             pad_header_font: DEFAULT_FONT_PAD_TITLE.to_string(),
             pad_text_font: DEFAULT_FONT_PAD_DESCRIPTION.to_string(),
             pad_id_font: DEFAULT_FONT_PAD_ID.to_string(),
+            shrink_to_fit: false,
+            icon_size: None,
+            icon_placement: None,
+            svg_style: String::new(),
+            countdown: CountdownStyle::default(),
+            layer_hints: false,
         }
     }
 }
@@ -254,7 +643,17 @@ impl AppSettings {
     pub fn timeout(&self) -> u64 { self.timeout }
     pub fn feedback(&self) -> u64 { self.feedback }
     pub fn delay(&self) -> u64 { self.delay }
+    pub fn debounce(&self) -> u64 { self.debounce }
     pub fn layout(&self) -> &Option<LayoutSettings> { &self.layout }
+    pub fn listener(&self) -> &Option<ListenerSettings> { &self.listener }
+    pub fn tray(&self) -> bool { self.tray }
+    /// Whether the profile is picked on a board when none is given, only with more than one profile
+    pub fn profile_chooser(&self) -> bool { self.profile_chooser && self.profiles.len() > 1 }
+    pub fn ephemeral(&self) -> bool { self.ephemeral }
+    pub fn privacy(&self) -> bool { self.privacy.unwrap_or(true) }
+    pub fn json_log(&self) -> Option<&str> { self.json_log.as_deref() }
+    pub fn audit_log(&self) -> Option<&str> { self.audit_log.as_deref() }
+    pub fn zero_key(&self) -> &ZeroKey { &self.zero_key }
 
     pub fn get_color_scheme(&self, name: &str) -> Option<&ColorScheme> {
         self.color_schemes.iter().find(|s| s.name == name)
@@ -265,8 +664,19 @@ impl AppSettings {
     }
 
     pub fn get_keyboard_layout(&self) -> KeyboardLayout {
-        let layout_name = self.keyboard_layout.clone();
+        self.find_keyboard_layout(&self.keyboard_layout)
+    }
 
+    /// Keyboard layout of the given profile, the global layout if the profile does not set one
+    pub fn get_profile_keyboard_layout(&self, profile: &str) -> KeyboardLayout {
+        self.profiles.iter()
+            .find(|p| p.name == profile)
+            .and_then(|p| p.keyboard_layout.as_deref())
+            .map(|layout_name| self.find_keyboard_layout(layout_name))
+            .unwrap_or_else(|| self.get_keyboard_layout())
+    }
+
+    fn find_keyboard_layout(&self, layout_name: &str) -> KeyboardLayout {
         self.keyboard_layouts.iter()
         .find(|l| l.name == layout_name)
         .cloned()
@@ -283,180 +693,641 @@ impl AppSettings {
         self.padset_configs.iter().find(|p| p.name == name)
     }
 
+    pub fn color_schemes(&self) -> &[ColorScheme] {
+        &self.color_schemes
+    }
+
+    pub fn text_styles(&self) -> &[TextStyle] {
+        &self.text_styles
+    }
+
+    /// File a component was loaded from, the settings file for its own components
+    pub fn component_file(&self, collection: &'static str, name: &str) -> PathBuf {
+        self.origins.get(&(collection, name.to_string()))
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from(&self.file_path))
+    }
+
     pub fn file_path(&self) -> &str {
         &self.file_path
     }
 
-    /// Validate the entire settings configuration
-    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
-        if self.board_configs.is_empty() {
-            return Err("No boards defined in settings".to_string());
+    /// Settings file and all included files the settings were loaded from
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        std::iter::once(PathBuf::from(&self.file_path))
+            .chain(self.included_files.iter().cloned())
+            .collect()
+    }
+
+    /// Files the include entries resolve to now, compared with [AppSettings::source_files] by the settings cache
+    pub(crate) fn resolve_include_files(&self, resources: &Resources) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for include in &self.includes {
+            files.extend(resolve_include(include, resources)?);
         }
+        Ok(files)
+    }
 
-        if self.profiles.is_empty() {
-            return Err("No profiles defined in settings".to_string());
+    /// Directories of the directory and pattern includes, with the pattern of the files they load,
+    /// watched by the daemon so files added to them are loaded
+    pub fn include_directories(&self, resources: &Resources) -> Vec<(PathBuf, IncludePattern)> {
+        self.includes.iter()
+            .flat_map(|include| resources.include_directories(&interpolation::expand_env(include, &mut Vec::new())))
+            .collect()
+    }
+
+    /// Restore the files a cached copy of the settings was loaded from, they are not serialized
+    pub(crate) fn with_source_files(mut self, files: &[PathBuf]) -> Self {
+        if let Some((settings_file, included_files)) = files.split_first() {
+            self.file_path = settings_file.to_string_lossy().to_string();
+            self.included_files = included_files.to_vec();
         }
+        self
+    }
 
-        self.validate_color_scheme_references()
-            .map_err(|e| format!("Color scheme validation failed: {}", e))?;
+    /// Validate the entire settings configuration, failing with all errors of the validation report.
+    /// Missing icons and unknown shortcut keys are only logged, the runtime degrades gracefully on them
+    pub fn validate(&self, resources: &Resources) -> Result<(), String> {
+        let report = self.validation_report(resources, ValidationReport::default());
+        for warning in report.warnings() {
+            log::warn!("{}", warning);
+        }
 
-        self.validate_text_style_references()
-            .map_err(|e| format!("Text style validation failed: {}", e))?;
+        let errors: Vec<String> = report.errors().map(Finding::to_string).collect();
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors.join("; ")),
+        }
+    }
 
-        self.validate_profile_board_references()
-            .map_err(|e| format!("Profile board validation failed: {}", e))?;
+    /// All errors and warnings of the settings, with the file and location they come from.
+    /// Pass [ValidationReport::strict] to report missing icons and unknown shortcut keys as errors
+    pub fn validation_report(&self, resources: &Resources, mut report: ValidationReport) -> ValidationReport {
+        let settings_file = PathBuf::from(&self.file_path);
 
-        self.validate_pad_references()
-            .map_err(|e| format!("Pad reference validation failed: {}", e))?;
+        if self.board_configs.is_empty() {
+            report.error(&settings_file, "boards", "No boards defined in settings");
+        }
+        if self.profiles.is_empty() {
+            report.error(&settings_file, "profiles", "No profiles defined in settings");
+        }
 
-        self.validate_cross_board_references()
-            .map_err(|e| format!("Cross board validation failed: {}", e))?;
+        self.validate_board_references(&mut report);
+        self.validate_modifier_pads(&mut report);
+        self.validate_profile_board_references(&mut report);
+        self.validate_profile_keyboard_layouts(&mut report);
+        self.validate_profile_chooser(&mut report);
+        self.validate_zero_key(&mut report);
+        self.validate_layout(&mut report);
+        self.validate_pads(&mut report);
+        self.validate_icons_availability(resources, &mut report);
+        self.validate_sounds_availability(resources, &mut report);
+        self.validate_action_targets(&mut report);
+        self.validate_shortcut_keys(&mut report);
+        self.validate_listener_triggers(&mut report);
+
+        report
+    }
 
-        self.validate_icons_availability(resources)
-            .map_err(|e| format!("Icon availability validation failed: {}", e))?;
+    /// Source file of a component and its location within that file, e.g. `boards[3]`
+    fn component_location<T>(&self, collection: &'static str, items: &[T], index: usize, name: fn(&T) -> &String) -> (PathBuf, String) {
+        let file = |item: &T| self.origins.get(&(collection, name(item).clone()))
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from(&self.file_path));
 
-        self.validate_action_order()
-            .map_err(|e| format!("Action order validation failed: {}", e))?;
+        let item_file = file(&items[index]);
+        let local_index = items[..index].iter().filter(|item| file(item) == item_file).count();
+        (item_file, format!("{}[{}]", collection, local_index))
+    }
 
-        Ok(())
+    /// Source file and location of a padset's pad, derived padsets point to the override or the base
+    fn pad_location(&self, padset_index: usize, pad_index: usize) -> (PathBuf, String) {
+        let padset = &self.padset_configs[padset_index];
+        let (file, location) = self.component_location("padsets", &self.padset_configs, padset_index, |p| &p.name);
+
+        let pad_location = match &padset.base {
+            Some(_) if padset.overrides.contains_key(&(pad_index + 1)) => format!("overrides.{}", pad_index + 1),
+            Some(_) => "base".to_string(),
+            None => format!("items[{}]", pad_index),
+        };
+        (file, format!("{}.{}", location, pad_location))
     }
 
-    fn validate_color_scheme_references(&self) -> Result<(), String> {
-        for board in &self.board_configs {
-            if let Some(scheme_name) = &board.color_scheme {
-                if self.get_color_scheme(scheme_name).is_none() {
-                    return Err(format!("Color scheme '{}' not found in settings", scheme_name));
+    fn validate_board_references(&self, report: &mut ValidationReport) {
+        for (index, board) in self.board_configs.iter().enumerate() {
+            let (file, location) = self.component_location("boards", &self.board_configs, index, |b| &b.name);
+
+            let color_schemes = [("color_scheme", &board.color_scheme), ("color_scheme_dark", &board.color_scheme_dark), ("color_scheme_light", &board.color_scheme_light)];
+            for (field, scheme_name) in color_schemes {
+                if let Some(scheme_name) = scheme_name.as_ref().filter(|name| self.get_color_scheme(name).is_none()) {
+                    report.error(&file, format!("{}.{}", location, field), format!("Color scheme '{}' not found in settings", scheme_name));
+                }
+            }
+            if let Some(text_style) = board.text_style.as_ref().filter(|name| self.get_text_style(name).is_none()) {
+                report.error(&file, format!("{}.text_style", location), format!("Text style '{}' not found in settings", text_style));
+            }
+            if let Some(padset_name) = board.base_pads.as_ref().filter(|name| self.get_padset_config(name).is_none()) {
+                report.error(&file, format!("{}.base_pads", location), format!("Base pad set '{}' not found for board '{}'", padset_name, board.name));
+            }
+            if let Some(padset_name) = board.double_pads.as_ref().filter(|name| self.get_padset_config(name).is_none()) {
+                report.error(&file, format!("{}.double_pads", location), format!("Double tap pad set '{}' not found for board '{}'", padset_name, board.name));
+            }
+
+            let mut modifier_pads: Vec<_> = board.modifier_pads.iter().collect();
+            modifier_pads.sort();
+            for (modifier, padset_name) in modifier_pads {
+                if self.get_padset_config(padset_name).is_none() {
+                    report.error(&file, format!("{}.modifier_pads.{}", location, modifier),
+                        format!("Modifier pad set '{}' not found for board '{}' with modifier '{}'", padset_name, board.name, modifier));
                 }
             }
         }
-        Ok(())
     }
 
-    fn validate_text_style_references(&self) -> Result<(), String> {
-        for board in &self.board_configs {
-            if let Some(text_style) = &board.text_style {
-                if self.get_text_style(text_style).is_none() {
-                    return Err(format!("Text style '{}' not found in settings", text_style));
-                }
+    /// Modifier pads keys that are never shown: invalid keys, and keys naming modifiers another key binds
+    fn validate_modifier_pads(&self, report: &mut ValidationReport) {
+        for (index, board) in self.board_configs.iter().enumerate() {
+            let (file, location) = self.component_location("boards", &self.board_configs, index, |b| &b.name);
+            let mut keys: Vec<&String> = board.modifier_pads.keys().collect();
+            keys.sort();
+
+            let mut bound: Vec<(ModifierState, &String)> = Vec::new();
+            for key in keys {
+                let message = match key.parse::<ModifierState>() {
+                    Err(e) => format!("Modifier pads '{}' of board '{}' are never shown: {}", key, board.name, e),
+                    Ok(modifiers) => match bound.iter().find(|(other, _)| *other == modifiers) {
+                        Some((_, other)) => format!("Modifier pads '{}' of board '{}' are never shown, '{}' binds the same modifiers", key, board.name, other),
+                        None => {
+                            bound.push((modifiers, key));
+                            continue;
+                        },
+                    },
+                };
+                report.warning(&file, format!("{}.modifier_pads.{}", location, key), message);
             }
         }
-        Ok(())
     }
 
-    fn validate_profile_board_references(&self) -> Result<(), String> {
-        for profile in &self.profiles {
-            for board_name in &profile.boards {
-                let found = self.board_configs.iter().any(|b| b.name == *board_name);
-                if !found {
-                    return Err(format!("Board '{}' not found in settings for profile '{}'", board_name, profile.name));
-                }
+    fn validate_profile_keyboard_layouts(&self, report: &mut ValidationReport) {
+        for (index, profile) in self.profiles.iter().enumerate() {
+            let unknown = profile.keyboard_layout.as_deref()
+                .filter(|name| *name != DEFAULT_KEYBOARD_LAYOUT && !self.keyboard_layouts.iter().any(|l| l.name == *name));
+            if let Some(name) = unknown {
+                let (file, location) = self.component_location("profiles", &self.profiles, index, |p| &p.name);
+                report.error(&file, format!("{}.keyboard_layout", location),
+                    format!("Keyboard layout '{}' not found in settings for profile '{}'", name, profile.name));
             }
-            let default_found = self.board_configs.iter().any(|b| b.name == profile.default);
-            if !default_found {
-                return Err(format!("Default board '{}' not found in settings for profile '{}'", profile.default, profile.name));
+        }
+    }
+
+    fn validate_profile_chooser(&self, report: &mut ValidationReport) {
+        if self.profile_chooser() && self.profiles.len() > MAX_CHOOSER_PROFILES {
+            report.warning(Path::new(&self.file_path), "profile_chooser",
+                format!("The profile chooser shows only the first {} of {} profiles", MAX_CHOOSER_PROFILES, self.profiles.len()));
+        }
+    }
+
+    fn validate_zero_key(&self, report: &mut ValidationReport) {
+        let ZeroKey::Pad { padset, pad } = &self.zero_key else {
+            return;
+        };
+        let file = Path::new(&self.file_path);
+        match self.get_padset_config(padset) {
+            None => report.error(file, "zero_key.pad.padset", format!("Pad set '{}' of the 0 key not found in settings", padset)),
+            Some(padset_config) if !(1..=padset_config.items.len()).contains(pad) =>
+                report.error(file, "zero_key.pad.pad", format!("Pad {} of the 0 key not found in pad set '{}'", pad, padset)),
+            Some(_) => {},
+        }
+    }
+
+    /// A long press or double tap time of 0 would select every pad key as a long press or a single tap at once
+    fn validate_layout(&self, report: &mut ValidationReport) {
+        let Some(layout) = &self.layout else {
+            return;
+        };
+        let file = Path::new(&self.file_path);
+        for (field, millis) in [("long_press", layout.long_press), ("double_tap", layout.double_tap)] {
+            if millis == Some(0) {
+                report.error(file, format!("layout.{}", field), format!("Invalid {} time 0, expected a positive number of milliseconds", field));
             }
         }
-        Ok(())
     }
 
-    fn validate_pad_references(&self) -> Result<(), String> {
-        for board in &self.board_configs {
-            if let Some(ref padset_name) = board.base_pads {
-                if self.get_padset_config(padset_name).is_none() {
-                    return Err(format!("Base pad set '{}' not found for board '{}'", padset_name, board.name));
+    fn validate_profile_board_references(&self, report: &mut ValidationReport) {
+        for (index, profile) in self.profiles.iter().enumerate() {
+            let (file, location) = self.component_location("profiles", &self.profiles, index, |p| &p.name);
+
+            for (board_index, board_name) in profile.boards.iter().enumerate() {
+                if !self.board_configs.iter().any(|b| b.name == *board_name) {
+                    report.error(&file, format!("{}.boards[{}]", location, board_index),
+                        format!("Board '{}' not found in settings for profile '{}'", board_name, profile.name));
                 }
             }
-
-            for (modifier, padset_name) in &board.modifier_pads {
-                if self.get_padset_config(padset_name).is_none() {
-                    return Err(format!("Modifier pad set '{}' not found for board '{}' with modifier '{}'", padset_name, board.name, modifier));
+            if profile.default_is_last_used() {
+                if profile.boards.is_empty() {
+                    report.error(&file, format!("{}.default", location),
+                        format!("Profile '{}' has no boards to start with until a board was shown", profile.name));
                 }
+            } else if !self.board_configs.iter().any(|b| b.name == profile.default) {
+                report.error(&file, format!("{}.default", location),
+                    format!("Default board '{}' not found in settings for profile '{}'", profile.default, profile.name));
             }
         }
-        Ok(())
     }
 
-    fn validate_cross_board_references(&self) -> Result<(), String> {
-        for padset in &self.padset_configs {
-            for pad in &padset.items {
-                if let Some(ref board_ref) = pad.board {
-                    let found = self.board_configs.iter().any(|b| b.name == *board_ref);
-                    if !found {
-                        return Err(format!("Invalid board reference '{}' in settings for pad '{:?}'", board_ref, pad));
-                    }
-                }
+    /// Board, color scheme and text style references and the action order of every pad
+    fn validate_pads(&self, report: &mut ValidationReport) {
+        for (padset_index, padset) in self.padset_configs.iter().enumerate() {
+            for (pad_index, pad) in padset.items.iter().enumerate() {
+                let (file, location) = self.pad_location(padset_index, pad_index);
 
-                // Validate pad-level color scheme references
-                if let Some(ref scheme_name) = pad.color_scheme {
-                    if self.get_color_scheme(scheme_name).is_none() {
-                        return Err(format!("Color scheme '{}' not found for pad '{:?}'", scheme_name, pad));
-                    }
+                if let Some(board_ref) = pad.board.as_ref().filter(|name| !self.board_configs.iter().any(|b| b.name == **name)) {
+                    report.error(&file, format!("{}.board", location), format!("Invalid board reference '{}'", board_ref));
+                }
+                if let Some(scheme_name) = pad.color_scheme.as_ref().filter(|name| self.get_color_scheme(name).is_none()) {
+                    report.error(&file, format!("{}.color_scheme", location), format!("Color scheme '{}' not found", scheme_name));
                 }
+                if let Some(style_name) = pad.text_style.as_ref().filter(|name| self.get_text_style(name).is_none()) {
+                    report.error(&file, format!("{}.text_style", location), format!("Text style '{}' not found", style_name));
+                }
+                if let Some(opacity) = pad.opacity.filter(|opacity| !(0.0..=1.0).contains(opacity)) {
+                    report.error(&file, format!("{}.opacity", location), format!("Invalid opacity {}, expected 0 to 1", opacity));
+                }
+                if let Some(size) = pad.icon_size.filter(|size| *size <= 0.0) {
+                    report.error(&file, format!("{}.icon_size", location), format!("Invalid icon size {}, expected a positive size", size));
+                }
+                for (field, _) in pad.action_lists().into_iter().filter(|(_, actions)| !actions.is_order_valid()) {
+                    report.error(&file, format!("{}.{}", location, field), format!("Invalid action order in padset '{}'", padset.name));
+                }
+            }
+        }
+    }
 
-                // Validate pad-level text style references
-                if let Some(ref style_name) = pad.text_style {
-                    if self.get_text_style(style_name).is_none() {
-                        return Err(format!("Text style '{}' not found for pad '{:?}'", style_name, pad));
-                    }
+    /// Missing icons are drawn as a placeholder, reported as warnings (errors in strict mode)
+    fn validate_icons_availability(&self, resources: &Resources, report: &mut ValidationReport) {
+        for (index, board) in self.board_configs.iter().enumerate() {
+            if let Some(icon) = board.icon.as_ref().filter(|icon| !resources.has_icon(icon)) {
+                let (file, location) = self.component_location("boards", &self.board_configs, index, |b| &b.name);
+                report.degraded(&file, format!("{}.icon", location), format!("Icon '{}' not found for board '{}'", icon, board.name));
+            }
+            if let Some(background) = board.background_image.as_ref().filter(|background| !resources.has_icon(&background.image)) {
+                let (file, location) = self.component_location("boards", &self.board_configs, index, |b| &b.name);
+                report.degraded(&file, format!("{}.background_image", location),
+                    format!("Background image '{}' not found for board '{}'", background.image, board.name));
+            }
+        }
+        for (padset_index, padset) in self.padset_configs.iter().enumerate() {
+            for (pad_index, pad) in padset.items.iter().enumerate() {
+                if !pad.icon.is_empty() && !resources.has_icon(&pad.icon) {
+                    let (file, location) = self.pad_location(padset_index, pad_index);
+                    report.degraded(&file, format!("{}.icon", location), format!("Icon '{}' not found in padset '{}'", pad.icon, padset.name));
                 }
             }
         }
-        Ok(())
     }
 
-    fn validate_icons_availability(&self, resources: &Resources) -> Result<(), String> {
-        for board in &self.board_configs {
-            if let Some(ref icon) = board.icon {
-                let _ = resources.icon(icon)
-                        .ok_or_else(|| anyhow::anyhow!("Icon '{}' not found for board '{}'", icon, board.name));
+    /// Missing sound files are not played, reported as warnings (errors in strict mode)
+    fn validate_sounds_availability(&self, resources: &Resources, report: &mut ValidationReport) {
+        for (index, profile) in self.profiles.iter().enumerate() {
+            for (event, sound) in profile.sounds.sounds().filter(|(_, sound)| !resources.has_sound(sound)) {
+                let (file, location) = self.component_location("profiles", &self.profiles, index, |p| &p.name);
+                report.degraded(&file, format!("{}.sounds.{}", location, event),
+                    format!("Sound '{}' not found for profile '{}'", sound, profile.name));
             }
         }
-        for padset in &self.padset_configs {
-            for pad in &padset.items {
-                if !pad.icon.is_empty() {
-                    let _ = resources.icon(&pad.icon)
-                        .ok_or_else(|| anyhow::anyhow!("Icon '{}' not found for pad '{:?}' in padset '{}'", pad.icon, pad, padset.name));
+    }
+
+    /// Problems that do not prevent loading the settings but will make pads fail when pressed:
+    /// Command programs not found on PATH, missing file:// targets of OpenUrl actions and
+    /// Secret actions without attributes or secret-tool
+    fn validate_action_targets(&self, report: &mut ValidationReport) {
+        let path_var = std::env::var("PATH").unwrap_or_default();
+
+        for (padset_index, padset) in self.padset_configs.iter().enumerate() {
+            for (pad_index, pad) in padset.items.iter().enumerate() {
+                for (field, action_index, action) in pad.indexed_actions() {
+                    let problem = match action {
+                        Action::Command(command) => command_program(command)
+                            .filter(|program| !program_exists(program, &path_var))
+                            .map(|program| format!("Command '{}' not found on PATH", program)),
+                        Action::OpenUrl(url) => url.strip_prefix("file://")
+                            .map(percent_decode)
+                            .filter(|path| !Path::new(path).exists())
+                            .map(|path| format!("File '{}' does not exist", path)),
+                        Action::Secret(attributes) if attributes.is_empty() => Some("Secret has no lookup attributes".to_string()),
+                        Action::Secret(_) => (!program_exists(secret::SECRET_TOOL, &path_var))
+                            .then(|| format!("{} not found on PATH, install libsecret-tools", secret::SECRET_TOOL)),
+                        _ => None,
+                    };
+                    if let Some(problem) = problem {
+                        let (file, location) = self.pad_location(padset_index, pad_index);
+                        report.warning(&file, format!("{}.{}[{}]", location, field, action_index), problem);
+                    }
                 }
             }
         }
-        Ok(())
     }
 
-    fn validate_action_order(&self) -> Result<(), String> {
-        for padset in &self.padset_configs {
-            for pad in &padset.items {
-                if !pad.actions.is_order_valid() {
-                    return Err(format!("Invalid action order in pad '{:?}' of padset '{}'", pad, padset.name));
+    /// Unknown keys in Shortcut actions are skipped when the shortcut runs,
+    /// reported as warnings (errors in strict mode)
+    fn validate_shortcut_keys(&self, report: &mut ValidationReport) {
+        for (padset_index, padset) in self.padset_configs.iter().enumerate() {
+            for (pad_index, pad) in padset.items.iter().enumerate() {
+                for (field, action_index, action) in pad.indexed_actions() {
+                    let Action::Shortcut(shortcut) = action else {
+                        continue;
+                    };
+                    let unknown = script::unknown_keys(shortcut);
+                    if !unknown.is_empty() {
+                        let (file, location) = self.pad_location(padset_index, pad_index);
+                        report.degraded(&file, format!("{}.{}[{}]", location, field, action_index),
+                            format!("Unknown key(s) {} in shortcut '{}'", unknown.iter().map(|key| format!("'{}'", key)).collect::<Vec<_>>().join(", "), shortcut));
+                    }
                 }
             }
         }
-        Ok(())
+    }
+
+    fn validate_listener_triggers(&self, report: &mut ValidationReport) {
+        let Some(listener) = &self.listener else {
+            return;
+        };
+        let file = PathBuf::from(&self.file_path);
+
+        for (index, trigger) in listener.triggers.iter().enumerate() {
+            let location = format!("listener.triggers[{}]", index);
+            if let Err(e) = KeyChord::from_str(&trigger.keys) {
+                report.error(&file, format!("{}.keys", location), format!("Invalid trigger keys '{}': {}", trigger.keys, e));
+            }
+            if let Some(profile) = trigger.profile.as_ref().filter(|p| self.get_profile(p).is_err()) {
+                report.error(&file, format!("{}.profile", location), format!("Profile '{}' not found for trigger '{}'", profile, trigger.keys));
+            }
+            if let Some(board) = trigger.board.as_ref().filter(|b| !self.board_configs.iter().any(|c| c.name == **b)) {
+                report.error(&file, format!("{}.board", location), format!("Board '{}' not found for trigger '{}'", board, trigger.keys));
+            }
+        }
     }
 }
 
 /// Components structure for loading additional settings files
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 struct Components {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     color_schemes: Vec<ColorScheme>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     text_styles: Vec<TextStyle>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     keyboard_layouts: Vec<KeyboardLayout>,
-    #[serde(default, rename = "boards")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "boards")]
     board_configs: Vec<BoardConfig>,
-    #[serde(default, rename = "padsets")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty", rename = "padsets")]
     padset_configs: Vec<PadSetConfig>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     profiles: Vec<Profile>,
 }
 
-fn load_components(file_path: &str) -> Result<Components> {
+fn override_named<T>(items: &mut Vec<T>, others: Vec<T>, kind: &str, source: &Path, name: fn(&T) -> &String) {
+    for other in others {
+        match items.iter_mut().find(|item| name(item) == name(&other)) {
+            Some(existing) => {
+                log::info!("{} '{}' overridden by {:?}", kind, name(&other), source);
+                *existing = other;
+            },
+            None => items.push(other),
+        }
+    }
+}
+
+fn replace_named<T>(items: &mut Vec<T>, previous_name: Option<&str>, item: T, name: fn(&T) -> &String) {
+    match items.iter_mut().find(|existing| previous_name == Some(name(existing).as_str())) {
+        Some(existing) => *existing = item,
+        None => items.push(item),
+    }
+}
+
+/// File format of settings and included components files, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => ConfigFormat::Toml,
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, text: &str) -> Result<T> {
+        match self {
+            ConfigFormat::Json => Ok(serde_json::from_str(text)?),
+            ConfigFormat::Toml => Ok(toml::from_str(text)?),
+            ConfigFormat::Yaml => Self::from_yaml(serde_yaml::from_str(text)?),
+        }
+    }
+
+    pub fn to_string<T: Serialize>(self, value: &T) -> Result<String> {
+        match self {
+            ConfigFormat::Json => Ok(serde_json::to_string_pretty(value)? + "\n"),
+            ConfigFormat::Toml => Ok(toml::to_string_pretty(value)?),
+            ConfigFormat::Yaml => Ok(serde_yaml::to_string(&serde_json::to_value(value)?)?),
+        }
+    }
+
+    /// YAML goes through a JSON value (in both directions) so that enums keep their JSON shape
+    /// ("Shortcut: F12") instead of serde_yaml's tag syntax ("!Shortcut F12")
+    fn from_yaml<T: DeserializeOwned>(document: serde_json::Value) -> Result<T> {
+        Ok(serde_json::from_value(document)?)
+    }
+
+    /// Parse every document of a file, only YAML files can hold more than one (separated by '---')
+    pub fn parse_all<T: DeserializeOwned>(self, text: &str) -> Result<Vec<T>> {
+        match self {
+            ConfigFormat::Yaml => serde_yaml::Deserializer::from_str(text)
+                .map(|document| Self::from_yaml(serde_json::Value::deserialize(document)?))
+                .collect(),
+            _ => Ok(vec![self.parse(text)?]),
+        }
+    }
+}
+
+fn load_components(file_path: &Path) -> Result<Vec<Components>> {
     let text = fs::read_to_string(file_path)?;
-    let components = serde_json::from_str::<Components>(&text)?;
+    let components = ConfigFormat::from_path(file_path).parse_all::<Components>(&text)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {:?}: {}", file_path, e))?;
     Ok(components)
 }
 
+/// Shell builtins and keywords, commands starting with them have no program to look up
+const SHELL_BUILTINS: &[&str] = &[
+    ".", ":", "[", "alias", "case", "cd", "command", "eval", "exec", "exit", "export", "for",
+    "if", "read", "set", "source", "test", "trap", "ulimit", "umask", "unset", "wait", "while",
+];
+
+/// Program a Command action runs: the first word after variable assignments, `None` when it cannot
+/// be determined statically (builtins, unexpanded variables, subshells)
+fn command_program(command: &str) -> Option<&str> {
+    let program = command.split_whitespace()
+        .find(|word| !word.split_once('=').is_some_and(|(name, _)| !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')))?
+        .trim_matches(|c| c == '"' || c == '\'');
+
+    let dynamic = program.is_empty() || program.contains(['$', '`', '(', '{']);
+    (!dynamic && !SHELL_BUILTINS.contains(&program)).then_some(program)
+}
+
+/// Whether a program path exists, or a program name is found in one of the PATH directories
+fn program_exists(program: &str, path_var: &str) -> bool {
+    if program.contains('/') {
+        let program = match program.strip_prefix("~/") {
+            Some(relative) => dirs::home_dir().map(|home| home.join(relative)).unwrap_or_else(|| PathBuf::from(program)),
+            None => PathBuf::from(program),
+        };
+        return program.exists();
+    }
+    std::env::split_paths(path_var).any(|directory| directory.join(program).is_file())
+}
+
+/// Decode %XX escapes of a URL path, invalid escapes are kept as written
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            },
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Settings file serialized in canonical form: fields in declaration order and maps sorted by key.
+/// Includes, padset bases, variables and environment references are kept as written
+pub fn normalize_settings(file_path: &Path) -> Result<String> {
+    let text = fs::read_to_string(file_path)?;
+    let format = ConfigFormat::from_path(file_path);
+
+    let mut document: serde_json::Value = format.parse(&text)?;
+    if !migration::migrate(&mut document)?.is_empty() {
+        anyhow::bail!("Settings file {:?} uses an older format, run 'hotkeys migrate' first", file_path);
+    }
+
+    let settings = format.parse::<AppSettings>(&text)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {:?}: {}", file_path, e))?;
+    format.to_string(&settings)
+}
+
+/// Included settings file serialized in canonical form, YAML documents stay separate
+pub fn normalize_components(file_path: &Path) -> Result<String> {
+    let format = ConfigFormat::from_path(file_path);
+    let documents = load_components(file_path)?.iter()
+        .map(|components| format.to_string(components))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(documents.join("---\n"))
+}
+
+/// Documents of a settings or components file as written, only YAML files can hold more than one
+fn read_documents(file_path: &Path) -> Result<Vec<serde_json::Value>> {
+    let text = fs::read_to_string(file_path)?;
+    ConfigFormat::from_path(file_path).parse_all(&text)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {:?}: {}", file_path, e))
+}
+
+/// Component of a collection (e.g. "boards") as written in the file, before bases and variables are resolved
+pub fn read_component<T: DeserializeOwned>(file_path: &Path, collection: &str, name: &str) -> Result<Option<T>> {
+    let component = read_documents(file_path)?.into_iter()
+        .filter_map(|mut document| match document.get_mut(collection)?.take() {
+            serde_json::Value::Array(items) => Some(items),
+            _ => None,
+        })
+        .flatten()
+        .find(|item| item.get("name").and_then(serde_json::Value::as_str) == Some(name));
+    component.map(|component| Ok(serde_json::from_value(component)?)).transpose()
+}
+
+/// What writing the file in canonical form loses: "comments" of TOML and YAML files and "anchors"
+/// (and aliases) of YAML files. Empty for JSON files and files that cannot be read
+pub fn rewrite_losses(file_path: &Path) -> Vec<&'static str> {
+    let format = ConfigFormat::from_path(file_path);
+    let Ok(text) = fs::read_to_string(file_path) else {
+        return Vec::new();
+    };
+    let (mut comments, mut anchors) = (false, false);
+    for line in text.lines() {
+        let mut quote = None;
+        let mut previous = ' ';
+        for (index, c) in line.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {},
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if c == '#' && previous.is_whitespace() => comments = true,
+                None if (c == '&' || c == '*') && previous.is_whitespace() => {
+                    anchors |= line[index + 1..].starts_with(|next: char| next.is_alphanumeric() || next == '_');
+                },
+                None => {},
+            }
+            previous = c;
+        }
+    }
+    match format {
+        ConfigFormat::Json => Vec::new(),
+        ConfigFormat::Toml => [("comments", comments)].into_iter().filter_map(|(lost, found)| found.then_some(lost)).collect(),
+        ConfigFormat::Yaml => [("comments", comments), ("anchors", anchors)].into_iter().filter_map(|(lost, found)| found.then_some(lost)).collect(),
+    }
+}
+
+/// Replace the component called `name` in the file, or add it to the (first document of the) file.
+/// Other components and settings of the file are kept, the file is written in canonical form (see `hotkeys fmt`),
+/// losing the comments and YAML anchors, see [rewrite_losses]
+pub fn write_component<T: Serialize>(file_path: &Path, collection: &str, name: &str, component: &T) -> Result<()> {
+    let format = ConfigFormat::from_path(file_path);
+    let mut documents = read_documents(file_path)?;
+    let losses = rewrite_losses(file_path);
+    if !losses.is_empty() {
+        log::warn!("Writing {:?} in canonical form, its {} are not kept", file_path, losses.join(" and "));
+    }
+    let component = serde_json::to_value(component)?;
+
+    let existing = documents.iter_mut()
+        .filter_map(|document| document.get_mut(collection)?.as_array_mut())
+        .flatten()
+        .find(|item| item.get("name").and_then(serde_json::Value::as_str) == Some(name));
+    match existing {
+        Some(existing) => *existing = component,
+        None => {
+            let items = documents.first_mut()
+                .and_then(serde_json::Value::as_object_mut)
+                .ok_or_else(|| anyhow::anyhow!("{:?} holds no settings", file_path))?
+                .entry(collection)
+                .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+            items.as_array_mut()
+                .ok_or_else(|| anyhow::anyhow!("'{}' in {:?} is not a list", collection, file_path))?
+                .push(component);
+        }
+    }
+
+    let documents = documents.iter()
+        .map(|document| format.to_string(document))
+        .collect::<Result<Vec<_>>>()?;
+    fs::write(file_path, documents.join("---\n"))
+        .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", file_path, e))
+}
+
 impl AppSettings {
     /// Append all components from a Components instance
     fn append_all(&mut self, components: Components) {
@@ -468,6 +1339,207 @@ impl AppSettings {
         self.profiles.extend(components.profiles);
     }
 
+    /// Build the items of padsets declaring a `base`: the base pads with `overrides` applied.
+    /// Bases may have bases of their own, cycles and unknown bases are reported as errors.
+    fn resolve_padset_bases(&mut self) -> Result<(), String> {
+        let mut resolved: HashSet<String> = self.padset_configs.iter()
+            .filter(|padset| padset.base.is_none())
+            .map(|padset| padset.name.clone())
+            .collect();
+
+        for padset in self.padset_configs.iter().filter(|padset| padset.base.is_some()) {
+            if !padset.items.is_empty() {
+                return Err(format!("Padset '{}' declares a base, use 'overrides' instead of 'items'", padset.name));
+            }
+            if let Some(id) = padset.overrides.keys().find(|id| !(1..=9).contains(*id)) {
+                return Err(format!("Invalid pad id {} in overrides of padset '{}', expected 1 to 9", id, padset.name));
+            }
+        }
+
+        while resolved.len() < self.padset_configs.len() {
+            let next = self.padset_configs.iter().position(|padset| !resolved.contains(&padset.name)
+                && padset.base.as_ref().is_some_and(|base| resolved.contains(base)));
+
+            let Some(index) = next else {
+                let padset = self.padset_configs.iter().find(|padset| !resolved.contains(&padset.name)).unwrap();
+                let base = padset.base.as_deref().unwrap_or_default();
+                return match self.get_padset_config(base) {
+                    Some(_) => Err(format!("Padset '{}' has a cyclic base '{}'", padset.name, base)),
+                    None => Err(format!("Padset '{}' has unknown base '{}'", padset.name, base)),
+                };
+            };
+
+            let base = self.padset_configs[index].base.clone().unwrap_or_default();
+            let mut items = self.get_padset_config(&base).map(|base| base.items.clone()).unwrap_or_default();
+            let padset = &mut self.padset_configs[index];
+            for (id, pad) in &padset.overrides {
+                if items.len() < *id {
+                    items.resize_with(*id, PadConfig::default);
+                }
+                items[id - 1] = pad.clone();
+            }
+            padset.items = items;
+            resolved.insert(padset.name.clone());
+        }
+        Ok(())
+    }
+
+    /// Compute the colors of color schemes declaring a `base`, see [ColorScheme::derived_from].
+    /// Bases may have bases of their own, cycles and unknown bases are reported as errors.
+    fn resolve_color_scheme_bases(&mut self) -> Result<(), String> {
+        if let Some(scheme) = self.color_schemes.iter().find(|scheme| scheme.base.is_none() && !scheme.is_complete()) {
+            return Err(format!("Color scheme '{}' must declare opacity, background, foreground1 and foreground2, or a base", scheme.name));
+        }
+
+        let mut resolved: HashSet<String> = self.color_schemes.iter()
+            .filter(|scheme| scheme.base.is_none())
+            .map(|scheme| scheme.name.clone())
+            .collect();
+
+        while resolved.len() < self.color_schemes.len() {
+            let next = self.color_schemes.iter().position(|scheme| !resolved.contains(&scheme.name)
+                && scheme.base.as_ref().is_some_and(|base| resolved.contains(base)));
+
+            let Some(index) = next else {
+                let scheme = self.color_schemes.iter().find(|scheme| !resolved.contains(&scheme.name)).unwrap();
+                let base = scheme.base.as_deref().unwrap_or_default();
+                return match self.get_color_scheme(base) {
+                    Some(_) => Err(format!("Color scheme '{}' has a cyclic base '{}'", scheme.name, base)),
+                    None => Err(format!("Color scheme '{}' has unknown base '{}'", scheme.name, base)),
+                };
+            };
+
+            let base = self.color_schemes[index].base.clone().unwrap_or_default();
+            let derived = self.get_color_scheme(&base).map(|base| self.color_schemes[index].derived_from(base)).unwrap();
+            resolved.insert(derived.name.clone());
+            self.color_schemes[index] = derived;
+        }
+        Ok(())
+    }
+
+    /// Replace `{var:name}` references in pad headers, texts and actions with the configured variables
+    fn interpolate_variables(&mut self) -> Result<(), String> {
+        let mut unknown = Vec::new();
+
+        for padset in &mut self.padset_configs {
+            for pad in &mut padset.items {
+                pad.header = interpolation::expand_variables(&pad.header, &self.variables, &mut unknown);
+                pad.text = interpolation::expand_variables(&pad.text, &self.variables, &mut unknown);
+                for action in pad.actions.iter_mut().chain(pad.long_actions.iter_mut()) {
+                    let target = match action {
+                        crate::core::Action::Shortcut(keys) => Some(keys),
+                        other => other.text_mut(),
+                    };
+                    if let Some(text) = target {
+                        *text = interpolation::expand_variables(text, &self.variables, &mut unknown);
+                    }
+                }
+            }
+
+            if let Some(name) = unknown.first() {
+                return Err(format!("Unknown variable '{}' in padset '{}'", name, padset.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Leave out the profiles and boards not meant for this host (`only_on_hosts`, `except_hosts`).
+    /// Boards left out are removed from the profiles, and pads no longer navigate to them
+    fn apply_host_conditions(&mut self, hostname: &str) {
+        let excluded_boards: Vec<String> = self.board_configs.iter()
+            .filter(|board| !is_on_host(&board.only_on_hosts, &board.except_hosts, hostname))
+            .map(|board| board.name.clone())
+            .collect();
+        for name in &excluded_boards {
+            log::info!("Board '{}' is not loaded on host '{}'", name, hostname);
+        }
+        self.profiles.retain(|profile| {
+            let on_host = is_on_host(&profile.only_on_hosts, &profile.except_hosts, hostname);
+            if !on_host {
+                log::info!("Profile '{}' is not loaded on host '{}'", profile.name, hostname);
+            }
+            on_host
+        });
+        if excluded_boards.is_empty() {
+            return;
+        }
+
+        self.board_configs.retain(|board| !excluded_boards.contains(&board.name));
+        for profile in &mut self.profiles {
+            profile.boards.retain(|board| !excluded_boards.contains(board));
+            // Without boards left validation reports the profile
+            let first = profile.boards.first().filter(|_| excluded_boards.contains(&profile.default)).cloned();
+            if let Some(first) = first {
+                log::warn!("Default board '{}' of profile '{}' is not loaded on host '{}', using '{}' instead",
+                    profile.default, profile.name, hostname, first);
+                profile.default = first;
+            }
+        }
+        for pad in self.padset_configs.iter_mut().flat_map(|padset| padset.items.iter_mut()) {
+            if pad.board.as_ref().is_some_and(|board| excluded_boards.contains(board)) {
+                pad.board = None;
+            }
+        }
+    }
+
+    /// Expand `${NAME}` environment variables in action texts, commands, URLs, icon and sound paths
+    fn expand_environment(&mut self) {
+        let mut unknown = Vec::new();
+
+        for board in &mut self.board_configs {
+            if let Some(icon) = &mut board.icon {
+                *icon = interpolation::expand_env(icon, &mut unknown);
+            }
+            if let Some(background) = &mut board.background_image {
+                background.image = interpolation::expand_env(&background.image, &mut unknown);
+            }
+        }
+
+        for sound in self.profiles.iter_mut().flat_map(|profile| profile.sounds.sounds_mut()) {
+            *sound = interpolation::expand_env(sound, &mut unknown);
+        }
+
+        for pad in self.padset_configs.iter_mut().flat_map(|padset| padset.items.iter_mut()) {
+            pad.icon = interpolation::expand_env(&pad.icon, &mut unknown);
+            for text in pad.actions.iter_mut().chain(pad.long_actions.iter_mut()).filter_map(|action| action.text_mut()) {
+                *text = interpolation::expand_env(text, &mut unknown);
+            }
+        }
+
+        unknown.sort();
+        unknown.dedup();
+        for name in unknown {
+            log::warn!("Environment variable '{}' is not set, leaving '${{{}}}' unexpanded", name, name);
+        }
+    }
+
+    /// Remember the included file components come from, for locations in the validation report
+    fn record_origins(&mut self, components: &Components, source: &Path) {
+        let names = [
+            ("color_schemes", components.color_schemes.iter().map(|c| &c.name).collect::<Vec<_>>()),
+            ("text_styles", components.text_styles.iter().map(|s| &s.name).collect()),
+            ("keyboard_layouts", components.keyboard_layouts.iter().map(|l| &l.name).collect()),
+            ("boards", components.board_configs.iter().map(|b| &b.name).collect()),
+            ("padsets", components.padset_configs.iter().map(|p| &p.name).collect()),
+            ("profiles", components.profiles.iter().map(|p| &p.name).collect()),
+        ];
+        for (collection, names) in names {
+            for name in names {
+                self.origins.insert((collection, name.clone()), source.to_path_buf());
+            }
+        }
+    }
+
+    /// Merge all components from a Components instance, replacing earlier components with the same name
+    fn override_all(&mut self, components: Components, source: &Path) {
+        override_named(&mut self.color_schemes, components.color_schemes, "ColorScheme", source, |c| &c.name);
+        override_named(&mut self.text_styles, components.text_styles, "TextStyle", source, |s| &s.name);
+        override_named(&mut self.keyboard_layouts, components.keyboard_layouts, "KeyboardLayout", source, |l| &l.name);
+        override_named(&mut self.board_configs, components.board_configs, "Board", source, |b| &b.name);
+        override_named(&mut self.padset_configs, components.padset_configs, "PadSet", source, |p| &p.name);
+        override_named(&mut self.profiles, components.profiles, "Profile", source, |p| &p.name);
+    }
+
     /// Validate if no two components of the same type have equal name
     fn validate_unique_names(&self) -> Result<(), String> {
         let mut seen = HashSet::new();
@@ -515,14 +1587,92 @@ impl AppSettings {
         Ok(())
     }
 
+    /// Built-in settings used when the settings file fails to load, see [SAFE_MODE_SETTINGS]
+    pub fn safe_mode(file_path: &str) -> Self {
+        serde_json::from_str::<Self>(SAFE_MODE_SETTINGS)
+            .expect("Built-in safe mode settings are valid")
+            .with_file_path(file_path)
+    }
+
     fn with_file_path(mut self, file_path: &str) -> Self {
         self.file_path = file_path.to_string();
         self
     }
+
+    /// Override the auto-close timeout for this invocation (0 disables auto-close)
+    pub fn with_timeout(mut self, timeout: u64) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Write nothing to disk for this invocation, see [AppSettings::ephemeral]
+    pub fn with_ephemeral(mut self) -> Self {
+        self.ephemeral = true;
+        self
+    }
+
+    /// Replace the board called `previous_name` (or add the board), e.g. to validate an edit before saving it
+    pub fn with_board(mut self, previous_name: Option<&str>, board: BoardConfig) -> Self {
+        replace_named(&mut self.board_configs, previous_name, board, |b| &b.name);
+        self
+    }
+
+    /// Replace the padset called `previous_name` (or add the padset)
+    pub fn with_padset(mut self, previous_name: Option<&str>, padset: PadSetConfig) -> Self {
+        replace_named(&mut self.padset_configs, previous_name, padset, |p| &p.name);
+        self
+    }
+
+    /// Replace the color scheme called `previous_name` (or add the color scheme)
+    pub fn with_color_scheme(mut self, previous_name: Option<&str>, color_scheme: ColorScheme) -> Self {
+        replace_named(&mut self.color_schemes, previous_name, color_scheme, |c| &c.name);
+        self
+    }
+
+    /// Replace the text style called `previous_name` (or add the text style)
+    pub fn with_text_style(mut self, previous_name: Option<&str>, text_style: TextStyle) -> Self {
+        replace_named(&mut self.text_styles, previous_name, text_style, |s| &s.name);
+        self
+    }
 }
 
+/// Load and validate the settings, from the settings cache when the settings files are unchanged
 pub fn load_settings(resources: &Resources) -> Result<AppSettings> {
-    let settings_path: PathBuf = resources.settings_json().ok_or_else(|| anyhow::anyhow!("Settings file not found"))?;
+    let started = std::time::Instant::now();
+    if let Some(mut settings) = settings_cache::load(resources) {
+        settings.expand_environment();
+        settings.apply_host_conditions(&hostname());
+        perf::record("config load", started);
+        return Ok(settings);
+    }
+
+    let merged = load_merged_settings(resources)?;
+    let mut settings = merged.clone();
+    settings.expand_environment();
+    settings.apply_host_conditions(&hostname());
+    perf::record("config load", started);
+
+    // Validate the entire settings configuration
+    perf::measure("validation", || settings.validate(resources))
+        .map_err(|e| anyhow::Error::msg(format!("Settings validation failed: {}", e)))?;
+
+    if !settings.ephemeral {
+        settings_cache::store(&merged);
+    }
+    Ok(settings)
+}
+
+/// Load the settings and their includes, with padset bases and variables resolved but without
+/// validating references, see [AppSettings::validation_report]
+pub fn load_settings_unvalidated(resources: &Resources) -> Result<AppSettings> {
+    let mut settings = load_merged_settings(resources)?;
+    settings.expand_environment();
+    Ok(settings)
+}
+
+/// Settings merged with their includes, before environment variables are expanded (the cached form)
+fn load_merged_settings(resources: &Resources) -> Result<AppSettings> {
+    let settings_path: PathBuf = resources.settings_file().ok_or_else(|| anyhow::anyhow!("Settings file not found"))?;
 
     if !settings_path.exists() {
         anyhow::bail!("Settings file does not exist: {:?}", settings_path);
@@ -531,25 +1681,600 @@ pub fn load_settings(resources: &Resources) -> Result<AppSettings> {
     log::info!("Loading settings: {:?}", settings_path);
     let contents = fs::read_to_string(settings_path.clone())?;
 
-    let mut settings: AppSettings = serde_json::from_str::<AppSettings>(&contents)?
-        .with_file_path(settings_path.to_str().unwrap());
+    let format = ConfigFormat::from_path(&settings_path);
+    let mut document: serde_json::Value = format.parse(&contents)?;
+    let transformations = migration::migrate(&mut document)?;
+    let settings: AppSettings = if transformations.is_empty() {
+        // Parse the text again for errors with line and column information
+        format.parse::<AppSettings>(&contents)?
+    } else {
+        log::warn!("Settings file {:?} uses an older format, run 'hotkeys migrate' to upgrade it", settings_path);
+        for transformation in &transformations {
+            log::info!("Migrated settings in memory, {}", transformation);
+        }
+        serde_json::from_value::<AppSettings>(document)?
+    };
+    let mut settings = settings.with_file_path(settings_path.to_str().unwrap());
 
     // Load includes
     for include in &settings.includes.clone() {
-        let include_path = resources.file(include)
-            .ok_or_else(|| anyhow::anyhow!("Included settings file not found: {}", include))?;
-
-        log::info!("Loading components: {:?}", include_path);
-        let components = load_components(include_path.to_str().unwrap())?;
-        settings.append_all(components);
+        for include_path in resolve_include(include, resources)? {
+            log::info!("Loading components: {:?}", include_path);
+            for components in load_components(&include_path)? {
+                settings.record_origins(&components, &include_path);
+                match settings.include_mode {
+                    IncludeMode::Append => settings.append_all(components),
+                    IncludeMode::Override => settings.override_all(components, &include_path),
+                }
+            }
 
-        settings.validate_unique_names()
-            .map_err(|e| anyhow::Error::msg(format!("Validation error in included file '{:?}': {}", include_path, e)))?;
+            settings.validate_unique_names()
+                .map_err(|e| anyhow::Error::msg(format!("Validation error in included file '{:?}': {}", include_path, e)))?;
+            settings.included_files.push(include_path);
+        }
     }
 
-    // Validate the entire settings configuration
-    settings.validate(&resources)
+    settings.resolve_color_scheme_bases()
+        .map_err(|e| anyhow::Error::msg(format!("Settings validation failed: {}", e)))?;
+    settings.resolve_padset_bases()
+        .map_err(|e| anyhow::Error::msg(format!("Settings validation failed: {}", e)))?;
+    settings.interpolate_variables()
         .map_err(|e| anyhow::Error::msg(format!("Settings validation failed: {}", e)))?;
 
     Ok(settings)
+}
+
+/// Name of this machine, matched against `only_on_hosts` and `except_hosts`
+fn hostname() -> String {
+    let mut buffer = [0u8; 256];
+    if unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) } != 0 {
+        log::warn!("Failed to get the hostname: {}", std::io::Error::last_os_error());
+        return String::new();
+    }
+    std::ffi::CStr::from_bytes_until_nul(&buffer)
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Whether a profile or board with these host lists is loaded on the host, hostnames match case-insensitively
+fn is_on_host(only_on_hosts: &[String], except_hosts: &[String], hostname: &str) -> bool {
+    let matches = |host: &String| host.eq_ignore_ascii_case(hostname);
+    (only_on_hosts.is_empty() || only_on_hosts.iter().any(matches)) && !except_hosts.iter().any(matches)
+}
+
+/// Files an include entry resolves to, with environment variables expanded
+fn resolve_include(include: &str, resources: &Resources) -> Result<Vec<PathBuf>> {
+    let mut unknown = Vec::new();
+    let include = interpolation::expand_env(include, &mut unknown);
+    for name in unknown {
+        log::warn!("Environment variable '{}' in include '{}' is not set", name, include);
+    }
+
+    let include_paths = resources.include_files(&include)
+        .ok_or_else(|| anyhow::anyhow!("Included settings file not found: {}", include))?;
+    if include_paths.is_empty() {
+        log::warn!("Include '{}' matched no files", include);
+    }
+    Ok(include_paths)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    pub(crate) use serde_json::json;
+
+    /// Settings with the required fields and no components, the fields of `extra` replace or add to them
+    pub(crate) fn test_settings(extra: serde_json::Value) -> AppSettings {
+        let mut settings = json!({
+            "timeout": 4, "feedback": 2, "delay": 1,
+            "color_schemes": [], "text_styles": [],
+            "keyboard_layout": "default", "keyboard_layouts": [],
+            "boards": [], "padsets": []
+        });
+        if let (Some(settings), serde_json::Value::Object(extra)) = (settings.as_object_mut(), extra) {
+            settings.extend(extra);
+        }
+        serde_json::from_value(settings).unwrap()
+    }
+
+    #[test]
+    fn test_config_format() {
+        assert_eq!(ConfigFormat::from_path(Path::new("settings.toml")), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path(Path::new("settings.json")), ConfigFormat::Json);
+
+        let components: Components = ConfigFormat::Toml.parse(r#"
+            [[boards]]
+            name = "code"
+            base_pads = "code"
+            detection = { ps = "code" }
+
+            [[padsets]]
+            name = "code"
+            items = [{ header = "F12", actions = [{ Shortcut = "F12" }, "CustomHomeAction"] }]
+        "#).unwrap();
+        assert_eq!(components.board_configs[0].detection, Detection::PS("code".to_string()));
+        assert_eq!(components.padset_configs[0].items[0].actions.len(), 2);
+    }
+
+    #[test]
+    fn test_override_all() {
+        let mut settings = test_settings(json!({
+            "include_mode": "override",
+            "boards": [{ "name": "code", "title": "Code", "detection": { "ps": "code" } }]
+        }));
+        let components: Components = serde_json::from_str(r#"{
+            "boards": [{ "name": "code", "title": "VS Code", "detection": { "ps": "code" } }, { "name": "chrome", "detection": "none" }]
+        }"#).unwrap();
+
+        settings.override_all(components, Path::new("user.json"));
+        assert_eq!(settings.board_configs.len(), 2);
+        assert_eq!(settings.board_configs[0].title.as_deref(), Some("VS Code"));
+        assert_eq!(settings.board_configs[1].name, "chrome");
+        assert!(settings.validate_unique_names().is_ok());
+    }
+
+    #[test]
+    fn test_resolve_padset_bases() {
+        let mut settings = test_settings(json!({
+            "padsets": [
+                { "name": "code/shift", "base": "code/ctrl", "overrides": { "9": { "header": "S9" } } },
+                { "name": "code/ctrl", "base": "code", "overrides": { "2": { "header": "C2" } } },
+                { "name": "code", "items": [{ "header": "1" }, { "header": "2" }, { "header": "3" }] }
+            ]
+        }));
+
+        settings.resolve_padset_bases().unwrap();
+        let headers = |name: &str| settings.get_padset_config(name).unwrap().items.iter()
+            .map(|pad| pad.header.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(headers("code/ctrl"), vec!["1", "C2", "3"]);
+        assert_eq!(headers("code/shift"), vec!["1", "C2", "3", "", "", "", "", "", "S9"]);
+
+        for padset in &mut settings.padset_configs {
+            padset.items.clear();
+        }
+        settings.padset_configs[2].base = Some("code/shift".to_string());
+        assert_eq!(settings.resolve_padset_bases(), Err("Padset 'code/shift' has a cyclic base 'code/ctrl'".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_color_scheme_bases() {
+        let mut settings = test_settings(json!({
+            "color_schemes": [
+                { "name": "code/muted", "base": "code", "darken": 50, "foreground2": "#ffffff" },
+                { "name": "code", "base": "dark", "hue_shift": 120 },
+                { "name": "dark", "opacity": 0.8, "background": "#800000", "foreground1": "#ff0000", "foreground2": "#ffffff" }
+            ]
+        }));
+
+        settings.resolve_color_scheme_bases().unwrap();
+        let code = settings.get_color_scheme("code").unwrap();
+        assert_eq!((code.opacity, code.background.as_str(), code.foreground1.as_str()), (Some(0.8), "#008000", "#00ff00"));
+        let muted = settings.get_color_scheme("code/muted").unwrap();
+        assert_eq!((muted.background.as_str(), muted.foreground1.as_str(), muted.foreground2.as_str()), ("#004000", "#008000", "#ffffff"));
+
+        settings.color_schemes[2].base = Some("code/muted".to_string());
+        assert_eq!(settings.resolve_color_scheme_bases(), Err("Color scheme 'code/muted' has a cyclic base 'code'".to_string()));
+
+        settings.color_schemes[2].base = None;
+        settings.color_schemes[2].background.clear();
+        assert_eq!(settings.resolve_color_scheme_bases(),
+            Err("Color scheme 'dark' must declare opacity, background, foreground1 and foreground2, or a base".to_string()));
+    }
+
+    #[test]
+    fn test_profile_keyboard_layout() {
+        let settings = test_settings(json!({
+            "keyboard_layout": "us",
+            "keyboard_layouts": [{ "name": "us" }, { "name": "de", "mappings": { "y": "z" } }],
+            "profiles": [
+                { "name": "default", "boards": [], "default": "code" },
+                { "name": "german", "boards": [], "default": "code", "keyboard_layout": "de" }
+            ]
+        }));
+
+        assert_eq!(settings.get_profile_keyboard_layout("default").name, "us");
+        assert_eq!(settings.get_profile_keyboard_layout("german").name, "de");
+        assert_eq!(settings.get_profile_keyboard_layout("unknown").name, "us");
+        let mut report = ValidationReport::default();
+        settings.validate_profile_keyboard_layouts(&mut report);
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_apply_host_conditions() {
+        let mut settings = test_settings(json!({
+            "boards": [
+                { "name": "code", "base_pads": "common" },
+                { "name": "vpn", "base_pads": "common", "only_on_hosts": ["work-laptop"] },
+                { "name": "games", "base_pads": "common", "except_hosts": ["WORK-LAPTOP"] }
+            ],
+            "padsets": [{ "name": "common", "items": [{ "board": "vpn" }, { "board": "games" }] }],
+            "profiles": [
+                { "name": "default", "boards": ["code", "vpn", "games"], "default": "code" },
+                { "name": "work", "boards": ["vpn"], "default": "vpn", "only_on_hosts": ["work-laptop", "work-desktop"] },
+                { "name": "travel", "boards": ["vpn", "games", "code"], "default": "vpn" }
+            ]
+        }));
+
+        let mut home = settings.clone();
+        home.apply_host_conditions("home");
+        let names = |settings: &AppSettings| settings.board_configs.iter().map(|board| board.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&home), vec!["code", "games"]);
+        assert_eq!(home.profiles.len(), 2);
+        assert_eq!(home.profiles[0].boards, vec!["code", "games"]);
+        assert_eq!(home.profiles[1].default, "games");
+        assert_eq!(home.padset_configs[0].items[0].board, None);
+        assert_eq!(home.padset_configs[0].items[1].board.as_deref(), Some("games"));
+
+        settings.apply_host_conditions("work-laptop");
+        assert_eq!(names(&settings), vec!["code", "vpn"]);
+        assert_eq!(settings.profiles.len(), 3);
+        assert_eq!(settings.profiles[2].default, "vpn");
+        assert_eq!(settings.padset_configs[0].items[1].board, None);
+    }
+
+    #[test]
+    fn test_profile_chooser() {
+        let mut settings = test_settings(json!({
+            "profile_chooser": true,
+            "profiles": [{ "name": "default", "boards": [], "default": "code" }]
+        }));
+
+        // A single profile is used without asking
+        assert!(!settings.profile_chooser());
+        let profile = settings.profiles[0].clone();
+        settings.profiles.extend((1..10).map(|index| Profile { name: format!("profile{}", index), ..profile.clone() }));
+        assert!(settings.profile_chooser());
+
+        let mut report = ValidationReport::default();
+        settings.validate_profile_chooser(&mut report);
+        assert_eq!(report.warnings().count(), 1);
+        settings.profiles.pop();
+        let mut report = ValidationReport::default();
+        settings.validate_profile_chooser(&mut report);
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_validate_zero_key() {
+        let mut settings = test_settings(json!({
+            "padsets": [{ "name": "common", "items": [{ "header": "1" }, { "header": "2" }] }],
+            "zero_key": { "pad": { "padset": "common", "pad": 2 } }
+        }));
+        let mut report = ValidationReport::default();
+        settings.validate_zero_key(&mut report);
+        assert!(report.findings.is_empty());
+
+        settings.zero_key = ZeroKey::Pad { padset: "common".to_string(), pad: 3 };
+        let mut report = ValidationReport::default();
+        settings.validate_zero_key(&mut report);
+        assert_eq!(report.errors().map(|e| e.location.as_str()).collect::<Vec<_>>(), vec!["zero_key.pad.pad"]);
+
+        settings.zero_key = ZeroKey::Pad { padset: "missing".to_string(), pad: 1 };
+        let mut report = ValidationReport::default();
+        settings.validate_zero_key(&mut report);
+        assert_eq!(report.errors().map(|e| e.location.as_str()).collect::<Vec<_>>(), vec!["zero_key.pad.padset"]);
+
+        let settings = test_settings(json!({ "zero_key": "back" }));
+        assert_eq!(*settings.zero_key(), ZeroKey::Back);
+        assert!(!serde_json::to_string(&AppSettings::safe_mode("settings.json")).unwrap().contains("zero_key"));
+    }
+
+    #[test]
+    fn test_validate_layout() {
+        let layout = |extra: serde_json::Value| {
+            let mut layout = json!({ "width": 800, "height": 600, "window_style": "Window" });
+            layout.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            let mut report = ValidationReport::default();
+            test_settings(json!({ "layout": layout })).validate_layout(&mut report);
+            report.errors().map(|e| e.location.clone()).collect::<Vec<_>>()
+        };
+        assert!(layout(json!({})).is_empty());
+        assert!(layout(json!({ "long_press": 400, "double_tap": 250 })).is_empty());
+        assert_eq!(layout(json!({ "long_press": 0, "double_tap": 0 })), vec!["layout.long_press", "layout.double_tap"]);
+    }
+
+    #[test]
+    fn test_interpolate_variables() {
+        let mut settings = test_settings(json!({
+            "variables": { "project": "~/src/app", "email": "me@example.com" },
+            "padsets": [{ "name": "shell", "items": [
+                { "header": "cd {var:project}", "actions": [{ "Line": "cd {var:project}" }, { "Text": "{var:email}" }] }
+            ] }]
+        }));
+
+        settings.interpolate_variables().unwrap();
+        let pad = &settings.padset_configs[0].items[0];
+        assert_eq!(pad.header, "cd ~/src/app");
+        assert!(matches!(&pad.actions[1], crate::core::Action::Text(text) if text == "me@example.com"));
+
+        settings.padset_configs[0].items[0].text = "{var:missing}".to_string();
+        assert_eq!(settings.interpolate_variables(), Err("Unknown variable 'missing' in padset 'shell'".to_string()));
+    }
+
+    #[test]
+    fn test_normalize_settings() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-normalize-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let settings: AppSettings = serde_json::from_str(include_str!("../../resources/settings.json")).unwrap();
+        let canonical = serde_json::to_value(&settings).unwrap();
+
+        for (file_name, format) in [("settings.json", ConfigFormat::Json), ("settings.toml", ConfigFormat::Toml), ("settings.yaml", ConfigFormat::Yaml)] {
+            let path = dir.join(file_name);
+            fs::write(&path, format.to_string(&settings).unwrap()).unwrap();
+
+            let normalized = normalize_settings(&path).unwrap();
+            assert_eq!(normalized, fs::read_to_string(&path).unwrap(), "{} is not stable", file_name);
+            assert_eq!(serde_json::to_value(format.parse::<AppSettings>(&normalized).unwrap()).unwrap(), canonical);
+        }
+
+        let path = dir.join("boards.yaml");
+        fs::write(&path, "boards:\n  - { name: code, detection: { ps: code } }\n---\nprofiles: []\n").unwrap();
+        assert_eq!(normalize_components(&path).unwrap(),
+            "boards:\n- name: code\n  detection:\n    ps: code\n---\n{}\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_validation_report_locations() {
+        let mut settings = test_settings(json!({
+            "boards": [{ "name": "code", "detection": { "ps": "code" }, "color_scheme": "dark" }],
+            "profiles": [{ "name": "default", "boards": ["code", "chrome"], "default": "code" }]
+        })).with_file_path("settings.json");
+        let components: Components = serde_json::from_str(r#"{
+            "boards": [
+                { "name": "chrome", "detection": { "ps": "chrome" } },
+                { "name": "term", "detection": { "ps": "kitty" }, "modifier_pads": { "Ctrl": "term/ctrl" }, "double_pads": "term/double" }
+            ]
+        }"#).unwrap();
+        settings.record_origins(&components, Path::new("boards.json"));
+        settings.append_all(components);
+
+        let report = settings.validation_report(&Resources::new(vec![]), ValidationReport::default());
+        let findings: Vec<String> = report.findings.iter().map(Finding::to_string).collect();
+        assert_eq!(findings, vec![
+            "error: settings.json: boards[0].color_scheme: Color scheme 'dark' not found in settings",
+            "error: boards.json: boards[1].double_pads: Double tap pad set 'term/double' not found for board 'term'",
+            "error: boards.json: boards[1].modifier_pads.Ctrl: Modifier pad set 'term/ctrl' not found for board 'term' with modifier 'Ctrl'",
+        ]);
+        assert!(settings.validate(&Resources::new(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_safe_mode_settings() {
+        let settings = AppSettings::safe_mode("/home/me/.config/hotkeys/settings.json");
+        assert_eq!(settings.timeout(), 0);
+        assert!(settings.board_configs.is_empty());
+        assert_eq!(settings.file_path(), "/home/me/.config/hotkeys/settings.json");
+        assert_eq!(settings.get_keyboard_layout().name, "default");
+    }
+
+    #[test]
+    fn test_validate_last_used_default() {
+        let settings = test_settings(json!({
+            "boards": [{ "name": "code" }],
+            "profiles": [
+                { "name": "default", "boards": ["code"], "default": "last_used" },
+                { "name": "empty", "boards": [], "default": "last_used" }
+            ]
+        }));
+        let mut report = ValidationReport::default();
+        settings.validate_profile_board_references(&mut report);
+        let errors: Vec<_> = report.errors().map(|e| e.location.as_str()).collect();
+        assert_eq!(errors, vec!["profiles[1].default"]);
+    }
+
+    #[test]
+    fn test_validate_action_targets() {
+        assert_eq!(command_program("code ~/projects"), Some("code"));
+        assert_eq!(command_program("GDK_BACKEND=x11 'gnome-terminal' --tab"), Some("gnome-terminal"));
+        assert_eq!(command_program("cd ~/src && make"), None);
+        assert_eq!(command_program("$EDITOR notes.md"), None);
+        assert_eq!(percent_decode("/tmp/My%20Notes%2"), "/tmp/My Notes%2");
+
+        assert!(program_exists("sh", "/usr/local/bin:/usr/bin:/bin"));
+        assert!(!program_exists("sh", ""));
+        assert!(!program_exists("/nonexistent/sh", "/bin"));
+
+        let settings = test_settings(json!({
+            "padsets": [{ "name": "tools", "items": [
+                { "actions": [{ "Command": "sh -c true" }, { "OpenUrl": "https://example.com" }] },
+                { "actions": [{ "Command": "hotkeys-no-such-program --help" }] },
+                { "actions": [{ "OpenUrl": "file:///nonexistent/notes.md" }] },
+                { "actions": [{ "Secret": {} }] }
+            ]}]
+        }));
+        let mut report = ValidationReport::default();
+        settings.validate_action_targets(&mut report);
+        let findings: Vec<_> = report.warnings().map(|w| (w.location.as_str(), w.message.as_str())).collect();
+        assert_eq!(findings, vec![
+            ("padsets[0].items[1].actions[0]", "Command 'hotkeys-no-such-program' not found on PATH"),
+            ("padsets[0].items[2].actions[0]", "File '/nonexistent/notes.md' does not exist"),
+            ("padsets[0].items[3].actions[0]", "Secret has no lookup attributes"),
+        ]);
+    }
+
+    #[test]
+    fn test_validate_pad_opacity() {
+        let settings = test_settings(json!({
+            "padsets": [{ "name": "code", "items": [
+                { "header": "Build", "opacity": 0.5, "fill_background": true },
+                { "header": "Deploy", "opacity": 1.5 },
+                { "header": "Run", "icon": "run.svg", "icon_size": 0, "icon_placement": "Left" }
+            ]}]
+        }));
+        assert!(settings.padset_configs[0].items[0].fill_background);
+        assert_eq!(settings.padset_configs[0].items[2].icon_placement, Some(IconPlacement::Left));
+
+        let mut report = ValidationReport::default();
+        settings.validate_pads(&mut report);
+        let errors: Vec<_> = report.errors().map(|e| (e.location.as_str(), e.message.as_str())).collect();
+        assert_eq!(errors, vec![
+            ("padsets[0].items[1].opacity", "Invalid opacity 1.5, expected 0 to 1"),
+            ("padsets[0].items[2].icon_size", "Invalid icon size 0, expected a positive size"),
+        ]);
+    }
+
+    #[test]
+    fn test_validate_strict() {
+        let settings = test_settings(json!({
+            "padsets": [{ "name": "code", "items": [
+                { "icon": "missing.svg", "actions": [{ "Shortcut": "Ctrl Shift T" }] },
+                { "actions": [{ "Pause": 100 }, { "Shortcut": "Ctrl Shft T" }], "long_actions": [{ "Shortcut": "Ctrl Alt Tb" }] }
+            ]}]
+        }));
+        let resources = Resources::new(vec![]);
+
+        let lenient = settings.validation_report(&resources, ValidationReport::default());
+        let warnings: Vec<_> = lenient.warnings().map(|w| (w.location.as_str(), w.message.as_str())).collect();
+        assert_eq!(warnings, vec![
+            ("padsets[0].items[0].icon", "Icon 'missing.svg' not found in padset 'code'"),
+            ("padsets[0].items[1].actions[1]", "Unknown key(s) 'shft' in shortcut 'Ctrl Shft T'"),
+            ("padsets[0].items[1].long_actions[0]", "Unknown key(s) 'tb' in shortcut 'Ctrl Alt Tb'"),
+        ]);
+
+        let strict = settings.validation_report(&resources, ValidationReport::strict());
+        assert_eq!(strict.warnings().count(), 0);
+        let errors: Vec<_> = strict.errors().map(|e| e.location.as_str()).collect();
+        assert!(errors.contains(&"padsets[0].items[0].icon"));
+        assert!(errors.contains(&"padsets[0].items[1].actions[1]"));
+    }
+
+    #[test]
+    fn test_validate_sounds() {
+        let settings = test_settings(json!({
+            "profiles": [{ "name": "default", "boards": [], "default": "code",
+                "sounds": { "open": "bell", "select": "click.oga" } }]
+        }));
+        assert_eq!(settings.profiles[0].sounds.timeout, None);
+
+        let mut report = ValidationReport::default();
+        settings.validate_sounds_availability(&Resources::new(vec![]), &mut report);
+        let warnings: Vec<_> = report.warnings().map(|w| (w.location.as_str(), w.message.as_str())).collect();
+        assert_eq!(warnings, vec![("profiles[0].sounds.select", "Sound 'click.oga' not found for profile 'default'")]);
+    }
+
+    #[test]
+    fn test_config_format_yaml() {
+        assert_eq!(ConfigFormat::from_path(Path::new("boards.yml")), ConfigFormat::Yaml);
+
+        let components: Vec<Components> = ConfigFormat::Yaml.parse_all(r#"
+boards:
+  - name: code
+    base_pads: code
+    detection:
+      ps: code
+---
+padsets:
+  - name: code
+    items:
+      - header: F12
+        actions:
+          - Shortcut: F12
+          - CustomHomeAction
+"#).unwrap();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].board_configs[0].detection, Detection::PS("code".to_string()));
+        assert_eq!(components[1].padset_configs[0].items[0].actions.len(), 2);
+
+        let document: serde_json::Value = ConfigFormat::Yaml.parse("actions:\n  - Pause: 200\n").unwrap();
+        assert_eq!(document["actions"][0]["Pause"], 200);
+    }
+
+    #[test]
+    fn test_write_component() {
+        let path = std::env::temp_dir().join(format!("hotkeys-components-{}.yaml", std::process::id()));
+        fs::write(&path, "boards:\n  - name: code\n    title: Code\n---\npadsets:\n  - name: code\n    items:\n      - header: Copy\n").unwrap();
+
+        let mut padset: PadSetConfig = read_component(&path, "padsets", "code").unwrap().unwrap();
+        assert_eq!(padset.items[0].header, "Copy");
+        assert!(read_component::<BoardConfig>(&path, "boards", "term").unwrap().is_none());
+
+        padset.name = "code2".to_string();
+        padset.items[0].header = "Paste".to_string();
+        write_component(&path, "padsets", "code", &padset).unwrap();
+        let board: BoardConfig = serde_json::from_str(r#"{ "name": "term", "base_pads": "code2" }"#).unwrap();
+        write_component(&path, "boards", "term", &board).unwrap();
+
+        let components = load_components(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].board_configs.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), vec!["code", "term"]);
+        assert_eq!(components[0].board_configs[0].title.as_deref(), Some("Code"));
+        assert_eq!(components[1].padset_configs[0].name, "code2");
+        assert_eq!(components[1].padset_configs[0].items[0].header, "Paste");
+    }
+
+    #[test]
+    fn test_rewrite_losses() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-losses-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let losses = |name: &str, text: &str| {
+            fs::write(dir.join(name), text).unwrap();
+            rewrite_losses(&dir.join(name))
+        };
+
+        assert_eq!(losses("a.yaml", "# boards\ncolor_schemes:\n  - name: red\n    background: \"#ff0000\"\n"), vec!["comments"]);
+        assert_eq!(losses("b.yaml", "base: &base\n  header: Copy\nitems:\n  - *base\n  - text: a & b * c\n"), vec!["anchors"]);
+        assert!(losses("c.yaml", "color_schemes:\n  - name: red\n    background: '#ff0000'\n").is_empty());
+        assert_eq!(losses("d.toml", "timeout = 4 # seconds\n"), vec!["comments"]);
+        assert!(losses("e.json", "{ \"text\": \"# not a comment\" }").is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_padset_set_pad() {
+        let pad = |header: &str| PadConfig { header: header.to_string(), ..Default::default() };
+        let mut padset = PadSetConfig { name: "code".to_string(), items: vec![pad("Copy")], ..Default::default() };
+
+        padset.set_pad(3, pad("Paste"));
+        assert_eq!(padset.items.iter().map(|pad| pad.header.as_str()).collect::<Vec<_>>(), vec!["Copy", "", "Paste"]);
+        padset.set_pad(3, PadConfig::default());
+        assert_eq!(padset.items.len(), 1);
+
+        let mut derived = PadSetConfig { name: "code/ctrl".to_string(), base: Some("code".to_string()), ..Default::default() };
+        derived.set_pad(5, pad("Find"));
+        assert_eq!(derived.pad(5).header, "Find");
+        derived.set_pad(5, PadConfig::default());
+        assert!(derived.pad(5).is_empty());
+        assert_eq!(derived.overrides.keys().collect::<Vec<_>>(), vec![&5]);
+    }
+
+    #[test]
+    fn test_board_padset_name() {
+        let board: BoardConfig = serde_json::from_str(r#"{ "name": "code", "base_pads": "code", "modifier_pads": { "ctrl+Shift": "code/cs" } }"#).unwrap();
+        assert_eq!(board.padset_name(&ModifierState::default()), Some("code"));
+        assert_eq!(board.padset_name(&ModifierState { ctrl: true, shift: true, ..Default::default() }), Some("code/cs"));
+        assert_eq!(board.padset_name(&ModifierState { alt: true, ..Default::default() }), Some("code"));
+
+        let board: BoardConfig = serde_json::from_str(r#"{ "name": "code", "base_pads": "code", "modifier_pads": { "Alt": "code/alt", "RAlt": "code/ralt" } }"#).unwrap();
+        assert_eq!(board.padset_name(&"RAlt".parse().unwrap()), Some("code/ralt"));
+        assert_eq!(board.padset_name(&"LAlt".parse().unwrap()), Some("code/alt"));
+        assert_eq!(board.padset_name(&"Alt".parse().unwrap()), Some("code/alt"));
+    }
+
+    #[test]
+    fn test_modifier_bindings() {
+        let settings = test_settings(json!({
+            "boards": [{ "name": "code", "base_pads": "code", "modifier_pads": {
+                "shift + ctrl": "code/cs", "Ctrl+Shift": "code/cs2", "ALT": "code/alt", "Hyper": "code/hyper"
+            } }]
+        }));
+        let settings = settings.with_file_path("settings.json");
+
+        let board = &settings.board_configs[0];
+        let bindings: Vec<(String, &str)> = board.modifier_bindings().into_iter().map(|(modifiers, padset)| (modifiers.to_string(), padset)).collect();
+        assert_eq!(bindings, vec![("Alt".to_string(), "code/alt"), ("Ctrl+Shift".to_string(), "code/cs2")]);
+        assert_eq!(board.padset_name(&"Shift+Ctrl".parse().unwrap()), Some("code/cs2"));
+
+        let mut report = ValidationReport::default();
+        settings.validate_modifier_pads(&mut report);
+        let warnings: Vec<String> = report.warnings().map(ToString::to_string).collect();
+        assert_eq!(warnings, vec![
+            "warning: settings.json: boards[0].modifier_pads.Hyper: Modifier pads 'Hyper' of board 'code' are never shown: Unknown modifier: 'Hyper'",
+            "warning: settings.json: boards[0].modifier_pads.shift + ctrl: Modifier pads 'shift + ctrl' of board 'code' are never shown, 'Ctrl+Shift' binds the same modifiers",
+        ]);
+    }
 }
\ No newline at end of file
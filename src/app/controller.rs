@@ -1,29 +1,74 @@
 /// Application controller for HotKeys Linux
 /// Handles board detection, board navigation and action execution coordination
 
-use crate::core::{Action, ActionList, Board, ModifierState, DataRepository, Resources};
-use crate::process;
+use crate::core::{perf, ActionList, ColorScheme, Pad, Board, ModifierState, DataRepository, Resources, TextStyle};
+use crate::process::{self, ProcessInfo};
 use crate::executor;
-use crate::windows::layout::{Size, WindowLayout, WindowStyle};
-use crate::windows::board::BoardWindow;
+use crate::windows::layout::{Size, WindowGeometry, WindowLayout, WindowStyle, DEFAULT_DOUBLE_TAP, DEFAULT_LONG_PRESS};
+use crate::windows::board::{BoardResult, BoardWindow, EditRequest, PadPress, ZERO_KEY_PAD};
+use crate::windows::renderer;
+use crate::windows::settings_window::PadEdit;
+use crate::components::boards::{ProfileBoard, SafeModeBoard};
 
-use super::config::{AppSettings, LayoutSettings, Profile, BoardConfig};
+use super::config::{self, AppSettings, LayoutSettings, Profile, BoardConfig, BoardKind, ZeroKey};
 use super::board_factory::BoardFactory;
 use super::json_repository::JsonRepository;
+use super::memory_repository::MemoryRepository;
+use super::sound;
+use super::usage;
+use super::counters;
+use super::json_log;
+use super::debounce;
+use super::audit::AuditLog;
+use super::notification;
+use super::instance::InstanceRequest;
 
 use anyhow::Result;
 
+use gtk4::glib;
 use gtk4::prelude::*;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::pin::pin;
 use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::Instant;
 use std::rc::Rc;
 use std::cell::RefCell;
 
+/// Id of the GTK application showing the boards
+const APPLICATION_ID: &str = "com.github.ivicakukic.hotkeys";
+/// Board data key of the window geometry, see [WindowGeometry]
+const WINDOW_GEOMETRY_KEY: &str = "window_geometry";
+/// Profile data key of the board shown last, the default board with `"default": "last_used"`
+const LAST_BOARD_KEY: &str = "last_board";
+/// Board data key of the pad selected last, e.g. "Ctrl+5"
+const LAST_PAD_KEY: &str = "last_pad";
+/// Name of the built-in board shown when the settings or a board fail, see [SafeModeBoard]
+const SAFE_MODE_BOARD: &str = "safe_mode";
+/// Log file of the file appender in log.toml, relative to the data directory
+const LOG_FILE: &str = "hotkeys/hotkeys.log";
+
+/// Board chosen by detection, with the reason for the choice
+pub struct BoardDetection {
+    pub board: BoardConfig,
+    pub reason: String,
+    pub process: Option<ProcessInfo>,
+}
+
 pub struct HotKeysApp {
     settings: AppSettings,
-    factory: BoardFactory,
+    /// Created again from the reloaded settings when a pad is edited on a board
+    factory: RefCell<Arc<BoardFactory>>,
     profile: String,
     resources: Resources,
     repository: Arc<Mutex<dyn DataRepository>>,
+    board: Option<String>,
+    detection: bool,
+    /// Error shown on the safe mode board instead of any configured board
+    safe_mode: Option<String>,
+    /// Requests of later invocations handed over while the boards are shown, see [Self::wait_window]
+    requests: Option<async_channel::Receiver<InstanceRequest>>,
 }
 
 impl HotKeysApp {
@@ -34,25 +79,124 @@ impl HotKeysApp {
         log::info!("Using profile: {}", profile);
 
         // Initialize DataRepository
-        let repo_path = resources.data_json().to_str().unwrap().to_string();
-        let repository = Arc::new(Mutex::new(JsonRepository::new(repo_path)?));
+        let repository: Arc<Mutex<dyn DataRepository>> = if settings.ephemeral() {
+            log::info!("Ephemeral mode, repository data is not saved");
+            Arc::new(Mutex::new(MemoryRepository::new()))
+        } else {
+            let repo_path = resources.data_json().to_str().unwrap().to_string();
+            Arc::new(Mutex::new(JsonRepository::new(repo_path)?))
+        };
         log::info!("Initialized DataRepository");
 
         let factory = BoardFactory::new(settings.clone())
             .with_repository(repository.clone(), profile.clone());
 
-        Ok(Self { settings, factory, profile, resources, repository })
+        Ok(Self { settings, factory: RefCell::new(Arc::new(factory)), profile, resources, repository, board: None, detection: true, safe_mode: None, requests: None })
     }
 
-    /// Main application loop - handles board navigation and action execution
-    pub fn run(&mut self) -> Result<()> {
-        log::info!("Starting HotKeys application main loop");
+    /// Start with the given board instead of detecting one
+    pub fn with_board(mut self, board: Option<String>) -> Self {
+        self.board = board;
+        self
+    }
+
+    /// Show the safe mode board with the error, e.g. when the settings failed to load
+    pub fn with_safe_mode(mut self, error: Option<String>) -> Self {
+        self.safe_mode = error;
+        self
+    }
+
+    /// Handle the requests other invocations hand over while the boards are shown (`hotkeys` when one is open)
+    pub fn with_requests(mut self, requests: Option<async_channel::Receiver<InstanceRequest>>) -> Self {
+        self.requests = requests;
+        self
+    }
+
+    /// Enable or disable active application detection (profile default board is used when disabled)
+    pub fn with_detection(mut self, detection: bool) -> Self {
+        self.detection = detection;
+        self
+    }
+
+    /// Show a board with one pad per profile in the application and return the selected profile,
+    /// `None` when it is cancelled or times out
+    async fn choose_profile(app: &gtk4::Application, resources: Resources, settings: &AppSettings) -> Result<Option<String>> {
+        let profiles: Vec<String> = settings.profiles.iter().map(|profile| profile.name.clone()).collect();
+        let color_scheme = settings.get_color_scheme(&ColorScheme::default().name).cloned().unwrap_or_default();
+        let text_style = settings.get_text_style(&TextStyle::default().name).cloned().unwrap_or_default();
+        let board: Arc<dyn Board> = Arc::new(ProfileBoard::new(color_scheme, text_style, &profiles));
+        let layout = settings.layout().clone().map(WindowLayout::from).unwrap_or_default();
+
+        let window = BoardWindow::show_with_app(app, board, None, settings.timeout(), settings.feedback(), layout, resources)?;
+        // Pads are not edited on the chooser, edit requests are dropped
+        let mut result = window.wait().await;
+        while window.take_edit().is_some() {
+            result = window.wait().await;
+        }
+        window.close().await;
+        Ok(result.selection.and_then(|(pad_id, _)| profiles.get((pad_id - 1) as usize).cloned()))
+    }
+
+    /// Main application loop - handles board navigation and action execution.
+    /// Failures are shown as a desktop notification, the board window is already closed by then
+    pub fn run(self) -> Result<()> {
+        let (initial_board_name, board) = self.start_board();
+        let controller = Rc::new(self);
+        Self::notify_failure(Self::run_application(move |app| async move {
+            controller.navigate(&app, initial_board_name, board).await
+        }))
+    }
+
+    /// Show the profile chooser first and then the boards of the chosen profile, in the same GTK application.
+    /// Nothing is shown when the chooser is closed, the `default` profile is used when it fails
+    pub fn run_choosing_profile(resources: Resources, settings: AppSettings, board: Option<String>, requests: Option<async_channel::Receiver<InstanceRequest>>) -> Result<()> {
+        Self::notify_failure(Self::run_application(move |app| async move {
+            let profile = match Self::choose_profile(&app, resources.clone(), &settings).await {
+                Ok(Some(profile)) => Some(profile),
+                Ok(None) => {
+                    log::info!("No profile chosen");
+                    return Ok(());
+                },
+                Err(e) => {
+                    log::error!("Profile chooser failed: {}", e);
+                    None
+                },
+            };
+            let controller = Self::new(resources, profile, settings)?.with_board(board).with_requests(requests);
+            let (board_name, board) = controller.start_board();
+            controller.navigate(&app, board_name, board).await
+        }))
+    }
 
-        let initial_board_config = self.detect_initial_board()?;
-        let mut board = self.factory.create_board(&initial_board_config)?;
+    fn notify_failure(result: Result<()>) -> Result<()> {
+        if let Err(e) = &result {
+            notification::show_error("HotKeys failed", &format!("{:#}", e));
+        }
+        result
+    }
 
+    /// Board to start with: the safe mode board with the error, the initial board or the safe mode board
+    /// when it cannot be created
+    fn start_board(&self) -> (String, Arc<dyn Board>) {
+        let initial_board = match &self.safe_mode {
+            Some(error) => Ok((SAFE_MODE_BOARD.to_string(), self.safe_mode_board(error))),
+            None => self.initial_board(),
+        };
+        let (initial_board_name, board) = initial_board.unwrap_or_else(|e| {
+            log::error!("Showing the safe mode board: {:#}", e);
+            (SAFE_MODE_BOARD.to_string(), self.safe_mode_board(&format!("{:#}", e)))
+        });
         log::info!("Starting with board: {}", board.title());
-        let mut timeout = self.settings.timeout();
+        (initial_board_name, board)
+    }
+
+    /// One GTK application stays alive while `main` shows its windows, e.g. navigating between boards
+    fn run_application<F, Fut>(main: F) -> Result<()>
+    where
+        F: FnOnce(gtk4::Application) -> Fut + 'static,
+        Fut: std::future::Future<Output = Result<()>> + 'static,
+    {
+        log::info!("Starting HotKeys application main loop");
 
         // Spawn uinput device creation in a new thread asynchronously
         std::thread::spawn(|| {
@@ -62,96 +206,537 @@ impl HotKeysApp {
             let _ignore = api::init_global_device();
         });
 
+        let gtk_started = Instant::now();
+        let app = gtk4::Application::builder()
+            .application_id(APPLICATION_ID)
+            .build();
+
+        let outcome: Rc<RefCell<Result<()>>> = Rc::new(RefCell::new(Ok(())));
+        let main = RefCell::new(Some(main));
+        let outcome_clone = outcome.clone();
+
+        app.connect_activate(move |app| {
+            let Some(main) = main.take() else {
+                return;
+            };
+            perf::record("GTK init", gtk_started);
+            // Keeps the application running while no window is open
+            let hold = app.hold();
+            let (app, outcome) = (app.clone(), outcome_clone.clone());
+            glib::spawn_future_local(async move {
+                *outcome.borrow_mut() = main(app.clone()).await;
+                drop(hold);
+                app.quit();
+            });
+        });
+
+        // Run the application
+        let empty_args: Vec<String> = vec![];
+        app.run_with_args(&empty_args);
+
+        log::info!("HotKeys application main loop completed");
+        outcome.replace(Ok(()))
+    }
+
+    /// Show boards until no pad navigates to another one, executing the actions of the selected pads
+    async fn navigate(&self, app: &gtk4::Application, mut current_board: String, mut board: Arc<dyn Board>) -> Result<()> {
+        let mut timeout = self.settings.timeout();
+        // Boards navigated from, for the 0 key going back
+        let mut history: Vec<String> = Vec::new();
+        let sounds = self.settings.get_profile(&self.profile)
+            .map(|profile| profile.sounds.clone())
+            .unwrap_or_default();
+
+        let mut reused_window: Option<BoardWindow> = None;
+
         loop {
-            // Show board and wait for user selection
-            let selection = self.show_dialog(board.as_ref(), timeout)?;
+            // Show board and wait for user selection, navigation without actions keeps the window open
+            self.play_sound(&sounds.open);
+            let window = match reused_window.take() {
+                Some(window) => {
+                    window.show_board(board.clone(), self.load_last_pad(&current_board));
+                    window
+                },
+                None => self.show_dialog(app, &current_board, board.clone(), timeout)?,
+            };
+            let result = self.wait_editing(&window, &current_board, &mut board).await;
+            if let Some(requested_board) = result.requested_board {
+                match self.find_board_config(&requested_board) {
+                    Some(board_config) => {
+                        log::info!("Navigating to board requested by another invocation: {}", requested_board);
+                        if board_config.name != current_board {
+                            history.push(current_board.clone());
+                        }
+                        (current_board, board) = self.create_board_or_safe_mode(board_config).await;
+                    },
+                    None => log::warn!("Board '{}' requested by another invocation not found, showing board '{}' again", requested_board, current_board),
+                }
+                reused_window = Some(window);
+                continue;
+            }
+            if result.timed_out {
+                self.play_sound(&sounds.timeout);
+            }
 
-            match selection {
+            match result.selection {
                 Some((pad_id, modifier_state)) => {
-                    log::info!("User selected pad {} with modifiers: {}", pad_id, modifier_state.to_string());
+                    let modifier_state = board.modifiers(&modifier_state);
+                    log::info!("User selected pad {} with modifiers: {} ({:?})", pad_id, modifier_state.to_string(), result.press);
+                    self.play_sound(&sounds.select);
+                    // Enter selects the last pad again with a plain press
+                    if result.press == PadPress::Press && pad_id != ZERO_KEY_PAD {
+                        self.save_last_pad(&current_board, pad_id, &modifier_state);
+                    }
+                    if self.settings.debounce() > 0 {
+                        debounce::record_selection();
+                    }
 
                     // Determine which pad source to use based on modifier state
-                    let pad = board.pads(Some(modifier_state)).get_or_default((pad_id - 1) as usize);
+                    let pad = match (pad_id, result.press) {
+                        (ZERO_KEY_PAD, _) => self.zero_key_pad(&current_board, &history)?,
+                        (_, PadPress::Press) => board.pads(Some(&modifier_state)).get_or_default((pad_id - 1) as usize),
+                        (_, PadPress::Long) => board.pads(Some(&modifier_state)).get_or_default((pad_id - 1) as usize).long_pressed(),
+                        (_, PadPress::Double) => board.double_pads().map(|pads| pads.get_or_default((pad_id - 1) as usize)).unwrap_or_default(),
+                    };
+                    let back = pad_id == ZERO_KEY_PAD && *self.settings.zero_key() == ZeroKey::Back;
+
+                    // Actions go to the application focused before the board, so the window is closed first
+                    let new_board_config = pad.board.as_deref().and_then(|board_name| self.find_board_config(board_name));
+                    if new_board_config.is_some() && pad.actions.is_empty() {
+                        reused_window = Some(window);
+                    } else {
+                        self.close_dialog(&window, &current_board).await;
+                    }
+
+                    // Long presses, double taps and the 0 key run other actions than the pad counted in usage
+                    if result.press == PadPress::Press && pad_id != ZERO_KEY_PAD {
+                        self.record_usage(&current_board, pad_id, &modifier_state, &pad);
+                    }
 
-                    // Execute actions
-                    self.execute_actions(pad.actions)?;
+                    // Execute actions, those of the 0 key are logged as the pad of its padset
+                    let (source, source_pad) = match self.settings.zero_key() {
+                        ZeroKey::Pad { padset, pad } if pad_id == ZERO_KEY_PAD => (padset.clone(), *pad as u8),
+                        _ => (current_board.clone(), pad_id),
+                    };
+                    self.execute_actions(&source, source_pad, &modifier_state, pad)?;
 
                     // Handle potential board navigation
-                    if let Some(board_name) = pad.board {
-                        if let Some(new_board_config) = self.find_board_config(&board_name) {
-                            log::info!("Navigating to board: {}", new_board_config.name);
-                            board = self.factory.create_board(&new_board_config)?;
-                            timeout = 0; // Any navigation deactivates auto-close
-                            continue; // Show new board
+                    if let Some(new_board_config) = new_board_config {
+                        log::info!("Navigating to board: {}", new_board_config.name);
+                        if back {
+                            history.pop();
+                        } else if new_board_config.name != current_board {
+                            history.push(current_board.clone());
                         }
+                        (current_board, board) = self.create_board_or_safe_mode(new_board_config).await;
+                        timeout = 0; // Any navigation deactivates auto-close
+                        continue; // Show new board
                     }
                     // If no board navigation, exit app
                     break;
                 },
                 None => {
+                    self.close_dialog(&window, &current_board).await;
                     break; // User cancelled (Escape/timeout)
                 }
             }
         }
-
-        log::info!("HotKeys application main loop completed");
         Ok(())
     }
 
-    /// Show board dialog and wait for user selection
-    fn show_dialog(&self, board: &dyn Board, timeout: u64) -> Result<Option<(u8, ModifierState)>> {
-        log::info!("Showing board: {}", board.title());
+    /// Board navigated to with its name, the safe mode board with the error when it cannot be created
+    async fn create_board_or_safe_mode(&self, board_config: BoardConfig) -> (String, Arc<dyn Board>) {
+        match self.create_board_async(&board_config).await {
+            Ok(board) => (board_config.name, board),
+            Err(e) => {
+                log::error!("Showing the safe mode board: {:#}", e);
+                (SAFE_MODE_BOARD.to_string(), self.safe_mode_board(&format!("{:#}", e)))
+            },
+        }
+    }
 
-        // Create GTK application for this board instance
-        let app = gtk4::Application::builder()
-            .application_id("com.github.ivicakukic.hotkeys")
-            .build();
+    /// Pad of the 0 key: the configured pad, or a pad without actions navigating back or to the default board
+    /// of the profile. Without a board to go back to (or a default board) the current board is shown again
+    fn zero_key_pad(&self, current_board: &str, history: &[String]) -> Result<Pad> {
+        let navigate_to = |board_name: &str| Pad { board: Some(board_name.to_string()), ..Default::default() };
+        match self.settings.zero_key() {
+            ZeroKey::None => Ok(navigate_to(current_board)),
+            ZeroKey::Back => Ok(navigate_to(history.last().map(String::as_str).unwrap_or(current_board))),
+            ZeroKey::Home => match self.default_board_config() {
+                Ok(board_config) => Ok(navigate_to(&board_config.name)),
+                Err(e) => {
+                    log::error!("No default board for the 0 key, showing board '{}' again: {:#}", current_board, e);
+                    Ok(navigate_to(current_board))
+                },
+            },
+            ZeroKey::Pad { padset, pad } => self.factory.borrow().create_pad(current_board, padset, *pad),
+        }
+    }
 
-        // Create shared state for result communication
-        let result: Rc<RefCell<Option<(u8, ModifierState)>>> = Rc::new(RefCell::new(None));
+    /// Wait for the board window, creating the board again every `refresh` seconds of its configuration.
+    /// The board is created on a worker thread and replaces `board` when it is ready, so a selection runs the pad that was shown:
+    /// the refreshed board is dropped when a pad was selected while it was created, and a failed refresh keeps the shown board
+    async fn wait_refreshing(&self, window: &BoardWindow, board_name: &str, board: &mut Arc<dyn Board>) -> BoardResult {
+        let Some(board_config) = self.find_board_config(board_name).filter(|config| config.refresh > 0) else {
+            return self.wait_window(window).await;
+        };
+        let interval = std::time::Duration::from_secs(board_config.refresh);
+        loop {
+            match glib::future_with_timeout(interval, self.wait_window(window)).await {
+                Ok(result) => return result,
+                Err(_) => {
+                    log::debug!("Refreshing board '{}'", board_name);
+                    match self.create_board_async(&board_config).await {
+                        Ok(_) if window.has_selection() => log::debug!("Pad selected while board '{}' was refreshing, keeping the shown board", board_name),
+                        Ok(refreshed) => {
+                            *board = refreshed;
+                            window.refresh_board(board.clone());
+                        },
+                        Err(e) => log::error!("Refreshing board '{}' failed, keeping the shown board: {:#}", board_name, e),
+                    }
+                },
+            }
+        }
+    }
 
-        // Clone data for use inside connect_activate
-        let board_clone = board.clone_box();
-        let settings_feedback = self.settings.feedback();
-        let layout = self.settings.layout()
-            .clone()
-            .map(WindowLayout::from)
-            .unwrap_or_else(WindowLayout::default);
-        let resources = self.resources.clone();
-        let result_clone = result.clone();
+    /// Wait for the board window, raising it when another invocation hands over a request meanwhile.
+    /// A request for a board ends the wait with [BoardResult::requested_board]
+    async fn wait_window(&self, window: &BoardWindow) -> BoardResult {
+        let Some(requests) = &self.requests else {
+            return window.wait().await;
+        };
+        let mut wait = pin!(window.wait());
+        loop {
+            let mut request = pin!(requests.recv());
+            let next = std::future::poll_fn(|cx| match wait.as_mut().poll(cx) {
+                Poll::Ready(result) => Poll::Ready(Ok(result)),
+                Poll::Pending => request.as_mut().poll(cx).map(Err),
+            }).await;
+            match next {
+                Ok(result) => return result,
+                Err(Ok(request)) => {
+                    if request.profile.as_ref().is_some_and(|profile| *profile != self.profile) {
+                        log::warn!("Profile {:?} requested by another invocation is not used, the boards of profile '{}' stay open", request.profile, self.profile);
+                    }
+                    window.present();
+                    if request.board.is_some() {
+                        return BoardResult { requested_board: request.board, ..Default::default() };
+                    }
+                },
+                Err(Err(_)) => return wait.await,
+            }
+        }
+    }
 
-        app.connect_activate(move |app| {
-            match BoardWindow::show_with_app(app, board_clone.as_ref(), timeout, settings_feedback, layout.clone(), resources.clone(), result_clone.clone()) {
-                Ok(()) => {
-                    log::info!("Board window setup completed");
+    /// Wait for the board window like [Self::wait_refreshing], changing the pads requested in edit mode in between.
+    /// The changed board replaces `board`, failed changes are shown as a notification and the board stays open
+    async fn wait_editing(&self, window: &BoardWindow, board_name: &str, board: &mut Arc<dyn Board>) -> BoardResult {
+        loop {
+            let result = self.wait_refreshing(window, board_name, board).await;
+            if result.requested_board.is_some() {
+                return result;
+            }
+            let Some(request) = window.take_edit() else {
+                return result;
+            };
+            let changed = match &request {
+                EditRequest::Pad(pad_id, modifier_state) => self.edit_pad(window, board_name, *pad_id, modifier_state).await,
+                EditRequest::Swap(pad_id, other, modifier_state) => self.swap_pads(board_name, *pad_id, *other, modifier_state),
+            };
+            match changed {
+                Ok(Some(changed)) => {
+                    *board = changed;
+                    window.refresh_board(board.clone());
                 },
+                Ok(None) => {},
                 Err(e) => {
-                    log::error!("Failed to show board: {}", e);
-                }
+                    log::error!("Changing the pads of board '{}' ({:?}) failed: {:#}", board_name, request, e);
+                    notification::show_error("Pads not saved", &format!("{:#}", e));
+                },
             }
-        });
+        }
+    }
 
-        // Run the application
-        let empty_args: Vec<String> = vec![];
-        app.run_with_args(&empty_args);
+    /// Padset shown on a configured board while the modifiers are held, read again since the files may have
+    /// changed since the board was shown. `None` for built-in boards and boards without pads
+    fn edited_padset(&self, board_name: &str, modifier_state: &ModifierState) -> Result<Option<(AppSettings, String)>> {
+        let settings = config::load_settings_unvalidated(&self.resources)?;
+        let padset_name = settings.board_configs.iter()
+            .find(|config| config.name == board_name && matches!(config.kind, BoardKind::Static))
+            .and_then(|config| config.padset_name(modifier_state))
+            .map(str::to_string);
+        if padset_name.is_none() {
+            log::warn!("Board '{}' has no configured pads for {}, nothing to change", board_name, modifier_state);
+        }
+        Ok(padset_name.map(|padset_name| (settings, padset_name)))
+    }
+
+    /// Edit a pad of a configured board in a dialog, saving its padset into the file it was loaded from
+    async fn edit_pad(&self, window: &BoardWindow, board_name: &str, pad_id: u8, modifier_state: &ModifierState) -> Result<Option<Arc<dyn Board>>> {
+        let Some((settings, padset_name)) = self.edited_padset(board_name, modifier_state)? else {
+            return Ok(None);
+        };
+
+        let edit = PadEdit::open(&settings, &padset_name, pad_id as usize)?;
+        let shown_pad = edit.shown_pad(&settings, pad_id as usize)?;
+        let title = format!("HotKeys - {} pad {}", padset_name, pad_id);
+        let (check_edit, resources) = (edit.clone(), self.resources.clone().with_icon_theme(renderer::has_theme_icon));
+        let check = move |pad: &config::PadConfig| check_edit.check(&settings, &resources, pad);
+        let Some(pad) = window.edit_pad(&title, &shown_pad, check).await else {
+            return Ok(None);
+        };
+        edit.save(&pad)?;
+        self.reload_board(board_name).map(Some)
+    }
+
+    /// Swap two pads of a configured board, saving their padset into the file it was loaded from
+    fn swap_pads(&self, board_name: &str, pad_id: u8, other: u8, modifier_state: &ModifierState) -> Result<Option<Arc<dyn Board>>> {
+        let Some((settings, padset_name)) = self.edited_padset(board_name, modifier_state)? else {
+            return Ok(None);
+        };
+        PadEdit::open(&settings, &padset_name, pad_id as usize)?.swap(&settings, other as usize)?;
+        self.reload_board(board_name).map(Some)
+    }
+
+    /// Create the board again from the reloaded settings after its pads were changed,
+    /// the board factory is replaced so boards navigated to later show the changes too
+    fn reload_board(&self, board_name: &str) -> Result<Arc<dyn Board>> {
+        let settings = config::load_settings(&self.resources)?;
+        let board_config = settings.board_configs.iter()
+            .find(|config| config.name == board_name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Board '{}' not found", board_name))?;
+        *self.factory.borrow_mut() = Arc::new(BoardFactory::new(settings)
+            .with_repository(self.repository.clone(), self.profile.clone()));
+        self.create_board(&board_config)
+    }
+
+    /// Board detected or configured to start with
+    fn initial_board(&self) -> Result<(String, Arc<dyn Board>)> {
+        let board_config = match &self.board {
+            Some(board_name) => self.find_board_config(board_name)
+                .ok_or_else(|| anyhow::anyhow!("Board '{}' not found", board_name))?,
+            None if self.detection => perf::measure("detection", || self.detect_initial_board())?,
+            None => self.default_board_config()?,
+        };
+        let board = self.create_board(&board_config)?;
+        Ok((board_config.name, board))
+    }
+
+    /// Create a board, turning a panic of the board creation into an error
+    fn create_board(&self, board_config: &BoardConfig) -> Result<Arc<dyn Board>> {
+        std::panic::catch_unwind(AssertUnwindSafe(|| self.factory.borrow().create_board(board_config)))
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Creating board '{}' panicked", board_config.name)))
+    }
+
+    /// Create a board on a worker thread, so running its text commands does not block the GTK main loop
+    async fn create_board_async(&self, board_config: &BoardConfig) -> Result<Arc<dyn Board>> {
+        let (factory, config) = (self.factory.borrow().clone(), board_config.clone());
+        gtk4::gio::spawn_blocking(move || factory.create_board(&config))
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Creating board '{}' panicked", board_config.name)))
+    }
+
+    fn safe_mode_board(&self, error: &str) -> Arc<dyn Board> {
+        let settings_file = self.resources.settings_file()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.settings.file_path().to_string());
+        let log_file = dirs::data_dir().unwrap_or_default().join(LOG_FILE);
+        Arc::new(SafeModeBoard::new(error, &settings_file, &log_file.to_string_lossy()))
+    }
+
+    /// Execute the actions of a single pad without showing any board
+    pub fn run_pad(&self, board_name: &str, pad_id: u8, modifier_state: ModifierState) -> Result<()> {
+        let board_config = self.find_board_config(board_name)
+            .ok_or_else(|| anyhow::anyhow!("Board '{}' not found", board_name))?;
+        let board = self.create_board(&board_config)?;
+
+        let pad = board.pads(Some(&modifier_state)).get_or_default((pad_id - 1) as usize);
+        log::info!("Running pad {} with modifiers: {} of board: {}", pad_id, modifier_state, board.title());
+
+        if pad.actions.is_empty() {
+            log::warn!("Pad {} of board '{}' has no actions", pad_id, board_name);
+        }
+        if let Some(target) = &pad.board {
+            log::info!("Ignoring navigation to board '{}' in headless mode", target);
+        }
+
+        self.record_usage(board_name, pad_id, &modifier_state, &pad);
+        self.execute_actions(board_name, pad_id, &modifier_state, pad)
+    }
+
+    /// Render a board into a PNG file in the configured window size, without showing it
+    pub fn render_board(&self, board_name: &str, path: &Path) -> Result<()> {
+        let board_config = self.find_board_config(board_name)
+            .ok_or_else(|| anyhow::anyhow!("Board '{}' not found", board_name))?;
+        let board = self.create_board(&board_config)?;
+
+        let size = self.window_layout().size;
+        log::info!("Rendering board '{}' into {}", board.title(), path.display());
+        renderer::write_png(board.as_ref(), size.width as i32, size.height as i32, &self.resources, path)
+    }
+
+    /// Play one of the profile sounds, if configured
+    fn play_sound(&self, sound: &Option<String>) {
+        if let Some(sound) = sound {
+            sound::play(sound, &self.resources);
+        }
+    }
+
+    /// Window size and position the board had when it was last closed
+    fn load_window_geometry(&self, board_name: &str) -> Option<WindowGeometry> {
+        let value = self.repository.lock().ok()?.get_board_data(&self.profile, board_name, WINDOW_GEOMETRY_KEY)?;
+        value.parse()
+            .inspect_err(|e| log::warn!("Ignoring geometry of board '{}': {}", board_name, e))
+            .ok()
+    }
+
+    /// Remember the window geometry of a board, failures are only logged
+    fn save_window_geometry(&self, board_name: &str, geometry: WindowGeometry) {
+        let result = self.repository.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire repository lock: {}", e))
+            .and_then(|mut repository| {
+                repository.set_board_data(&self.profile, board_name, WINDOW_GEOMETRY_KEY, &geometry.to_string())?;
+                repository.flush()
+            });
+
+        match result {
+            Ok(()) => log::info!("Saved geometry {} of board '{}'", geometry, board_name),
+            Err(e) => log::warn!("Failed to save geometry of board '{}': {}", board_name, e),
+        }
+    }
+
+    /// Pad selected last on a board, with its modifiers
+    fn load_last_pad(&self, board_name: &str) -> Option<(u8, ModifierState)> {
+        let value = self.repository.lock().ok()?.get_board_data(&self.profile, board_name, LAST_PAD_KEY)?;
+        let (modifiers, pad) = value.rsplit_once('+').unwrap_or(("", &value));
+        Some((pad.parse().ok()?, modifiers.parse().ok()?))
+    }
+
+    /// Remember the pad selected on a board, failures are only logged
+    fn save_last_pad(&self, board_name: &str, pad_id: u8, modifier_state: &ModifierState) {
+        let value = match modifier_state.is_none() {
+            true => pad_id.to_string(),
+            false => format!("{}+{}", modifier_state, pad_id),
+        };
+        let result = self.repository.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire repository lock: {}", e))
+            .and_then(|mut repository| {
+                repository.set_board_data(&self.profile, board_name, LAST_PAD_KEY, &value)?;
+                repository.flush()
+            });
+
+        if let Err(e) = result {
+            log::warn!("Failed to save last pad of board '{}': {}", board_name, e);
+        }
+    }
+
+    /// Remember the board shown last in the profile, failures are only logged
+    fn save_last_board(&self, board_name: &str) {
+        if board_name == SAFE_MODE_BOARD {
+            return;
+        }
+        let result = self.repository.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire repository lock: {}", e))
+            .and_then(|mut repository| {
+                if repository.get_profile_data(&self.profile, LAST_BOARD_KEY).as_deref() == Some(board_name) {
+                    return Ok(());
+                }
+                repository.set_profile_data(&self.profile, LAST_BOARD_KEY, board_name)?;
+                repository.flush()
+            });
+
+        if let Err(e) = result {
+            log::warn!("Failed to save last board '{}': {}", board_name, e);
+        }
+    }
+
+    /// Show board window, the selection is awaited on the returned window
+    fn show_dialog(&self, app: &gtk4::Application, board_name: &str, board: Arc<dyn Board>, timeout: u64) -> Result<BoardWindow> {
+        log::info!("Showing board: {}", board.title());
+
+        let mut layout = self.window_layout();
+        if layout.style.has_decorations() {
+            layout.geometry = self.load_window_geometry(board_name);
+        }
+        layout.zero_key = *self.settings.zero_key() != ZeroKey::None;
+
+        let last_pad = self.load_last_pad(board_name);
+        let window = BoardWindow::show_with_app(app, board, last_pad, timeout, self.settings.feedback(), layout, self.resources.clone())?;
+        log::info!("Board window setup completed");
+        Ok(window)
+    }
+
+    /// Close board window and remember its geometry for the board shown last
+    async fn close_dialog(&self, window: &BoardWindow, board_name: &str) {
+        let result = window.close().await;
+        self.save_last_board(board_name);
 
-        let final_result = result.borrow().clone();
+        // Only decorated windows can be resized and moved by the user
+        if !self.window_layout().style.has_decorations() {
+            return;
+        }
+        if let Some(geometry) = result.geometry.filter(|geometry| self.load_window_geometry(board_name) != Some(*geometry)) {
+            self.save_window_geometry(board_name, geometry);
+        }
+    }
+
+    fn window_layout(&self) -> WindowLayout {
+        self.settings.layout()
+            .clone()
+            .map(WindowLayout::from)
+            .unwrap_or_else(WindowLayout::default)
+    }
+
+    /// Execute the actions of a pad, writing a JSON log record of the execution when configured
+    fn execute_actions(&self, board_name: &str, pad_id: u8, modifier_state: &ModifierState, pad: Pad) -> Result<()> {
+        let Some(json_log) = self.settings.json_log().filter(|_| !pad.actions.is_empty()) else {
+            return self.run_actions(board_name, pad_id, modifier_state, pad);
+        };
+
+        let started = Instant::now();
+        let record = json_log::PadRecord::new(&self.profile, board_name, pad_id, modifier_state, &pad.actions);
+        let result = self.run_actions(board_name, pad_id, modifier_state, pad);
+        if let Err(e) = json_log::write(json_log, &record.with_result(started.elapsed(), &result)) {
+            log::warn!("Failed to write JSON log record to {}: {}", json_log, e);
+        }
+        result
+    }
 
-        Ok(final_result)
+    /// Count the execution of a pad with actions in its usage, see [usage::record_pad]
+    fn record_usage(&self, board_name: &str, pad_id: u8, modifier_state: &ModifierState, pad: &Pad) {
+        if pad.actions.is_empty() {
+            return;
+        }
+        if let Err(e) = usage::record_pad(&self.repository, &self.profile, board_name, pad_id, modifier_state) {
+            log::warn!("Failed to record usage of pad {} of board '{}': {}", pad_id, board_name, e);
+        }
     }
 
-    /// Execute actions
-    fn execute_actions(&mut self, actions: Vec<Action>) -> Result<()> {
+    /// Execute the actions of a pad, counting it in its counter
+    fn run_actions(&self, board_name: &str, pad_id: u8, modifier_state: &ModifierState, pad: Pad) -> Result<()> {
+        if let Some(counter) = &pad.counter {
+            match counters::increment(&self.repository, &self.profile, counter) {
+                Ok(count) => log::info!("Counter '{}' is now {}", counter, count),
+                Err(e) => log::warn!("Failed to increment counter '{}': {}", counter, e),
+            }
+        }
+
+        let actions = pad.actions;
         if !actions.is_empty() {
             log::info!("Processing {} actions", actions.len());
-            let keyboard_layout = self.settings.get_keyboard_layout();
+            let keyboard_layout = self.settings.get_profile_keyboard_layout(&self.profile);
             let delay = self.settings.delay();
+            let redact = self.settings.privacy() || pad.sensitive;
+            let audit = self.settings.audit_log()
+                .map(|path| AuditLog::new(path, &self.profile).with_pad(board_name, pad_id, modifier_state).with_redact(pad.sensitive));
 
             let (background_actions, main_actions) = actions.split();
 
             let keyboard_layout_clone = keyboard_layout.clone();
             let repository_clone = self.repository.clone();
             let profile_clone = self.profile.clone();
+            let audit_clone = audit.clone();
             let join_handle = std::thread::spawn(move || {
                 // Giving the desktop manager enough time to return focus to the target application
                 if background_actions.is_delayed() {
@@ -162,6 +747,8 @@ impl HotKeysApp {
                     &keyboard_layout_clone,
                     Some(repository_clone),
                     Some(&profile_clone),
+                    redact,
+                    audit_clone.as_ref(),
                 ).map_err(|e| format!("Failed to execute background actions: {}", e))
             });
 
@@ -182,12 +769,21 @@ impl HotKeysApp {
                 &keyboard_layout,
                 Some(self.repository.clone()),
                 Some(&self.profile),
+                redact,
+                audit.as_ref(),
             );
         }
         Ok(())
     }
 
     fn detect_initial_board(&self) -> Result<BoardConfig> {
+        let detection = self.detect_board()?;
+        log::info!("Detected board '{}': {}", detection.board.name, detection.reason);
+        Ok(detection.board)
+    }
+
+    /// Detect the board for the current profile and explain the choice
+    pub fn detect_board(&self) -> Result<BoardDetection> {
         let profile = self.settings.get_profile(&self.profile)?;
         let profile_boards = self.get_profile_board_configs(profile);
 
@@ -195,8 +791,8 @@ impl HotKeysApp {
             .filter(|b| b.detection.is_xprop()).copied().collect();
         let ps_boards: Vec<&BoardConfig> = profile_boards.iter()
             .filter(|b| b.detection.is_ps()).copied().collect();
-        let default_board = self.find_board_config(&profile.default)
-            .ok_or_else(|| anyhow::anyhow!("Default board '{}' not found", profile.default))?;
+        let default_board = self.default_board_config()?;
+        let mut active_process = None;
 
         if !xprop_boards.is_empty() {
             if process::is_x11_available() {
@@ -206,8 +802,13 @@ impl HotKeysApp {
                         if let Some(board) = xprop_boards.iter().find(|board| {
                             board.detection.matches(&process_info.name)
                         }) {
-                            return Ok((**board).clone());
+                            return Ok(BoardDetection {
+                                board: (**board).clone(),
+                                reason: format!("active process '{}' matches {:?}", process_info.name, board.detection),
+                                process: Some(process_info),
+                            });
                         }
+                        active_process = Some(process_info);
                     },
                     Err(e) => {
                         log::warn!("Could not detect active process: {}", e);
@@ -221,11 +822,33 @@ impl HotKeysApp {
         if !ps_boards.is_empty() {
             if let Some(process_board) = self.find_board_among_running_processes(&ps_boards, &default_board) {
                 log::info!("Found board based on running processes: {}", process_board.name);
-                return Ok(process_board);
+                return Ok(BoardDetection {
+                    reason: format!("a running process matches {:?}", process_board.detection),
+                    board: process_board,
+                    process: active_process,
+                });
             }
         }
 
-        Ok(default_board)
+        Ok(BoardDetection {
+            board: default_board,
+            reason: format!("no detection rule matched, using default board of profile '{}'", profile.name),
+            process: active_process,
+        })
+    }
+
+    fn default_board_config(&self) -> Result<BoardConfig> {
+        let profile = self.settings.get_profile(&self.profile)?;
+        if !profile.default_is_last_used() {
+            return self.find_board_config(&profile.default)
+                .ok_or_else(|| anyhow::anyhow!("Default board '{}' not found", profile.default));
+        }
+
+        let last_board = self.repository.lock().ok()
+            .and_then(|repository| repository.get_profile_data(&self.profile, LAST_BOARD_KEY));
+        last_board.and_then(|board_name| self.find_board_config(&board_name))
+            .or_else(|| profile.boards.first().and_then(|board_name| self.find_board_config(board_name)))
+            .ok_or_else(|| anyhow::anyhow!("Profile '{}' has no board to start with", profile.name))
     }
 
     fn get_profile_board_configs(&self, profile: &Profile) -> Vec<&BoardConfig> {
@@ -291,6 +914,18 @@ impl From<LayoutSettings> for WindowLayout {
                 height: layout.height as f64,
             },
             style: WindowStyle::from_string(&layout.window_style),
+            fade: layout.fade,
+            placement: layout.placement,
+            keep_above: layout.keep_above,
+            skip_taskbar: layout.skip_taskbar,
+            grab_keyboard: layout.grab_keyboard,
+            no_focus: layout.no_focus,
+            sticky_modifiers: layout.sticky_modifiers,
+            long_press: layout.long_press.unwrap_or(DEFAULT_LONG_PRESS),
+            double_tap: layout.double_tap.unwrap_or(DEFAULT_DOUBLE_TAP),
+            // Set from the settings when the boards of the profile are shown
+            zero_key: false,
+            geometry: None,
         }
     }
 }
\ No newline at end of file
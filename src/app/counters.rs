@@ -0,0 +1,51 @@
+/// Counter pads: named counters kept in the data repository (profile data `counter.<name>`), incremented
+/// each time a pad with the counter is selected and shown in its header and text with the `{count}` placeholder
+
+use crate::core::DataRepository;
+use anyhow::Result;
+use std::sync::Mutex;
+
+const COUNT_PLACEHOLDER: &str = "{count}";
+
+fn key(name: &str) -> String {
+    format!("counter.{}", name)
+}
+
+/// Current value of a counter, 0 until it was incremented
+pub fn value(repository: &dyn DataRepository, profile: &str, name: &str) -> u64 {
+    repository.get_profile_data(profile, &key(name))
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Increment a counter and persist it, returns the new value
+pub fn increment(repository: &Mutex<dyn DataRepository>, profile: &str, name: &str) -> Result<u64> {
+    let mut repository = repository.lock()
+        .map_err(|e| anyhow::anyhow!("Failed to acquire repository lock: {}", e))?;
+    let count = value(&*repository, profile, name) + 1;
+    repository.set_profile_data(profile, &key(name), &count.to_string())?;
+    repository.flush()?;
+    Ok(count)
+}
+
+/// Replace the `{count}` placeholder with the counter value
+pub fn show_count(text: &str, count: u64) -> String {
+    text.replace(COUNT_PLACEHOLDER, &count.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::memory_repository::MemoryRepository;
+
+    #[test]
+    fn test_counter() {
+        let repository = Mutex::new(MemoryRepository::new());
+        assert_eq!(value(&*repository.lock().unwrap(), "default", "water"), 0);
+        assert_eq!(increment(&repository, "default", "water").unwrap(), 1);
+        assert_eq!(increment(&repository, "default", "water").unwrap(), 2);
+        assert_eq!(value(&*repository.lock().unwrap(), "default", "water"), 2);
+        assert_eq!(value(&*repository.lock().unwrap(), "other", "water"), 0);
+        assert_eq!(show_count("Glasses: {count}", 2), "Glasses: 2");
+    }
+}
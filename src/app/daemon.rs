@@ -0,0 +1,278 @@
+/// Daemon mode for HotKeys Linux
+/// Stays in the background and opens boards when configured trigger keys are pressed,
+/// or when `hotkeys` is invoked again (handed over through the instance socket).
+/// Settings are reloaded when the settings file or one of its includes changes
+
+use crate::core::{perf, Resources};
+use crate::input::{api, evdev::{self, KeyChord}};
+
+use super::config::{self, AppSettings};
+use super::controller::HotKeysApp;
+use super::tray::HotKeysTray;
+use super::instance::{InstanceReply, InstanceServer};
+use super::notification;
+use super::debounce;
+use super::watcher::SettingsWatcher;
+
+use anyhow::Result;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+/// How long a `hotkeys profile` request waits for the daemon, which handles it only after an open board closes
+const PROFILE_REPLY_WAIT: Duration = Duration::from_secs(2);
+
+/// Requests handled by the daemon main loop
+#[derive(Debug, Clone)]
+pub enum DaemonEvent {
+    /// Open a board (`None` values fall back to the daemon profile / board detection)
+    Trigger { profile: Option<String>, board: Option<String> },
+    /// Use another profile for triggers without one, until the daemon stops. The outcome is sent to `reply`
+    SetProfile { profile: String, reply: Sender<InstanceReply> },
+    /// Reload settings from disk
+    Reload,
+    /// Enable or disable active application detection
+    SetDetection(bool),
+    /// Pause or resume keyboard input injection
+    SetInjectionPaused(bool),
+    /// Stop the daemon
+    Quit,
+}
+
+pub struct Daemon {
+    resources: Resources,
+    profile: String,
+    settings: AppSettings,
+    detection: bool,
+    timeout: Option<u64>,
+    ephemeral: bool,
+    sender: Sender<DaemonEvent>,
+    receiver: Receiver<DaemonEvent>,
+    tray: Option<ksni::blocking::Handle<HotKeysTray>>,
+    watcher: Option<SettingsWatcher>,
+    /// Stops the trigger key listeners, to restart them with changed triggers
+    listener_stop: Option<Arc<AtomicBool>>,
+}
+
+impl Daemon {
+    pub fn new(resources: Resources, profile: Option<String>, settings: AppSettings) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            resources,
+            profile: profile.unwrap_or_else(|| "default".to_string()),
+            settings,
+            detection: true,
+            timeout: None,
+            ephemeral: false,
+            sender,
+            receiver,
+            tray: None,
+            watcher: None,
+            listener_stop: None,
+        }
+    }
+
+    /// Keep the timeout given on the command line across settings reloads
+    pub fn with_timeout(mut self, timeout: Option<u64>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Keep the ephemeral mode given on the command line across settings reloads
+    pub fn with_ephemeral(mut self, ephemeral: bool) -> Self {
+        self.ephemeral = ephemeral;
+        self
+    }
+
+    /// Start trigger key listeners and the tray icon, and process their events
+    pub fn run(&mut self) -> Result<()> {
+        let Some(instance) = InstanceServer::bind()? else {
+            anyhow::bail!("HotKeys is already running");
+        };
+        let sender = self.sender.clone();
+        instance.spawn(move |request| {
+            let Some(profile) = request.session_profile else {
+                let _ = sender.send(DaemonEvent::Trigger { profile: request.profile, board: request.board });
+                return InstanceReply::Done;
+            };
+            let (reply, replied) = mpsc::channel();
+            let _ = sender.send(DaemonEvent::SetProfile { profile, reply });
+            replied.recv_timeout(PROFILE_REPLY_WAIT).unwrap_or(InstanceReply::Pending)
+        })?;
+
+        if self.settings.listener().is_some() {
+            self.start_listener()?;
+        }
+
+        if self.settings.tray() {
+            match HotKeysTray::start(&self.settings, self.sender.clone()) {
+                Ok(handle) => self.tray = Some(handle),
+                Err(e) => log::error!("{}", e),
+            }
+        }
+
+        let sender = self.sender.clone();
+        let directories = self.settings.include_directories(&self.resources);
+        let watcher = SettingsWatcher::start(&self.settings.source_files(), &directories, move || {
+            let _ = sender.send(DaemonEvent::Reload);
+        });
+        match watcher {
+            Ok(watcher) => self.watcher = Some(watcher),
+            Err(e) => log::error!("Settings will not be reloaded on change: {}", e),
+        }
+
+        log::info!("HotKeys daemon running");
+        while let Ok(event) = self.receiver.recv() {
+            if matches!(event, DaemonEvent::Quit) {
+                break;
+            }
+            self.handle_event(event);
+        }
+
+        if let Some(tray) = self.tray.take() {
+            tray.shutdown().wait();
+        }
+
+        log::info!("HotKeys daemon stopped");
+        Ok(())
+    }
+
+    fn start_listener(&mut self) -> Result<()> {
+        let listener = self.settings.listener().clone()
+            .ok_or_else(|| anyhow::anyhow!("No 'listener' configured in settings"))?;
+
+        if listener.triggers.is_empty() {
+            anyhow::bail!("No triggers configured for the listener");
+        }
+
+        let chords = listener.triggers.iter()
+            .map(|trigger| KeyChord::from_str(&trigger.keys).map_err(anyhow::Error::msg))
+            .collect::<Result<Vec<_>>>()?;
+        let keys: Vec<u16> = chords.iter().flat_map(|chord| chord.keys()).collect();
+
+        let devices = evdev::find_keyboards(&listener.devices, &keys)?;
+        if devices.is_empty() {
+            anyhow::bail!("No input devices found for the listener. Make sure you are a member of the 'input' group");
+        }
+
+        let (sender, stop) = (self.sender.clone(), Arc::new(AtomicBool::new(false)));
+        self.listener_stop = Some(stop.clone());
+        evdev::spawn_listeners(devices, chords, stop, move |index| {
+            let trigger = &listener.triggers[index];
+            let _ = sender.send(DaemonEvent::Trigger {
+                profile: trigger.profile.clone(),
+                board: trigger.board.clone(),
+            });
+        });
+
+        Ok(())
+    }
+
+    /// Stop the trigger key listeners and start them with the reloaded listener settings
+    fn restart_listener(&mut self) {
+        if let Some(stop) = self.listener_stop.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        if self.settings.listener().is_none() {
+            log::info!("Listener removed from settings, trigger keys stopped");
+            return;
+        }
+        match self.start_listener() {
+            Ok(()) => log::info!("Listener restarted with the reloaded triggers"),
+            Err(e) => {
+                log::error!("Failed to restart the listener: {}", e);
+                notification::show_error("HotKeys listener not restarted", &e.to_string());
+            },
+        }
+    }
+
+    fn handle_event(&mut self, event: DaemonEvent) {
+        match event {
+            DaemonEvent::Trigger { profile, board } => {
+                if debounce::is_debounced(self.settings.debounce()) {
+                    log::info!("Ignoring trigger received right after a pad selection");
+                    return;
+                }
+                let profile = profile.unwrap_or_else(|| self.profile.clone());
+                log::info!("Opening board for profile '{}'", profile);
+                perf::start();
+
+                match HotKeysApp::new(self.resources.clone(), Some(profile), self.settings.clone()) {
+                    Ok(app) => {
+                        if let Err(e) = app.with_board(board).with_detection(self.detection).run() {
+                            log::error!("HotKeys application failed: {}", e);
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Failed to create HotKeys application: {}", e);
+                    }
+                }
+                perf::report();
+
+                // Triggers pressed while the board was open are dropped, other events are still handled
+                while let Ok(pending) = self.receiver.try_recv() {
+                    match pending {
+                        DaemonEvent::Trigger { .. } => log::info!("Ignoring trigger received while board was open"),
+                        DaemonEvent::Quit => {
+                            let _ = self.sender.send(DaemonEvent::Quit);
+                            break;
+                        },
+                        other => self.handle_event(other),
+                    }
+                }
+            },
+            DaemonEvent::SetProfile { profile, reply } => {
+                match self.settings.get_profile(&profile) {
+                    Ok(_) => {
+                        log::info!("Using profile '{}' for this session", profile);
+                        self.profile = profile;
+                        let _ = reply.send(InstanceReply::Done);
+                    },
+                    Err(e) => {
+                        log::error!("Keeping profile '{}': {}", self.profile, e);
+                        notification::show_error("HotKeys profile not changed", &e.to_string());
+                        let _ = reply.send(InstanceReply::Failed(e.to_string()));
+                    },
+                }
+            },
+            DaemonEvent::Reload => {
+                match config::load_settings(&self.resources) {
+                    Ok(settings) => {
+                        log::info!("Settings reloaded");
+                        let settings = match self.timeout {
+                            Some(timeout) => settings.with_timeout(timeout),
+                            None => settings,
+                        };
+                        let settings = if self.ephemeral { settings.with_ephemeral() } else { settings };
+                        if let Some(tray) = &self.tray {
+                            tray.update(|tray| tray.update_settings(&settings));
+                        }
+                        let directories = settings.include_directories(&self.resources);
+                        if let Some(Err(e)) = self.watcher.as_mut().map(|watcher| watcher.watch(&settings.source_files(), &directories)) {
+                            log::error!("Failed to watch settings files: {}", e);
+                        }
+                        let listener_changed = settings.listener() != self.settings.listener();
+                        self.settings = settings;
+                        if listener_changed {
+                            self.restart_listener();
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Failed to reload settings, keeping previous ones: {}", e);
+                        notification::show_error("HotKeys settings not reloaded", &e.to_string());
+                    },
+                }
+            },
+            DaemonEvent::SetDetection(detection) => {
+                log::info!("Application detection {}", if detection { "enabled" } else { "disabled" });
+                self.detection = detection;
+            },
+            DaemonEvent::SetInjectionPaused(paused) => {
+                api::set_injection_paused(paused);
+            },
+            DaemonEvent::Quit => {},
+        }
+    }
+}
@@ -0,0 +1,61 @@
+/// Re-trigger debounce: a board summoned again within `debounce` milliseconds of a pad selection
+/// is not opened, so a doubled hotkey press does not execute the actions twice.
+/// The selection time is shared by all HotKeys processes through a file in the runtime directory
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const STAMP_NAME: &str = "hotkeys.selected";
+
+/// $XDG_RUNTIME_DIR/hotkeys.selected, or a per-user file in the temp directory
+fn stamp_path() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join(STAMP_NAME),
+        None => std::env::temp_dir().join(format!("hotkeys-{}.selected", unsafe { libc::getuid() })),
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_millis() as u64).unwrap_or_default()
+}
+
+/// Remember that a pad was selected now, failures are only logged
+pub fn record_selection() {
+    if let Err(e) = record_at(&stamp_path(), now_millis()) {
+        log::warn!("Failed to record the pad selection time: {}", e);
+    }
+}
+
+/// A pad was selected less than `debounce` milliseconds ago (never with 0)
+pub fn is_debounced(debounce: u64) -> bool {
+    debounce > 0 && is_debounced_at(&stamp_path(), debounce, now_millis())
+}
+
+fn record_at(path: &Path, now: u64) -> Result<()> {
+    std::fs::write(path, now.to_string())?;
+    Ok(())
+}
+
+fn is_debounced_at(path: &Path, debounce: u64, now: u64) -> bool {
+    std::fs::read_to_string(path).ok()
+        .and_then(|stamp| stamp.trim().parse::<u64>().ok())
+        .is_some_and(|selected| now.saturating_sub(selected) < debounce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounce() {
+        let path = std::env::temp_dir().join(format!("hotkeys-debounce-{}.selected", std::process::id()));
+        assert!(!is_debounced_at(&path, 500, 10_000));
+
+        record_at(&path, 10_000).unwrap();
+        assert!(is_debounced_at(&path, 500, 10_200));
+        assert!(!is_debounced_at(&path, 500, 10_500));
+        assert!(!is_debounced(0));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,118 @@
+/// Pad texts from command output (`text_command`): the trimmed stdout of the command, run with `sh -c`
+/// when the board is created, optionally cached in the data repository for `text_cache` seconds.
+/// The commands of a padset run in parallel, each in its own process group
+
+use crate::core::DataRepository;
+use anyhow::Result;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+/// Commands running longer are killed, so a hanging command does not keep the board from showing
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(2);
+
+fn cache_key(command: &str) -> String {
+    format!("text_command.{}", command)
+}
+
+/// Pad text from the command output, None if the command fails or prints nothing
+pub fn command_text(repository: Option<&Mutex<dyn DataRepository>>, profile: &str, board: &str, command: &str, cache_secs: u64) -> Option<String> {
+    let cached = repository.filter(|_| cache_secs > 0)
+        .and_then(|repository| repository.lock().ok()?.get_board_data(profile, board, &cache_key(command)));
+    if cached.is_some() {
+        return cached;
+    }
+
+    let text = run(command)
+        .inspect_err(|e| log::warn!("Text command '{}' failed: {}", command, e))
+        .ok()
+        .filter(|text| !text.is_empty())?;
+
+    if let Some(repository) = repository.filter(|_| cache_secs > 0) {
+        let result = repository.lock()
+            .map_err(|e| anyhow::anyhow!("Failed to acquire repository lock: {}", e))
+            .and_then(|mut repository| {
+                repository.set_board_data_with_ttl(profile, board, &cache_key(command), &text, Duration::from_secs(cache_secs))?;
+                repository.flush()
+            });
+        if let Err(e) = result {
+            log::warn!("Failed to cache the output of '{}': {}", command, e);
+        }
+    }
+    Some(text)
+}
+
+/// Pad texts of several commands with their `text_cache` seconds, run in parallel.
+/// The texts are in the order of the commands, None where there is no command or it produced no text
+pub fn command_texts(repository: Option<&Mutex<dyn DataRepository>>, profile: &str, board: &str, commands: &[Option<(&str, u64)>]) -> Vec<Option<String>> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = commands.iter()
+            .map(|command| command.map(|(command, cache_secs)| scope.spawn(move || command_text(repository, profile, board, command, cache_secs))))
+            .collect();
+        handles.into_iter()
+            .map(|handle| handle.and_then(|handle| handle.join().ok().flatten()))
+            .collect()
+    })
+}
+
+/// Run the command and return its trimmed output, failing on an exit code other than 0 or after the timeout.
+/// The command is the leader of a new process group, so a timeout kills the processes it started as well
+fn run(command: &str) -> Result<String> {
+    let child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()?;
+    let pid = child.id();
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(child.wait_with_output());
+    });
+    let output = match receiver.recv_timeout(COMMAND_TIMEOUT) {
+        Ok(output) => output?,
+        Err(_) => {
+            unsafe { libc::killpg(pid as i32, libc::SIGKILL) };
+            anyhow::bail!("timed out after {} seconds", COMMAND_TIMEOUT.as_secs());
+        }
+    };
+
+    if !output.status.success() {
+        anyhow::bail!("exited with {}", output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::memory_repository::MemoryRepository;
+
+    #[test]
+    fn test_command_text() {
+        assert_eq!(run("echo ' main '").unwrap(), "main");
+        assert!(run("exit 3").is_err());
+        assert!(run("sleep 5").is_err());
+        // The background process keeps stdout open until the group is killed
+        let started = std::time::Instant::now();
+        assert!(run("sleep 5 & wait").is_err());
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        let repository = Mutex::new(MemoryRepository::new());
+        let text = |command| command_text(Some(&repository), "default", "git", command, 60);
+        assert_eq!(text("echo main").as_deref(), Some("main"));
+        assert_eq!(text("true"), None);
+        assert_eq!(repository.lock().unwrap().get_board_data("default", "git", "text_command.echo main").as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_command_texts_in_parallel() {
+        let started = std::time::Instant::now();
+        let texts = command_texts(None, "default", "git", &[Some(("sleep 1; echo a", 0)), None, Some(("sleep 1; echo b", 0))]);
+        assert_eq!(texts, vec![Some("a".to_string()), None, Some("b".to_string())]);
+        assert!(started.elapsed() < Duration::from_millis(1900));
+    }
+}
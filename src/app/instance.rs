@@ -0,0 +1,193 @@
+/// Single-instance support for HotKeys Linux
+/// The first instance listens on a Unix socket; later invocations hand their arguments over, read the reply and exit
+
+use serde::{Deserialize, Serialize};
+use anyhow::Result;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SOCKET_NAME: &str = "hotkeys.sock";
+/// How long a later invocation waits for the reply of the running instance
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Arguments handed over from a second invocation to the running instance
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct InstanceRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board: Option<String>,
+
+    /// Profile the running daemon uses from now on for requests and triggers without one (`hotkeys profile <name>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_profile: Option<String>,
+}
+
+/// Reply of the running instance to a request
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum InstanceReply {
+    /// The request was applied
+    Done,
+    /// The request is applied later (e.g. when the open board closes), or the instance did not reply in time
+    Pending,
+    /// The request was rejected, with the reason
+    Failed(String),
+}
+
+/// Socket of the running (primary) instance, removed when dropped
+pub struct InstanceServer {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl InstanceServer {
+    /// Become the primary instance, returns `None` if another instance is already running
+    pub fn bind() -> Result<Option<Self>> {
+        Self::bind_at(&socket_path())
+    }
+
+    fn bind_at(path: &Path) -> Result<Option<Self>> {
+        if UnixStream::connect(path).is_ok() {
+            return Ok(None);
+        }
+
+        // Nobody is listening, any existing file is a leftover from a crashed instance
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+
+        match UnixListener::bind(path) {
+            Ok(listener) => {
+                log::debug!("Listening for other instances on {:?}", path);
+                Ok(Some(Self { listener, path: path.to_path_buf() }))
+            },
+            // Lost the race against another instance starting at the same time
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => Ok(None),
+            Err(e) => Err(anyhow::anyhow!("Failed to bind instance socket {:?}: {}", path, e)),
+        }
+    }
+
+    /// Handle requests from other instances on a background thread, replying with the result of `handler`
+    pub fn spawn<F>(&self, handler: F) -> Result<()>
+    where
+        F: Fn(InstanceRequest) -> InstanceReply + Send + 'static,
+    {
+        let listener = self.listener.try_clone()?;
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+
+                let mut line = String::new();
+                if let Err(e) = BufReader::new(&stream).read_line(&mut line) {
+                    log::warn!("Failed to read instance request: {}", e);
+                    continue;
+                }
+                if line.trim().is_empty() {
+                    continue; // connection probe from InstanceServer::bind
+                }
+
+                match serde_json::from_str::<InstanceRequest>(&line) {
+                    Ok(request) => {
+                        log::info!("Received request from another instance: {:?}", request);
+                        let reply = handler(request);
+                        if let Err(e) = reply_to(&stream, &reply) {
+                            log::debug!("Failed to reply {:?} to another instance: {}", reply, e);
+                        }
+                    },
+                    Err(e) => log::warn!("Invalid instance request '{}': {}", line.trim(), e),
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Drop for InstanceServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn reply_to(mut stream: &UnixStream, reply: &InstanceReply) -> Result<()> {
+    let mut line = serde_json::to_string(reply)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Hand a request over to the running instance and wait for its reply
+pub fn send(request: &InstanceRequest) -> Result<InstanceReply> {
+    send_to(&socket_path(), request)
+}
+
+fn send_to(path: &Path, request: &InstanceRequest) -> Result<InstanceReply> {
+    let mut stream = UnixStream::connect(path)
+        .map_err(|e| anyhow::anyhow!("Failed to connect to running instance: {}", e))?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+
+    // Instances of earlier versions close the connection without a reply
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+    let mut line = String::new();
+    if let Err(e) = BufReader::new(&stream).read_line(&mut line) {
+        log::warn!("No reply from the running instance: {}", e);
+    }
+    Ok(serde_json::from_str(&line).unwrap_or(InstanceReply::Pending))
+}
+
+/// $XDG_RUNTIME_DIR/hotkeys.sock, or a per-user file in the temp directory
+fn socket_path() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join(SOCKET_NAME),
+        None => std::env::temp_dir().join(format!("hotkeys-{}.sock", unsafe { libc::getuid() })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_hand_over_request() {
+        let path = std::env::temp_dir().join(format!("hotkeys-test-{}.sock", std::process::id()));
+
+        let server = InstanceServer::bind_at(&path).unwrap().expect("first instance should be primary");
+        assert!(InstanceServer::bind_at(&path).unwrap().is_none());
+
+        let (sender, receiver) = mpsc::channel();
+        server.spawn(move |request| {
+            let reply = match request.session_profile {
+                Some(_) => InstanceReply::Failed("Unknown profile".to_string()),
+                None => InstanceReply::Done,
+            };
+            sender.send(request).unwrap();
+            reply
+        }).unwrap();
+
+        let request = InstanceRequest { profile: Some("ides".to_string()), ..Default::default() };
+        assert_eq!(send_to(&path, &request).unwrap(), InstanceReply::Done);
+
+        let received = receiver.recv_timeout(std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(received, request);
+
+        let request = InstanceRequest { session_profile: Some("missing".to_string()), ..Default::default() };
+        assert_eq!(send_to(&path, &request).unwrap(), InstanceReply::Failed("Unknown profile".to_string()));
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_session_profile_request() {
+        let request = InstanceRequest { session_profile: Some("ides".to_string()), ..Default::default() };
+        let line = serde_json::to_string(&request).unwrap();
+        assert_eq!(line, r#"{"session_profile":"ides"}"#);
+        // Requests of earlier versions have no session profile
+        assert_eq!(serde_json::from_str::<InstanceRequest>(r#"{"board":"code"}"#).unwrap().session_profile, None);
+    }
+}
@@ -0,0 +1,94 @@
+/// Placeholder expansion in configuration strings.
+/// `${NAME}` is replaced with the environment variable NAME (`$${NAME}` keeps a literal `${NAME}`),
+/// `{var:name}` with a variable from settings
+
+use std::collections::BTreeMap;
+
+/// Replace every `<prefix>name}` placeholder with the value returned by `lookup`.
+/// Placeholders with unknown names are left unchanged and their names are added to `unknown`.
+pub fn expand_with<F>(text: &str, prefix: &str, lookup: F, unknown: &mut Vec<String>) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(prefix) {
+        let after_prefix = &rest[start + prefix.len()..];
+        let Some(end) = after_prefix.find('}') else {
+            break;
+        };
+
+        let name = &after_prefix[..end];
+        result.push_str(&rest[..start]);
+        match lookup(name) {
+            Some(value) => result.push_str(&value),
+            None => {
+                result.push_str(&rest[start..start + prefix.len() + end + 1]);
+                unknown.push(name.to_string());
+            }
+        }
+        rest = &after_prefix[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Expand `${NAME}` environment variable references, unknown variables are left unchanged.
+/// `$${` is an escaped `${`, e.g. for shell parameter expansion in typed text
+pub fn expand_env(text: &str, unknown: &mut Vec<String>) -> String {
+    text.split("$${")
+        .map(|part| expand_with(part, "${", |name| std::env::var(name).ok(), unknown))
+        .collect::<Vec<_>>()
+        .join("${")
+}
+
+/// Expand `{var:name}` references to settings variables, unknown variables are left unchanged
+pub fn expand_variables(text: &str, variables: &BTreeMap<String, String>, unknown: &mut Vec<String>) -> String {
+    expand_with(text, "{var:", |name| variables.get(name).cloned(), unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_with() {
+        let lookup = |name: &str| match name {
+            "HOME" => Some("/home/me".to_string()),
+            "PROJECT_DIR" => Some("${HOME}/src".to_string()),
+            _ => None,
+        };
+        let mut unknown = Vec::new();
+
+        assert_eq!(expand_with("code ${PROJECT_DIR}/app", "${", lookup, &mut unknown), "code ${HOME}/src/app");
+        assert_eq!(expand_with("${HOME}${HOME}", "${", lookup, &mut unknown), "/home/me/home/me");
+        assert_eq!(expand_with("no placeholders", "${", lookup, &mut unknown), "no placeholders");
+        assert_eq!(expand_with("open ${HOME", "${", lookup, &mut unknown), "open ${HOME");
+        assert!(unknown.is_empty());
+
+        assert_eq!(expand_with("${NOPE}/x", "${", lookup, &mut unknown), "${NOPE}/x");
+        assert_eq!(unknown, vec!["NOPE"]);
+    }
+
+    #[test]
+    fn test_expand_env_escape() {
+        let mut unknown = Vec::new();
+        let home = std::env::var("HOME").unwrap();
+
+        assert_eq!(expand_env("echo $${HOME} ${HOME}", &mut unknown), format!("echo ${{HOME}} {}", home));
+        assert_eq!(expand_env("$${1:-default}", &mut unknown), "${1:-default}");
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        let variables = BTreeMap::from([("email".to_string(), "me@example.com".to_string())]);
+        let mut unknown = Vec::new();
+
+        assert_eq!(expand_variables("Mail: {var:email}", &variables, &mut unknown), "Mail: me@example.com");
+        assert_eq!(expand_variables("{var:name} ${HOME}", &variables, &mut unknown), "{var:name} ${HOME}");
+        assert_eq!(unknown, vec!["name"]);
+    }
+}
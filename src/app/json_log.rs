@@ -0,0 +1,114 @@
+/// Structured log of pad executions as JSON lines, for shipping into journald or ELK.
+/// Written to the `json_log` file (or stderr) next to the log4rs log, one record per executed pad
+
+use crate::core::{Action, ModifierState};
+use anyhow::Result;
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// `json_log` value writing the records to stderr, e.g. for the journal of a systemd user service
+pub const STDERR: &str = "stderr";
+
+/// Record of one executed pad, action payloads are never included
+#[derive(Serialize, Debug)]
+pub struct PadRecord<'a> {
+    pub timestamp: String,
+    pub level: &'static str,
+    pub profile: &'a str,
+    pub board: &'a str,
+    pub pad: u8,
+    /// Modifiers held when the pad was selected, e.g. "Ctrl+Shift"
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub modifiers: String,
+    /// Action types, e.g. ["Shortcut", "Line"]
+    pub actions: Vec<&'static str>,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<'a> PadRecord<'a> {
+    pub fn new(profile: &'a str, board: &'a str, pad: u8, modifiers: &ModifierState, actions: &[Action]) -> Self {
+        Self {
+            timestamp: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+            level: "info",
+            profile,
+            board,
+            pad,
+            modifiers: modifiers.to_string(),
+            actions: actions.iter().map(Action::kind).collect(),
+            duration_ms: 0,
+            error: None,
+        }
+    }
+
+    /// How long the execution took and how it ended
+    pub fn with_result(mut self, duration: Duration, result: &Result<()>) -> Self {
+        self.duration_ms = duration.as_millis() as u64;
+        if let Err(e) = result {
+            self.level = "error";
+            self.error = Some(e.to_string());
+        }
+        self
+    }
+}
+
+fn log_path(json_log: &str) -> PathBuf {
+    match json_log.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((relative, home)) => home.join(relative),
+        None => PathBuf::from(json_log),
+    }
+}
+
+/// Append the record as a JSON line to the file, or write it to stderr
+pub fn write(json_log: &str, record: &impl Serialize) -> Result<()> {
+    let line = format!("{}\n", serde_json::to_string(record)?);
+    if json_log == STDERR {
+        std::io::stderr().write_all(line.as_bytes())?;
+        return Ok(());
+    }
+
+    let path = log_path(json_log);
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    // A single write of a whole line keeps the lines of concurrent processes apart
+    OpenOptions::new().create(true).append(true).open(&path)?.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_pad_records() {
+        let path = std::env::temp_dir().join(format!("hotkeys-json-log-{}.jsonl", std::process::id()));
+        let ctrl = ModifierState { ctrl: true, ..Default::default() };
+        let actions = [Action::Shortcut("Ctrl C".to_string()), Action::Line("secret".to_string())];
+
+        let ok = PadRecord::new("default", "code", 5, &ctrl, &actions)
+            .with_result(Duration::from_millis(42), &Ok(()));
+        let failed = PadRecord::new("default", "code", 1, &ModifierState::default(), &actions[..1])
+            .with_result(Duration::ZERO, &Err(anyhow::anyhow!("no uinput")));
+        write(path.to_str().unwrap(), &ok).unwrap();
+        write(path.to_str().unwrap(), &failed).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let records: Vec<serde_json::Value> = contents.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["level"], "info");
+        assert_eq!(records[0]["pad"], 5);
+        assert_eq!(records[0]["modifiers"], "Ctrl");
+        assert_eq!(records[0]["actions"], serde_json::json!(["Shortcut", "Line"]));
+        assert_eq!(records[0]["duration_ms"], 42);
+        assert!(!contents.contains("secret"));
+        assert_eq!(records[1]["level"], "error");
+        assert_eq!(records[1]["error"], "no uinput");
+        assert!(records[1].get("modifiers").is_none());
+    }
+}
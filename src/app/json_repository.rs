@@ -1,18 +1,69 @@
-use std::{collections::HashMap, fs, path::Path};
+use std::{collections::HashMap, fs, os::fd::AsRawFd, path::Path, time::Duration};
 use serde::{Deserialize, Serialize};
-use anyhow::Result;
-use crate::core::DataRepository;
+use anyhow::{Result, anyhow};
+use crate::core::{DataRepository, repository::unix_now};
+
+/// Value of a key, stored as a plain string unless it was set with a time to live
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+enum Entry {
+    Value(String),
+    /// Value with its expiry time (unix seconds)
+    Expiring { value: String, expires: u64 },
+}
+
+impl Entry {
+    fn is_expired(&self, now: u64) -> bool {
+        matches!(self, Entry::Expiring { expires, .. } if *expires <= now)
+    }
+}
+
+/// Values of one level
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+struct Entries {
+    #[serde(flatten)]
+    values: HashMap<String, Entry>,
+}
+
+impl Entries {
+    fn get(&self, key: &str, now: u64) -> Option<String> {
+        match self.values.get(key)? {
+            entry if entry.is_expired(now) => None,
+            Entry::Value(value) | Entry::Expiring { value, .. } => Some(value.clone()),
+        }
+    }
+
+    fn insert(&mut self, key: &str, value: &str, expires: Option<u64>) {
+        let value = value.to_string();
+        let entry = match expires {
+            Some(expires) => Entry::Expiring { value, expires },
+            None => Entry::Value(value),
+        };
+        self.values.insert(key.to_string(), entry);
+    }
+
+    /// Remove the expired values, returns how many were removed
+    fn prune(&mut self, now: u64) -> usize {
+        let count = self.values.len();
+        self.values.retain(|_, entry| !entry.is_expired(now));
+        count - self.values.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 struct PadSetData {
     #[serde(flatten)]
-    data: HashMap<String, String>,
+    data: Entries,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 struct BoardData {
     #[serde(flatten)]
-    data: HashMap<String, String>,
+    data: Entries,
     
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     padsets: HashMap<String, PadSetData>,
@@ -21,7 +72,7 @@ struct BoardData {
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
 struct ProfileData {
     #[serde(flatten)]
-    data: HashMap<String, String>,
+    data: Entries,
     
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     boards: HashMap<String, BoardData>,
@@ -33,31 +84,132 @@ struct RepositoryData {
     profiles: HashMap<String, ProfileData>,
 }
 
+impl RepositoryData {
+    /// Data of the file, an unreadable file is an error so that it is not overwritten by the next flush
+    fn load(file_path: &str) -> Result<Self> {
+        if Path::new(file_path).exists() {
+            let contents = fs::read_to_string(file_path)?;
+            serde_json::from_str(&contents).map_err(|e| anyhow!("Failed to parse {}: {}", file_path, e))
+        } else {
+            Ok(RepositoryData::default())
+        }
+    }
+
+    /// Remove the expired values and the boards and profiles left empty, returns how many values were removed
+    fn prune(&mut self, now: u64) -> usize {
+        let mut removed = 0;
+        for profile in self.profiles.values_mut() {
+            removed += profile.data.prune(now);
+            for board in profile.boards.values_mut() {
+                removed += board.data.prune(now);
+                for padset in board.padsets.values_mut() {
+                    removed += padset.data.prune(now);
+                }
+                board.padsets.retain(|_, padset| !padset.data.is_empty());
+            }
+            profile.boards.retain(|_, board| !board.data.is_empty() || !board.padsets.is_empty());
+        }
+        self.profiles.retain(|_, profile| !profile.data.is_empty() || !profile.boards.is_empty());
+        removed
+    }
+
+    fn apply(&mut self, change: &Change) {
+        let profile_data = self.profiles
+            .entry(change.profile.clone())
+            .or_default();
+        let Some(board) = &change.board else {
+            profile_data.data.insert(&change.key, &change.value, change.expires);
+            return;
+        };
+        let board_data = profile_data.boards
+            .entry(board.clone())
+            .or_default();
+        let Some(padset) = &change.padset else {
+            board_data.data.insert(&change.key, &change.value, change.expires);
+            return;
+        };
+        let padset_data = board_data.padsets
+            .entry(padset.clone())
+            .or_default();
+        padset_data.data.insert(&change.key, &change.value, change.expires);
+    }
+}
+
+/// Value set since the last flush, replayed on the file contents when flushing so that
+/// values saved meanwhile by other instances are kept
+#[derive(Debug)]
+struct Change {
+    profile: String,
+    board: Option<String>,
+    padset: Option<String>,
+    key: String,
+    value: String,
+    expires: Option<u64>,
+}
+
+/// Exclusive advisory lock (flock) on a lock file next to the data file, released when dropped.
+/// The data file itself is replaced on every write, so it cannot hold the lock
+struct FileLock {
+    _file: fs::File,
+}
+
+impl FileLock {
+    fn acquire(file_path: &str) -> Result<Self> {
+        let lock_path = format!("{}.lock", file_path);
+        let file = fs::OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path)?;
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            return Err(anyhow!("Failed to lock {}: {}", lock_path, std::io::Error::last_os_error()));
+        }
+        Ok(Self { _file: file })
+    }
+}
+
 #[derive(Debug)]
 pub struct JsonRepository {
     file_path: String,
     data: RepositoryData,
-    dirty: bool,
+    changes: Vec<Change>,
 }
 
 impl JsonRepository {
     pub fn new(file_path: String) -> Result<Self> {
-        let data = if Path::new(&file_path).exists() {
-            let contents = fs::read_to_string(&file_path)?;
-            serde_json::from_str(&contents).unwrap_or_default()
-        } else {
-            RepositoryData::default()
-        };
-        
+        // Changes are not saved until the file can be read again
+        let mut data = RepositoryData::load(&file_path)
+            .inspect_err(|e| log::warn!("{}, starting without the saved data", e))
+            .unwrap_or_default();
+        let pruned = data.prune(unix_now());
+        if pruned > 0 {
+            log::info!("Ignoring {} expired repository values", pruned);
+        }
+
         Ok(Self {
             file_path,
             data,
-            dirty: false,
+            changes: Vec::new(),
         })
     }
-    
-    fn mark_dirty(&mut self) {
-        self.dirty = true;
+
+    fn set(&mut self, profile: &str, board: Option<&str>, padset: Option<&str>, key: &str, value: &str, ttl: Option<Duration>) {
+        let change = Change {
+            profile: profile.to_string(),
+            board: board.map(str::to_string),
+            padset: padset.map(str::to_string),
+            key: key.to_string(),
+            value: value.to_string(),
+            expires: ttl.map(|ttl| unix_now() + ttl.as_secs()),
+        };
+        self.data.apply(&change);
+        self.changes.push(change);
+    }
+
+    /// Write the data to a temporary file renamed over the data file, so readers never see a partial file
+    fn write_atomic(&self, data: &RepositoryData) -> Result<()> {
+        let json = serde_json::to_string_pretty(data)?;
+        let temp_path = format!("{}.{}.tmp", self.file_path, std::process::id());
+        fs::write(&temp_path, json)?;
+        fs::rename(&temp_path, &self.file_path)
+            .inspect_err(|_| { let _ = fs::remove_file(&temp_path); })?;
+        Ok(())
     }
 }
 
@@ -66,16 +218,11 @@ impl DataRepository for JsonRepository {
         self.data.profiles
             .get(profile)?
             .data
-            .get(key)
-            .cloned()
+            .get(key, unix_now())
     }
     
     fn set_profile_data(&mut self, profile: &str, key: &str, value: &str) -> Result<()> {
-        let profile_data = self.data.profiles
-            .entry(profile.to_string())
-            .or_default();
-        profile_data.data.insert(key.to_string(), value.to_string());
-        self.mark_dirty();
+        self.set(profile, None, None, key, value, None);
         Ok(())
     }
     
@@ -85,19 +232,11 @@ impl DataRepository for JsonRepository {
             .boards
             .get(board)?
             .data
-            .get(key)
-            .cloned()
+            .get(key, unix_now())
     }
     
     fn set_board_data(&mut self, profile: &str, board: &str, key: &str, value: &str) -> Result<()> {
-        let profile_data = self.data.profiles
-            .entry(profile.to_string())
-            .or_default();
-        let board_data = profile_data.boards
-            .entry(board.to_string())
-            .or_default();
-        board_data.data.insert(key.to_string(), value.to_string());
-        self.mark_dirty();
+        self.set(profile, Some(board), None, key, value, None);
         Ok(())
     }
     
@@ -109,32 +248,115 @@ impl DataRepository for JsonRepository {
             .padsets
             .get(padset)?
             .data
-            .get(key)
-            .cloned()
+            .get(key, unix_now())
     }
     
     fn set_padset_data(&mut self, profile: &str, board: &str, padset: &str, key: &str, value: &str) -> Result<()> {
-        let profile_data = self.data.profiles
-            .entry(profile.to_string())
-            .or_default();
-        let board_data = profile_data.boards
-            .entry(board.to_string())
-            .or_default();
-        let padset_data = board_data.padsets
-            .entry(padset.to_string())
-            .or_default();
-        padset_data.data.insert(key.to_string(), value.to_string());
-        self.mark_dirty();
+        self.set(profile, Some(board), Some(padset), key, value, None);
+        Ok(())
+    }
+
+    fn set_board_data_with_ttl(&mut self, profile: &str, board: &str, key: &str, value: &str, ttl: Duration) -> Result<()> {
+        self.set(profile, Some(board), None, key, value, Some(ttl));
+        Ok(())
+    }
+
+    fn set_padset_data_with_ttl(&mut self, profile: &str, board: &str, padset: &str, key: &str, value: &str, ttl: Duration) -> Result<()> {
+        self.set(profile, Some(board), Some(padset), key, value, Some(ttl));
         Ok(())
     }
+
+    fn prune(&mut self) -> usize {
+        self.data.prune(unix_now())
+    }
     
     fn flush(&mut self) -> Result<()> {
-        if self.dirty {
-            let json = serde_json::to_string_pretty(&self.data)?;
-            fs::write(&self.file_path, json)?;
-            self.dirty = false;
+        if !self.changes.is_empty() {
+            // Other instances may have saved since the data was loaded, the changes are applied to their data
+            let _lock = FileLock::acquire(&self.file_path)?;
+            let mut data = RepositoryData::load(&self.file_path)?;
+            for change in &self.changes {
+                data.apply(change);
+            }
+            data.prune(unix_now());
+            self.write_atomic(&data)?;
+            self.data = data;
+            self.changes.clear();
             log::info!("Repository data saved to {}", self.file_path);
         }
         Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flush_keeps_concurrent_changes() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-repository-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("data.json").to_str().unwrap().to_string();
+
+        let mut first = JsonRepository::new(file_path.clone()).unwrap();
+        let mut second = JsonRepository::new(file_path.clone()).unwrap();
+        first.set_board_data("default", "code", "geometry", "1").unwrap();
+        second.set_padset_data("default", "code", "code", "key", "2").unwrap();
+        first.flush().unwrap();
+        second.flush().unwrap();
+
+        let repository = JsonRepository::new(file_path).unwrap();
+        assert_eq!(repository.get_board_data("default", "code", "geometry").as_deref(), Some("1"));
+        assert_eq!(repository.get_padset_data("default", "code", "code", "key").as_deref(), Some("2"));
+        assert_eq!(second.get_board_data("default", "code", "geometry").as_deref(), Some("1"));
+        assert!(fs::read_dir(&dir).unwrap().all(|entry| !entry.unwrap().path().to_string_lossy().ends_with(".tmp")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_expired_values() {
+        let mut data: RepositoryData = serde_json::from_str(r#"{ "profiles": { "default": {
+            "boards": {
+                "clipboard": { "padsets": { "history": { "1": { "value": "old", "expires": 100 } } } },
+                "code": { "output": { "value": "ok", "expires": 200 }, "geometry": "1" }
+            }
+        } } }"#).unwrap();
+        let board = |data: &RepositoryData, board: &str, key: &str| data.profiles["default"].boards[board].data.get(key, 150);
+        assert_eq!(board(&data, "code", "output").as_deref(), Some("ok"));
+        assert_eq!(data.profiles["default"].boards["clipboard"].padsets["history"].data.get("1", 150), None);
+
+        assert_eq!(data.prune(150), 1);
+        assert!(!data.profiles["default"].boards.contains_key("clipboard"));
+        assert_eq!(data.prune(250), 1);
+        assert_eq!(board(&data, "code", "geometry").as_deref(), Some("1"));
+        assert_eq!(serde_json::to_value(&data).unwrap(), serde_json::json!({ "profiles": { "default": { "boards": { "code": { "geometry": "1" } } } } }));
+    }
+
+    #[test]
+    fn test_key_named_expires() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-repository-expires-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("data.json").to_str().unwrap().to_string();
+
+        let mut repository = JsonRepository::new(file_path.clone()).unwrap();
+        repository.set_profile_data("default", "expires", "soon").unwrap();
+        repository.set_board_data_with_ttl("default", "code", "expires", "later", Duration::from_secs(60)).unwrap();
+        repository.set_board_data("default", "code", "geometry", "1").unwrap();
+        repository.flush().unwrap();
+
+        let repository = JsonRepository::new(file_path.clone()).unwrap();
+        assert_eq!(repository.get_profile_data("default", "expires").as_deref(), Some("soon"));
+        assert_eq!(repository.get_board_data("default", "code", "expires").as_deref(), Some("later"));
+        assert_eq!(repository.get_board_data("default", "code", "geometry").as_deref(), Some("1"));
+
+        // A file that cannot be read is kept
+        fs::write(&file_path, "{ not json").unwrap();
+        let mut repository = JsonRepository::new(file_path.clone()).unwrap();
+        repository.set_profile_data("default", "key", "value").unwrap();
+        assert!(repository.flush().is_err());
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "{ not json");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,102 @@
+use std::{collections::HashMap, time::Duration};
+use anyhow::Result;
+use crate::core::{DataRepository, repository::unix_now};
+
+/// Profile, board (empty for profile data), padset (empty for profile and board data) and key
+type DataKey = (String, String, String, String);
+
+/// Repository keeping the data in memory only, for setups where nothing should be written to disk
+/// (kiosks, demos). Remembered window positions and board data last until the application exits
+#[derive(Debug, Default)]
+pub struct MemoryRepository {
+    /// Values with their expiry time (unix seconds), if set with a time to live
+    data: HashMap<DataKey, (String, Option<u64>)>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get(&self, profile: &str, board: &str, padset: &str, key: &str) -> Option<String> {
+        let (value, expires) = self.data.get(&(profile.to_string(), board.to_string(), padset.to_string(), key.to_string()))?;
+        expires.is_none_or(|expires| expires > unix_now()).then(|| value.clone())
+    }
+
+    fn set(&mut self, profile: &str, board: &str, padset: &str, key: &str, value: &str, ttl: Option<Duration>) -> Result<()> {
+        let expires = ttl.map(|ttl| unix_now() + ttl.as_secs());
+        self.data.insert((profile.to_string(), board.to_string(), padset.to_string(), key.to_string()), (value.to_string(), expires));
+        Ok(())
+    }
+}
+
+impl DataRepository for MemoryRepository {
+    fn get_profile_data(&self, profile: &str, key: &str) -> Option<String> {
+        self.get(profile, "", "", key)
+    }
+
+    fn set_profile_data(&mut self, profile: &str, key: &str, value: &str) -> Result<()> {
+        self.set(profile, "", "", key, value, None)
+    }
+
+    fn get_board_data(&self, profile: &str, board: &str, key: &str) -> Option<String> {
+        self.get(profile, board, "", key)
+    }
+
+    fn set_board_data(&mut self, profile: &str, board: &str, key: &str, value: &str) -> Result<()> {
+        self.set(profile, board, "", key, value, None)
+    }
+
+    fn get_padset_data(&self, profile: &str, board: &str, padset: &str, key: &str) -> Option<String> {
+        self.get(profile, board, padset, key)
+    }
+
+    fn set_padset_data(&mut self, profile: &str, board: &str, padset: &str, key: &str, value: &str) -> Result<()> {
+        self.set(profile, board, padset, key, value, None)
+    }
+
+    fn set_board_data_with_ttl(&mut self, profile: &str, board: &str, key: &str, value: &str, ttl: Duration) -> Result<()> {
+        self.set(profile, board, "", key, value, Some(ttl))
+    }
+
+    fn set_padset_data_with_ttl(&mut self, profile: &str, board: &str, padset: &str, key: &str, value: &str, ttl: Duration) -> Result<()> {
+        self.set(profile, board, padset, key, value, Some(ttl))
+    }
+
+    fn prune(&mut self) -> usize {
+        let now = unix_now();
+        let count = self.data.len();
+        self.data.retain(|_, (_, expires)| expires.is_none_or(|expires| expires > now));
+        count - self.data.len()
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_repository() {
+        let mut repository = MemoryRepository::new();
+        repository.set_profile_data("default", "key", "profile").unwrap();
+        repository.set_board_data("default", "code", "key", "board").unwrap();
+        repository.set_padset_data("default", "code", "code", "key", "padset").unwrap();
+        repository.flush().unwrap();
+
+        assert_eq!(repository.get_profile_data("default", "key").as_deref(), Some("profile"));
+        assert_eq!(repository.get_board_data("default", "code", "key").as_deref(), Some("board"));
+        assert_eq!(repository.get_padset_data("default", "code", "code", "key").as_deref(), Some("padset"));
+        assert_eq!(repository.get_board_data("default", "other", "key"), None);
+        assert_eq!(repository.get_profile_data("other", "key"), None);
+
+        repository.set_board_data_with_ttl("default", "code", "cache", "value", Duration::from_secs(60)).unwrap();
+        repository.set_padset_data_with_ttl("default", "code", "code", "cache", "value", Duration::ZERO).unwrap();
+        assert_eq!(repository.get_board_data("default", "code", "cache").as_deref(), Some("value"));
+        assert_eq!(repository.get_padset_data("default", "code", "code", "cache"), None);
+        assert_eq!(repository.prune(), 1);
+    }
+}
@@ -0,0 +1,87 @@
+/// Settings file versioning.
+/// Older settings files are upgraded step by step to the current version, every step is reported
+
+use serde_json::{Map, Value};
+use anyhow::Result;
+
+/// Version of the settings format understood by this build
+pub const SETTINGS_VERSION: u64 = 1;
+
+struct Migration {
+    /// Version produced by this migration
+    version: u64,
+    description: &'static str,
+    apply: fn(&mut Map<String, Value>),
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "added 'version' field",
+        apply: |_| {},
+    },
+];
+
+/// Version of a settings document, files without a 'version' field are version 0
+pub fn settings_version(settings: &Value) -> u64 {
+    settings.get("version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Keep 'version' as the first field so it is visible at the top of the file
+fn set_version(settings: &mut Map<String, Value>, version: u64) {
+    if let Some(value) = settings.get_mut("version") {
+        *value = Value::from(version);
+    } else {
+        let rest = std::mem::take(settings);
+        settings.insert("version".to_string(), Value::from(version));
+        settings.extend(rest);
+    }
+}
+
+/// Upgrade a settings document to [SETTINGS_VERSION].
+/// Returns a description of every applied transformation, empty if the document is up to date.
+pub fn migrate(settings: &mut Value) -> Result<Vec<String>> {
+    let version = settings_version(settings);
+    if version > SETTINGS_VERSION {
+        anyhow::bail!("Settings version {} is newer than the supported version {}, please upgrade HotKeys", version, SETTINGS_VERSION);
+    }
+
+    let Some(object) = settings.as_object_mut() else {
+        anyhow::bail!("Settings must be a JSON object");
+    };
+
+    let mut transformations = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        (migration.apply)(object);
+        set_version(object, migration.version);
+        transformations.push(format!("version {} -> {}: {}", migration.version - 1, migration.version, migration.description));
+    }
+    Ok(transformations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrate_unversioned() {
+        let mut settings = json!({ "timeout": 4, "boards": [] });
+        let transformations = migrate(&mut settings).unwrap();
+
+        assert_eq!(transformations, vec!["version 0 -> 1: added 'version' field"]);
+        assert_eq!(settings_version(&settings), SETTINGS_VERSION);
+        assert_eq!(settings.as_object().unwrap().keys().next().unwrap(), "version");
+        assert_eq!(settings["timeout"], 4);
+    }
+
+    #[test]
+    fn test_migrate_current_and_newer() {
+        let mut settings = json!({ "version": SETTINGS_VERSION, "timeout": 4 });
+        assert!(migrate(&mut settings).unwrap().is_empty());
+
+        let mut settings = json!({ "version": SETTINGS_VERSION + 1 });
+        assert!(migrate(&mut settings).is_err());
+        assert!(migrate(&mut json!([])).is_err());
+    }
+}
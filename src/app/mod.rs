@@ -2,5 +2,23 @@ pub mod config;
 pub mod board_factory;
 pub mod controller;
 pub mod json_repository;
+pub mod memory_repository;
+pub mod daemon;
+pub mod tray;
+pub mod instance;
+pub mod migration;
+pub mod interpolation;
+pub mod watcher;
+pub mod settings_cache;
+pub mod notification;
+pub mod validation;
+pub mod appearance;
+pub mod sound;
+pub mod usage;
+pub mod counters;
+pub mod dynamic_text;
+pub mod json_log;
+pub mod audit;
+pub mod debounce;
 
 pub use controller::HotKeysApp;
\ No newline at end of file
@@ -0,0 +1,19 @@
+/// Desktop notifications, shown with notify-send (libnotify).
+/// Surface errors of the daemon and of boards run from a shortcut, where no terminal shows the log
+
+use std::process::{Command, Stdio};
+
+const APP_NAME: &str = "HotKeys";
+
+/// Show an error notification, failures are only logged since notifications are optional
+pub fn show_error(summary: &str, body: &str) {
+    let result = Command::new("notify-send")
+        .args(["--app-name", APP_NAME, "--urgency", "critical", summary, body])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    if let Err(e) = result {
+        log::warn!("Failed to show notification '{}': {}", summary, e);
+    }
+}
@@ -0,0 +1,148 @@
+/// Merged and validated settings cached in the XDG cache directory, so launches with unchanged
+/// settings files skip parsing the includes, resolving bases and variables and the validation
+
+use super::config::AppSettings;
+use crate::core::Resources;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+
+/// Settings file the cache was written for, with its modification time and size
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Source {
+    path: PathBuf,
+    modified: u128,
+    len: u64,
+}
+
+impl Source {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+        Some(Source { path: path.to_path_buf(), modified, len: metadata.len() })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedSettings {
+    /// Version of the application which wrote the cache, the settings format may differ between versions
+    version: String,
+    /// The settings file followed by the included files
+    sources: Vec<Source>,
+    /// Settings before environment variables are expanded
+    settings: AppSettings,
+}
+
+/// Set when nothing may be written to disk (`--ephemeral`)
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Stop writing the cache, it is still read
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+fn cache_file() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("hotkeys").join("settings.json"))
+}
+
+/// Cached settings, if they were written by this version from the unchanged settings files
+pub fn load(resources: &Resources) -> Option<AppSettings> {
+    load_from(&cache_file()?, resources)
+}
+
+/// Cache the merged settings, failures are only logged
+pub fn store(settings: &AppSettings) {
+    if DISABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some(cache_file) = cache_file() else {
+        return;
+    };
+    if let Err(e) = store_to(&cache_file, settings) {
+        log::warn!("Failed to write settings cache {:?}: {}", cache_file, e);
+    }
+}
+
+fn load_from(cache_file: &Path, resources: &Resources) -> Option<AppSettings> {
+    let cached: CachedSettings = serde_json::from_str(&fs::read_to_string(cache_file).ok()?)
+        .inspect_err(|e| log::debug!("Ignoring settings cache {:?}: {}", cache_file, e))
+        .ok()?;
+    if cached.version != env!("CARGO_PKG_VERSION") {
+        return None;
+    }
+
+    // Same settings file, unchanged, including the same unchanged files
+    let paths: Vec<PathBuf> = cached.sources.iter().map(|source| source.path.clone()).collect();
+    let (settings_file, includes) = paths.split_first()?;
+    if resources.settings_file().as_ref() != Some(settings_file) {
+        return None;
+    }
+    if cached.settings.resolve_include_files(resources).ok()? != includes {
+        return None;
+    }
+    if cached.sources.iter().any(|source| Source::of(&source.path).as_ref() != Some(source)) {
+        return None;
+    }
+
+    log::info!("Loaded cached settings: {:?}", settings_file);
+    Some(cached.settings.with_source_files(&paths))
+}
+
+fn store_to(cache_file: &Path, settings: &AppSettings) -> Result<()> {
+    let sources = settings.source_files().iter()
+        .map(|path| Source::of(path).ok_or_else(|| anyhow::anyhow!("Cannot read {:?}", path)))
+        .collect::<Result<Vec<_>>>()?;
+    let cached = CachedSettings { version: env!("CARGO_PKG_VERSION").to_string(), sources, settings: settings.clone() };
+
+    if let Some(dir) = cache_file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Written aside and renamed, a concurrent launch never reads a partial cache
+    let temp_file = cache_file.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&temp_file, serde_json::to_string(&cached)?)?;
+    fs::rename(&temp_file, cache_file)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::load_settings_unvalidated;
+
+    #[test]
+    fn test_settings_cache() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-settings-cache-{}", std::process::id()));
+        fs::create_dir_all(dir.join("boards.d")).unwrap();
+        let mut settings: serde_json::Value = serde_json::from_str(include_str!("../../resources/settings.json")).unwrap();
+        settings["includes"] = serde_json::json!(["boards.d"]);
+        fs::write(dir.join("settings.json"), settings.to_string()).unwrap();
+        fs::write(dir.join("boards.d").join("a.json"), "{}").unwrap();
+        let resources = Resources::new(vec![dir.clone()]);
+        let cache_file = dir.join("cache").join("settings.json");
+
+        let settings = load_settings_unvalidated(&resources).unwrap();
+        store_to(&cache_file, &settings).unwrap();
+        let cached = load_from(&cache_file, &resources).unwrap();
+        assert_eq!(cached.source_files(), settings.source_files());
+        assert_eq!(serde_json::to_value(&cached).unwrap(), serde_json::to_value(&settings).unwrap());
+
+        // A new included file invalidates the cache, as does a changed one
+        fs::write(dir.join("boards.d").join("b.json"), "{}").unwrap();
+        assert!(load_from(&cache_file, &resources).is_none());
+        fs::remove_file(dir.join("boards.d").join("b.json")).unwrap();
+        assert!(load_from(&cache_file, &resources).is_some());
+        fs::write(dir.join("boards.d").join("a.json"), "{ }").unwrap();
+        assert!(load_from(&cache_file, &resources).is_none());
+
+        // A cache without sources is not used, even when there is no settings file either
+        let mut cached: serde_json::Value = serde_json::from_str(&fs::read_to_string(&cache_file).unwrap()).unwrap();
+        cached["sources"] = serde_json::json!([]);
+        fs::write(&cache_file, cached.to_string()).unwrap();
+        assert!(load_from(&cache_file, &Resources::new(vec![dir.join("boards.d")])).is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
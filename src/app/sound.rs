@@ -0,0 +1,30 @@
+/// Sound effects, played with canberra-gtk-play (libcanberra)
+
+use crate::core::Resources;
+use std::process::{Command, Stdio};
+
+const APP_NAME: &str = "HotKeys";
+
+/// Play a sound theme name or a sound file, failures are only logged since sounds are optional
+pub fn play(sound: &str, resources: &Resources) {
+    let mut command = Command::new("canberra-gtk-play");
+    if Resources::is_theme_sound(sound) {
+        command.args(["--id", sound]);
+    } else {
+        let Some(sound_file) = resources.sound(sound) else {
+            log::warn!("Sound '{}' not found", sound);
+            return;
+        };
+        command.arg("--file").arg(sound_file);
+    }
+
+    let result = command
+        .args(["--description", APP_NAME])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    if let Err(e) = result {
+        log::warn!("Failed to play sound '{}': {}", sound, e);
+    }
+}
@@ -0,0 +1,153 @@
+/// Status tray icon (StatusNotifierItem) for daemon mode
+/// Lists profiles and boards, and offers reload and quick toggles
+
+use super::config::AppSettings;
+use super::daemon::DaemonEvent;
+
+use anyhow::Result;
+use ksni::blocking::{Handle, TrayMethods};
+use ksni::menu::{CheckmarkItem, StandardItem, SubMenu};
+use ksni::MenuItem;
+use std::sync::mpsc::Sender;
+
+/// Profile entry shown in the tray menu
+#[derive(Debug, Clone)]
+struct TrayProfile {
+    name: String,
+    boards: Vec<String>,
+}
+
+pub struct HotKeysTray {
+    sender: Sender<DaemonEvent>,
+    profiles: Vec<TrayProfile>,
+    detection: bool,
+    injection_paused: bool,
+}
+
+impl HotKeysTray {
+    /// Spawn the tray icon service, menu entries are sent to the daemon as events
+    pub fn start(settings: &AppSettings, sender: Sender<DaemonEvent>) -> Result<Handle<Self>> {
+        let tray = Self {
+            sender,
+            profiles: Self::profiles(settings),
+            detection: true,
+            injection_paused: false,
+        };
+        TrayMethods::spawn(tray)
+            .map_err(|e| anyhow::anyhow!("Failed to create tray icon: {}", e))
+    }
+
+    /// Refresh profiles and boards after the configuration was reloaded
+    pub fn update_settings(&mut self, settings: &AppSettings) {
+        self.profiles = Self::profiles(settings);
+    }
+
+    fn profiles(settings: &AppSettings) -> Vec<TrayProfile> {
+        settings.profiles.iter()
+            .map(|profile| TrayProfile {
+                name: profile.name.clone(),
+                boards: profile.boards.clone(),
+            })
+            .collect()
+    }
+
+    fn send(&self, event: DaemonEvent) {
+        if let Err(e) = self.sender.send(event) {
+            log::error!("Failed to send tray event: {}", e);
+        }
+    }
+
+    fn profile_menu(profile: &TrayProfile) -> MenuItem<Self> {
+        let name = profile.name.clone();
+        let mut submenu: Vec<MenuItem<Self>> = vec![
+            StandardItem {
+                label: "Detect board".into(),
+                activate: Box::new(move |this: &mut Self| this.send(DaemonEvent::Trigger {
+                    profile: Some(name.clone()),
+                    board: None,
+                })),
+                ..Default::default()
+            }.into(),
+            MenuItem::Separator,
+        ];
+
+        for board in &profile.boards {
+            let name = profile.name.clone();
+            let board = board.clone();
+            submenu.push(StandardItem {
+                label: board.clone(),
+                activate: Box::new(move |this: &mut Self| this.send(DaemonEvent::Trigger {
+                    profile: Some(name.clone()),
+                    board: Some(board.clone()),
+                })),
+                ..Default::default()
+            }.into());
+        }
+
+        SubMenu {
+            label: profile.name.clone(),
+            submenu,
+            ..Default::default()
+        }.into()
+    }
+}
+
+impl ksni::Tray for HotKeysTray {
+    fn id(&self) -> String {
+        "hotkeys".into()
+    }
+
+    fn title(&self) -> String {
+        "HotKeys".into()
+    }
+
+    fn icon_name(&self) -> String {
+        "hotkeys".into()
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        self.send(DaemonEvent::Trigger { profile: None, board: None });
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        let mut menu: Vec<MenuItem<Self>> = self.profiles.iter()
+            .map(Self::profile_menu)
+            .collect();
+
+        menu.push(MenuItem::Separator);
+        menu.push(CheckmarkItem {
+            label: "Disable detection".into(),
+            checked: !self.detection,
+            activate: Box::new(|this: &mut Self| {
+                this.detection = !this.detection;
+                this.send(DaemonEvent::SetDetection(this.detection));
+            }),
+            ..Default::default()
+        }.into());
+        menu.push(CheckmarkItem {
+            label: "Pause input injection".into(),
+            checked: self.injection_paused,
+            activate: Box::new(|this: &mut Self| {
+                this.injection_paused = !this.injection_paused;
+                this.send(DaemonEvent::SetInjectionPaused(this.injection_paused));
+            }),
+            ..Default::default()
+        }.into());
+
+        menu.push(MenuItem::Separator);
+        menu.push(StandardItem {
+            label: "Reload config".into(),
+            icon_name: "view-refresh".into(),
+            activate: Box::new(|this: &mut Self| this.send(DaemonEvent::Reload)),
+            ..Default::default()
+        }.into());
+        menu.push(StandardItem {
+            label: "Quit".into(),
+            icon_name: "application-exit".into(),
+            activate: Box::new(|this: &mut Self| this.send(DaemonEvent::Quit)),
+            ..Default::default()
+        }.into());
+
+        menu
+    }
+}
@@ -0,0 +1,140 @@
+/// Pad usage statistics kept in the data repository (profile data `pad_usage`): how often and when
+/// each pad was last executed, and the most recent executions in order
+
+use crate::core::{DataRepository, ModifierState, repository::unix_now};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+const USAGE_KEY: &str = "pad_usage";
+/// Executions kept in the history, older ones only count in the pad statistics
+const HISTORY_LENGTH: usize = 100;
+/// Age after which an execution counts half in the ranking (two weeks)
+const HALF_LIFE_SECS: f64 = 14.0 * 24.0 * 3600.0;
+
+/// How often a pad was executed, per board, pad and modifiers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PadUsage {
+    pub board: String,
+    pub pad: u8,
+    #[serde(default, skip_serializing_if = "ModifierState::is_none")]
+    pub modifiers: ModifierState,
+    pub count: u64,
+    /// Unix time of the last execution
+    pub last_used: u64,
+}
+
+/// Single execution of a pad
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PadExecution {
+    pub board: String,
+    pub pad: u8,
+    #[serde(default, skip_serializing_if = "ModifierState::is_none")]
+    pub modifiers: ModifierState,
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct UsageStats {
+    #[serde(default)]
+    pub pads: Vec<PadUsage>,
+    /// Most recent executions, oldest first
+    #[serde(default)]
+    pub history: Vec<PadExecution>,
+}
+
+impl UsageStats {
+    /// Usage statistics of a profile, empty if none were recorded or they cannot be read
+    pub fn load(repository: &dyn DataRepository, profile: &str) -> Self {
+        repository.get_profile_data(profile, USAGE_KEY)
+            .and_then(|value| serde_json::from_str(&value)
+                .inspect_err(|e| log::warn!("Ignoring pad usage of profile '{}': {}", profile, e))
+                .ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, repository: &mut dyn DataRepository, profile: &str) -> Result<()> {
+        repository.set_profile_data(profile, USAGE_KEY, &serde_json::to_string(self)?)
+    }
+
+    /// Pads ordered by their usage, most used first: the execution count weighted by how recently
+    /// the pad was last used, an execution counting half after two weeks
+    pub fn ranked(&self, now: u64) -> Vec<&PadUsage> {
+        let score = |usage: &PadUsage| {
+            let age = now.saturating_sub(usage.last_used) as f64;
+            usage.count as f64 * 0.5f64.powf(age / HALF_LIFE_SECS)
+        };
+        let mut pads: Vec<&PadUsage> = self.pads.iter().collect();
+        pads.sort_by(|a, b| score(b).total_cmp(&score(a)).then(b.last_used.cmp(&a.last_used)));
+        pads
+    }
+
+    fn record(&mut self, board: &str, pad: u8, modifiers: &ModifierState, time: u64) {
+        match self.pads.iter_mut().find(|usage| usage.board == board && usage.pad == pad && usage.modifiers == *modifiers) {
+            Some(usage) => {
+                usage.count += 1;
+                usage.last_used = time;
+            },
+            None => self.pads.push(PadUsage { board: board.to_string(), pad, modifiers: modifiers.clone(), count: 1, last_used: time }),
+        }
+
+        self.history.push(PadExecution { board: board.to_string(), pad, modifiers: modifiers.clone(), time });
+        let excess = self.history.len().saturating_sub(HISTORY_LENGTH);
+        self.history.drain(..excess);
+    }
+}
+
+/// Record an executed pad and persist the statistics
+pub fn record_pad(repository: &Mutex<dyn DataRepository>, profile: &str, board: &str, pad: u8, modifiers: &ModifierState) -> Result<()> {
+    let mut repository = repository.lock()
+        .map_err(|e| anyhow::anyhow!("Failed to acquire repository lock: {}", e))?;
+    let mut stats = UsageStats::load(&*repository, profile);
+    stats.record(board, pad, modifiers, unix_now());
+    stats.save(&mut *repository, profile)?;
+    repository.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::memory_repository::MemoryRepository;
+
+    #[test]
+    fn test_record_usage() {
+        let ctrl = ModifierState { ctrl: true, ..Default::default() };
+        let mut stats = UsageStats::default();
+        stats.record("code", 5, &ModifierState::default(), 100);
+        stats.record("code", 5, &ctrl, 110);
+        stats.record("code", 5, &ModifierState::default(), 120);
+        assert_eq!(stats.pads.len(), 2);
+        assert_eq!((stats.pads[0].count, stats.pads[0].last_used), (2, 120));
+        assert_eq!(stats.history.iter().map(|execution| execution.time).collect::<Vec<_>>(), vec![100, 110, 120]);
+
+        for time in 0..HISTORY_LENGTH as u64 {
+            stats.record("code", 1, &ModifierState::default(), 200 + time);
+        }
+        assert_eq!(stats.history.len(), HISTORY_LENGTH);
+        assert_eq!(stats.history[0].time, 200);
+
+        let mut repository = MemoryRepository::new();
+        stats.save(&mut repository, "default").unwrap();
+        assert_eq!(UsageStats::load(&repository, "default"), stats);
+        assert_eq!(UsageStats::load(&repository, "other"), UsageStats::default());
+    }
+
+    #[test]
+    fn test_ranked() {
+        let day = 24 * 3600;
+        let mut stats = UsageStats::default();
+        for _ in 0..4 {
+            stats.record("old", 1, &ModifierState::default(), 0);
+        }
+        stats.record("recent", 1, &ModifierState::default(), 60 * day);
+        stats.record("recent", 1, &ModifierState::default(), 60 * day);
+        stats.record("once", 1, &ModifierState::default(), 59 * day);
+
+        let boards = |now| stats.ranked(now).iter().map(|usage| usage.board.as_str()).collect::<Vec<_>>();
+        assert_eq!(boards(0), vec!["old", "recent", "once"]);
+        assert_eq!(boards(60 * day), vec!["recent", "once", "old"]);
+    }
+}
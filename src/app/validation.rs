@@ -0,0 +1,147 @@
+/// Settings validation report.
+/// Collects every problem found in the settings with the file and location (e.g. `boards[3].modifier_pads.Ctrl`) it comes from
+
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The settings cannot be used
+    Error,
+    /// The settings load, but something will not work as configured
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub file: PathBuf,
+    /// Path to the value within the file, e.g. `padsets[0].items[4].actions[1]`
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}", severity, self.file.display())?;
+        if !self.location.is_empty() {
+            write!(f, ": {}", self.location)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationReport {
+    pub findings: Vec<Finding>,
+    /// Report problems the runtime can work around (missing icons, unknown keys) as errors
+    #[serde(skip)]
+    strict: bool,
+}
+
+impl ValidationReport {
+    pub fn strict() -> Self {
+        Self { strict: true, ..Self::default() }
+    }
+
+    pub fn add(&mut self, severity: Severity, file: &Path, location: impl Into<String>, message: impl Into<String>) {
+        self.findings.push(Finding {
+            severity,
+            file: file.to_path_buf(),
+            location: location.into(),
+            message: message.into(),
+        });
+    }
+
+    pub fn error(&mut self, file: &Path, location: impl Into<String>, message: impl Into<String>) {
+        self.add(Severity::Error, file, location, message);
+    }
+
+    pub fn warning(&mut self, file: &Path, location: impl Into<String>, message: impl Into<String>) {
+        self.add(Severity::Warning, file, location, message);
+    }
+
+    /// Problem the runtime degrades gracefully on (placeholder icon, skipped key):
+    /// a warning, or an error in strict mode
+    pub fn degraded(&mut self, file: &Path, location: impl Into<String>, message: impl Into<String>) {
+        let severity = if self.strict { Severity::Error } else { Severity::Warning };
+        self.add(severity, file, location, message);
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|finding| finding.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|finding| finding.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Findings as a JSON document for editor plugins and CI jobs:
+    /// `{"valid": false, "strict": false, "errors": 1, "warnings": 0, "findings": [{"severity": "error", "file": ..., "location": ..., "message": ...}]}`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "valid": !self.has_errors(),
+            "strict": self.strict,
+            "errors": self.errors().count(),
+            "warnings": self.warnings().count(),
+            "findings": self.findings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_report() {
+        let mut report = ValidationReport::default();
+        report.warning(Path::new("settings.json"), "boards[0].icon", "Icon 'code.svg' not found");
+        assert!(!report.has_errors());
+
+        report.error(Path::new("boards.json"), "boards[3].modifier_pads.Ctrl", "Padset 'code/ctrl' not found");
+        report.error(Path::new("settings.json"), "", "No profiles defined");
+        assert!(report.has_errors());
+        assert_eq!(report.errors().count(), 2);
+        assert_eq!(report.warnings().count(), 1);
+
+        let lines: Vec<String> = report.findings.iter().map(Finding::to_string).collect();
+        assert_eq!(lines, vec![
+            "warning: settings.json: boards[0].icon: Icon 'code.svg' not found",
+            "error: boards.json: boards[3].modifier_pads.Ctrl: Padset 'code/ctrl' not found",
+            "error: settings.json: No profiles defined",
+        ]);
+
+        let json = report.to_json();
+        assert_eq!(json["valid"], false);
+        assert_eq!((json["errors"].as_u64(), json["warnings"].as_u64()), (Some(2), Some(1)));
+        assert_eq!(json["findings"][1], serde_json::json!({
+            "severity": "error",
+            "file": "boards.json",
+            "location": "boards[3].modifier_pads.Ctrl",
+            "message": "Padset 'code/ctrl' not found",
+        }));
+    }
+
+    #[test]
+    fn test_strict_report() {
+        let mut report = ValidationReport::default();
+        report.degraded(Path::new("settings.json"), "boards[0].icon", "Icon 'code.svg' not found");
+        assert!(!report.has_errors());
+
+        let mut report = ValidationReport::strict();
+        report.degraded(Path::new("settings.json"), "boards[0].icon", "Icon 'code.svg' not found");
+        assert!(report.has_errors());
+        assert_eq!(report.findings[0].to_string(), "error: settings.json: boards[0].icon: Icon 'code.svg' not found");
+    }
+}
@@ -0,0 +1,147 @@
+/// Settings file watcher for daemon mode.
+/// Watches the directories of the settings file and its includes with inotify, so files replaced
+/// by editors (written to a temporary file and renamed) are noticed as well, and files added to
+/// the directories of directory and pattern includes (e.g. "boards.d/*.json")
+
+use crate::core::IncludePattern;
+use anyhow::Result;
+use inotify::{Inotify, WatchDescriptor, WatchMask, Watches};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Time to wait for related events (e.g. several files saved at once) before reporting a change
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Default)]
+struct WatchedFiles {
+    directories: HashMap<WatchDescriptor, PathBuf>,
+    files: HashSet<PathBuf>,
+    /// Patterns of the files the include directories load, files matching them are new includes
+    patterns: HashMap<PathBuf, Vec<IncludePattern>>,
+}
+
+impl WatchedFiles {
+    fn contains(&self, wd: &WatchDescriptor, name: &Path) -> bool {
+        self.directories.get(wd).is_some_and(|directory| {
+            let path = directory.join(name);
+            self.files.contains(&path) || self.patterns.get(directory)
+                .is_some_and(|patterns| patterns.iter().any(|pattern| pattern.matches(&path)))
+        })
+    }
+}
+
+pub struct SettingsWatcher {
+    watches: Watches,
+    watched: Arc<Mutex<WatchedFiles>>,
+}
+
+impl SettingsWatcher {
+    /// Start watching the files and the include directories, `on_change` is called from the watcher thread
+    /// after any of the files changed or a file matching the pattern of its directory was added
+    pub fn start<F>(files: &[PathBuf], directories: &[(PathBuf, IncludePattern)], on_change: F) -> Result<Self>
+    where
+        F: Fn() + Send + 'static,
+    {
+        let mut inotify = Inotify::init()
+            .map_err(|e| anyhow::anyhow!("Failed to initialize inotify: {}", e))?;
+        let mut watcher = Self {
+            watches: inotify.watches(),
+            watched: Arc::new(Mutex::new(WatchedFiles::default())),
+        };
+        watcher.watch(files, directories)?;
+
+        let watched = watcher.watched.clone();
+        std::thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            loop {
+                let changed = match inotify.read_events_blocking(&mut buffer) {
+                    Ok(events) => {
+                        let watched = watched.lock().unwrap();
+                        events.into_iter()
+                            .any(|event| event.name.is_some_and(|name| watched.contains(&event.wd, Path::new(name))))
+                    },
+                    Err(e) => {
+                        log::error!("Settings watcher stopped: {}", e);
+                        return;
+                    }
+                };
+
+                if changed {
+                    std::thread::sleep(DEBOUNCE);
+                    while inotify.read_events(&mut buffer).is_ok_and(|events| events.count() > 0) {}
+                    log::info!("Settings files changed");
+                    on_change();
+                }
+            }
+        });
+
+        Ok(watcher)
+    }
+
+    /// Replace the watched files and include directories, e.g. after a reload changed the includes
+    pub fn watch(&mut self, files: &[PathBuf], directories: &[(PathBuf, IncludePattern)]) -> Result<()> {
+        let canonical = |path: &PathBuf| path.canonicalize().unwrap_or_else(|_| path.clone());
+        let mut watched = self.watched.lock().unwrap();
+        watched.files = files.iter().map(canonical).collect();
+        watched.patterns = HashMap::new();
+        for (directory, pattern) in directories {
+            watched.patterns.entry(canonical(directory)).or_default().push(pattern.clone());
+        }
+
+        let directories: HashSet<PathBuf> = watched.files.iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .chain(watched.patterns.keys().cloned())
+            .collect();
+        for directory in directories {
+            if watched.directories.values().any(|watched| *watched == directory) {
+                continue;
+            }
+            let wd = self.watches.add(&directory, WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::DELETE)
+                .map_err(|e| anyhow::anyhow!("Failed to watch {:?}: {}", directory, e))?;
+            log::debug!("Watching settings directory {:?}", directory);
+            watched.directories.insert(wd, directory);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_settings_watcher() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-watcher-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let settings = dir.join("settings.json");
+        let other = dir.join("other.json");
+        std::fs::write(&settings, "{}").unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let boards = dir.join("boards.d");
+        std::fs::create_dir_all(&boards).unwrap();
+        let pattern = IncludePattern::Wildcard("*.json".to_string());
+        let _watcher = SettingsWatcher::start(std::slice::from_ref(&settings), &[(boards.clone(), pattern)], move || sender.send(()).unwrap()).unwrap();
+
+        std::fs::write(&other, "{}").unwrap();
+        assert!(receiver.recv_timeout(Duration::from_secs(1)).is_err());
+
+        // Written in place and replaced by rename
+        std::fs::write(&settings, r#"{"version": 1}"#).unwrap();
+        assert!(receiver.recv_timeout(Duration::from_secs(2)).is_ok());
+        std::fs::write(&other, "{}").unwrap();
+        std::fs::rename(&other, &settings).unwrap();
+        assert!(receiver.recv_timeout(Duration::from_secs(2)).is_ok());
+
+        // Files added to an include directory only when they match its pattern
+        std::fs::write(boards.join("notes.txt"), "").unwrap();
+        assert!(receiver.recv_timeout(Duration::from_secs(1)).is_err());
+        std::fs::write(boards.join("code.json"), "{}").unwrap();
+        assert!(receiver.recv_timeout(Duration::from_secs(2)).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,5 @@
 use std::{collections::HashMap, sync::{Arc, Mutex}};
-use crate::core::{Board, PadSet, Pad, ColorScheme, TextStyle, ModifierState, Action, DataRepository};
+use crate::core::{BackgroundImage, Board, PadSet, Pad, ColorScheme, TextStyle, ModifierState, Action, DataRepository};
 
 #[derive(Clone)]
 pub struct StaticBoard {
@@ -7,8 +7,10 @@ pub struct StaticBoard {
     icon: Option<String>,
     color_scheme: ColorScheme,
     text_style: TextStyle,
-    base_pads: Box<dyn PadSet>,
-    modifier_pads: HashMap<String, Box<dyn PadSet>>,
+    background_image: Option<BackgroundImage>,
+    base_pads: Arc<dyn PadSet>,
+    modifier_pads: HashMap<ModifierState, Arc<dyn PadSet>>,
+    double_pads: Option<Arc<dyn PadSet>>,
 }
 
 impl StaticBoard {
@@ -17,18 +19,30 @@ impl StaticBoard {
         icon: Option<String>,
         color_scheme: ColorScheme,
         text_style: TextStyle,
-        base_pads: Box<dyn PadSet>,
-        modifier_pads: HashMap<String, Box<dyn PadSet>>,
+        base_pads: Arc<dyn PadSet>,
+        modifier_pads: HashMap<ModifierState, Arc<dyn PadSet>>,
     ) -> Self {
         Self {
             title,
             icon,
             color_scheme,
             text_style,
+            background_image: None,
             base_pads,
             modifier_pads,
+            double_pads: None,
         }
     }
+
+    pub fn with_background_image(mut self, background_image: Option<BackgroundImage>) -> Self {
+        self.background_image = background_image;
+        self
+    }
+
+    pub fn with_double_pads(mut self, double_pads: Option<Arc<dyn PadSet>>) -> Self {
+        self.double_pads = double_pads;
+        self
+    }
 }
 
 impl Board for StaticBoard {
@@ -48,17 +62,33 @@ impl Board for StaticBoard {
         &self.text_style
     }
 
-    fn pads(&self, modifier: Option<ModifierState>) -> Box<dyn PadSet> {
-        if let Some(modifier) = modifier {
-            if let Some(pads) = self.modifier_pads.get(&modifier.to_string()) {
-                return (*pads).clone();
-            }
+    fn background_image(&self) -> Option<&BackgroundImage> {
+        self.background_image.as_ref()
+    }
+
+    fn pads(&self, modifier: Option<&ModifierState>) -> Arc<dyn PadSet> {
+        let bindings = self.modifier_pads.iter().map(|(binding, pads)| (binding.clone(), pads));
+        if let Some((_, pads)) = modifier.and_then(|modifier| modifier.find_binding(bindings)) {
+            return pads.clone();
         }
         self.base_pads.clone()
     }
 
-    fn clone_box(&self) -> Box<dyn Board> {
-        Box::new(self.clone())
+    fn double_pads(&self) -> Option<Arc<dyn PadSet>> {
+        self.double_pads.clone()
+    }
+
+    fn modifier_layers(&self) -> Vec<ModifierState> {
+        let mut layers: Vec<ModifierState> = self.modifier_pads.keys().cloned().collect();
+        layers.sort_by_key(|modifiers| (modifiers.abbreviation().len(), modifiers.abbreviation()));
+        layers
+    }
+
+    fn modifiers(&self, held: &ModifierState) -> ModifierState {
+        let bindings = self.modifier_pads.keys().map(|binding| (binding.clone(), ()));
+        held.find_binding(bindings)
+            .map(|(binding, _)| binding)
+            .unwrap_or_else(|| held.without_sides())
     }
 }
 
@@ -70,7 +100,7 @@ pub struct HomeBoard {
     profile: String,
     #[allow(dead_code)]
     repository: Arc<Mutex<dyn DataRepository>>,
-    settings_file_path: String,
+    base_pads: Arc<dyn PadSet>,
 }
 
 // impl std::fmt::Debug for HomeBoard {
@@ -91,9 +121,56 @@ impl HomeBoard {
             text_style,
             profile,
             repository,
-            settings_file_path,
+            base_pads: Self::create_base_pads(&settings_file_path),
         }
     }
+
+    fn create_base_pads(settings_file_path: &str) -> Arc<dyn PadSet> {
+        Arc::new(vec![
+            Pad::default(),
+            Pad {
+                header: "Press a NumPad key, a modifier key\nor Escape".to_string(),
+                ..Default::default()
+            },
+            Pad::default(),
+            Pad {
+                text: "Project url".to_string(),
+                actions: vec![
+                    Action::Pause(200),
+                    Action::OpenUrl("https://github.com/ivicakukic/hotkeys-linux".to_string()),
+                ],
+                ..Default::default()
+            },
+            Pad::default(),
+            Pad {
+                text: "Documentation".to_string(),
+                actions: vec![
+                    Action::Pause(200),
+                    Action::OpenUrl("file:///usr/share/doc/hotkeys/README.md".to_string()),
+                ],
+                ..Default::default()
+            },
+            Pad::default(),
+            Pad {
+                text: "Configuration".to_string(),
+                actions: vec![
+                    Action::Pause(200),
+                    Action::OpenUrl(settings_file_path.to_string()),
+                ],
+                ..Default::default()
+            },
+            Pad::default()
+            // Pad {
+            //     header: "Last Action".to_string(),
+            //     icon: "".to_string(),
+            //     text: format!("🕐\n{}", last_timestamp),
+            //     actions: vec![
+            //         Action::CustomHomeAction,
+            //     ],
+            //     ..Default::default()
+            // }
+        ])
+    }
 }
 
 impl Board for HomeBoard {
@@ -113,7 +190,7 @@ impl Board for HomeBoard {
         &self.text_style
     }
 
-    fn pads(&self, modifier: Option<ModifierState>) -> Box<dyn PadSet> {
+    fn pads(&self, modifier: Option<&ModifierState>) -> Arc<dyn PadSet> {
         // Get last timestamp from repository
         // let last_timestamp = self.repository.lock()
         //     .map(|repo| repo.get_board_data(&self.profile, "home", "last_action_time"))
@@ -127,7 +204,7 @@ impl Board for HomeBoard {
                 comment_text_style.pad_text_font = comment_text_style.pad_header_font.clone() + " Italic";
 
                 let modifier = modifier.to_string();
-                return Box::new(vec![
+                return Arc::new(vec![
                     Pad::default(),
                     Pad {
                         header: "Custom pad colors\nand styles".to_string(),
@@ -158,53 +235,142 @@ impl Board for HomeBoard {
             }
         }
 
-        Box::new(vec![
+        self.base_pads.clone()
+    }
+}
+
+/// Built-in board shown at startup to pick the profile, one pad per profile in the order of the settings
+#[derive(Clone)]
+pub struct ProfileBoard {
+    color_scheme: ColorScheme,
+    text_style: TextStyle,
+    base_pads: Arc<dyn PadSet>,
+}
+
+impl ProfileBoard {
+    pub fn new(color_scheme: ColorScheme, text_style: TextStyle, profiles: &[String]) -> Self {
+        let base_pads: Vec<Pad> = profiles.iter()
+            .map(|name| Pad {
+                text: name.clone(),
+                ..Default::default()
+            })
+            .collect();
+
+        Self {
+            color_scheme,
+            text_style,
+            base_pads: Arc::new(base_pads),
+        }
+    }
+}
+
+impl Board for ProfileBoard {
+    fn title(&self) -> &str {
+        "HotKeys - Profiles"
+    }
+
+    fn icon(&self) -> Option<&str> {
+        Some("icon.png")
+    }
+
+    fn color_scheme(&self) -> &ColorScheme {
+        &self.color_scheme
+    }
+
+    fn text_style(&self) -> &TextStyle {
+        &self.text_style
+    }
+
+    fn pads(&self, _modifier: Option<&ModifierState>) -> Arc<dyn PadSet> {
+        self.base_pads.clone()
+    }
+}
+
+/// Built-in board shown instead of exiting when the settings fail to load or a board cannot be
+/// created, with the error and pads to open the settings and the log file
+#[derive(Clone)]
+pub struct SafeModeBoard {
+    color_scheme: ColorScheme,
+    text_style: TextStyle,
+    base_pads: Arc<dyn PadSet>,
+}
+
+/// Longest error text shown on the board, the log has the full error
+const MAX_ERROR_CHARS: usize = 240;
+
+impl SafeModeBoard {
+    pub fn new(error: &str, settings_file_path: &str, log_file_path: &str) -> Self {
+        let color_scheme = ColorScheme::default();
+        let mut error_text: String = error.chars().take(MAX_ERROR_CHARS).collect();
+        if error_text.len() < error.len() {
+            error_text.push('…');
+        }
+
+        let base_pads: Arc<dyn PadSet> = Arc::new(vec![
             Pad::default(),
             Pad {
-                header: "Press a NumPad key, a modifier key\nor Escape".to_string(),
+                header: "Safe mode".to_string(),
+                text: error_text,
+                color_scheme: Some(color_scheme.inverted()),
+                fill_background: true,
                 ..Default::default()
             },
             Pad::default(),
             Pad {
-                text: "Project url".to_string(),
+                text: "Configuration".to_string(),
                 actions: vec![
                     Action::Pause(200),
-                    Action::OpenUrl("https://github.com/ivicakukic/hotkeys-linux".to_string()),
+                    Action::OpenUrl(settings_file_path.to_string()),
                 ],
                 ..Default::default()
             },
             Pad::default(),
             Pad {
-                text: "Documentation".to_string(),
+                text: "Log file".to_string(),
                 actions: vec![
                     Action::Pause(200),
-                    Action::OpenUrl("file:///usr/share/doc/hotkeys/README.md".to_string()),
+                    Action::OpenUrl(log_file_path.to_string()),
                 ],
                 ..Default::default()
             },
             Pad::default(),
             Pad {
-                text: "Configuration".to_string(),
+                text: "Documentation".to_string(),
                 actions: vec![
                     Action::Pause(200),
-                    Action::OpenUrl(self.settings_file_path.clone()),
+                    Action::OpenUrl("file:///usr/share/doc/hotkeys/README.md".to_string()),
                 ],
                 ..Default::default()
             },
-            Pad::default()
-            // Pad {
-            //     header: "Last Action".to_string(),
-            //     icon: "".to_string(),
-            //     text: format!("🕐\n{}", last_timestamp),
-            //     actions: vec![
-            //         Action::CustomHomeAction,
-            //     ],
-            //     ..Default::default()
-            // }
-        ])
+            Pad::default(),
+        ]);
+
+        Self {
+            color_scheme,
+            text_style: TextStyle::default(),
+            base_pads,
+        }
+    }
+}
+
+impl Board for SafeModeBoard {
+    fn title(&self) -> &str {
+        "HotKeys - Safe Mode"
+    }
+
+    fn icon(&self) -> Option<&str> {
+        Some("icon.png")
     }
 
-    fn clone_box(&self) -> Box<dyn Board> {
-        Box::new(self.clone())
+    fn color_scheme(&self) -> &ColorScheme {
+        &self.color_scheme
+    }
+
+    fn text_style(&self) -> &TextStyle {
+        &self.text_style
+    }
+
+    fn pads(&self, _modifier: Option<&ModifierState>) -> Arc<dyn PadSet> {
+        self.base_pads.clone()
     }
 }
\ No newline at end of file
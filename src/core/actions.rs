@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
@@ -10,6 +11,8 @@ pub enum Action {
     OpenUrl(String),
     CustomHomeAction,
     Command(String),
+    /// Type the Secret Service secret stored with these lookup attributes
+    Secret(BTreeMap<String, String>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -28,9 +31,31 @@ impl Action {
         }
     }
 
+    /// Action type as written in the settings, e.g. "Shortcut"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Action::Shortcut(_) => "Shortcut",
+            Action::Text(_) => "Text",
+            Action::Line(_) => "Line",
+            Action::Pause(_) => "Pause",
+            Action::OpenUrl(_) => "OpenUrl",
+            Action::CustomHomeAction => "CustomHomeAction",
+            Action::Command(_) => "Command",
+            Action::Secret(_) => "Secret",
+        }
+    }
+
     pub fn is_delayed(&self) -> bool {
         matches!(self, Action::Pause(_))
     }
+
+    /// Free text payload (typed text, command or URL), shortcuts and pauses have none
+    pub fn text_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Action::Text(text) | Action::Line(text) | Action::Command(text) | Action::OpenUrl(text) => Some(text),
+            _ => None,
+        }
+    }
 }
 
 /// Internal utility trait for action collections
@@ -1,31 +1,39 @@
-use super::{Pad, ColorScheme, TextStyle, ModifierState};
+use std::sync::Arc;
+use super::{BackgroundImage, Pad, ColorScheme, TextStyle, ModifierState};
 
-pub trait PadSet {
+pub trait PadSet: Send + Sync {
     fn pads(&self) -> &Vec<Pad>;
-    fn get_or_default(&self, index: usize) -> Pad {
-        self.pads().get(index).cloned().unwrap_or_default()
+    fn get(&self, index: usize) -> Option<&Pad> {
+        self.pads().get(index)
     }
-    fn clone_box(&self) -> Box<dyn PadSet>;
-}
-
-impl Clone for Box<dyn PadSet> {
-    fn clone(&self) -> Box<dyn PadSet> {
-        self.clone_box()
+    fn get_or_default(&self, index: usize) -> Pad {
+        self.get(index).cloned().unwrap_or_default()
     }
 }
 
-pub trait Board {
+/// Boards are shared as `Arc<dyn Board>` and hand out shared pad sets,
+/// so showing and drawing a board does not copy its pads
+pub trait Board: Send + Sync {
     fn title(&self) -> &str;
     fn icon(&self) -> Option<&str>;
     fn color_scheme(&self) -> &ColorScheme;
     fn text_style(&self) -> &TextStyle;
-    fn pads(&self, modifier: Option<ModifierState>) -> Box<dyn PadSet>;
-    fn clone_box(&self) -> Box<dyn Board>;
-}
-
-impl Clone for Box<dyn Board> {
-    fn clone(&self) -> Box<dyn Board> {
-        self.clone_box()
+    fn background_image(&self) -> Option<&BackgroundImage> {
+        None
+    }
+    fn pads(&self, modifier: Option<&ModifierState>) -> Arc<dyn PadSet>;
+    /// Pads selected by tapping a number key twice
+    fn double_pads(&self) -> Option<Arc<dyn PadSet>> {
+        None
+    }
+    /// Modifiers the board has modifier pads for
+    fn modifier_layers(&self) -> Vec<ModifierState> {
+        Vec::new()
+    }
+    /// Modifiers of the pads shown while `held` is held, without the sides no pads are bound to
+    /// (e.g. "Alt" for a held "RAlt"), as selections are recorded
+    fn modifiers(&self, held: &ModifierState) -> ModifierState {
+        held.without_sides()
     }
 }
 
@@ -33,8 +41,4 @@ impl PadSet for Vec<Pad> {
     fn pads(&self) -> &Vec<Pad> {
         self
     }
-
-    fn clone_box(&self) -> Box<dyn PadSet> {
-        Box::new(self.clone())
-    }
 }
\ No newline at end of file
@@ -7,19 +7,120 @@ pub struct Pad {
     pub text: String,
     pub icon: String,
     pub actions: Vec<super::Action>,
+    /// Actions run instead when the pad key is held past the long press time
+    pub long_actions: Vec<super::Action>,
     pub board: Option<String>,
     pub color_scheme: Option<ColorScheme>,
     pub text_style: Option<TextStyle>,
+    /// Opacity of the tile background, the color scheme opacity when not set
+    pub opacity: Option<f64>,
+    /// Paint the tile background even when it matches the board background
+    pub fill_background: bool,
+    /// Icon size in pixels, the text style icon size when not set
+    pub icon_size: Option<f64>,
+    /// Icon placement relative to the text, the text style placement when not set
+    pub icon_placement: Option<IconPlacement>,
+    /// CSS for SVG icons of the pad, applied after the text style CSS
+    pub svg_style: String,
+    /// Counter incremented each time the pad is selected
+    pub counter: Option<String>,
+    /// Action payloads of the pad are never logged and its text is masked
+    pub sensitive: bool,
+}
+
+/// Text shown instead of the text of sensitive pads, the same for any length
+pub const MASKED_TEXT: &str = "********";
+
+impl Pad {
+    /// Text drawn on the tile, masked for sensitive pads
+    pub fn display_text(&self) -> &str {
+        match self.sensitive && !self.text.is_empty() {
+            true => MASKED_TEXT,
+            false => &self.text,
+        }
+    }
+
+    /// The pad as selected with a long press: its long press actions instead of its actions, without navigation
+    pub fn long_pressed(self) -> Self {
+        Self { actions: self.long_actions.clone(), board: None, ..self }
+    }
 }
 
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ColorScheme {
     pub name: String,
-    pub opacity: f64,
+    /// Scheme the colors not set here are derived from, see [ColorScheme::derived_from]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub background: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub foreground1: String, // lines
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub foreground2: String, // text
+    /// Second background color, the background is a linear gradient from `background` to it
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub background2: String,
+    #[serde(default, skip_serializing_if = "GradientDirection::is_default")]
+    pub gradient: GradientDirection,
+    #[serde(flatten)]
+    pub transform: ColorTransform,
+}
+
+/// Direction of a gradient background, from `background` to `background2`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum GradientDirection {
+    /// Top to bottom
+    #[default]
+    Vertical,
+    /// Left to right
+    Horizontal,
+    /// Top left to bottom right
+    Diagonal,
+}
+
+impl GradientDirection {
+    pub fn is_default(&self) -> bool {
+        *self == GradientDirection::default()
+    }
+}
+
+/// Transforms applied to the colors a derived scheme inherits from its base,
+/// in the order invert, hue shift, lighten, darken
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ColorTransform {
+    /// Move the lightness towards white by percent (0-100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lighten: Option<f64>,
+    /// Move the lightness towards black by percent (0-100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub darken: Option<f64>,
+    /// Rotate the hue by degrees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hue_shift: Option<f64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub invert: bool,
+}
+
+impl ColorTransform {
+    pub fn apply(&self, color: Color) -> Color {
+        let color = if self.invert { color.inverted() } else { color };
+        let (mut hue, saturation, mut lightness) = color.to_hsl();
+
+        if let Some(degrees) = self.hue_shift {
+            hue = (hue + degrees).rem_euclid(360.0);
+        }
+        if let Some(percent) = self.lighten {
+            lightness += (1.0 - lightness) * percent.clamp(0.0, 100.0) / 100.0;
+        }
+        if let Some(percent) = self.darken {
+            lightness -= lightness * percent.clamp(0.0, 100.0) / 100.0;
+        }
+        Color::from_hsl(hue, saturation, lightness)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -75,6 +176,45 @@ impl Color {
             b: 255 - self.b,
         }
     }
+
+    /// Hue in degrees (0-360), saturation and lightness (0.0-1.0)
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (r, g, b) = self.to_rgb();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.0;
+        let delta = max - min;
+        if delta == 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+
+        let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+        let hue = if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        (hue, saturation, lightness)
+    }
+
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match sector as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = lightness - chroma / 2.0;
+        let channel = |value: f64| ((value + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Color { r: channel(r), g: channel(g), b: channel(b) }
+    }
 }
 
 impl ColorScheme {
@@ -90,17 +230,206 @@ impl ColorScheme {
         self.to_color(&self.foreground2, "#dbdbec")
     }
 
+    /// Second color of a gradient background, `None` for a flat background
+    pub fn background2(&self) -> Option<Color> {
+        Color::from_hex(&self.background2)
+    }
+
     fn to_color(&self, value: &String, default: &str) -> Color {
         Color::from_hex_or(value.as_str(), default).unwrap()
     }
 
+    /// Board opacity (1 is fully opaque)
+    pub fn opacity(&self) -> f64 {
+        self.opacity.unwrap_or(1.0)
+    }
+
     pub fn inverted(&self) -> ColorScheme {
         ColorScheme {
             name: format!("Inverted{}", self.name),
+            base: None,
             opacity: self.opacity,
             background: self.background().inverted().to_hex(),
             foreground1: self.foreground1().inverted().to_hex(),
             foreground2: self.foreground2().inverted().to_hex(),
+            background2: self.background2().map(|color| color.inverted().to_hex()).unwrap_or_default(),
+            gradient: self.gradient,
+            transform: ColorTransform::default(),
+        }
+    }
+
+    /// Resolve a derived scheme against its (resolved) base: opacity and colors not set here
+    /// are inherited from the base, inherited colors with the transforms applied
+    pub fn derived_from(&self, base: &ColorScheme) -> ColorScheme {
+        let inherit = |own: &String, base: Color| match own.is_empty() {
+            true => self.transform.apply(base).to_hex(),
+            false => own.clone(),
+        };
+
+        ColorScheme {
+            name: self.name.clone(),
+            base: self.base.clone(),
+            opacity: self.opacity.or(base.opacity),
+            background: inherit(&self.background, base.background()),
+            foreground1: inherit(&self.foreground1, base.foreground1()),
+            foreground2: inherit(&self.foreground2, base.foreground2()),
+            background2: match (self.background2.is_empty(), base.background2()) {
+                (true, Some(base)) => self.transform.apply(base).to_hex(),
+                _ => self.background2.clone(),
+            },
+            gradient: if self.background2.is_empty() && !base.background2.is_empty() { base.gradient } else { self.gradient },
+            transform: self.transform.clone(),
+        }
+    }
+
+    /// Schemes without a base must set every value
+    pub fn is_complete(&self) -> bool {
+        self.opacity.is_some() && !self.background.is_empty() && !self.foreground1.is_empty() && !self.foreground2.is_empty()
+    }
+
+    /// Stylesheet injected into SVG icons: the `board-s`, `board-f` and `board-sf` classes in the foreground2 color,
+    /// then the custom CSS with `var(--background)`, `var(--background2)`, `var(--foreground1)` and `var(--foreground2)`
+    /// replaced by the scheme colors
+    pub fn svg_stylesheet(&self, custom: &str) -> String {
+        let color = self.foreground2().to_hex();
+        let custom = custom
+            .replace("var(--background2)", &self.background2().unwrap_or(self.background()).to_hex())
+            .replace("var(--background)", &self.background().to_hex())
+            .replace("var(--foreground1)", &self.foreground1().to_hex())
+            .replace("var(--foreground2)", &color);
+        format!(".board-s {{ stroke: {0}; }}  .board-f {{ fill: {0}; }}  .board-sf {{ stroke: {0}; fill: {0}; }} {1}", color, custom)
+    }
+}
+
+/// Icon of a board or pad: an image (icon file or icon theme name) or text drawn with Pango
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IconKind<'a> {
+    /// "mine/code.svg" or "utilities-terminal"
+    Image(&'a str),
+    /// "emoji:🚀", drawn with the emoji font in its own colors
+    Emoji(&'a str),
+    /// "glyph:★", drawn in the foreground color, e.g. symbols of an installed icon font
+    Glyph(&'a str),
+}
+
+impl<'a> IconKind<'a> {
+    pub fn parse(icon: &'a str) -> Self {
+        if let Some(text) = icon.strip_prefix("emoji:") {
+            IconKind::Emoji(text)
+        } else if let Some(text) = icon.strip_prefix("glyph:") {
+            IconKind::Glyph(text)
+        } else {
+            IconKind::Image(icon)
+        }
+    }
+}
+
+/// Image drawn behind the pads of a board
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BackgroundImage {
+    /// PNG or SVG file, looked up in the icons directories
+    pub image: String,
+    #[serde(default = "BackgroundImage::default_opacity")]
+    pub opacity: f64,
+    #[serde(default, skip_serializing_if = "ImageFit::is_default")]
+    pub fit: ImageFit,
+}
+
+impl BackgroundImage {
+    fn default_opacity() -> f64 {
+        0.3
+    }
+}
+
+/// How an image is scaled to the area it is drawn in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ImageFit {
+    /// Keep the aspect ratio and fill the area, cropping the image
+    #[default]
+    Cover,
+    /// Keep the aspect ratio and show the whole image
+    Contain,
+    /// Fill the area, ignoring the aspect ratio
+    Stretch,
+}
+
+impl ImageFit {
+    pub fn is_default(&self) -> bool {
+        *self == ImageFit::default()
+    }
+}
+
+/// Where the board window is placed on screen
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct WindowPlacement {
+    /// Monitor the window is placed on, the first monitor when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<MonitorSelection>,
+    /// Window corner, edge or center placed at the same point of the monitor (or at the cursor)
+    #[serde(default, skip_serializing_if = "Anchor::is_default")]
+    pub anchor: Anchor,
+    /// Horizontal offset in pixels, to the right
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub x: i32,
+    /// Vertical offset in pixels, downwards
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub y: i32,
+    /// Place the window at the mouse cursor, on the monitor with the cursor
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub at_cursor: bool,
+}
+
+fn is_zero(value: &i32) -> bool {
+    *value == 0
+}
+
+/// Monitor by index (0 is the first monitor), or "with-focused-window"
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum MonitorSelection {
+    Index(usize),
+    Rule(MonitorRule),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MonitorRule {
+    /// Monitor of the window that had the focus before the board was shown
+    WithFocusedWindow,
+}
+
+/// Point of a window aligned with the same point of the area it is placed in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    pub fn is_default(&self) -> bool {
+        *self == Anchor::default()
+    }
+
+    /// Horizontal and vertical position of the anchor, 0 is left/top, 1 is right/bottom
+    pub fn factors(&self) -> (f64, f64) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
         }
     }
 }
@@ -112,9 +441,123 @@ pub struct TextStyle {
     pub pad_header_font: String, // e.g. "Consolas 14"
     pub pad_text_font: String, // e.g. "Arial Bold 16"
     pub pad_id_font: String, // e.g. "Impact Bold 16"
+    /// Reduce the font size of pad headers and texts that do not fit their tile before ellipsizing them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shrink_to_fit: bool,
+    /// Size of pad icons in pixels, 32 when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_size: Option<f64>,
+    /// Placement of pad icons relative to the pad text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_placement: Option<IconPlacement>,
+    /// CSS for SVG icons, see [ColorScheme::svg_stylesheet]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub svg_style: String,
+    /// How the auto-close countdown is shown in the board header
+    #[serde(default, skip_serializing_if = "CountdownStyle::is_default")]
+    pub countdown: CountdownStyle,
+    /// Mark the modifier pads and double tap pads of the board after its title, e.g. "C" for Ctrl pads
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub layer_hints: bool,
+}
+
+/// Visualization of the seconds left until the board closes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum CountdownStyle {
+    /// One dot per second after the board title
+    #[default]
+    Dots,
+    /// Ring at the right of the header emptying clockwise
+    Ring,
+    /// Bar along the bottom of the header shrinking to the left
+    Bar,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+impl CountdownStyle {
+    pub fn is_default(&self) -> bool {
+        *self == CountdownStyle::default()
+    }
+}
+
+/// Where a pad icon is drawn relative to the pad text
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum IconPlacement {
+    /// Icon in the tile center, the text is not drawn
+    #[default]
+    Center,
+    /// Icon above the text, centered together
+    Above,
+    /// Icon left of the text, centered together
+    Left,
+    /// Large faded icon behind the text
+    Watermark,
+}
+
+/// Modifier keys, in the order of [ModifierState::sides]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Super,
+}
+
+impl Modifier {
+    pub const ALL: [Modifier; 4] = [Modifier::Ctrl, Modifier::Shift, Modifier::Alt, Modifier::Super];
+
+    fn name(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "Ctrl",
+            Modifier::Shift => "Shift",
+            Modifier::Alt => "Alt",
+            Modifier::Super => "Super",
+        }
+    }
+
+    /// Letter of the modifier in abbreviations, "W" (Windows key) for Super
+    fn letter(self) -> char {
+        match self {
+            Modifier::Ctrl => 'C',
+            Modifier::Shift => 'S',
+            Modifier::Alt => 'A',
+            Modifier::Super => 'W',
+        }
+    }
+}
+
+/// Side of the keyboard a modifier is held on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum ModifierSide {
+    /// Either side: the sides are not told apart, or the modifier is held on both sides
+    #[default]
+    Any,
+    Left,
+    Right,
+}
+
+impl ModifierSide {
+    fn prefix(self) -> &'static str {
+        match self {
+            ModifierSide::Any => "",
+            ModifierSide::Left => "L",
+            ModifierSide::Right => "R",
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            ModifierSide::Any => ModifierSide::Any,
+            ModifierSide::Left => ModifierSide::Right,
+            ModifierSide::Right => ModifierSide::Left,
+        }
+    }
+}
+
+fn is_any_side(sides: &[ModifierSide; 4]) -> bool {
+    sides.iter().all(|side| *side == ModifierSide::Any)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub struct ModifierState {
     #[serde(default)]
     pub ctrl: bool,
@@ -124,15 +567,17 @@ pub struct ModifierState {
     pub alt: bool,
     #[serde(default, rename = "super")]
     pub super_key: bool,
+    /// Side of each modifier (Ctrl, Shift, Alt, Super), e.g. `Right` for "RAlt"
+    #[serde(default, skip_serializing_if = "is_any_side")]
+    pub sides: [ModifierSide; 4],
 }
 
 impl std::fmt::Display for ModifierState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut parts = Vec::new();
-        if self.ctrl { parts.push("Ctrl"); }
-        if self.shift { parts.push("Shift"); }
-        if self.alt { parts.push("Alt"); }
-        if self.super_key { parts.push("Super"); }
+        let parts: Vec<String> = Modifier::ALL.iter()
+            .filter(|modifier| self.is_held(**modifier))
+            .map(|modifier| format!("{}{}", self.side(*modifier).prefix(), modifier.name()))
+            .collect();
         write!(f, "{}", parts.join("+"))
     }
 }
@@ -141,5 +586,205 @@ impl ModifierState {
     pub fn is_none(&self) -> bool {
         !self.ctrl && !self.shift && !self.alt && !self.super_key
     }
+
+    pub fn is_held(&self, modifier: Modifier) -> bool {
+        match modifier {
+            Modifier::Ctrl => self.ctrl,
+            Modifier::Shift => self.shift,
+            Modifier::Alt => self.alt,
+            Modifier::Super => self.super_key,
+        }
+    }
+
+    fn held_mut(&mut self, modifier: Modifier) -> &mut bool {
+        match modifier {
+            Modifier::Ctrl => &mut self.ctrl,
+            Modifier::Shift => &mut self.shift,
+            Modifier::Alt => &mut self.alt,
+            Modifier::Super => &mut self.super_key,
+        }
+    }
+
+    pub fn side(&self, modifier: Modifier) -> ModifierSide {
+        self.sides[modifier as usize]
+    }
+
+    /// Modifier key pressed on one side, a modifier held on both sides is held on `Any` side
+    pub fn press(&mut self, modifier: Modifier, side: ModifierSide) {
+        let side = match self.is_held(modifier) && self.side(modifier) != side {
+            true => ModifierSide::Any,
+            false => side,
+        };
+        *self.held_mut(modifier) = true;
+        self.sides[modifier as usize] = side;
+    }
+
+    /// Modifier key released on one side, a modifier held on both sides stays held on the other side
+    pub fn release(&mut self, modifier: Modifier, side: ModifierSide) {
+        if self.is_held(modifier) && self.side(modifier) == ModifierSide::Any && side != ModifierSide::Any {
+            self.sides[modifier as usize] = side.opposite();
+            return;
+        }
+        *self.held_mut(modifier) = false;
+        self.sides[modifier as usize] = ModifierSide::Any;
+    }
+
+    /// Short label of the modifiers, e.g. "CS" for "Ctrl+Shift" and "RA" for "RAlt"
+    pub fn abbreviation(&self) -> String {
+        Modifier::ALL.iter()
+            .filter(|modifier| self.is_held(**modifier))
+            .map(|modifier| format!("{}{}", self.side(*modifier).prefix(), modifier.letter()))
+            .collect()
+    }
+
+    /// The same modifiers on either side, e.g. "Ctrl+Alt" for "LCtrl+RAlt"
+    pub fn without_sides(&self) -> Self {
+        Self { sides: Default::default(), ..self.clone() }
+    }
+
+    /// The same modifiers with the sides `tracked` holds them on, for modifiers held in both
+    pub fn with_sides_from(&self, tracked: &ModifierState) -> Self {
+        let mut state = self.without_sides();
+        for modifier in Modifier::ALL.into_iter().filter(|modifier| self.is_held(*modifier) && tracked.is_held(*modifier)) {
+            state.sides[modifier as usize] = tracked.side(modifier);
+        }
+        state
+    }
+
+    /// Whether pads bound to these modifiers are shown while `held` is held: the same modifiers,
+    /// each on the bound side when the binding names one (e.g. "RAlt")
+    pub fn matches(&self, held: &ModifierState) -> bool {
+        Modifier::ALL.iter().all(|modifier| {
+            self.is_held(*modifier) == held.is_held(*modifier)
+                && (self.side(*modifier) == ModifierSide::Any || self.side(*modifier) == held.side(*modifier))
+        })
+    }
+
+    /// The binding shown while these modifiers are held, of the matching bindings the one binding most sides
+    pub fn find_binding<T>(&self, bindings: impl IntoIterator<Item = (ModifierState, T)>) -> Option<(ModifierState, T)> {
+        bindings.into_iter()
+            .filter(|(binding, _)| binding.matches(self))
+            .max_by_key(|(binding, _)| {
+                let sided = binding.sides.iter().filter(|side| **side != ModifierSide::Any).count();
+                (sided, binding.to_string())
+            })
+    }
+}
+
+impl std::str::FromStr for ModifierState {
+    type Err = String;
+
+    /// Parse modifier combinations like "Ctrl+Shift" or "LCtrl+RAlt" (case-insensitive, empty string for no modifiers)
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut state = ModifierState::default();
+        for part in text.split('+').map(str::trim).filter(|p| !p.is_empty()) {
+            let name = part.to_lowercase();
+            let sided = [ModifierSide::Left, ModifierSide::Right].into_iter()
+                .find_map(|side| name.strip_prefix(&side.prefix().to_lowercase()).map(|name| (side, name.to_string())));
+            let modifier = |name: &str| Modifier::ALL.into_iter().find(|modifier| modifier.name().eq_ignore_ascii_case(name));
+            let (modifier, side) = match modifier(&name) {
+                Some(modifier) => (modifier, ModifierSide::Any),
+                None => sided.and_then(|(side, name)| modifier(&name).map(|modifier| (modifier, side)))
+                    .ok_or_else(|| format!("Unknown modifier: '{}'", part))?,
+            };
+            state.press(modifier, side);
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_transform() {
+        let color = Color::from_hex("#3060c0").unwrap();
+        assert_eq!(Color::from_hsl(color.to_hsl().0, color.to_hsl().1, color.to_hsl().2).to_hex(), "#3060c0");
+
+        let transform = |json: &str| serde_json::from_str::<ColorTransform>(json).unwrap().apply(color.clone()).to_hex();
+        assert_eq!(transform(r#"{}"#), "#3060c0");
+        assert_eq!(transform(r#"{"invert": true}"#), "#cf9f3f");
+        assert_eq!(transform(r#"{"hue_shift": 180}"#), "#c09030");
+        assert_eq!(transform(r#"{"lighten": 100}"#), "#ffffff");
+        assert_eq!(transform(r#"{"darken": 100}"#), "#000000");
+        assert_eq!(transform(r#"{"hue_shift": -360, "lighten": 50}"#), "#93aee4");
+    }
+
+    #[test]
+    fn test_modifier_sides() {
+        let state: ModifierState = "lctrl+RShift".parse().unwrap();
+        assert_eq!(state.to_string(), "LCtrl+RShift");
+        assert_eq!(state.without_sides().to_string(), "Ctrl+Shift");
+        assert_eq!(state.abbreviation(), "LCRS");
+        assert_eq!("Alt+Super".parse::<ModifierState>().unwrap().abbreviation(), "AW");
+        assert!("RCtrl+Alt".parse::<ModifierState>().is_ok());
+        assert!("RHyper".parse::<ModifierState>().is_err());
+        // Saved without sides as before
+        assert_eq!(serde_json::to_string(&state.without_sides()).unwrap(), r#"{"ctrl":true,"shift":true,"alt":false,"super":false}"#);
+
+        let ralt: ModifierState = "RAlt".parse().unwrap();
+        let alt: ModifierState = "Alt".parse().unwrap();
+        assert!(alt.matches(&ralt));
+        assert!(ralt.matches(&ralt));
+        assert!(!ralt.matches(&"LAlt".parse().unwrap()));
+        assert!(!ralt.matches(&alt));
+        assert!(!alt.matches(&"Ctrl+RAlt".parse().unwrap()));
+
+        let bindings = [(alt.clone(), "alt"), (ralt.clone(), "ralt")];
+        assert_eq!(ralt.find_binding(bindings.clone()), Some((ralt.clone(), "ralt")));
+        assert_eq!("LAlt".parse::<ModifierState>().unwrap().find_binding(bindings.clone()), Some((alt.clone(), "alt")));
+        assert_eq!(ModifierState::default().find_binding(bindings), None);
+
+        let mut held = ModifierState::default();
+        held.press(Modifier::Ctrl, ModifierSide::Left);
+        held.press(Modifier::Ctrl, ModifierSide::Right);
+        assert_eq!(held.to_string(), "Ctrl");
+        held.release(Modifier::Ctrl, ModifierSide::Left);
+        assert_eq!(held.to_string(), "RCtrl");
+        held.release(Modifier::Ctrl, ModifierSide::Right);
+        assert!(held.is_none());
+
+        let masked = ModifierState { ctrl: true, alt: true, ..Default::default() };
+        assert_eq!(masked.with_sides_from(&"RAlt+LShift".parse().unwrap()).to_string(), "Ctrl+RAlt");
+    }
+
+    #[test]
+    fn test_icon_kind() {
+        assert_eq!(IconKind::parse("mine/code.svg"), IconKind::Image("mine/code.svg"));
+        assert_eq!(IconKind::parse("utilities-terminal"), IconKind::Image("utilities-terminal"));
+        assert_eq!(IconKind::parse("emoji:🚀"), IconKind::Emoji("🚀"));
+        assert_eq!(IconKind::parse("glyph:\u{f135}"), IconKind::Glyph("\u{f135}"));
+    }
+
+    #[test]
+    fn test_gradient_background() {
+        let base: ColorScheme = serde_json::from_str(r##"{
+            "name": "dark", "opacity": 0.8, "background": "#000080", "foreground1": "#ffffff", "foreground2": "#ffffff",
+            "background2": "#000000", "gradient": "Diagonal"
+        }"##).unwrap();
+        assert_eq!(base.background2().map(|color| color.to_hex()), Some("#000000".to_string()));
+        assert_eq!(base.inverted().background2, "#ffffff");
+
+        let derived: ColorScheme = serde_json::from_str(r#"{ "name": "light", "base": "dark", "invert": true }"#).unwrap();
+        let derived = derived.derived_from(&base);
+        assert_eq!((derived.background2.as_str(), derived.gradient), ("#ffffff", GradientDirection::Diagonal));
+
+        let flat: ColorScheme = serde_json::from_str(r##"{ "name": "flat", "opacity": 1, "background": "#000080", "foreground1": "#ffffff", "foreground2": "#ffffff" }"##).unwrap();
+        assert!(flat.background2().is_none());
+        assert_eq!(serde_json::to_value(&flat).unwrap().get("gradient"), None);
+    }
+
+    #[test]
+    fn test_svg_stylesheet() {
+        let scheme: ColorScheme = serde_json::from_str(r##"{
+            "name": "dark", "opacity": 1, "background": "#000080", "foreground1": "#ff0000", "foreground2": "#ffffff"
+        }"##).unwrap();
+        assert_eq!(scheme.svg_stylesheet(""),
+            ".board-s { stroke: #ffffff; }  .board-f { fill: #ffffff; }  .board-sf { stroke: #ffffff; fill: #ffffff; } ");
+
+        let stylesheet = scheme.svg_stylesheet(".accent { fill: var(--foreground1); } .shade { fill: var(--background2); }");
+        assert!(stylesheet.ends_with(" .accent { fill: #ff0000; } .shade { fill: #000080; }"));
+    }
 }
 
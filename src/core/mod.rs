@@ -3,6 +3,7 @@ pub mod board;
 pub mod actions;
 pub mod repository;
 pub mod resources;
+pub mod perf;
 
 // Re-export core types for convenience
 pub use data::*;
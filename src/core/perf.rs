@@ -0,0 +1,99 @@
+/// Startup latency instrumentation (`--perf`): how long each stage between requesting a board and
+/// seeing it takes, printed to stderr when the board closes
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TIMINGS: Mutex<Option<Timings>> = Mutex::new(None);
+
+/// Stage and the time it started and ended at, relative to the start of the clock
+#[derive(Debug, Clone, PartialEq)]
+struct Stage {
+    name: &'static str,
+    start: Duration,
+    end: Duration,
+}
+
+struct Timings {
+    start: Instant,
+    stages: Vec<Stage>,
+}
+
+/// Enable the instrumentation and start the clock
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+    start();
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Restart the clock and forget the recorded stages (e.g. when the daemon is triggered)
+pub fn start() {
+    if enabled() {
+        *TIMINGS.lock().unwrap() = Some(Timings { start: Instant::now(), stages: Vec::new() });
+    }
+}
+
+/// Run a stage and record how long it took
+pub fn measure<T>(name: &'static str, stage: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = stage();
+    record(name, started);
+    result
+}
+
+/// Record a stage which started at the given time and ends now.
+/// Only the first occurrence of a stage since the clock started is kept (e.g. the first draw)
+pub fn record(name: &'static str, started: Instant) {
+    if !enabled() {
+        return;
+    }
+    let ended = Instant::now();
+    if let Some(timings) = TIMINGS.lock().unwrap().as_mut()
+        .filter(|timings| !timings.stages.iter().any(|stage| stage.name == name)) {
+        let offset = |instant: Instant| instant.saturating_duration_since(timings.start);
+        timings.stages.push(Stage { name, start: offset(started), end: offset(ended) });
+    }
+}
+
+/// Print the stages recorded since the clock started, nothing if there are none
+pub fn report() {
+    if !enabled() {
+        return;
+    }
+    let stages = TIMINGS.lock().unwrap().as_mut().map(|timings| std::mem::take(&mut timings.stages)).unwrap_or_default();
+    if !stages.is_empty() {
+        eprint!("{}", format_report(&stages));
+    }
+}
+
+fn format_report(stages: &[Stage]) -> String {
+    let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    let mut stages = stages.to_vec();
+    stages.sort_by_key(|stage| stage.start);
+
+    let mut report = String::from("Startup timings (ms):\n");
+    for stage in stages {
+        report.push_str(&format!("  {:<14} {:>8.1}  (at {:.1} - {:.1})\n",
+            stage.name, millis(stage.end - stage.start), millis(stage.start), millis(stage.end)));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        let stage = |name, start, end| Stage { name, start: Duration::from_micros(start), end: Duration::from_micros(end) };
+        let report = format_report(&[stage("first draw", 40_000, 52_500), stage("config load", 100, 3_100)]);
+        assert_eq!(report, "Startup timings (ms):\n\
+            \x20 config load         3.0  (at 0.1 - 3.1)\n\
+            \x20 first draw         12.5  (at 40.0 - 52.5)\n");
+    }
+}
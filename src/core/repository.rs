@@ -1,4 +1,5 @@
 use anyhow::Result;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[allow(dead_code)]
 /// DataRepository trait for hierarchical data storage
@@ -22,6 +23,21 @@ pub trait DataRepository: Send + Sync + std::fmt::Debug {
     /// Set padset-level data within a board
     fn set_padset_data(&mut self, profile: &str, board: &str, padset: &str, key: &str, value: &str) -> Result<()>;
 
+    /// Set board-level data which expires after the time to live (e.g. caches of dynamic boards).
+    /// Expired data is not returned and is removed by [DataRepository::prune]
+    fn set_board_data_with_ttl(&mut self, profile: &str, board: &str, key: &str, value: &str, ttl: Duration) -> Result<()>;
+
+    /// Set padset-level data which expires after the time to live, the padset being the namespace of its board
+    fn set_padset_data_with_ttl(&mut self, profile: &str, board: &str, padset: &str, key: &str, value: &str, ttl: Duration) -> Result<()>;
+
+    /// Remove expired data, returns how many values were removed
+    fn prune(&mut self) -> usize;
+
     /// Persist any pending changes to storage
     fn flush(&mut self) -> Result<()>;
+}
+
+/// Current time in seconds since the unix epoch, the unit of data expiry times
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or_default()
 }
\ No newline at end of file
@@ -1,15 +1,51 @@
-use std::path::PathBuf;
+use super::IconKind;
+use std::path::{Path, PathBuf};
 
+/// Supported settings file formats, in order of preference within a config directory
+const SETTINGS_EXTENSIONS: &[&str] = &["json", "toml", "yaml", "yml"];
+
+/// Image file extensions, icon names without them are looked up in the icon theme
+const ICON_EXTENSIONS: &[&str] = &[".png", ".svg"];
+
+/// Sound file extensions, sound names without them are sound theme names
+const SOUND_EXTENSIONS: &[&str] = &[".oga", ".ogg", ".wav", ".flac"];
+
+/// Files a directory or pattern include loads, see [Resources::include_files]
+#[derive(Debug, Clone, PartialEq)]
+pub enum IncludePattern {
+    /// Every settings file of the directory
+    Settings,
+    /// File names matching the pattern, with '*' and '?' wildcards
+    Wildcard(String),
+}
+
+impl IncludePattern {
+    pub fn matches(&self, path: &Path) -> bool {
+        match self {
+            IncludePattern::Wildcard(pattern) => path.file_name().and_then(|n| n.to_str()).is_some_and(|name| wildcard_match(pattern, name)),
+            IncludePattern::Settings => path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| SETTINGS_EXTENSIONS.contains(&ext)),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Resources {
-    config_paths: Vec<PathBuf>
+    config_paths: Vec<PathBuf>,
+    /// Checks icon theme names, see [Self::with_icon_theme]
+    icon_theme: Option<fn(&str) -> bool>,
 }
 
 impl Resources {
 
     pub fn new(config_paths: Vec<PathBuf>) -> Self {
-        Resources { config_paths }
+        Resources { config_paths, icon_theme: None }
+    }
+
+    /// Check icon theme names with `has_icon` (the desktop icon theme once GTK is initialized),
+    /// without it any theme name is assumed to exist
+    pub fn with_icon_theme(mut self, has_icon: fn(&str) -> bool) -> Self {
+        self.icon_theme = Some(has_icon);
+        self
     }
 
     pub fn file(&self, file_name: &str) -> Option<PathBuf> {
@@ -22,17 +58,113 @@ impl Resources {
         None
     }
 
+    /// Resolve an include entry: a file, a directory (all settings files in it) or a pattern
+    /// with '*' and '?' wildcards in the file name (e.g. "boards.d/*.json").
+    /// Directories and patterns are resolved in the first config directory with matches, files are sorted by name.
+    /// Returns None if a plain file does not exist, directories and patterns may match nothing.
+    pub fn include_files(&self, include: &str) -> Option<Vec<PathBuf>> {
+        let Some((directory, pattern)) = self.include_pattern(include) else {
+            return self.file(include).map(|path| vec![path]);
+        };
+
+        for path in &self.config_paths {
+            let Ok(entries) = std::fs::read_dir(path.join(directory)) else {
+                continue;
+            };
+            let mut files: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && pattern.matches(path))
+                .collect();
+            if !files.is_empty() {
+                files.sort();
+                return Some(files);
+            }
+        }
+        Some(Vec::new())
+    }
+
+    /// Existing directories a directory or pattern include lists (in every config directory, as files added to any
+    /// of them change what it resolves to) and the pattern of the files it loads. Empty for plain file includes
+    pub fn include_directories(&self, include: &str) -> Vec<(PathBuf, IncludePattern)> {
+        let Some((directory, pattern)) = self.include_pattern(include) else {
+            return Vec::new();
+        };
+        self.config_paths.iter()
+            .map(|path| path.join(directory))
+            .filter(|path| path.is_dir())
+            .map(|path| (path, pattern.clone()))
+            .collect()
+    }
+
+    /// Directory to list and the pattern of the files to load, None for plain file includes
+    fn include_pattern<'a>(&self, include: &'a str) -> Option<(&'a Path, IncludePattern)> {
+        let include_path = Path::new(include);
+        let file_name = include_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if file_name.contains(['*', '?']) {
+            Some((include_path.parent().unwrap_or(Path::new("")), IncludePattern::Wildcard(file_name.to_string())))
+        } else if self.file(include).is_some_and(|path| path.is_dir()) {
+            Some((include_path, IncludePattern::Settings))
+        } else {
+            None
+        }
+    }
+
     pub fn icon(&self, icon_file: &str) -> Option<PathBuf> {
         let icon_file = format!("icons/{}", icon_file);
         self.file(&icon_file)
     }
 
+    /// Whether the icon can be shown: an icon file, or a name from the desktop icon theme
+    /// (looked up by the board window, only checked with [Self::with_icon_theme])
+    pub fn has_icon(&self, icon_file: &str) -> bool {
+        match IconKind::parse(icon_file) {
+            IconKind::Emoji(text) | IconKind::Glyph(text) => !text.is_empty(),
+            IconKind::Image(icon_file) => self.icon(icon_file).is_some()
+                || Self::is_theme_icon(icon_file) && self.icon_theme.is_none_or(|has_icon| has_icon(icon_file)),
+        }
+    }
+
+    /// Icon names without a path and an image file extension are icon theme names
+    pub fn is_theme_icon(icon_file: &str) -> bool {
+        !icon_file.is_empty() && !icon_file.contains(['/', ':']) && !ICON_EXTENSIONS.iter().any(|ext| icon_file.ends_with(ext))
+    }
+
+    /// Sound file under sounds/ in the config directories, or an absolute path
+    pub fn sound(&self, sound_file: &str) -> Option<PathBuf> {
+        if Path::new(sound_file).is_absolute() {
+            return Some(PathBuf::from(sound_file)).filter(|path| path.exists());
+        }
+        let sound_file = format!("sounds/{}", sound_file);
+        self.file(&sound_file)
+    }
+
+    /// Whether the sound can be played: a sound file, or a name from the freedesktop sound theme
+    /// (played by libcanberra, they cannot be checked here)
+    pub fn has_sound(&self, sound: &str) -> bool {
+        Self::is_theme_sound(sound) || self.sound(sound).is_some()
+    }
+
+    /// Sound names without a path and a sound file extension are sound theme names, e.g. "bell"
+    pub fn is_theme_sound(sound: &str) -> bool {
+        !sound.is_empty() && !sound.contains('/') && !SOUND_EXTENSIONS.iter().any(|ext| sound.ends_with(ext))
+    }
+
+    /// Optional GTK stylesheet for the board window
+    pub fn style_css(&self) -> Option<PathBuf> {
+        self.file(env!("RESOURCE_STYLE_FILE"))
+    }
+
     pub fn log_toml(&self) -> Option<PathBuf> {
         self.file(env!("RESOURCE_LOG_FILE"))
     }
 
-    pub fn settings_json(&self) -> Option<PathBuf> {
-        self.file(env!("RESOURCE_SETTINGS_FILE"))
+    /// Settings file (settings.json, .toml or .yaml) from the first config directory containing one
+    pub fn settings_file(&self) -> Option<PathBuf> {
+        let settings_file = Path::new(env!("RESOURCE_SETTINGS_FILE"));
+        self.config_paths.iter()
+            .flat_map(|path| SETTINGS_EXTENSIONS.iter().map(move |ext| path.join(settings_file.with_extension(ext))))
+            .find(|path| path.exists())
     }
 
     pub fn data_json(&self) -> PathBuf {
@@ -40,3 +172,120 @@ impl Resources {
     }
 
 }
+
+/// Match a file name against a pattern with '*' (any sequence) and '?' (any character) wildcards
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Position after the last '*' in pattern and the name position it was tried at
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p + 1, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.json", "code.json"));
+        assert!(wildcard_match("board.*.json", "board.chrome.json"));
+        assert!(wildcard_match("?.yaml", "a.yaml"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(!wildcard_match("*.json", "code.json.bak"));
+        assert!(!wildcard_match("?.yaml", "ab.yaml"));
+    }
+
+    #[test]
+    fn test_theme_icon() {
+        assert!(Resources::is_theme_icon("utilities-terminal"));
+        assert!(Resources::is_theme_icon("org.gnome.Terminal"));
+        assert!(!Resources::is_theme_icon("mine/code.svg"));
+        assert!(!Resources::is_theme_icon("code.png"));
+        assert!(!Resources::is_theme_icon("emoji:🚀"));
+
+        let resources = Resources::new(vec![]);
+        assert!(resources.has_icon("utilities-terminal"));
+        assert!(!resources.has_icon("code.png"));
+        assert!(resources.has_icon("emoji:🚀"));
+        assert!(!resources.has_icon("glyph:"));
+
+        let resources = resources.with_icon_theme(|name| name == "utilities-terminal");
+        assert!(resources.has_icon("utilities-terminal"));
+        assert!(!resources.has_icon("utilities-termnial"));
+    }
+
+    #[test]
+    fn test_theme_sound() {
+        assert!(Resources::is_theme_sound("bell"));
+        assert!(Resources::is_theme_sound("dialog-information"));
+        assert!(!Resources::is_theme_sound("open.oga"));
+        assert!(!Resources::is_theme_sound("/usr/share/sounds/freedesktop/stereo/bell.oga"));
+
+        let resources = Resources::new(vec![]);
+        assert!(resources.has_sound("bell"));
+        assert!(!resources.has_sound("open.wav"));
+        assert!(!resources.has_sound(""));
+    }
+
+    #[test]
+    fn test_absolute_sound() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-sounds-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sounds")).unwrap();
+        let (absolute, relative) = (dir.join("bye.oga"), dir.join("sounds").join("open.oga"));
+        std::fs::write(&absolute, "").unwrap();
+        std::fs::write(&relative, "").unwrap();
+
+        let resources = Resources::new(vec![dir.clone()]);
+        assert_eq!(resources.sound(absolute.to_str().unwrap()), Some(absolute.clone()));
+        assert!(resources.has_sound(absolute.to_str().unwrap()));
+        assert_eq!(resources.sound("open.oga"), Some(relative));
+        assert_eq!(resources.sound(dir.join("missing.oga").to_str().unwrap()), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_include_files() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-resources-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("boards.d")).unwrap();
+        for file in ["b.json", "a.yaml", "notes.txt"] {
+            std::fs::write(dir.join("boards.d").join(file), "{}").unwrap();
+        }
+        let resources = Resources::new(vec![dir.clone()]);
+
+        let names = |files: Option<Vec<PathBuf>>| files.unwrap().iter()
+            .map(|f| f.file_name().unwrap().to_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names(resources.include_files("boards.d")), vec!["a.yaml", "b.json"]);
+        assert_eq!(names(resources.include_files("boards.d/*.json")), vec!["b.json"]);
+        assert_eq!(names(resources.include_files("boards.d/notes.txt")), vec!["notes.txt"]);
+        assert_eq!(resources.include_files("boards.d/*.toml"), Some(Vec::new()));
+        assert_eq!(resources.include_files("missing.json"), None);
+
+        assert_eq!(resources.include_directories("boards.d"), vec![(dir.join("boards.d"), IncludePattern::Settings)]);
+        assert_eq!(resources.include_directories("boards.d/*.json"), vec![(dir.join("boards.d"), IncludePattern::Wildcard("*.json".to_string()))]);
+        assert!(resources.include_directories("boards.d/notes.txt").is_empty());
+        assert!(IncludePattern::Wildcard("*.json".to_string()).matches(Path::new("boards.d/c.json")));
+        assert!(!IncludePattern::Settings.matches(Path::new("boards.d/notes.txt")));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
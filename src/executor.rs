@@ -1,25 +1,37 @@
 /// Action execution module - handles all pad action types
 
 use crate::core::{Action, DataRepository};
-use crate::app::config::KeyboardLayout;
-use crate::input::script;
+use crate::app::{audit::AuditLog, config::{KeyboardLayout, UnmappedCharacters}};
+use crate::input::{clipboard, script, secret};
 use anyhow::Result;
 use open;
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 
-/// Execute a list of actions sequentially with optional repository access
+/// Pause (ms) letting the application insert pasted text before Enter is pressed
+const PASTE_PAUSE: u16 = 50;
+
+/// Execute a list of actions sequentially with optional repository access.
+/// With `redact` the typed texts, commands and URLs are not written to the log,
+/// each executed action is recorded in the audit log when given
 pub fn execute_actions(
     actions: &[Action],
     keyboard_layout: &KeyboardLayout,
     repository: Option<Arc<Mutex<dyn DataRepository>>>,
-    profile: Option<&str>
+    profile: Option<&str>,
+    redact: bool,
+    audit: Option<&AuditLog>
 ) -> Result<()> {
     log::info!("Executing {} actions", actions.len());
 
     for action in actions {
-        match execute_action(action, keyboard_layout, repository.as_ref(), profile) {
+        let result = execute_action(action, keyboard_layout, repository.as_ref(), profile, redact);
+        if let Some(audit) = audit {
+            audit.record(action, &result);
+        }
+        match result {
             Err(e) => {
-                log::error!("Failed to execute action {:?}: {}", action, e);
+                log::error!("Failed to execute action {}: {}", describe(action, redact), e);
                 return Err(e);
             },
             _ => {}
@@ -31,43 +43,63 @@ pub fn execute_actions(
 }
 
 
+/// Payload as written to the log, only its length when redacted
+fn logged(text: &str, redact: bool) -> String {
+    match redact {
+        true => format!("<{} characters>", text.chars().count()),
+        false => text.to_string(),
+    }
+}
+
+/// Action as written to the log
+fn describe(action: &Action, redact: bool) -> String {
+    let mut action = action.clone();
+    if let Some(text) = action.text_mut() {
+        *text = logged(text, redact);
+    }
+    format!("{:?}", action)
+}
+
 /// Execute a single action
 fn execute_action(
     action: &Action,
     keyboard_layout: &KeyboardLayout,
     repository: Option<&Arc<Mutex<dyn DataRepository>>>,
-    profile: Option<&str>
+    profile: Option<&str>,
+    redact: bool
 ) -> Result<()> {
-    let keyboard_layout_mapping = keyboard_layout.mappings.clone();
-
     match action {
         Action::Shortcut(shortcut_text) => {
             log::info!("Executing shortcut: {}", shortcut_text);
             script::for_shortcut(shortcut_text.clone()).play()
         },
         Action::Text(text) => {
-            log::info!("Executing text input: {}", text);
-            script::for_text(text.clone(), keyboard_layout_mapping).play()
+            log::info!("Executing text input: {}", logged(text, redact));
+            type_text(text, false, keyboard_layout, redact)
         },
         Action::Line(line_text) => {
-            log::info!("Executing line input: {}", line_text);
-            script::for_line(line_text.clone(), keyboard_layout_mapping).play()
+            log::info!("Executing line input: {}", logged(line_text, redact));
+            type_text(line_text, true, keyboard_layout, redact)
         },
         Action::Pause(milliseconds) => {
             log::info!("Executing pause: {} ms", milliseconds);
             script::for_pause((*milliseconds).min(u16::MAX as u64) as u16).play()
         },
         Action::OpenUrl(url) => {
-            log::info!("Executing OpenUrl: {}", url);
-            open_url(url)
+            log::info!("Executing OpenUrl: {}", logged(url, redact));
+            open_url(url, redact)
         },
         Action::CustomHomeAction => {
             log::info!("Executing CustomHomeAction");
             execute_custom_home_action(repository, profile)
         },
         Action::Command(command) => {
-            log::info!("Executing command: {}", command);
-            execute_command(command)
+            log::info!("Executing command: {}", logged(command, redact));
+            execute_command(command, redact)
+        },
+        Action::Secret(attributes) => {
+            log::info!("Executing secret input: {:?}", attributes);
+            type_secret(attributes, keyboard_layout)
         }
     }
 }
@@ -102,13 +134,45 @@ fn execute_custom_home_action(
     }
 }
 
+/// Type text with the keyboard layout, or paste it from the clipboard when the layout
+/// cannot type some of its characters and is configured to paste them
+fn type_text(text: &str, new_line: bool, keyboard_layout: &KeyboardLayout, redact: bool) -> Result<()> {
+    let ckey = keyboard_layout.character_keys();
+    if keyboard_layout.unmapped_characters != UnmappedCharacters::Clipboard || ckey.can_type(text) {
+        return match new_line {
+            true => script::for_line(text.to_owned(), &ckey).play(),
+            false => script::for_text(text.to_owned(), &ckey).play(),
+        };
+    }
+
+    log::info!("Pasting text the keyboard layout cannot type: {}", logged(text, redact));
+    clipboard::set_text(text)?;
+    script::for_shortcut(keyboard_layout.paste_shortcut()).play()?;
+    if new_line {
+        script::for_pause(PASTE_PAUSE).play()?;
+        script::for_line(String::new(), &ckey).play()?;
+    }
+    Ok(())
+}
+
+/// Look up a secret and type it. Secrets the layout cannot type are not pasted,
+/// that would leave them in the clipboard
+fn type_secret(attributes: &BTreeMap<String, String>, keyboard_layout: &KeyboardLayout) -> Result<()> {
+    let secret = secret::lookup(attributes)?;
+    let ckey = keyboard_layout.character_keys();
+    if !ckey.can_type(&secret) {
+        anyhow::bail!("Secret for {:?} has characters the keyboard layout cannot type", attributes);
+    }
+    script::for_text(secret, &ckey).play()
+}
+
 /// Open a URL in the default web browser
-fn open_url(url: &str) -> Result<()> {
-    open::that(url).map_err(|e| anyhow::anyhow!("Failed to open URL {}: {}", url, e))
+fn open_url(url: &str, redact: bool) -> Result<()> {
+    open::that(url).map_err(|e| anyhow::anyhow!("Failed to open URL {}: {}", logged(url, redact), e))
 }
 
 /// Execute a shell command asynchronously without waiting for completion
-fn execute_command(command: &str) -> Result<()> {
+fn execute_command(command: &str, redact: bool) -> Result<()> {
     use std::process::{Command, Stdio};
 
     let mut cmd = Command::new("sh");
@@ -122,13 +186,26 @@ fn execute_command(command: &str) -> Result<()> {
     // Spawn the process without waiting for completion
     match cmd.spawn() {
         Ok(_) => {
-            log::info!("Successfully spawned command: {}", command);
+            log::info!("Successfully spawned command: {}", logged(command, redact));
             Ok(())
         },
         Err(e) => {
-            let error_msg = format!("Failed to spawn command '{}': {}", command, e);
+            let error_msg = format!("Failed to spawn command '{}': {}", logged(command, redact), e);
             log::error!("{}", error_msg);
             Err(anyhow::anyhow!(error_msg))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_redacted() {
+        let action = Action::Line("hunter2".to_string());
+        assert_eq!(describe(&action, false), r#"Line("hunter2")"#);
+        assert_eq!(describe(&action, true), r#"Line("<7 characters>")"#);
+        assert_eq!(describe(&Action::Shortcut("Ctrl V".to_string()), true), r#"Shortcut("Ctrl V")"#);
+    }
+}
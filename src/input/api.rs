@@ -2,15 +2,17 @@
 
 
 use super::keys::get_vkey;
+use crate::core::perf;
 
 use std::fmt::Display;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::os::fd::AsRawFd;
 use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 use anyhow::{Result, anyhow};
 // Linux input event constants
-const EV_KEY: u16 = 0x01;
+pub(super) const EV_KEY: u16 = 0x01;
 const EV_SYN: u16 = 0x00;
 const SYN_REPORT: u16 = 0;
 
@@ -53,19 +55,22 @@ unsafe fn ui_dev_destroy(fd: libc::c_int) -> Result<()> {
     Ok(())
 }
 
+/// Name of the virtual keyboard created by HotKeys
+pub(super) const VIRTUAL_KEYBOARD_NAME: &str = "HotKeys Virtual Keyboard";
+
 /// Input event structure matching Linux input_event
 #[repr(C)]
 #[derive(Debug)]
-struct InputEvent {
-    tv_sec: i64,      // Time seconds
-    tv_usec: i64,     // Time microseconds
-    type_: u16,       // Event type
-    code: u16,        // Event code
-    value: i32,       // Event value
+pub(super) struct InputEvent {
+    pub(super) tv_sec: i64,      // Time seconds
+    pub(super) tv_usec: i64,     // Time microseconds
+    pub(super) type_: u16,       // Event type
+    pub(super) code: u16,        // Event code
+    pub(super) value: i32,       // Event value
 }
 
 impl InputEvent {
-    fn new(type_: u16, code: u16, value: i32) -> Self {
+    pub(super) fn new(type_: u16, code: u16, value: i32) -> Self {
         Self {
             tv_sec: 0,
             tv_usec: 0,
@@ -153,7 +158,7 @@ impl UinputDevice {
         };
 
         // Set device name
-        let name = b"HotKeys Virtual Keyboard";
+        let name = VIRTUAL_KEYBOARD_NAME.as_bytes();
         let name_len = std::cmp::min(name.len(), 79);
         dev.name[..name_len].copy_from_slice(&name[..name_len]);
 
@@ -241,7 +246,7 @@ fn get_global_device_with_timeout(sleep: u64) -> Result<std::sync::MutexGuard<'s
 
     if guard.is_none() {
         log::debug!("Creating new global uinput device");
-        let device = UinputDevice::new()?;
+        let device = perf::measure("uinput init", UinputDevice::new)?;
         // Wait for device to be ready (solve timing issue)
         if sleep > 0 {
             std::thread::sleep(std::time::Duration::from_millis(sleep));
@@ -253,8 +258,26 @@ fn get_global_device_with_timeout(sleep: u64) -> Result<std::sync::MutexGuard<'s
     Ok(guard)
 }
 
+/// When set, keyboard input is dropped instead of being sent to the uinput device
+static INJECTION_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Pause or resume keyboard input injection
+pub fn set_injection_paused(paused: bool) {
+    INJECTION_PAUSED.store(paused, Ordering::SeqCst);
+    log::info!("Input injection {}", if paused { "paused" } else { "resumed" });
+}
+
+pub fn is_injection_paused() -> bool {
+    INJECTION_PAUSED.load(Ordering::SeqCst)
+}
+
 /// Send a single keyboard input using Linux key code
 pub fn send_input(input: KeyboardInput) -> Result<()> {
+    if is_injection_paused() {
+        log::info!("Input injection paused - dropping input {}", input);
+        return Ok(());
+    }
+
     let mut device_guard = get_global_device()?;
     let device = device_guard.as_mut().ok_or_else(|| anyhow!("Global device not initialized"))?;
 
@@ -272,6 +295,11 @@ pub fn send_input(input: KeyboardInput) -> Result<()> {
 
 /// Send multiple keyboard inputs in sequence
 pub fn send_inputs(inputs: Vec<KeyboardInput>) -> Result<()> {
+    if is_injection_paused() {
+        log::info!("Input injection paused - dropping {} inputs", inputs.len());
+        return Ok(());
+    }
+
     let mut device_guard = get_global_device()?;
     let device = device_guard.as_mut().ok_or_else(|| anyhow!("Global device not initialized"))?;
 
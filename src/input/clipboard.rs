@@ -0,0 +1,66 @@
+/// Clipboard access for paste based text input.
+/// Uses the wl-clipboard (Wayland) or xclip/xsel (X11) command line tools, whichever is installed
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use anyhow::{anyhow, Result};
+
+const WAYLAND_COPY_COMMANDS: &[&[&str]] = &[&["wl-copy"]];
+const X11_COPY_COMMANDS: &[&[&str]] = &[&["xclip", "-selection", "clipboard"], &["xsel", "--clipboard", "--input"]];
+
+/// Commands copying stdin to the clipboard, in order of preference.
+/// X11 tools also work under Wayland through Xwayland's clipboard synchronization
+fn copy_commands(wayland: bool) -> Vec<&'static [&'static str]> {
+    let wayland_commands = if wayland { WAYLAND_COPY_COMMANDS } else { &[] };
+    wayland_commands.iter()
+        .chain(X11_COPY_COMMANDS)
+        .copied()
+        .collect()
+}
+
+/// Replace the clipboard contents with the text
+pub fn set_text(text: &str) -> Result<()> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+
+    for command in copy_commands(wayland) {
+        let child = Command::new(command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(anyhow!("Failed to start {}: {}", command[0], e)),
+        };
+
+        // Closing stdin lets the tool take the clipboard ownership and move to the background
+        child.stdin.take()
+            .ok_or_else(|| anyhow!("Failed to open {} stdin", command[0]))?
+            .write_all(text.as_bytes())?;
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(anyhow!("{} exited with {}", command[0], status));
+        }
+
+        log::debug!("Copied {} characters to the clipboard with {}", text.chars().count(), command[0]);
+        return Ok(());
+    }
+
+    Err(anyhow!("No clipboard tool found, install wl-clipboard (Wayland) or xclip (X11)"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_commands() {
+        let programs = |wayland| copy_commands(wayland).iter().map(|command| command[0]).collect::<Vec<_>>();
+
+        assert_eq!(programs(true), vec!["wl-copy", "xclip", "xsel"]);
+        assert_eq!(programs(false), vec!["xclip", "xsel"]);
+    }
+}
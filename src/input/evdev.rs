@@ -0,0 +1,351 @@
+/// Linux evdev reader for physical keyboards
+/// Listens on /dev/input/event* devices for configured trigger keys or key chords
+
+use super::api::{InputEvent, EV_KEY, VIRTUAL_KEYBOARD_NAME};
+use super::keys::{find_vkey, VK_LALT, VK_LCTRL, VK_LSHIFT, VK_LWIN, VK_RALT, VK_RCTRL, VK_RSHIFT, VK_RWIN};
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::Read;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use anyhow::{Result, anyhow};
+
+const INPUT_DIR: &str = "/dev/input";
+const KEY_MAX: usize = 0x2ff;
+/// How often the listener threads check whether they were stopped
+const LISTENER_POLL_MS: i32 = 500;
+
+// evdev ioctl request codes: _IOC(_IOC_READ, 'E', nr, len)
+const fn eviocg(nr: u64, len: usize) -> libc::c_ulong {
+    ((2u64 << 30) | ((len as u64) << 16) | (('E' as u64) << 8) | nr) as libc::c_ulong
+}
+
+const fn eviocgname(len: usize) -> libc::c_ulong {
+    eviocg(0x06, len)
+}
+
+const fn eviocgbit(ev: u16, len: usize) -> libc::c_ulong {
+    eviocg(0x20 + ev as u64, len)
+}
+
+const fn eviocgkey(len: usize) -> libc::c_ulong {
+    eviocg(0x18, len)
+}
+
+// _IOC(_IOC_WRITE, 'E', 0x90, sizeof(int))
+const EVIOCGRAB: libc::c_ulong = ((1u64 << 30) | (4 << 16) | (('E' as u64) << 8) | 0x90) as libc::c_ulong;
+
+/// Physical input device opened for reading
+pub struct EvdevDevice {
+    path: PathBuf,
+    name: String,
+    file: File,
+}
+
+impl EvdevDevice {
+    /// Open an evdev device (e.g. /dev/input/event3) for reading
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .map_err(|e| anyhow!("Failed to open {:?}: {}. Make sure you are a member of the 'input' group", path, e))?;
+
+        let mut buffer = [0u8; 256];
+        let ret = unsafe { libc::ioctl(file.as_raw_fd(), eviocgname(buffer.len()), buffer.as_mut_ptr()) };
+        let name = if ret > 0 {
+            let end = buffer.iter().position(|b| *b == 0).unwrap_or(buffer.len());
+            String::from_utf8_lossy(&buffer[..end]).to_string()
+        } else {
+            String::new()
+        };
+
+        Ok(Self { path: path.to_path_buf(), name, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check whether the device reports any of the given linux key codes
+    pub fn supports_any_key(&self, keys: &[u16]) -> bool {
+        let mut bits = [0u8; KEY_MAX / 8 + 1];
+        let ret = unsafe { libc::ioctl(self.file.as_raw_fd(), eviocgbit(EV_KEY, bits.len()), bits.as_mut_ptr()) };
+        if ret < 0 {
+            return false;
+        }
+        keys.iter().any(|key| {
+            let key = *key as usize;
+            key <= KEY_MAX && bits[key / 8] & (1 << (key % 8)) != 0
+        })
+    }
+
+    /// Check whether any key of the device is currently held down
+    pub fn has_pressed_keys(&self) -> bool {
+        let mut bits = [0u8; KEY_MAX / 8 + 1];
+        let ret = unsafe { libc::ioctl(self.file.as_raw_fd(), eviocgkey(bits.len()), bits.as_mut_ptr()) };
+        ret >= 0 && bits.iter().any(|b| *b != 0)
+    }
+
+    /// Grab the device exclusively, its events no longer reach other readers (X11, Wayland compositor)
+    /// until the device is closed
+    pub fn grab(&self) -> Result<()> {
+        let ret = unsafe { libc::ioctl(self.file.as_raw_fd(), EVIOCGRAB, 1 as libc::c_int) };
+        if ret < 0 {
+            return Err(anyhow!("Failed to grab {:?}: {}", self.path, std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    /// Wait up to `timeout_ms` for input, true when an event can be read without blocking
+    pub fn wait_readable(&self, timeout_ms: i32) -> bool {
+        let mut fd = libc::pollfd { fd: self.file.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        unsafe { libc::poll(&mut fd, 1, timeout_ms) > 0 }
+    }
+
+    /// Block until the next input event is available
+    pub fn read_key_event(&mut self) -> Result<Option<(u16, i32)>> {
+        let mut event = InputEvent::new(0, 0, 0);
+        let event_bytes: &mut [u8] = unsafe {
+            std::slice::from_raw_parts_mut(
+                &mut event as *mut _ as *mut u8,
+                std::mem::size_of::<InputEvent>()
+            )
+        };
+
+        self.file.read_exact(event_bytes)
+            .map_err(|e| anyhow!("Failed to read input event from {:?}: {}", self.path, e))?;
+
+        if event.type_ == EV_KEY {
+            Ok(Some((event.code, event.value)))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Find keyboard devices to listen on.
+/// `filters` select devices by path or by (case-insensitive) name fragment; when empty,
+/// every device able to produce one of `keys` is used. The HotKeys virtual keyboard is always skipped.
+pub fn find_keyboards(filters: &[String], keys: &[u16]) -> Result<Vec<EvdevDevice>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(INPUT_DIR)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("event")))
+        .collect();
+    paths.sort();
+
+    let mut devices = Vec::new();
+    for path in paths {
+        let device = match EvdevDevice::open(&path) {
+            Ok(device) => device,
+            Err(e) => {
+                log::debug!("Skipping input device: {}", e);
+                continue;
+            }
+        };
+
+        if device.name() == VIRTUAL_KEYBOARD_NAME {
+            continue;
+        }
+
+        let selected = if filters.is_empty() {
+            device.supports_any_key(keys)
+        } else {
+            filters.iter().any(|filter| device_matches(filter, device.path(), device.name()))
+        };
+
+        if selected {
+            log::info!("Listening on input device {:?} ({})", device.path(), device.name());
+            devices.push(device);
+        }
+    }
+
+    Ok(devices)
+}
+
+fn device_matches(filter: &str, path: &Path, name: &str) -> bool {
+    if filter.starts_with('/') {
+        // Allow stable /dev/input/by-id/* symlinks as well as direct event paths
+        let resolved = std::fs::canonicalize(filter).unwrap_or_else(|_| PathBuf::from(filter));
+        resolved == path
+    } else {
+        name.to_lowercase().contains(&filter.to_lowercase())
+    }
+}
+
+/// Set of physical keys that have to be held down together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChord {
+    keys: BTreeSet<u16>,
+}
+
+impl KeyChord {
+    pub fn keys(&self) -> Vec<u16> {
+        self.keys.iter().copied().collect()
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    /// Parse chords like "Menu", "Ctrl+Menu" or "Ctrl Alt F13"
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut keys = BTreeSet::new();
+        for token in text.split(|c: char| c == '+' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+            let vkey = find_vkey(token).map_err(|e| format!("{}: '{}'", e, token))?;
+            keys.insert(normalize_key(vkey.linux_key));
+        }
+        if keys.is_empty() {
+            return Err(format!("Empty key chord: '{}'", text));
+        }
+        Ok(Self { keys })
+    }
+}
+
+/// Left and right modifiers are treated as the same key
+fn normalize_key(code: u16) -> u16 {
+    match code {
+        c if c == VK_RCTRL.linux_key => VK_LCTRL.linux_key,
+        c if c == VK_RSHIFT.linux_key => VK_LSHIFT.linux_key,
+        c if c == VK_RALT.linux_key => VK_LALT.linux_key,
+        c if c == VK_RWIN.linux_key => VK_LWIN.linux_key,
+        c => c,
+    }
+}
+
+/// Tracks pressed keys of a single device and reports completed chords
+#[derive(Debug, Default)]
+pub struct ChordTracker {
+    pressed: BTreeSet<u16>,
+}
+
+impl ChordTracker {
+    /// Feed a key event (value: 0 = up, 1 = down, 2 = repeat).
+    /// Returns the index of the chord completed by this key press, if any.
+    pub fn on_key(&mut self, code: u16, value: i32, chords: &[KeyChord]) -> Option<usize> {
+        let code = normalize_key(code);
+        match value {
+            0 => {
+                self.pressed.remove(&code);
+                None
+            },
+            1 => {
+                self.pressed.insert(code);
+                chords.iter().position(|chord| chord.keys == self.pressed)
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Spawn one listener thread per device, calling `on_trigger` with the index of each completed chord,
+/// until `stop` is set
+pub fn spawn_listeners<F>(devices: Vec<EvdevDevice>, chords: Vec<KeyChord>, stop: Arc<AtomicBool>, on_trigger: F)
+where
+    F: Fn(usize) + Send + Clone + 'static,
+{
+    for mut device in devices {
+        let chords = chords.clone();
+        let (stop, on_trigger) = (stop.clone(), on_trigger.clone());
+        std::thread::spawn(move || {
+            let mut tracker = ChordTracker::default();
+            while !stop.load(Ordering::Relaxed) {
+                if !device.wait_readable(LISTENER_POLL_MS) {
+                    continue;
+                }
+                match device.read_key_event() {
+                    Ok(Some((code, value))) => {
+                        if let Some(index) = tracker.on_key(code, value, &chords) {
+                            log::info!("Trigger {} pressed on {}", index, device.name());
+                            on_trigger(index);
+                        }
+                    },
+                    Ok(None) => {},
+                    Err(e) => {
+                        log::warn!("Stopped listening on {}: {}", device.name(), e);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Spawn one reader thread per device, calling `on_key` with every key event (code, value)
+pub fn spawn_readers<F>(devices: Vec<EvdevDevice>, on_key: F)
+where
+    F: Fn(u16, i32) + Send + Clone + 'static,
+{
+    for mut device in devices {
+        let on_key = on_key.clone();
+        std::thread::spawn(move || {
+            loop {
+                match device.read_key_event() {
+                    Ok(Some((code, value))) => on_key(code, value),
+                    Ok(None) => {},
+                    Err(e) => {
+                        log::warn!("Stopped reading from {}: {}", device.name(), e);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::keys::VK_MENU;
+
+    #[test]
+    fn test_parse_key_chord() {
+        let chord = KeyChord::from_str("Ctrl+Menu").unwrap();
+        assert_eq!(chord.keys(), vec![VK_LCTRL.linux_key, VK_MENU.linux_key]);
+
+        assert_eq!(KeyChord::from_str("ctrl menu"), KeyChord::from_str("Menu+Ctrl"));
+        assert_eq!(KeyChord::from_str("rctrl+menu"), KeyChord::from_str("ctrl+menu"));
+        assert!(KeyChord::from_str("Ctrl+Nothing").is_err());
+        assert!(KeyChord::from_str(" + ").is_err());
+    }
+
+    #[test]
+    fn test_chord_tracker() {
+        let chords = vec![
+            KeyChord::from_str("Menu").unwrap(),
+            KeyChord::from_str("Ctrl+Menu").unwrap(),
+        ];
+        let ctrl = VK_LCTRL.linux_key;
+        let rctrl = VK_RCTRL.linux_key;
+        let menu = VK_MENU.linux_key;
+
+        let mut tracker = ChordTracker::default();
+        assert_eq!(tracker.on_key(menu, 1, &chords), Some(0));
+        assert_eq!(tracker.on_key(menu, 2, &chords), None); // auto-repeat
+        assert_eq!(tracker.on_key(menu, 0, &chords), None);
+
+        assert_eq!(tracker.on_key(rctrl, 1, &chords), None);
+        assert_eq!(tracker.on_key(menu, 1, &chords), Some(1));
+        assert_eq!(tracker.on_key(menu, 0, &chords), None);
+        assert_eq!(tracker.on_key(rctrl, 0, &chords), None);
+
+        // Extra keys held down prevent a match
+        assert_eq!(tracker.on_key(ctrl, 1, &chords), None);
+        assert_eq!(tracker.on_key(VK_LSHIFT.linux_key, 1, &chords), None);
+        assert_eq!(tracker.on_key(menu, 1, &chords), None);
+    }
+
+    #[test]
+    fn test_ioctl_codes() {
+        assert_eq!(eviocgname(256), 0x81004506);
+        assert_eq!(eviocgbit(EV_KEY, 96), 0x80604521);
+    }
+}
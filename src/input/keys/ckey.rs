@@ -1,22 +1,32 @@
 /// Linux character key mapping for HotKeys
 /// Maps Unicode characters to virtual keys with shift state information
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 use super::vkey::{self, VirtualKey, find_vkey, VK_A, VK_Z, VK_SPACE};
+use super::xkb::XkbKeymap;
+
+/// Prefix of mapping targets typed with AltGr (third level), e.g. "€": "AltGr e"
+const ALTGR_PREFIX: &str = "altgr ";
 
 #[derive(Debug, Clone)]
 pub struct CharacterKey<'a> {
     pub vkey: VirtualKey<'a>,
-    pub shift: bool
+    pub shift: bool,
+    pub altgr: bool,
 }
 
 impl<'a> CharacterKey<'a> {
     pub fn new(vkey: VirtualKey<'a>) -> Self {
-        Self { vkey, shift: false }
+        Self { vkey, shift: false, altgr: false }
     }
 
     pub fn new_sh(vkey: VirtualKey<'a>) -> Self {
-        Self { vkey, shift: true }
+        Self { vkey, shift: true, altgr: false }
+    }
+
+    pub fn with_altgr(mut self) -> Self {
+        self.altgr = true;
+        self
     }
 }
 
@@ -72,33 +82,79 @@ fn default_map<'a>() -> HashMap<String, CharacterKey<'a>> {
 }
 
 /// Layout-aware character key mapping
+/// Manually mapped characters resolve through the US map, all others through the xkb keymap (if any)
 pub struct WithLayout {
-    mapping: HashMap<String, String>
+    mapping: HashMap<String, String>,
+    xkb: Option<Arc<XkbKeymap>>,
+    unicode_fallback: bool,
 }
 
 impl WithLayout {
+    pub fn with_xkb(mut self, xkb: Option<Arc<XkbKeymap>>) -> Self {
+        self.xkb = xkb;
+        self
+    }
+
+    /// Enter characters without keys as Ctrl+Shift+U code points instead of skipping them
+    pub fn with_unicode_fallback(mut self, unicode_fallback: bool) -> Self {
+        self.unicode_fallback = unicode_fallback;
+        self
+    }
+
+    pub fn unicode_fallback(&self) -> bool {
+        self.unicode_fallback
+    }
+
     pub fn find_ckey<'a>(&self, ch: char) -> Option<CharacterKey<'a>> {
-        find_mapped_ckey(ch, &self.mapping)
+        if self.mapping.contains_key(&ch.to_string()) {
+            return find_mapped_ckey(ch, &self.mapping);
+        }
+        self.xkb.as_ref()
+            .and_then(|xkb| xkb.find_ckey(ch))
+            .or_else(|| find_mapped_ckey(ch, &self.mapping))
+    }
+
+    /// Whether every character of the text can be typed with keys
+    pub fn can_type(&self, text: &str) -> bool {
+        text.chars().all(|ch| !self.find_ckeys(ch).is_empty())
+    }
+
+    /// Keys typing a character, a dead key and a base character for characters the layout composes
+    pub fn find_ckeys<'a>(&self, ch: char) -> Vec<CharacterKey<'a>> {
+        let sequence = self.xkb.as_ref()
+            .filter(|xkb| !self.mapping.contains_key(&ch.to_string()) && xkb.find_ckey(ch).is_none())
+            .and_then(|xkb| xkb.find_sequence(ch));
+
+        match sequence {
+            Some(sequence) => sequence.to_vec(),
+            None => self.find_ckey(ch).into_iter().collect(),
+        }
     }
 }
 
-/// Find character key with optional layout remapping
+/// Find character key with optional layout remapping, "AltGr x" targets type x with AltGr held
 fn find_mapped_ckey<'a>(ch: char, mapping: &HashMap<String, String>) -> Option<CharacterKey<'a>> {
     let text = ch.to_string();
-    default_map().get(
-        mapping.get(&text).unwrap_or(&text)
-    ).cloned()
+    let target = mapping.get(&text).unwrap_or(&text);
+    let altgr_target = target.get(..ALTGR_PREFIX.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(ALTGR_PREFIX))
+        .map(|_| &target[ALTGR_PREFIX.len()..]);
+
+    match altgr_target {
+        Some(target) => default_map().get(target).cloned().map(CharacterKey::with_altgr),
+        None => default_map().get(target).cloned(),
+    }
 }
 
 /// Create a layout-aware character mapper
 pub fn with_layout(mapping: HashMap<String, String>) -> WithLayout {
-    WithLayout { mapping }
+    WithLayout { mapping, xkb: None, unicode_fallback: false }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::input::keys::vkey::{VK_A, VK_Z, VK_SPACE, VK_1, VK_SEMICOL, VK_S, VK_C, VK_D};
+    use crate::input::keys::vkey::{VK_A, VK_Z, VK_SPACE, VK_1, VK_SEMICOL, VK_S, VK_C, VK_D, VK_E, VK_Q};
 
     #[test]
     fn test_character_key_new() {
@@ -255,6 +311,24 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_find_mapped_ckey_altgr() {
+        let mut mapping = HashMap::new();
+        mapping.insert("€".to_owned(), "AltGr e".to_owned());
+        mapping.insert("@".to_owned(), "altgr Q".to_owned());
+
+        let ckey = find_mapped_ckey('€', &mapping).unwrap();
+        assert_eq!(ckey.vkey, VK_E);
+        assert_eq!((ckey.shift, ckey.altgr), (false, true));
+
+        let ckey = find_mapped_ckey('@', &mapping).unwrap();
+        assert_eq!(ckey.vkey, VK_Q);
+        assert_eq!((ckey.shift, ckey.altgr), (true, true));
+
+        let ckey = find_mapped_ckey('a', &mapping).unwrap();
+        assert!(!ckey.altgr);
+    }
+
     #[test]
     fn test_with_layout_find_ckey() {
         let mut mapping = HashMap::new();
@@ -287,6 +361,14 @@ mod tests {
         assert_eq!(ckey.shift, false);
     }
 
+    #[test]
+    fn test_with_layout_can_type() {
+        let layout = with_layout(HashMap::from([("č".to_owned(), "c".to_owned())]));
+
+        assert!(layout.can_type("Način: 1 + 2"));
+        assert!(!layout.can_type("5 €"));
+    }
+
     #[test]
     fn test_with_layout_invalid_char() {
         let mapping = HashMap::new();
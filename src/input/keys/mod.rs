@@ -1,5 +1,6 @@
 pub mod vkey;
 pub mod ckey;
+pub mod xkb;
 
 pub use vkey::*;
 // pub use ckey::*;
\ No newline at end of file
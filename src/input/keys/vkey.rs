@@ -147,10 +147,12 @@ const KEY_LEFTBRACE: u16 = 26;
 const KEY_BACKSLASH: u16 = 43;
 const KEY_RIGHTBRACE: u16 = 27;
 const KEY_APOSTROPHE: u16 = 40;
+const KEY_102ND: u16 = 86;      // Extra key between left Shift and Z on ISO keyboards (<>)
 
 // Special keys
 const KEY_LEFTMETA: u16 = 125;  // Windows key
 const KEY_RIGHTMETA: u16 = 126; // Windows key
+const KEY_COMPOSE: u16 = 127;   // Menu (context menu) key
 const KEY_NUMLOCK: u16 = 69;
 const KEY_SCROLLLOCK: u16 = 70;
 const KEY_PAUSE: u16 = 119;
@@ -215,6 +217,7 @@ virtual_keys! {
     "z",            0x5A,   KEY_Z,           "z";
     "lwin",         0x5B,   KEY_LEFTMETA,    "lwin";
     "rwin",         0x5C,   KEY_RIGHTMETA,   "rwin";
+    "menu",         0x5D,   KEY_COMPOSE,     "menu";
     "numpad0",      0x60,   KEY_KP0,         "numpad0";
     "numpad1",      0x61,   KEY_KP1,         "numpad1";
     "numpad2",      0x62,   KEY_KP2,         "numpad2";
@@ -263,6 +266,7 @@ virtual_keys! {
     "backslash",    0xDC,   KEY_BACKSLASH,   "\\";
     "rsbrck",       0xDD,   KEY_RIGHTBRACE,  "]";
     "sqote",        0xDE,   KEY_APOSTROPHE,  "'";
+    "oem102",       0xE2,   KEY_102ND,       "oem102";
 }
 
 pub fn find_vkey(text: &str) -> Result<&'static VirtualKey<'static>, &'static str> {
@@ -279,6 +283,14 @@ pub fn get_vkey(vk_code: u16) -> Result<&'static VirtualKey<'static>, &'static s
         .ok_or("Unknown virtual key code")
 }
 
+/// Find the virtual key for a linux KEY_* code (first match wins for keys with several names)
+pub fn find_vkey_by_linux_key(linux_key: u16) -> Result<&'static VirtualKey<'static>, &'static str> {
+    ALL_KEYS.iter()
+        .find(|vk| vk.linux_key == linux_key)
+        .copied()
+        .ok_or("Unknown linux key code")
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -302,6 +314,14 @@ mod tests {
         assert_eq!(Err("Unknown virtual key code"), get_vkey(0xFFFF));
     }
 
+    #[test]
+    fn test_find_vkey_by_linux_key() {
+        assert_eq!(Ok(&VK_A), find_vkey_by_linux_key(KEY_A));
+        assert_eq!(Ok(&VK_SHIFT), find_vkey_by_linux_key(KEY_LEFTSHIFT));
+        assert_eq!(Ok(&VK_RSHIFT), find_vkey_by_linux_key(KEY_RIGHTSHIFT));
+        assert_eq!(Err("Unknown linux key code"), find_vkey_by_linux_key(0xFFF));
+    }
+
     #[test]
     fn test_linux_key_mapping() {
         assert_eq!(VK_A.linux_key, KEY_A);
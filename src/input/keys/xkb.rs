@@ -0,0 +1,314 @@
+/// xkbcommon based character key resolution.
+/// Compiles the keymap of the user's keyboard layout and finds the key (and shift/AltGr state) producing each character,
+/// or the dead key sequence composing it
+
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+use std::ops::RangeInclusive;
+use std::hash::Hash;
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use xkbcommon_dl::{
+    xkb_compose_compile_flags::XKB_COMPOSE_COMPILE_NO_FLAGS, xkb_compose_state_flags::XKB_COMPOSE_STATE_NO_FLAGS,
+    xkb_compose_status::XKB_COMPOSE_COMPOSED, xkb_context_flags::XKB_CONTEXT_NO_FLAGS,
+    xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS, xkb_context, xkb_rule_names,
+    xkbcommon_compose_option, xkbcommon_option, XKB_MOD_INVALID,
+};
+
+use super::ckey::CharacterKey;
+use super::vkey::find_vkey_by_linux_key;
+
+/// Offset between xkb keycodes and linux KEY_* codes
+const EVDEV_OFFSET: u32 = 8;
+
+/// xkb rule names (RMLVO) selecting a keymap, unset names use the libxkbcommon defaults
+/// (which honour the XKB_DEFAULT_LAYOUT etc. environment variables)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct XkbNames {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<String>,
+}
+
+impl XkbNames {
+    /// Parse `setxkbmap -query` ("layout: de") or `localectl status` ("X11 Layout: de") output
+    pub fn parse(output: &str) -> Self {
+        let mut names = Self::default();
+        for line in output.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = Some(value.trim().to_string()).filter(|v| !v.is_empty() && v != "n/a");
+            match key.strip_prefix("x11 ").unwrap_or(&key) {
+                "rules" => names.rules = value,
+                "model" => names.model = value,
+                "layout" => names.layout = value,
+                "variant" => names.variant = value,
+                "options" => names.options = value,
+                _ => {}
+            }
+        }
+        names
+    }
+
+    /// Names of the layout currently used by the desktop
+    pub fn detect() -> Self {
+        for (program, args) in [("setxkbmap", &["-query"][..]), ("localectl", &["status"][..])] {
+            let output = Command::new(program).args(args).output();
+            if let Ok(output) = output.as_ref().map(|o| String::from_utf8_lossy(&o.stdout).to_string()) {
+                let names = Self::parse(&output);
+                if names.layout.is_some() {
+                    log::info!("Detected keyboard layout with {}: {:?}", program, names);
+                    return names;
+                }
+            }
+        }
+        log::info!("Keyboard layout not detected, using xkb defaults");
+        Self::default()
+    }
+}
+
+/// Dead keysyms (XKB_KEY_dead_grave to XKB_KEY_dead_longsolidusoverlay)
+const DEAD_KEYSYMS: RangeInclusive<u32> = 0xfe50..=0xfe93;
+
+/// Compose table locale used when the environment does not name a usable one
+const DEFAULT_COMPOSE_LOCALE: &str = "en_US.UTF-8";
+
+/// Characters of a compiled keymap and the keys producing them
+#[derive(Debug, Default)]
+pub struct XkbKeymap {
+    keys: HashMap<char, CharacterKey<'static>>,
+    /// Characters without a key of their own, typed as a dead key followed by a base character
+    sequences: HashMap<char, [CharacterKey<'static>; 2]>,
+}
+
+impl XkbKeymap {
+    pub fn new(names: &XkbNames) -> Result<Self> {
+        let xkb = xkbcommon_option().ok_or_else(|| anyhow!("libxkbcommon is not available"))?;
+
+        let to_cstring = |name: &Option<String>| name.as_deref().map(CString::new).transpose();
+        let (rules, model, layout, variant, options) = (
+            to_cstring(&names.rules)?, to_cstring(&names.model)?, to_cstring(&names.layout)?,
+            to_cstring(&names.variant)?, to_cstring(&names.options)?,
+        );
+        let as_ptr = |name: &Option<CString>| name.as_ref().map_or(std::ptr::null(), |n| n.as_ptr());
+        let rule_names = xkb_rule_names {
+            rules: as_ptr(&rules),
+            model: as_ptr(&model),
+            layout: as_ptr(&layout),
+            variant: as_ptr(&variant),
+            options: as_ptr(&options),
+        };
+
+        let mut keymap = Self::default();
+        unsafe {
+            let context = (xkb.xkb_context_new)(XKB_CONTEXT_NO_FLAGS);
+            if context.is_null() {
+                return Err(anyhow!("Failed to create xkb context"));
+            }
+            let xkb_keymap = (xkb.xkb_keymap_new_from_names)(context, &rule_names, XKB_KEYMAP_COMPILE_NO_FLAGS);
+            if xkb_keymap.is_null() {
+                (xkb.xkb_context_unref)(context);
+                return Err(anyhow!("Failed to compile xkb keymap for {:?}", names));
+            }
+            let state = (xkb.xkb_state_new)(xkb_keymap);
+
+            // Plain keys first, so characters reachable on several levels use the simplest one
+            let shift = (xkb.xkb_keymap_mod_get_index)(xkb_keymap, c"Shift".as_ptr());
+            let altgr = (xkb.xkb_keymap_mod_get_index)(xkb_keymap, c"Mod5".as_ptr());
+            let mod_masks = [(false, false), (true, false), (false, true), (true, true)]
+                .into_iter()
+                .filter(|(with_shift, with_altgr)| (!with_shift || shift != XKB_MOD_INVALID) && (!with_altgr || altgr != XKB_MOD_INVALID))
+                .map(|(with_shift, with_altgr)| {
+                    let mask = if with_shift { 1 << shift } else { 0 } | if with_altgr { 1 << altgr } else { 0 };
+                    (mask, with_shift, with_altgr)
+                });
+
+            // Keysyms of dead keys and of the keys typing characters, for compose lookups
+            let mut dead_keys: Vec<(u32, CharacterKey<'static>)> = Vec::new();
+            let mut base_keys: Vec<(u32, CharacterKey<'static>)> = Vec::new();
+
+            for (mask, with_shift, with_altgr) in mod_masks {
+                (xkb.xkb_state_update_mask)(state, mask, 0, 0, 0, 0, 0);
+                for keycode in (xkb.xkb_keymap_min_keycode)(xkb_keymap)..=(xkb.xkb_keymap_max_keycode)(xkb_keymap) {
+                    let Some(linux_key) = keycode.checked_sub(EVDEV_OFFSET).and_then(|k| u16::try_from(k).ok()) else {
+                        continue;
+                    };
+                    let Ok(vkey) = find_vkey_by_linux_key(linux_key) else {
+                        continue;
+                    };
+                    let ckey = CharacterKey { vkey: vkey.clone(), shift: with_shift, altgr: with_altgr };
+
+                    let keysym = (xkb.xkb_state_key_get_one_sym)(state, keycode);
+                    if DEAD_KEYSYMS.contains(&keysym) {
+                        if !dead_keys.iter().any(|(sym, _)| *sym == keysym) {
+                            dead_keys.push((keysym, ckey));
+                        }
+                        continue;
+                    }
+
+                    let Some(ch) = char::from_u32((xkb.xkb_state_key_get_utf32)(state, keycode)) else {
+                        continue;
+                    };
+                    if ch == '\0' || ch.is_control() || keymap.keys.contains_key(&ch) {
+                        continue;
+                    }
+                    keymap.keys.insert(ch, ckey.clone());
+                    base_keys.push((keysym, ckey));
+                }
+            }
+
+            if !dead_keys.is_empty() {
+                keymap.compose_sequences(context, &dead_keys, &base_keys);
+            }
+
+            (xkb.xkb_state_unref)(state);
+            (xkb.xkb_keymap_unref)(xkb_keymap);
+            (xkb.xkb_context_unref)(context);
+        }
+
+        log::debug!("Compiled xkb keymap {:?} with {} characters and {} dead key sequences",
+            names, keymap.keys.len(), keymap.sequences.len());
+        Ok(keymap)
+    }
+
+    /// Find the characters composed by each dead key followed by a base character,
+    /// using the compose table of the user's locale
+    unsafe fn compose_sequences(&mut self, context: *mut xkb_context,
+        dead_keys: &[(u32, CharacterKey<'static>)], base_keys: &[(u32, CharacterKey<'static>)]) {
+        let Some(compose) = xkbcommon_compose_option() else {
+            log::warn!("libxkbcommon compose support is not available, dead keys are not used");
+            return;
+        };
+
+        let locales = [compose_locale(), DEFAULT_COMPOSE_LOCALE.to_string()];
+        let table = locales.iter()
+            .filter_map(|locale| CString::new(locale.as_str()).ok())
+            .map(|locale| unsafe { (compose.xkb_compose_table_new_from_locale)(context, locale.as_ptr(), XKB_COMPOSE_COMPILE_NO_FLAGS) })
+            .find(|table| !table.is_null());
+        let Some(table) = table else {
+            log::warn!("No compose table found for locale {}, dead keys are not used", locales[0]);
+            return;
+        };
+
+        unsafe {
+            let state = (compose.xkb_compose_state_new)(table, XKB_COMPOSE_STATE_NO_FLAGS);
+            for (dead_keysym, dead_key) in dead_keys {
+                for (base_keysym, base_key) in base_keys {
+                    (compose.xkb_compose_state_reset)(state);
+                    (compose.xkb_compose_state_feed)(state, *dead_keysym);
+                    (compose.xkb_compose_state_feed)(state, *base_keysym);
+                    if (compose.xkb_compose_state_get_status)(state) != XKB_COMPOSE_COMPOSED {
+                        continue;
+                    }
+
+                    let mut buffer = [0 as c_char; 16];
+                    (compose.xkb_compose_state_get_utf8)(state, buffer.as_mut_ptr(), buffer.len());
+                    let composed = CStr::from_ptr(buffer.as_ptr()).to_string_lossy();
+                    let mut chars = composed.chars();
+                    let single_char = match (chars.next(), chars.next()) {
+                        (Some(ch), None) => Some(ch),
+                        _ => None,
+                    };
+                    if let Some(ch) = single_char.filter(|ch| !self.keys.contains_key(ch)) {
+                        self.sequences.entry(ch).or_insert_with(|| [dead_key.clone(), base_key.clone()]);
+                    }
+                }
+            }
+            (compose.xkb_compose_state_unref)(state);
+            (compose.xkb_compose_table_unref)(table);
+        }
+    }
+
+    pub fn find_ckey(&self, ch: char) -> Option<CharacterKey<'static>> {
+        self.keys.get(&ch).cloned()
+    }
+
+    /// Dead key and base character typing a character that has no key of its own
+    pub fn find_sequence(&self, ch: char) -> Option<&[CharacterKey<'static>; 2]> {
+        self.sequences.get(&ch)
+    }
+}
+
+/// Locale of the user's compose table, from LC_ALL, LC_CTYPE or LANG
+fn compose_locale() -> String {
+    ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|locale| !locale.is_empty())
+        .filter(|locale| locale != "C" && locale != "POSIX")
+        .unwrap_or_else(|| DEFAULT_COMPOSE_LOCALE.to_string())
+}
+
+/// Compiled keymap for the given names, or for the detected desktop layout when `None`.
+/// Keymaps are compiled once and cached, `None` is returned if compilation fails.
+pub fn keymap(names: Option<&XkbNames>) -> Option<Arc<XkbKeymap>> {
+    static DETECTED: OnceLock<XkbNames> = OnceLock::new();
+    static KEYMAPS: OnceLock<Mutex<HashMap<XkbNames, Option<Arc<XkbKeymap>>>>> = OnceLock::new();
+
+    let names = names.cloned().unwrap_or_else(|| DETECTED.get_or_init(XkbNames::detect).clone());
+    let mut keymaps = KEYMAPS.get_or_init(Default::default).lock().unwrap();
+    keymaps.entry(names.clone())
+        .or_insert_with(|| match XkbKeymap::new(&names) {
+            Ok(keymap) => Some(Arc::new(keymap)),
+            Err(e) => {
+                log::warn!("Using the built-in US character map: {}", e);
+                None
+            }
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::keys::vkey::{VK_2, VK_E, VK_PLUS, VK_Q, VK_TICK, VK_Y, VK_Z};
+
+    #[test]
+    fn test_parse_names() {
+        let names = XkbNames::parse("rules:      evdev\nmodel:      pc105\nlayout:     de,us\nvariant:    nodeadkeys,\n");
+        assert_eq!(names.rules.as_deref(), Some("evdev"));
+        assert_eq!(names.layout.as_deref(), Some("de,us"));
+        assert_eq!(names.options, None);
+
+        let names = XkbNames::parse("   System Locale: LANG=de_DE.UTF-8\n       X11 Layout: fr\n        X11 Model: pc105\n      X11 Variant: n/a\n");
+        assert_eq!(names.layout.as_deref(), Some("fr"));
+        assert_eq!(names.model.as_deref(), Some("pc105"));
+        assert_eq!(names.variant, None);
+    }
+
+    #[test]
+    fn test_keymap_layouts() {
+        let layout = |layout: &str| XkbNames { layout: Some(layout.to_string()), ..Default::default() };
+
+        // Skipped where libxkbcommon or the xkeyboard-config data is not installed
+        let (Ok(us), Ok(de)) = (XkbKeymap::new(&layout("us")), XkbKeymap::new(&layout("de"))) else {
+            return;
+        };
+
+        assert_eq!(us.find_ckey('z').map(|ck| (ck.vkey, ck.shift)), Some((VK_Z, false)));
+        assert_eq!(us.find_ckey('@').map(|ck| (ck.vkey, ck.shift)), Some((VK_2, true)));
+        assert_eq!(de.find_ckey('z').map(|ck| (ck.vkey, ck.shift)), Some((VK_Y, false)));
+        assert_eq!(de.find_ckey('Q').map(|ck| (ck.vkey, ck.shift)), Some((VK_Q, true)));
+        assert_eq!(de.find_ckey('"').map(|ck| (ck.vkey, ck.shift)), Some((VK_2, true)));
+        assert_eq!(de.find_ckey('@').map(|ck| (ck.vkey, ck.shift, ck.altgr)), Some((VK_Q, false, true)));
+        assert_eq!(de.find_ckey('€').map(|ck| (ck.vkey, ck.shift, ck.altgr)), Some((VK_E, false, true)));
+        assert_eq!(us.find_ckey('@').map(|ck| ck.altgr), Some(false));
+
+        // Dead keys: ^ is typed as dead circumflex followed by space, é as dead acute followed by e
+        if let Some([dead, base]) = de.find_sequence('é') {
+            assert_eq!((dead.vkey.vkey, base.vkey.vkey), (VK_PLUS.vkey, VK_E.vkey));
+            assert!(de.find_sequence('^').is_some_and(|[dead, _]| dead.vkey == VK_TICK));
+            assert!(de.find_ckey('^').is_none());
+        }
+    }
+}
@@ -1,4 +1,7 @@
 pub mod keys;
 pub mod api;
 pub mod script;
-pub mod steps;
\ No newline at end of file
+pub mod steps;
+pub mod evdev;
+pub mod clipboard;
+pub mod secret;
\ No newline at end of file
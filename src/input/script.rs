@@ -1,8 +1,7 @@
 /// Linux script system for HotKeys
 /// Converts user-defined shortcuts and text into input step sequences
 
-use super::{steps::*, keys::{vkey::{self, VK_SHIFT, VK_ENTER}, ckey::{self, CharacterKey}}};
-use std::collections::HashMap;
+use super::{steps::*, keys::{vkey::{self, VK_CTRL, VK_SHIFT, VK_RALT, VK_ENTER, VK_U}, ckey::{self, CharacterKey}}};
 use anyhow::Result;
 
 /// Container for sequences of input steps
@@ -83,7 +82,9 @@ fn scan(text: &str) -> Vec<Token> {
 
 /// Parse tokens into key combinations
 /// "Ctrl K + Ctrl B" -> [KeyCombination(Ctrl+K), KeyCombination(Ctrl+B)]
+/// Unknown keys are skipped with a warning
 fn parse<'a>(text: &'a str) -> Vec<KeyCombination<'a>> {
+    let original = text;
     scan(text.to_lowercase().as_str())
     .into_iter()
     .fold(Vec::new(), |mut acc, token| {
@@ -92,8 +93,9 @@ fn parse<'a>(text: &'a str) -> Vec<KeyCombination<'a>> {
                 if acc.is_empty() {
                     acc.push(KeyCombination::default());
                 }
-                if let Ok(vkey) = vkey::find_vkey(&text) {
-                    acc.last_mut().unwrap().keys.push(vkey.clone());
+                match vkey::find_vkey(&text) {
+                    Ok(vkey) => acc.last_mut().unwrap().keys.push(vkey.clone()),
+                    Err(_) => log::warn!("Unknown key '{}' skipped in shortcut '{}'", text, original),
                 }
             },
             PLUS => acc.push(KeyCombination::default())
@@ -102,6 +104,19 @@ fn parse<'a>(text: &'a str) -> Vec<KeyCombination<'a>> {
     })
 }
 
+/// Keys of a shortcut that are not known and would be skipped when it is executed
+/// "Ctrl Shft T" -> ["shft"]
+pub fn unknown_keys(text: &str) -> Vec<String> {
+    scan(text.to_lowercase().as_str())
+        .into_iter()
+        .filter_map(|token| match token {
+            CHAR(text) | QUOTED(text) | WORD(text) => Some(text),
+            PLUS => None,
+        })
+        .filter(|text| vkey::find_vkey(text).is_err())
+        .collect()
+}
+
 /// Create input script for shortcut sequence
 /// "Ctrl Shift A" -> Press Ctrl, Press Shift, Press A, Release A, Release Shift, Release Ctrl
 pub fn for_shortcut(text: String) -> InputScript {
@@ -129,44 +144,66 @@ pub fn for_pause(pause: u16) -> InputScript {
 }
 
 /// Create input script for text input
-pub fn for_text(text: String, keyboard_layout_mapping: HashMap<String, String>) -> InputScript {
-    log::trace!("Text: {}", text);
-    for_text_or_line(text, false, keyboard_layout_mapping)
+pub fn for_text(text: String, ckey: &ckey::WithLayout) -> InputScript {
+    log::trace!("Text: {} characters", text.chars().count());
+    for_text_or_line(text, false, ckey)
 }
 
 /// Create input script for text input with newline
-pub fn for_line(text: String, keyboard_layout_mapping: HashMap<String, String>) -> InputScript {
-    log::trace!("Line: {}", text);
-    for_text_or_line(text, true, keyboard_layout_mapping)
+pub fn for_line(text: String, ckey: &ckey::WithLayout) -> InputScript {
+    log::trace!("Line: {} characters", text.chars().count());
+    for_text_or_line(text, true, ckey)
 }
 
 /// Internal function for text/line input
-fn for_text_or_line(text: String, new_line: bool, keyboard_layout_mapping: HashMap<String, String>) -> InputScript {
-    let ckey = ckey::with_layout(keyboard_layout_mapping);
-
+fn for_text_or_line(text: String, new_line: bool, ckey: &ckey::WithLayout) -> InputScript {
     InputScript { steps : vec![
         Box::new(KeyInputs{
             inputs : text.chars()
-                    .filter_map(|ch| ckey.find_ckey(ch))
-                    .chain(new_line.then_some(CharacterKey::new(VK_ENTER.clone())))
-                    .flat_map(|ck| map_character_key(ck))
+                    .flat_map(|ch| map_character(ch, ckey))
+                    .chain(new_line.then(|| map_character_key(CharacterKey::new(VK_ENTER.clone()))).into_iter().flatten())
                     .collect()
         })
     ] }
 }
 
+/// Map character to key inputs, characters without keys are entered as Ctrl+Shift+U code points
+/// (IBus and GTK unicode input) when the fallback is enabled and skipped otherwise
+fn map_character(ch: char, ckey: &ckey::WithLayout) -> Vec<KeyInput> {
+    let ckeys = ckey.find_ckeys(ch);
+    if !ckeys.is_empty() {
+        return ckeys.into_iter().flat_map(map_character_key).collect();
+    }
+
+    if !ckey.unicode_fallback() {
+        log::warn!("No key for character {:?} (U+{:04X}), skipped", ch, ch as u32);
+        return vec![];
+    }
+
+    let mut inputs = [VK_CTRL.vkey, VK_SHIFT.vkey, VK_U.vkey].iter()
+        .map(|&vk_code| KeyInput { vk_code, key_down: true })
+        .chain([VK_U.vkey, VK_SHIFT.vkey, VK_CTRL.vkey].iter().map(|&vk_code| KeyInput { vk_code, key_down: false }))
+        .collect::<Vec<_>>();
+    inputs.extend(format!("{:x} ", ch as u32).chars()
+        .flat_map(|hex| ckey.find_ckeys(hex))
+        .flat_map(map_character_key));
+    inputs
+}
+
 /// Map virtual key to input step
 fn map_virtual_key(vk_code: u16, key_down: bool) -> Box<dyn InputStep> {
     Box::new(KeyInput { vk_code, key_down })
 }
 
-/// Map character key to sequence of key inputs (with shift handling)
+/// Map character key to sequence of key inputs (with AltGr and shift handling)
 fn map_character_key(ck: CharacterKey) -> Vec<KeyInput> {
     vec![
+        ck.altgr.then_some(KeyInput {vk_code: VK_RALT.vkey, key_down: true}),
         ck.shift.then_some(KeyInput {vk_code: VK_SHIFT.vkey, key_down: true}),
         Some(KeyInput {vk_code: ck.vkey.vkey, key_down: true}),
         Some(KeyInput {vk_code: ck.vkey.vkey, key_down: false}),
         ck.shift.then_some(KeyInput {vk_code: VK_SHIFT.vkey, key_down: false}),
+        ck.altgr.then_some(KeyInput {vk_code: VK_RALT.vkey, key_down: false}),
     ]
     .into_iter().flatten().collect()
 }
@@ -174,10 +211,20 @@ fn map_character_key(ck: CharacterKey) -> Vec<KeyInput> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::input::keys::{VK_A, VK_ALT, VK_CTRL, VK_SHIFT};
+    use crate::input::keys::{VK_9, VK_A, VK_ALT, VK_CTRL, VK_E, VK_SHIFT, VK_SPACE};
+    use std::collections::HashMap;
     use anyhow::anyhow;
 
 
+    #[test]
+    fn test_unknown_keys() {
+        assert!(unknown_keys("Ctrl K + Ctrl B").is_empty());
+        assert_eq!(unknown_keys("Ctrl Shft T + Alt Foo"), vec!["shft", "foo"]);
+
+        // Unknown keys are skipped
+        assert_eq!(for_shortcut("Ctrl Shft T".to_string()).steps.len(), 4);
+    }
+
     #[test]
     fn test_shortcut_behavior() {
         let script = for_shortcut("Ctrl A".to_string());
@@ -193,7 +240,7 @@ mod tests {
 
     #[test]
     fn test_text_behavior() {
-        let script = for_text("ab".to_string(), HashMap::new());
+        let script = for_text("ab".to_string(), &ckey::with_layout(HashMap::new()));
 
         // Should create one KeyInputs step with multiple inputs
         assert_eq!(script.steps.len(), 1);
@@ -205,6 +252,26 @@ mod tests {
         assert_eq!(key_inputs.inputs.len(), 4);
     }
 
+    #[test]
+    fn test_text_unicode_fallback() {
+        let inputs = |ckey: ckey::WithLayout| {
+            let script = for_text("é".to_string(), &ckey);
+            script.steps[0].as_any().downcast_ref::<KeyInputs>().unwrap().inputs.clone()
+        };
+
+        assert!(inputs(ckey::with_layout(HashMap::new())).is_empty());
+
+        // Ctrl+Shift+U, "e9", space
+        let inputs = inputs(ckey::with_layout(HashMap::new()).with_unicode_fallback(true));
+        assert_eq!(inputs.len(), 12);
+        assert_eq!(inputs[0], KeyInput { vk_code: VK_CTRL.vkey, key_down: true });
+        assert_eq!(inputs[2], KeyInput { vk_code: VK_U.vkey, key_down: true });
+        assert_eq!(inputs[5], KeyInput { vk_code: VK_CTRL.vkey, key_down: false });
+        assert_eq!(inputs[6], KeyInput { vk_code: VK_E.vkey, key_down: true });
+        assert_eq!(inputs[8], KeyInput { vk_code: VK_9.vkey, key_down: true });
+        assert_eq!(inputs[10], KeyInput { vk_code: VK_SPACE.vkey, key_down: true });
+    }
+
     #[test]
     fn test_pause_behavior() {
         let script = for_pause(100);
@@ -340,4 +407,16 @@ mod tests {
         assert_eq!(inputs[2], KeyInput { vk_code: VK_A.vkey, key_down: false });
         assert_eq!(inputs[3], KeyInput { vk_code: VK_SHIFT.vkey, key_down: false });
     }
+
+    #[test]
+    fn test_map_character_key_with_altgr() {
+        let ckey = CharacterKey::new_sh(VK_A.clone()).with_altgr();
+        let inputs = map_character_key(ckey);
+
+        assert_eq!(inputs.len(), 6);
+        assert_eq!(inputs[0], KeyInput { vk_code: VK_RALT.vkey, key_down: true });
+        assert_eq!(inputs[1], KeyInput { vk_code: VK_SHIFT.vkey, key_down: true });
+        assert_eq!(inputs[4], KeyInput { vk_code: VK_SHIFT.vkey, key_down: false });
+        assert_eq!(inputs[5], KeyInput { vk_code: VK_RALT.vkey, key_down: false });
+    }
 }
\ No newline at end of file
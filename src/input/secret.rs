@@ -0,0 +1,54 @@
+/// Secret Service (GNOME Keyring, KWallet) lookups for typed secrets.
+/// Uses the secret-tool command line tool of libsecret, secrets are never logged or cached
+
+use std::collections::BTreeMap;
+use std::process::{Command, Stdio};
+use anyhow::{anyhow, Result};
+
+pub const SECRET_TOOL: &str = "secret-tool";
+
+/// secret-tool arguments looking up the secret with all the attributes
+fn lookup_args(attributes: &BTreeMap<String, String>) -> Vec<&str> {
+    std::iter::once("lookup")
+        .chain(attributes.iter().flat_map(|(name, value)| [name.as_str(), value.as_str()]))
+        .collect()
+}
+
+/// Look up the secret stored with the attributes, the keyring may ask to be unlocked
+pub fn lookup(attributes: &BTreeMap<String, String>) -> Result<String> {
+    if attributes.is_empty() {
+        return Err(anyhow!("Secret lookup needs at least one attribute"));
+    }
+
+    let output = Command::new(SECRET_TOOL)
+        .args(lookup_args(attributes))
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => anyhow!("{} not found, install libsecret-tools", SECRET_TOOL),
+            _ => anyhow!("Failed to start {}: {}", SECRET_TOOL, e),
+        })?;
+
+    // Not found and locked keyrings both exit with an error and print nothing
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(anyhow!("No secret found for {:?}", attributes));
+    }
+    let secret = String::from_utf8(output.stdout).map_err(|_| anyhow!("Secret for {:?} is not valid UTF-8", attributes))?;
+    Ok(secret.strip_suffix('\n').map(str::to_string).unwrap_or(secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_args() {
+        let attributes = BTreeMap::from([
+            ("user".to_string(), "me".to_string()),
+            ("service".to_string(), "github".to_string()),
+        ]);
+        assert_eq!(lookup_args(&attributes), vec!["lookup", "service", "github", "user", "me"]);
+        assert!(lookup(&BTreeMap::new()).is_err());
+    }
+}
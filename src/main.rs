@@ -12,13 +12,54 @@ use std::{env, path::PathBuf};
 
 fn print_help() {
     println!("");
-    println!("Usage: hotkeys [mode] [options]");
+    println!("Usage: hotkeys [mode] [arguments] [options]");
     println!("");
-    println!("mode: help, gtk, validate-settings, input-test");
+    println!("mode: {}", MODES.join(", "));
     println!("");
     println!("options:");
     println!("  --config_dir <path>: use specified config directory");
-    println!("  --profile <name>: use specific profile for board selection");
+    println!("  --profile <name>: use specific profile for board selection (default: ${}, else \"default\", or chosen on a board with \"profile_chooser\": true)", PROFILE_ENV);
+    println!("  --board <name>: open specific board, skipping board detection");
+    println!("  --timeout <secs>: override the auto-close timeout of the board");
+    println!("  --no-timeout: keep the board open until a pad is selected or it is cancelled");
+    println!("  --ephemeral: write nothing to disk, board data and window positions are kept in memory only");
+    println!("  --perf: print how long the startup stages took (config load, detection, GTK init, first draw, ...)");
+    println!();
+    println!("run arguments:");
+    println!("  <board> <pad>: execute pad actions without showing the board, pad as 1-9 with optional modifiers, e.g. \"Ctrl+5\"");
+    println!();
+    println!("profile arguments:");
+    println!("  <name>: use the profile for boards opened by the running daemon without one, until it stops");
+    println!();
+    println!("render arguments and options:");
+    println!("  <board>: draw the board into a PNG file in the configured window size");
+    println!("  --out <path>: PNG file to write (default: <board>.png)");
+    println!();
+    println!("backup and restore arguments:");
+    println!("  [<file>]: archive the user config directory and data into a tarball (default: hotkeys-backup-<time>.tar.gz)");
+    println!("  <file>: restore a backup after validating its settings, keeping the current config directory as a .bak sibling");
+    println!();
+    println!("import arguments:");
+    println!("  <source> <path>: print an include file with boards converted from snippets of {}, e.g. \"espanso ~/.config/espanso/match\"", tools::import::IMPORT_SOURCES.join(", "));
+    println!();
+    println!("exec options (executed in the given order):");
+    println!("  --shortcut <keys>: send a keyboard shortcut, e.g. \"Ctrl Shift T\"");
+    println!("  --text <text>: type text");
+    println!("  --line <text>: type text followed by ENTER");
+    println!("  --command <command>: run a shell command");
+    println!("  --url <url>: open URL in the default browser");
+    println!("  --pause <ms>: wait before the next action");
+    println!();
+    println!("validate-settings options:");
+    println!("  --format <text|json>: output format of the validation findings (default: text)");
+    println!("  --strict: report missing icons and unknown shortcut keys as errors instead of warnings");
+    println!();
+    println!("fmt options:");
+    println!("  --check: only list settings files that are not formatted, failing if there are any");
+    println!("  --force: also format files whose comments or YAML anchors are lost, keeping a .bak copy of each");
+    println!();
+    println!("batch input (stdin, one action per line):");
+    println!("  a shortcut, e.g. Ctrl Shift T, or a JSON action, e.g. {{\"Line\": \"cd ~/projects\"}}");
     println!("");
     println!("Defaults:");
     println!("  mode: gtk");
@@ -26,18 +67,45 @@ fn print_help() {
     println!("");
 }
 
+const MODES: &[&str] = &["help", "gtk", "settings", "daemon", "profile", "exec", "batch", "run", "list", "doctor", "detect", "record", "edit", "migrate", "split", "fmt", "backup", "restore", "import", "cheatsheet", "render", "validate-settings", "input-test"];
+
+/// Profile used when --profile is not given, e.g. set by a desktop entry or a shell session
+const PROFILE_ENV: &str = "HOTKEYS_PROFILE";
+
 struct Args {
     mode: String,
     config_dir: Option<String>,
     profile: Option<String>,
+    board: Option<String>,
+    timeout: Option<u64>,
+    check: bool,
+    force: bool,
+    strict: bool,
+    format: Option<String>,
+    out: Option<String>,
+    perf: bool,
+    ephemeral: bool,
+    actions: Vec<core::Action>,
+    params: Vec<String>,
 }
 
 fn parse_args() -> Args {
     let args: Vec<String> = env::args().collect();
 
     let mut mode = "gtk".to_string();
-    let mut profile: Option<String> = Some("default".to_string());
+    let mut profile: Option<String> = None;
     let mut config_dir: Option<String> = None;
+    let mut board: Option<String> = None;
+    let mut timeout: Option<u64> = None;
+    let mut check = false;
+    let mut force = false;
+    let mut strict = false;
+    let mut format: Option<String> = None;
+    let mut out: Option<String> = None;
+    let mut perf = false;
+    let mut ephemeral = false;
+    let mut actions: Vec<core::Action> = Vec::new();
+    let mut params: Vec<String> = Vec::new();
 
     let mut i = 1;
 
@@ -60,6 +128,84 @@ fn parse_args() -> Args {
                     std::process::exit(1);
                 }
             },
+            "--board" => {
+                if i + 1 < args.len() {
+                    board = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("ERROR: --board requires a value");
+                    print_help();
+                    std::process::exit(1);
+                }
+            },
+            "--timeout" => {
+                match args.get(i + 1).map(|value| value.parse::<u64>()) {
+                    Some(Ok(value)) => {
+                        timeout = Some(value);
+                        i += 2;
+                    },
+                    Some(Err(_)) => {
+                        eprintln!("ERROR: --timeout requires a number of seconds, got '{}'", args[i + 1]);
+                        std::process::exit(1);
+                    },
+                    None => {
+                        eprintln!("ERROR: --timeout requires a value");
+                        print_help();
+                        std::process::exit(1);
+                    }
+                }
+            },
+            "--no-timeout" => {
+                timeout = Some(0);
+                i += 1;
+            },
+            "--check" => {
+                check = true;
+                i += 1;
+            },
+            "--force" => {
+                force = true;
+                i += 1;
+            },
+            "--strict" => {
+                strict = true;
+                i += 1;
+            },
+            "--perf" => {
+                perf = true;
+                i += 1;
+            },
+            "--ephemeral" => {
+                ephemeral = true;
+                i += 1;
+            },
+            "--format" => {
+                match args.get(i + 1) {
+                    Some(value) if tools::validate::OUTPUT_FORMATS.contains(&value.as_str()) => {
+                        format = Some(value.clone());
+                        i += 2;
+                    },
+                    Some(value) => {
+                        eprintln!("ERROR: --format must be one of {}, got '{}'", tools::validate::OUTPUT_FORMATS.join(", "), value);
+                        std::process::exit(1);
+                    },
+                    None => {
+                        eprintln!("ERROR: --format requires a value");
+                        print_help();
+                        std::process::exit(1);
+                    }
+                }
+            },
+            "--out" => {
+                if i + 1 < args.len() {
+                    out = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("ERROR: --out requires a value");
+                    print_help();
+                    std::process::exit(1);
+                }
+            },
             "--config_dir" => {
                 if i + 1 < args.len() {
                     config_dir = Some(args[i + 1].clone());
@@ -70,6 +216,26 @@ fn parse_args() -> Args {
                     std::process::exit(1);
                 }
             },
+            option if tools::exec::ACTION_OPTIONS.contains(&option) => {
+                if i + 1 < args.len() {
+                    match tools::exec::parse_action(option, &args[i + 1]) {
+                        Ok(action) => actions.push(action),
+                        Err(e) => {
+                            eprintln!("ERROR: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                    i += 2;
+                } else {
+                    eprintln!("ERROR: {} requires a value", option);
+                    print_help();
+                    std::process::exit(1);
+                }
+            },
+            param if !param.starts_with("--") => {
+                params.push(param.to_string());
+                i += 1;
+            },
             _ => {
                 eprintln!("ERROR: Unknown option: {}", args[i]);
                 print_help();
@@ -81,18 +247,84 @@ fn parse_args() -> Args {
     if mode == "help" {
         print_help();
         std::process::exit(0);
-    } else if mode != "gtk" && mode != "validate-settings" && mode != "input-test" {
+    } else if !MODES.contains(&mode.as_str()) {
         eprintln!("ERROR: Unknown mode: {}", mode);
         print_help();
         std::process::exit(1);
+    } else if mode != "exec" && !actions.is_empty() {
+        eprintln!("ERROR: Action options are only supported in 'exec' mode");
+        print_help();
+        std::process::exit(1);
+    } else if mode != "fmt" && check {
+        eprintln!("ERROR: --check is only supported in 'fmt' mode");
+        print_help();
+        std::process::exit(1);
+    } else if mode != "fmt" && force {
+        eprintln!("ERROR: --force is only supported in 'fmt' mode");
+        print_help();
+        std::process::exit(1);
+    } else if mode != "validate-settings" && strict {
+        eprintln!("ERROR: --strict is only supported in 'validate-settings' mode");
+        print_help();
+        std::process::exit(1);
+    } else if mode != "validate-settings" && format.is_some() {
+        eprintln!("ERROR: --format is only supported in 'validate-settings' mode");
+        print_help();
+        std::process::exit(1);
+    } else if mode == "run" && params.len() != 2 {
+        eprintln!("ERROR: 'run' requires <board> and <pad> arguments");
+        print_help();
+        std::process::exit(1);
+    } else if mode != "render" && out.is_some() {
+        eprintln!("ERROR: --out is only supported in 'render' mode");
+        print_help();
+        std::process::exit(1);
+    } else if mode == "render" && params.len() != 1 {
+        eprintln!("ERROR: 'render' requires a <board> argument");
+        print_help();
+        std::process::exit(1);
+    } else if mode == "profile" && params.len() != 1 {
+        eprintln!("ERROR: 'profile' requires a <name> argument");
+        print_help();
+        std::process::exit(1);
+    } else if mode == "backup" && params.len() > 1 {
+        eprintln!("ERROR: 'backup' takes at most a <file> argument");
+        print_help();
+        std::process::exit(1);
+    } else if mode == "restore" && params.len() != 1 {
+        eprintln!("ERROR: 'restore' requires a <file> argument");
+        print_help();
+        std::process::exit(1);
+    } else if mode == "import" && params.len() != 2 {
+        eprintln!("ERROR: 'import' requires <source> and <path> arguments");
+        print_help();
+        std::process::exit(1);
+    } else if !["run", "import", "render", "profile", "backup", "restore"].contains(&mode.as_str()) && !params.is_empty() {
+        eprintln!("ERROR: Unexpected argument: {}", params[0]);
+        print_help();
+        std::process::exit(1);
     }
 
-    Args { mode, config_dir, profile }
+    let profile = profile.or_else(|| env::var(PROFILE_ENV).ok().filter(|profile| !profile.is_empty()));
+
+    Args { mode, config_dir, profile, board, timeout, check, force, strict, format, out, perf, ephemeral, actions, params }
 }
 
 
 
 
+/// The --config_dir directory, or the hotkeys directory in the user config directory
+fn user_config_dir(config_dir: Option<PathBuf>) -> PathBuf {
+    if let Some(config_dir) = config_dir {
+        assert!(config_dir.exists());
+        config_dir
+    } else {
+        dirs::config_dir()
+            .map(|d| d.join("hotkeys"))
+            .unwrap_or_else(|| PathBuf::from("./config"))
+    }
+}
+
 pub fn get_config_resolution_order(config_dir: Option<PathBuf>) -> Vec<PathBuf> {
 
     fn get_dev_resources_dir() -> Option<PathBuf> {
@@ -128,15 +360,7 @@ pub fn get_config_resolution_order(config_dir: Option<PathBuf>) -> Vec<PathBuf>
 
     let mut paths = Vec::new();
 
-    let user_config_dir = if let Some(config_dir) = config_dir {
-        assert!(config_dir.exists());
-        config_dir
-    } else {
-        dirs::config_dir()
-            .map(|d| d.join("hotkeys"))
-            .unwrap_or_else(|| PathBuf::from("./config"))
-    };
-
+    let user_config_dir = user_config_dir(config_dir);
     if user_config_dir.exists() {
         paths.push(user_config_dir);
     }
@@ -155,15 +379,82 @@ fn run() -> Result<()> {
     // Check for command line arguments
     let args = parse_args();
     let mode = &args.mode;
+    if args.perf {
+        core::perf::enable();
+    }
+    if args.ephemeral {
+        app::settings_cache::disable();
+    }
+
+    let user_dir = user_config_dir(args.config_dir.clone().map(PathBuf::from));
+    let config_paths = get_config_resolution_order(args.config_dir.map(PathBuf::from));
+    let resources = core::Resources::new(config_paths.clone());
 
-    let resources = core::Resources::new(get_config_resolution_order(args.config_dir.map(PathBuf::from)));
+    // Diagnostics have to work even without valid logging configuration or settings
+    if mode == "doctor" {
+        return tools::doctor::run_diagnostics(&resources, &config_paths);
+    }
 
     log4rs::init_file(resources.log_toml().unwrap(), Default::default())
         .map_err(|e| anyhow::anyhow!("Failed to initialize logging: {}", e))?;
 
-    // Load settings once for all modes
-    let settings = app::config::load_settings(&resources)
-        .map_err(|e| anyhow::anyhow!("Failed to load settings: {}", e))?;
+    // Editing has to work with invalid settings
+    if mode == "edit" {
+        return tools::edit::edit_settings(&resources);
+    }
+
+    // Migration has to work with settings this version cannot load directly
+    if mode == "migrate" {
+        return tools::migrate::migrate_settings(&resources);
+    }
+
+    // Backups are taken and restored whatever state the settings are in, restores are validated on their own
+    if mode == "backup" {
+        return tools::backup::backup(&user_dir, args.params.first().map(String::as_str));
+    }
+    if mode == "restore" {
+        let other_config_paths: Vec<PathBuf> = config_paths.iter().filter(|path| **path != user_dir).cloned().collect();
+        return tools::backup::restore(&user_dir, &other_config_paths, &args.params[0]);
+    }
+
+    // Splitting works on the settings file as written, before includes and variables are resolved
+    if mode == "split" {
+        return tools::split::split_settings(&resources);
+    }
+
+    // Importing does not need the current settings
+    if mode == "import" {
+        return tools::import::import(&args.params[0], &args.params[1]);
+    }
+
+    // The settings window shows the validation problems, so it opens settings with errors
+    if mode == "settings" {
+        return tools::settings::edit_settings(resources);
+    }
+
+    // Validation reports all problems instead of failing on the first one
+    if mode == "validate-settings" {
+        return tools::validate::validate_settings(&resources, args.format.as_deref().unwrap_or("text"), args.strict);
+    }
+
+    // Load settings once for all modes. A board started by a hotkey shows what is wrong
+    // on the safe mode board instead of nothing happening
+    let mut safe_mode = None;
+    let settings = match app::config::load_settings(&resources) {
+        Ok(settings) => settings,
+        Err(e) if mode == "gtk" => {
+            log::error!("Failed to load settings, starting in safe mode: {:#}", e);
+            safe_mode = Some(format!("Failed to load settings: {:#}", e));
+            let settings_file = resources.settings_file().unwrap_or_default();
+            app::config::AppSettings::safe_mode(&settings_file.to_string_lossy())
+        },
+        Err(e) => return Err(anyhow::anyhow!("Failed to load settings: {}", e)),
+    };
+    let settings = match args.timeout {
+        Some(timeout) => settings.with_timeout(timeout),
+        None => settings,
+    };
+    let settings = if args.ephemeral { settings.with_ephemeral() } else { settings };
 
     log::info!("Starting HotKeys");
 
@@ -172,19 +463,95 @@ fn run() -> Result<()> {
         "gtk" => {
             log::info!("Starting GTK4 mode");
 
-            match crate::app::HotKeysApp::new(resources, args.profile.clone(), settings) {
-                Ok(mut app) => {
-                    if let Err(e) = app.run() {
-                        log::error!("HotKeys application failed: {}", e);
+            if app::debounce::is_debounced(settings.debounce()) {
+                log::info!("Ignoring invocation right after a pad selection");
+                return Ok(());
+            }
+
+            let Some(instance) = app::instance::InstanceServer::bind()? else {
+                log::info!("HotKeys is already running - handing over to the running instance");
+                let request = app::instance::InstanceRequest { profile: args.profile.clone(), board: args.board.clone(), ..Default::default() };
+                if let app::instance::InstanceReply::Failed(e) = app::instance::send(&request)? {
+                    log::warn!("The running instance rejected the request: {}", e);
+                }
+                return Ok(());
+            };
+            // Handed over to the open board, which is raised or navigates to the requested board
+            let (request_sender, requests) = async_channel::unbounded();
+            instance.spawn(move |request| {
+                if request.session_profile.is_some() {
+                    return app::instance::InstanceReply::Failed("A board is open, only a running daemon changes profiles".to_string());
+                }
+                let _ = request_sender.try_send(request);
+                app::instance::InstanceReply::Done
+            })?;
+
+            // Without --profile the profile is picked on a board, closing it starts nothing
+            if args.profile.is_none() && safe_mode.is_none() && settings.profile_chooser() {
+                if let Err(e) = crate::app::HotKeysApp::run_choosing_profile(resources, settings, args.board.clone(), Some(requests)) {
+                    log::error!("HotKeys application failed: {}", e);
+                }
+            } else {
+                match crate::app::HotKeysApp::new(resources, args.profile.clone(), settings) {
+                    Ok(app) => {
+                        if let Err(e) = app.with_board(args.board.clone()).with_safe_mode(safe_mode).with_requests(Some(requests)).run() {
+                            log::error!("HotKeys application failed: {}", e);
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Failed to create HotKeys application: {}", e);
                     }
-                },
-                Err(e) => {
-                    log::error!("Failed to create HotKeys application: {}", e);
                 }
             }
         },
-        "validate-settings" => {
-            log::info!("Validation SUCCESSFUL!");
+        "daemon" => {
+            log::info!("Starting daemon mode");
+
+            let mut daemon = crate::app::daemon::Daemon::new(resources, args.profile.clone(), settings)
+                .with_timeout(args.timeout)
+                .with_ephemeral(args.ephemeral);
+            if let Err(e) = daemon.run() {
+                log::error!("HotKeys daemon failed: {}", e);
+            }
+        },
+        "profile" => {
+            settings.get_profile(&args.params[0])?;
+            let request = app::instance::InstanceRequest { session_profile: Some(args.params[0].clone()), ..Default::default() };
+            match app::instance::send(&request)? {
+                app::instance::InstanceReply::Done => println!("The running daemon uses profile '{}'", args.params[0]),
+                app::instance::InstanceReply::Pending => println!("Requested profile '{}', the running daemon uses it once the open board closes", args.params[0]),
+                app::instance::InstanceReply::Failed(e) => anyhow::bail!("Profile not changed: {}", e),
+            }
+        },
+        "exec" => {
+            log::info!("Running exec mode");
+            tools::exec::execute(&args.actions, &settings, args.profile.as_deref().unwrap_or("default"))?;
+        },
+        "batch" => {
+            log::info!("Running batch mode");
+            tools::batch::execute_stdin(&settings, args.profile.as_deref().unwrap_or("default"))?;
+        },
+        "run" => {
+            log::info!("Running pad {} of board {}", args.params[1], args.params[0]);
+            tools::run::run_pad(resources, args.profile.clone(), settings, &args.params[0], &args.params[1])?;
+        },
+        "render" => {
+            tools::render::render_board(resources, args.profile.clone(), settings, &args.params[0], args.out.as_deref())?;
+        },
+        "list" => {
+            tools::list::print_inventory(&settings)?;
+        },
+        "cheatsheet" => {
+            tools::cheatsheet::print_cheatsheet(&settings, args.profile.as_deref().unwrap_or("default"))?;
+        },
+        "detect" => {
+            tools::detect::print_detection(resources, args.profile.clone(), settings)?;
+        },
+        "record" => {
+            tools::record::record_shortcuts(&settings)?;
+        },
+        "fmt" => {
+            tools::fmt::format_settings(&settings, args.check, args.force)?;
         },
         "input-test" => {
             log::info!("Running input test");
@@ -201,11 +568,23 @@ fn run() -> Result<()> {
     Ok(())
 }
 
+/// Log panics before the default hook prints them, stderr is not seen when started by a hotkey
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log::error!("{}", info);
+        default_hook(info);
+    }));
+}
+
 fn main() {
+    install_panic_hook();
     let result = run();
+    core::perf::report();
 
     // We do this for nicer HRESULT printing when errors occur.
     if let Err(error) = result {
         eprintln!("Error: {:?}", error);
+        std::process::exit(1);
     }
 }
\ No newline at end of file
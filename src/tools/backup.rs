@@ -0,0 +1,144 @@
+// Config backup and restore (`hotkeys backup [<file>]`, `hotkeys restore <file>`).
+// Archives the user config directory, settings, includes, icons and data.json, into a gzipped tarball
+// and restores it on another machine. Restored settings are validated before they replace the current ones
+
+use crate::{app::config, app::validation::ValidationReport, core::Resources};
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const TAR: &str = "tar";
+
+/// Backup file name when none is given, e.g. "hotkeys-backup-20250101-120000.tar.gz"
+fn default_archive() -> PathBuf {
+    PathBuf::from(format!("hotkeys-backup-{}.tar.gz", chrono::Local::now().format("%Y%m%d-%H%M%S")))
+}
+
+/// Directory next to `dir` with a suffix, e.g. "~/.config/hotkeys.restore"
+fn sibling(dir: &Path, suffix: &str) -> PathBuf {
+    let name = dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    dir.with_file_name(format!("{}.{}", name, suffix))
+}
+
+fn run_tar(args: &[&std::ffi::OsStr]) -> Result<()> {
+    let output = Command::new(TAR).args(args).output()
+        .map_err(|e| anyhow!("Failed to start {}: {}", TAR, e))?;
+    if !output.status.success() {
+        anyhow::bail!("{} failed: {}", TAR, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Archive the contents of the config directory
+fn create_archive(config_dir: &Path, archive: &Path) -> Result<()> {
+    run_tar(&["-czf".as_ref(), archive.as_os_str(), "-C".as_ref(), config_dir.as_os_str(), ".".as_ref()])
+}
+
+fn extract_archive(archive: &Path, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    run_tar(&["-xzf".as_ref(), archive.as_os_str(), "-C".as_ref(), dir.as_os_str(), "--no-same-owner".as_ref()])
+}
+
+/// Check that the restored directory holds loadable settings without errors and readable data,
+/// other config directories (e.g. /usr/share/hotkeys) are still used for the includes and icons
+fn validate_restored(dir: &Path, other_config_paths: &[PathBuf]) -> Result<()> {
+    let resources = Resources::new(std::iter::once(dir.to_path_buf()).chain(other_config_paths.iter().cloned()).collect());
+    let settings_file = resources.settings_file()
+        .filter(|path| path.starts_with(dir))
+        .ok_or_else(|| anyhow!("The backup contains no settings file"))?;
+
+    let data_json = dir.join(env!("RESOURCE_DATA_FILE"));
+    if data_json.exists() {
+        serde_json::from_str::<serde_json::Value>(&std::fs::read_to_string(&data_json)?)
+            .map_err(|e| anyhow!("The backup data file is invalid: {}", e))?;
+    }
+
+    let settings = config::load_settings_unvalidated(&resources)
+        .map_err(|e| anyhow!("The backup settings {:?} cannot be loaded: {}", settings_file, e))?;
+    let report = settings.validation_report(&resources, ValidationReport::default());
+    if report.has_errors() {
+        let errors: Vec<String> = report.errors().map(ToString::to_string).collect();
+        anyhow::bail!("The backup settings are invalid:\n{}", errors.join("\n"));
+    }
+    Ok(())
+}
+
+pub fn backup(config_dir: &Path, archive: Option<&str>) -> Result<()> {
+    if !config_dir.is_dir() {
+        anyhow::bail!("Config directory {:?} not found, there is nothing to back up", config_dir);
+    }
+    let archive = archive.map(PathBuf::from).unwrap_or_else(default_archive);
+
+    create_archive(config_dir, &archive)?;
+    log::info!("Backed up {:?} to {:?}", config_dir, archive);
+    println!("Backed up {:?} to {:?}", config_dir, archive);
+    Ok(())
+}
+
+/// Restore a backup into the config directory, the current directory is kept as a sibling "<dir>.<time>.bak"
+pub fn restore(config_dir: &Path, other_config_paths: &[PathBuf], archive: &str) -> Result<()> {
+    let archive = Path::new(archive);
+    if !archive.is_file() {
+        anyhow::bail!("Backup {:?} not found", archive);
+    }
+
+    // Extracted next to the config directory, so it can be moved into place
+    let staging = sibling(config_dir, "restore");
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    let restored = extract_archive(archive, &staging)
+        .and_then(|_| validate_restored(&staging, other_config_paths));
+    if let Err(e) = restored {
+        std::fs::remove_dir_all(&staging).ok();
+        return Err(e);
+    }
+
+    if config_dir.exists() {
+        let previous = sibling(config_dir, &format!("{}.bak", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+        std::fs::rename(config_dir, &previous)
+            .map_err(|e| anyhow!("Failed to move {:?} aside: {}", config_dir, e))?;
+        println!("Previous config moved to {:?}", previous);
+    }
+    std::fs::rename(&staging, config_dir)
+        .map_err(|e| anyhow!("Failed to move the restored config into {:?}: {}", config_dir, e))?;
+
+    log::info!("Restored {:?} from {:?}", config_dir, archive);
+    println!("Restored {:?} from {:?}", config_dir, archive);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_invalid_backup() {
+        let root = std::env::temp_dir().join(format!("hotkeys-backup-{}", std::process::id()));
+        let (source, target) = (root.join("source"), root.join("target"));
+        let archive = root.join("backup.tar.gz");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(source.join("settings.json"), "{ not json").unwrap();
+        std::fs::write(target.join("settings.json"), "{}").unwrap();
+
+        backup(&source, archive.to_str()).unwrap();
+        let error = restore(&target, &[], archive.to_str().unwrap()).unwrap_err();
+        assert!(error.to_string().contains("cannot be loaded"), "{}", error);
+        // The current config is left alone
+        assert_eq!(std::fs::read_to_string(target.join("settings.json")).unwrap(), "{}");
+        assert!(!sibling(&target, "restore").exists());
+
+        std::fs::remove_file(source.join("settings.json")).unwrap();
+        backup(&source, archive.to_str()).unwrap();
+        assert!(restore(&target, &[], archive.to_str().unwrap()).unwrap_err().to_string().contains("no settings file"));
+        assert!(restore(&target, &[], root.join("missing.tar.gz").to_str().unwrap()).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_sibling() {
+        assert_eq!(sibling(Path::new("/home/me/.config/hotkeys"), "restore"), PathBuf::from("/home/me/.config/hotkeys.restore"));
+    }
+}
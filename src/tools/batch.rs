@@ -0,0 +1,57 @@
+// Batch action execution (`hotkeys batch`).
+// Reads one action specification per line from stdin and executes them as they arrive
+
+use crate::{app::{audit::AuditLog, config::AppSettings}, core::Action, executor};
+use anyhow::Result;
+use std::io::BufRead;
+
+/// Parse a line of input, `None` for blank lines and comments.
+/// Lines starting with '{' or '[' are JSON actions (as in settings.json), anything else is a shortcut, e.g. "Ctrl Shift T"
+pub fn parse_line(line: &str) -> Result<Option<Vec<Action>>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let actions = if line.starts_with('{') {
+        vec![serde_json::from_str::<Action>(line).map_err(|e| format!("Invalid action: {}", e))?]
+    } else if line.starts_with('[') {
+        serde_json::from_str::<Vec<Action>>(line).map_err(|e| format!("Invalid action list: {}", e))?
+    } else {
+        vec![Action::Shortcut(line.to_string())]
+    };
+    Ok(Some(actions))
+}
+
+pub fn execute_stdin(settings: &AppSettings, profile: &str) -> Result<()> {
+    let keyboard_layout = settings.get_profile_keyboard_layout(profile);
+    let audit = settings.audit_log().map(|path| AuditLog::new(path, profile));
+
+    for (index, line) in std::io::stdin().lock().lines().enumerate() {
+        let line = line?;
+        let actions = parse_line(&line)
+            .map_err(|e| anyhow::anyhow!("Line {}: {}", index + 1, e))?;
+
+        if let Some(actions) = actions {
+            executor::execute_actions(&actions, &keyboard_layout, None, None, settings.privacy(), audit.as_ref())
+                .map_err(|e| anyhow::anyhow!("Line {}: {}", index + 1, e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert!(matches!(parse_line("  "), Ok(None)));
+        assert!(matches!(parse_line("# comment"), Ok(None)));
+        assert!(matches!(parse_line("Ctrl Shift T").unwrap().unwrap().as_slice(), [Action::Shortcut(s)] if s == "Ctrl Shift T"));
+        assert!(matches!(parse_line(r#"{"Line": "ls -la"}"#).unwrap().unwrap().as_slice(), [Action::Line(s)] if s == "ls -la"));
+        assert!(matches!(parse_line(r#"[{"Shortcut": "Ctrl T"}, {"Pause": 200}]"#).unwrap().unwrap().as_slice(),
+            [Action::Shortcut(_), Action::Pause(200)]));
+        assert!(parse_line(r#"{"Typo": "x"}"#).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+// Cheat sheet export (`hotkeys cheatsheet`).
+// Prints the boards of a profile as a printable HTML page: the pads in their numpad positions
+// with their titles and the shortcuts, texts and commands they trigger
+
+use crate::app::config::{AppSettings, BoardConfig, BoardKind, PadConfig};
+use crate::core::{Action, MASKED_TEXT};
+use anyhow::Result;
+
+/// Pad ids in the rows of the board, as on the numpad
+const PAD_ROWS: [[usize; 3]; 3] = [[7, 8, 9], [4, 5, 6], [1, 2, 3]];
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+section { break-inside: avoid; margin-bottom: 2em; }
+h2 { margin-bottom: 0.2em; }
+h3 { margin: 0.6em 0 0.2em; font-size: 1em; color: #555; }
+table { border-collapse: collapse; table-layout: fixed; width: 100%; }
+td { border: 1px solid #999; vertical-align: top; padding: 0.4em; height: 4.5em; width: 33%; }
+.id { float: right; color: #999; font-size: 0.8em; }
+.header { font-weight: bold; }
+.text { white-space: pre-line; }
+.action { font-family: monospace; font-size: 0.85em; color: #036; white-space: pre-wrap; }
+";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// What an action does, e.g. "Ctrl C" or "$ make deploy", pauses and internal actions are left out.
+/// The texts, commands and URLs of sensitive pads are masked
+fn describe_action(action: &Action, sensitive: bool) -> Option<String> {
+    let payload = |text: &str| match sensitive {
+        true => MASKED_TEXT.to_string(),
+        false => text.to_string(),
+    };
+    let typed = |text: &str| match sensitive {
+        true => MASKED_TEXT.to_string(),
+        false => format!("\u{201c}{}\u{201d}", text),
+    };
+    match action {
+        Action::Shortcut(keys) => Some(keys.clone()),
+        Action::Text(text) => Some(format!("types {}", typed(text))),
+        Action::Line(text) => Some(format!("types {} \u{23ce}", typed(text))),
+        Action::Command(command) => Some(format!("$ {}", payload(command))),
+        Action::OpenUrl(url) => Some(format!("opens {}", payload(url))),
+        Action::Secret(_) => Some("types a secret".to_string()),
+        Action::Pause(_) | Action::CustomHomeAction => None,
+    }
+}
+
+fn pad_cell(id: usize, pad: Option<&PadConfig>) -> String {
+    let mut cell = format!("<td><span class=\"id\">{}</span>", id);
+    if let Some(pad) = pad {
+        if !pad.header.is_empty() {
+            cell += &format!("<div class=\"header\">{}</div>", escape(&pad.header));
+        }
+        let text = if pad.sensitive && !pad.text.is_empty() { MASKED_TEXT } else { &pad.text };
+        if !text.is_empty() {
+            cell += &format!("<div class=\"text\">{}</div>", escape(text));
+        }
+        for description in pad.actions.iter().filter_map(|action| describe_action(action, pad.sensitive)) {
+            cell += &format!("<div class=\"action\">{}</div>", escape(&description));
+        }
+        for description in pad.long_actions.iter().filter_map(|action| describe_action(action, pad.sensitive)) {
+            cell += &format!("<div class=\"action\">hold: {}</div>", escape(&description));
+        }
+        if let Some(board) = &pad.board {
+            cell += &format!("<div class=\"action\">\u{2192} {}</div>", escape(board));
+        }
+    }
+    cell + "</td>"
+}
+
+fn pads_table(settings: &AppSettings, padset_name: &str) -> String {
+    let pads = settings.get_padset_config(padset_name).map(|padset| padset.items.as_slice()).unwrap_or_default();
+    let rows: String = PAD_ROWS.iter()
+        .map(|row| format!("<tr>{}</tr>", row.iter().map(|&id| pad_cell(id, pads.get(id - 1))).collect::<String>()))
+        .collect();
+    format!("<table>{}</table>\n", rows)
+}
+
+fn board_section(settings: &AppSettings, board: &BoardConfig) -> String {
+    let mut section = format!("<section>\n<h2>{}</h2>\n", escape(board.title.as_deref().unwrap_or(&board.name)));
+    if !matches!(board.kind, BoardKind::Static) {
+        return section + "<p>Built-in board, its pads are created when it is shown.</p>\n</section>\n";
+    }
+    if let Some(base_pads) = &board.base_pads {
+        section += &pads_table(settings, base_pads);
+    }
+    let mut modifier_pads: Vec<_> = board.modifier_pads.iter().collect();
+    modifier_pads.sort();
+    for (modifier, padset_name) in modifier_pads {
+        section += &format!("<h3>{}</h3>\n", escape(modifier));
+        section += &pads_table(settings, padset_name);
+    }
+    if let Some(double_pads) = &board.double_pads {
+        section += "<h3>Double tap</h3>\n";
+        section += &pads_table(settings, double_pads);
+    }
+    section + "</section>\n"
+}
+
+/// HTML cheat sheet of the boards of the profile, in the order of the profile
+pub fn cheatsheet(settings: &AppSettings, profile: &str) -> Result<String> {
+    let profile = settings.get_profile(profile)?;
+    let sections: String = profile.boards.iter()
+        .filter_map(|name| settings.board_configs.iter().find(|board| &board.name == name))
+        .map(|board| board_section(settings, board))
+        .collect();
+
+    Ok(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>HotKeys: {name}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>HotKeys: {name}</h1>\n{sections}</body>\n</html>\n",
+        name = escape(&profile.name),
+    ))
+}
+
+pub fn print_cheatsheet(settings: &AppSettings, profile: &str) -> Result<()> {
+    print!("{}", cheatsheet(settings, profile)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::tests::{json, test_settings};
+
+    #[test]
+    fn test_cheatsheet() {
+        let settings = test_settings(json!({
+            "boards": [
+                { "name": "code", "title": "VS Code", "base_pads": "code", "modifier_pads": { "Ctrl": "code/ctrl" } },
+                { "name": "other", "base_pads": "code" }
+            ],
+            "padsets": [
+                { "name": "code", "items": [
+                    { "header": "Copy", "actions": [{ "Shortcut": "Ctrl C" }], "long_actions": [{ "Shortcut": "Ctrl Shift C" }] },
+                    { "header": "<Deploy>", "actions": [{ "Pause": 100 }, { "Command": "make deploy" }] },
+                    {}, {}, {}, {},
+                    { "header": "Key", "text": "ABC-123", "sensitive": true, "actions": [{ "Text": "ABC-123" }] },
+                    { "header": "Token", "sensitive": true, "actions": [{ "Command": "login --token t0k3n" }],
+                      "long_actions": [{ "OpenUrl": "https://example.com/?token=t0k3n" }] }
+                ] },
+                { "name": "code/ctrl", "items": [{ "text": "Git", "board": "git" }, { "actions": [{ "Line": "ls" }] }] }
+            ],
+            "profiles": [{ "name": "default", "boards": ["code"], "default": "code" }]
+        }));
+
+        let html = cheatsheet(&settings, "default").unwrap();
+        assert!(html.contains("<h2>VS Code</h2>"));
+        assert!(!html.contains("other"));
+        assert!(html.contains("<div class=\"header\">&lt;Deploy&gt;</div><div class=\"action\">$ make deploy</div>"));
+        assert!(html.contains("<div class=\"action\">Ctrl C</div><div class=\"action\">hold: Ctrl Shift C</div>"));
+        // Pad 7 is in the first row
+        assert!(html.find("<span class=\"id\">7</span>").unwrap() < html.find("<span class=\"id\">1</span>").unwrap());
+        assert!(!html.contains("ABC-123"));
+        assert!(!html.contains("t0k3n"));
+        assert!(html.contains("$ ********</div><div class=\"action\">hold: opens ********"));
+        assert!(html.contains("<h3>Ctrl</h3>"));
+        assert!(html.contains("\u{2192} git"));
+        assert!(html.contains("types \u{201c}ls\u{201d} \u{23ce}"));
+        assert!(cheatsheet(&settings, "missing").is_err());
+    }
+}
@@ -0,0 +1,31 @@
+// Board detection preview (`hotkeys detect`).
+// Runs the same detection as the GUI and prints which board would be chosen and why
+
+use crate::{app::{config::AppSettings, HotKeysApp}, core::Resources, process};
+use anyhow::Result;
+
+pub fn print_detection(resources: Resources, profile: Option<String>, settings: AppSettings) -> Result<()> {
+    let profile_name = profile.clone().unwrap_or_else(|| "default".to_string());
+    let app = HotKeysApp::new(resources, profile, settings)?;
+
+    println!("Profile: {}", profile_name);
+    println!("X11 detection: {}", if process::is_x11_available() { "available" } else { "not available" });
+
+    let detection = app.detect_board()?;
+    match &detection.process {
+        Some(info) => {
+            println!("Active process: {} (PID: {})", info.name, info.pid);
+            if let Some(window_id) = info.window_id {
+                println!("Active window: 0x{:x}", window_id);
+            }
+            if let Some(window_class) = &info.window_class {
+                println!("Window class: {}", window_class);
+            }
+        },
+        None => println!("Active process: unknown"),
+    }
+
+    println!("Board: {}", detection.board.name);
+    println!("Reason: {}", detection.reason);
+    Ok(())
+}
@@ -0,0 +1,242 @@
+// Diagnostics (`hotkeys doctor`).
+// Checks the environment HotKeys depends on and prints actionable results
+
+use crate::{app::{config, instance}, core::Resources, input::evdev::{self, KeyChord}, process, windows::{layer_shell, renderer}};
+use anyhow::Result;
+use gtk4::prelude::*;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+struct Check {
+    status: Status,
+    message: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn ok(message: impl Into<String>) -> Self {
+        Self { status: Status::Ok, message: message.into(), hint: None }
+    }
+
+    fn warn(message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { status: Status::Warn, message: message.into(), hint: Some(hint.into()) }
+    }
+
+    fn fail(message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { status: Status::Fail, message: message.into(), hint: Some(hint.into()) }
+    }
+
+    fn print(&self) {
+        let label = match self.status {
+            Status::Ok => "[ OK ]",
+            Status::Warn => "[WARN]",
+            Status::Fail => "[FAIL]",
+        };
+        println!("{} {}", label, self.message);
+        if let Some(hint) = &self.hint {
+            println!("       -> {}", hint);
+        }
+    }
+}
+
+/// Run all diagnostics, fails if any check failed
+pub fn run_diagnostics(resources: &Resources, config_paths: &[std::path::PathBuf]) -> Result<()> {
+    let sections: Vec<(&str, Vec<Check>)> = vec![
+        ("Configuration", check_configuration(resources, config_paths)),
+        ("Input", check_input()),
+        ("Display", check_display()),
+        ("Instance", check_instance()),
+    ];
+
+    let mut failures = 0;
+    let mut warnings = 0;
+    for (title, checks) in &sections {
+        println!();
+        println!("{}:", title);
+        for check in checks {
+            check.print();
+            match check.status {
+                Status::Fail => failures += 1,
+                Status::Warn => warnings += 1,
+                Status::Ok => {},
+            }
+        }
+    }
+
+    println!();
+    println!("{} failed, {} warnings", failures, warnings);
+
+    if failures > 0 {
+        anyhow::bail!("{} diagnostics failed", failures);
+    }
+    Ok(())
+}
+
+fn check_configuration(resources: &Resources, config_paths: &[std::path::PathBuf]) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    for (index, path) in config_paths.iter().enumerate() {
+        if path.exists() {
+            checks.push(Check::ok(format!("Config directory {}: {:?}", index + 1, path)));
+        } else {
+            checks.push(Check::warn(format!("Config directory {} does not exist: {:?}", index + 1, path), "Create it or pass --config_dir <path>"));
+        }
+    }
+
+    match resources.log_toml() {
+        Some(path) => checks.push(Check::ok(format!("Logging configuration: {:?}", path))),
+        None => checks.push(Check::fail("Logging configuration not found", format!("Provide {} in one of the config directories", env!("RESOURCE_LOG_FILE")))),
+    }
+
+    let settings = match resources.settings_file() {
+        Some(path) => {
+            checks.push(Check::ok(format!("Settings file: {:?}", path)));
+            match config::load_settings(resources) {
+                Ok(settings) => {
+                    checks.push(Check::ok("Settings are valid"));
+                    Some(settings)
+                },
+                Err(e) => {
+                    checks.push(Check::fail(format!("Settings are invalid: {}", e), "Fix the reported problem, 'hotkeys validate-settings' repeats this check"));
+                    None
+                }
+            }
+        },
+        None => {
+            checks.push(Check::fail("Settings file not found", format!("Provide {} in one of the config directories", env!("RESOURCE_SETTINGS_FILE"))));
+            None
+        }
+    };
+
+    if let Some(settings) = settings {
+        // Icon theme names can only be checked with a display
+        let _ = gtk4::init();
+        let resources = resources.clone().with_icon_theme(renderer::has_theme_icon);
+        let mut missing = Vec::new();
+        let board_icons = settings.board_configs.iter().filter_map(|b| b.icon.clone());
+        let pad_icons = settings.padset_configs.iter()
+            .flat_map(|p| p.items.iter())
+            .filter(|pad| !pad.icon.is_empty())
+            .map(|pad| pad.icon.clone());
+        for icon in board_icons.chain(pad_icons) {
+            if !resources.has_icon(&icon) && !missing.contains(&icon) {
+                missing.push(icon);
+            }
+        }
+
+        if missing.is_empty() {
+            checks.push(Check::ok("All icons found"));
+        } else {
+            checks.push(Check::warn(format!("Icons not found: {}", missing.join(", ")), "Icons are looked up in the 'icons' folder of the config directories and the desktop icon theme"));
+        }
+
+        if let Some(listener) = settings.listener() {
+            let keys: Vec<u16> = listener.triggers.iter()
+                .filter_map(|trigger| KeyChord::from_str(&trigger.keys).ok())
+                .flat_map(|chord| chord.keys())
+                .collect();
+            match evdev::find_keyboards(&listener.devices, &keys) {
+                Ok(devices) if !devices.is_empty() => {
+                    let names: Vec<String> = devices.iter().map(|d| d.name().to_string()).collect();
+                    checks.push(Check::ok(format!("Listener devices: {}", names.join(", "))));
+                },
+                Ok(_) => checks.push(Check::fail("No input devices found for the listener", "Check 'listener.devices' and membership in the 'input' group")),
+                Err(e) => checks.push(Check::fail(format!("Failed to list input devices: {}", e), "Check membership in the 'input' group")),
+            }
+        }
+    }
+
+    checks
+}
+
+fn check_input() -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let uinput = ["/dev/uinput", "/dev/input/uinput"].into_iter()
+        .map(Path::new)
+        .find(|path| path.exists());
+
+    match uinput {
+        None => checks.push(Check::fail("uinput device not found", "Load the kernel module: sudo modprobe uinput")),
+        Some(path) => match OpenOptions::new().write(true).open(path) {
+            Ok(_) => checks.push(Check::ok(format!("{:?} is writable", path))),
+            Err(e) => checks.push(Check::fail(format!("{:?} is not writable: {}", path, e), "Install the udev rule and join the 'input' group, see 'Input Device Permissions' in README")),
+        },
+    }
+
+    match Command::new("id").arg("-nG").output() {
+        Ok(output) if output.status.success() => {
+            let groups = String::from_utf8_lossy(&output.stdout);
+            if groups.split_whitespace().any(|group| group == "input") {
+                checks.push(Check::ok("User is a member of the 'input' group"));
+            } else {
+                checks.push(Check::warn("User is not a member of the 'input' group", "sudo usermod -a -G input $USER, then log out and back in"));
+            }
+        },
+        _ => checks.push(Check::warn("Could not determine group membership", "Run 'id -nG' to check for the 'input' group")),
+    }
+
+    checks
+}
+
+fn check_display() -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let session_type = std::env::var("XDG_SESSION_TYPE").unwrap_or_default();
+    let display = std::env::var("DISPLAY").ok();
+    let wayland_display = std::env::var("WAYLAND_DISPLAY").ok();
+
+    match (&wayland_display, &display) {
+        (None, None) => checks.push(Check::fail("Neither DISPLAY nor WAYLAND_DISPLAY is set", "Run HotKeys from within a graphical session")),
+        _ => checks.push(Check::ok(format!("Session: {}, DISPLAY={}, WAYLAND_DISPLAY={}",
+            if session_type.is_empty() { "unknown" } else { &session_type },
+            display.as_deref().unwrap_or("-"),
+            wayland_display.as_deref().unwrap_or("-")))),
+    }
+
+    if process::is_x11_available() {
+        checks.push(Check::ok("xprop is available, active window detection enabled"));
+    } else if display.is_some() {
+        checks.push(Check::warn("xprop is not available, 'xprop' detection disabled", "Install x11-utils"));
+    } else {
+        checks.push(Check::warn("No X11 display, 'xprop' detection disabled", "Use 'ps' detection on Wayland"));
+    }
+
+    if wayland_display.is_some() {
+        if layer_shell::is_preloaded() {
+            checks.push(Check::ok("gtk4-layer-shell is preloaded, Overlay style enabled"));
+        } else {
+            checks.push(Check::warn("gtk4-layer-shell is not preloaded, Overlay style falls back to a borderless window",
+                "Install gtk4-layer-shell and start HotKeys with LD_PRELOAD=libgtk4-layer-shell.so.0"));
+        }
+    }
+
+    if gtk4::init().is_ok() {
+        match gtk4::gdk::Display::default() {
+            Some(display) if display.is_composited() => checks.push(Check::ok("Display supports compositing, transparency enabled")),
+            Some(_) => checks.push(Check::warn("Display does not support compositing", "Board opacity will be ignored, enable a compositor")),
+            None => checks.push(Check::fail("No default GTK display", "Run HotKeys from within a graphical session")),
+        }
+    } else {
+        checks.push(Check::fail("GTK initialization failed", "Run HotKeys from within a graphical session"));
+    }
+
+    checks
+}
+
+fn check_instance() -> Vec<Check> {
+    match instance::InstanceServer::bind() {
+        Ok(Some(_)) => vec![Check::ok("No other HotKeys instance running")],
+        Ok(None) => vec![Check::warn("Another HotKeys instance is running", "New invocations are handed over to it")],
+        Err(e) => vec![Check::fail(format!("Instance socket unavailable: {}", e), "Check $XDG_RUNTIME_DIR permissions")],
+    }
+}
@@ -0,0 +1,72 @@
+// Settings editing (`hotkeys edit`).
+// Opens settings.json in $VISUAL/$EDITOR and validates it after every edit until it is valid
+
+use crate::{app::config, core::Resources};
+use anyhow::Result;
+use std::io::{BufRead, Write};
+use std::process::Command;
+
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Editor program and arguments, $VISUAL takes precedence over $EDITOR (e.g. "code --wait")
+pub fn editor_command(visual: Option<String>, editor: Option<String>) -> Vec<String> {
+    visual.into_iter()
+        .chain(editor)
+        .map(|command| command.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .find(|command| !command.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_EDITOR.to_string()])
+}
+
+fn ask_edit_again() -> Result<bool> {
+    print!("Edit again? [Y/n] ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(!answer.trim().eq_ignore_ascii_case("n"))
+}
+
+pub fn edit_settings(resources: &Resources) -> Result<()> {
+    let settings_path = resources.settings_file()
+        .ok_or_else(|| anyhow::anyhow!("Settings file not found"))?;
+    let command = editor_command(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok());
+
+    loop {
+        log::info!("Editing {:?} with {}", settings_path, command.join(" "));
+        let status = Command::new(&command[0])
+            .args(&command[1..])
+            .arg(&settings_path)
+            .status()
+            .map_err(|e| anyhow::anyhow!("Failed to start editor '{}': {}", command[0], e))?;
+
+        if !status.success() {
+            anyhow::bail!("Editor '{}' exited with {}", command[0], status);
+        }
+
+        match config::load_settings(resources) {
+            Ok(_) => {
+                println!("Settings are valid: {:?}", settings_path);
+                return Ok(());
+            },
+            Err(e) => {
+                println!("Settings are invalid: {}", e);
+                if !ask_edit_again()? {
+                    anyhow::bail!("Settings left invalid: {:?}", settings_path);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_command() {
+        assert_eq!(editor_command(Some("code --wait".into()), Some("nano".into())), vec!["code", "--wait"]);
+        assert_eq!(editor_command(Some(" ".into()), Some("nano".into())), vec!["nano"]);
+        assert_eq!(editor_command(None, Some("nano".into())), vec!["nano"]);
+        assert_eq!(editor_command(None, None), vec!["vi"]);
+    }
+}
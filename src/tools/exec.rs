@@ -0,0 +1,46 @@
+// Headless action execution (`hotkeys exec`).
+// Runs actions given on the command line through the executor, without any GTK window
+
+use crate::{app::{audit::AuditLog, config::AppSettings}, core::Action, executor};
+use anyhow::Result;
+
+/// Command line options accepted by `exec`, in the order they are executed
+pub const ACTION_OPTIONS: &[&str] = &["--shortcut", "--text", "--line", "--command", "--url", "--pause"];
+
+/// Convert an action option and its value to an action
+pub fn parse_action(option: &str, value: &str) -> Result<Action, String> {
+    match option {
+        "--shortcut" => Ok(Action::Shortcut(value.to_string())),
+        "--text" => Ok(Action::Text(value.to_string())),
+        "--line" => Ok(Action::Line(value.to_string())),
+        "--command" => Ok(Action::Command(value.to_string())),
+        "--url" => Ok(Action::OpenUrl(value.to_string())),
+        "--pause" => value.parse::<u64>()
+            .map(Action::Pause)
+            .map_err(|_| format!("Invalid pause value: {}", value)),
+        _ => Err(format!("Unknown action option: {}", option)),
+    }
+}
+
+pub fn execute(actions: &[Action], settings: &AppSettings, profile: &str) -> Result<()> {
+    if actions.is_empty() {
+        anyhow::bail!("No actions given, use one or more of: {}", ACTION_OPTIONS.join(", "));
+    }
+
+    let audit = settings.audit_log().map(|path| AuditLog::new(path, profile));
+    executor::execute_actions(actions, &settings.get_profile_keyboard_layout(profile), None, None, settings.privacy(), audit.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_action() {
+        assert!(matches!(parse_action("--shortcut", "Ctrl Shift T"), Ok(Action::Shortcut(s)) if s == "Ctrl Shift T"));
+        assert!(matches!(parse_action("--line", "ls"), Ok(Action::Line(s)) if s == "ls"));
+        assert!(matches!(parse_action("--pause", "250"), Ok(Action::Pause(250))));
+        assert!(parse_action("--pause", "soon").is_err());
+        assert!(parse_action("--unknown", "x").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+// Settings formatting (`hotkeys fmt`).
+// Rewrites the settings file and its includes in canonical form, for clean hand-edited configs and stable diffs
+
+use crate::app::config::{self, AppSettings};
+use anyhow::Result;
+use std::collections::HashSet;
+
+/// Format all settings files, with `check` only report the files that are not formatted.
+/// Files whose comments or YAML anchors formatting would lose are skipped, with `force` they are
+/// formatted too and the original is kept as a "<file>.bak" copy
+pub fn format_settings(settings: &AppSettings, check: bool, force: bool) -> Result<()> {
+    let mut seen = HashSet::new();
+    let (mut unformatted, mut skipped) = (0, 0);
+
+    for (index, path) in settings.source_files().into_iter().enumerate() {
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+
+        let formatted = match index {
+            0 => config::normalize_settings(&path)?,
+            _ => config::normalize_components(&path)?,
+        };
+        if std::fs::read_to_string(&path)? == formatted {
+            continue;
+        }
+
+        unformatted += 1;
+        let losses = config::rewrite_losses(&path);
+        if check && losses.is_empty() {
+            println!("Not formatted: {:?}", path);
+        } else if check {
+            println!("Not formatted: {:?} (formatting loses its {})", path, losses.join(" and "));
+        } else if !losses.is_empty() && !force {
+            skipped += 1;
+            println!("Skipped: {:?}, formatting loses its {}", path, losses.join(" and "));
+        } else {
+            let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("json");
+            let backup_path = path.with_extension(format!("{}.bak", extension));
+            if !losses.is_empty() {
+                std::fs::copy(&path, &backup_path)
+                    .map_err(|e| anyhow::anyhow!("Failed to back up {:?}: {}", path, e))?;
+            }
+            std::fs::write(&path, formatted)
+                .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", path, e))?;
+            log::info!("Formatted {:?}", path);
+            match losses.is_empty() {
+                true => println!("Formatted: {:?}", path),
+                false => println!("Formatted: {:?}, without its {} (backup: {:?})", path, losses.join(" and "), backup_path),
+            }
+        }
+    }
+
+    if check && unformatted > 0 {
+        anyhow::bail!("{} settings file(s) are not formatted", unformatted);
+    }
+    if skipped > 0 {
+        println!("{} settings file(s) skipped, run with --force to format them anyway (a .bak copy of each is kept)", skipped);
+    } else if unformatted == 0 {
+        println!("All settings files are formatted");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::tests::{json, test_settings};
+    use crate::app::migration;
+    use crate::core::Resources;
+
+    #[test]
+    fn test_format_settings_losses() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-fmt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("settings.json"), serde_json::to_string(&test_settings(json!({
+            "version": migration::SETTINGS_VERSION,
+            "includes": ["extra.yaml"],
+            "boards": [{ "name": "code", "base_pads": "code" }],
+            "padsets": [{ "name": "code", "items": [] }],
+            "profiles": [{ "name": "default", "boards": ["code"], "default": "code" }]
+        }))).unwrap()).unwrap();
+        let extra = dir.join("extra.yaml");
+        let text = "padsets:\n    - name:   extra   # hand-written\n";
+        std::fs::write(&extra, text).unwrap();
+        let settings = config::load_settings_unvalidated(&Resources::new(vec![dir.clone()])).unwrap();
+
+        assert!(format_settings(&settings, true, false).is_err());
+        format_settings(&settings, false, false).unwrap();
+        assert_eq!(std::fs::read_to_string(&extra).unwrap(), text);
+        assert!(!dir.join("extra.yaml.bak").exists());
+
+        format_settings(&settings, false, true).unwrap();
+        assert!(!std::fs::read_to_string(&extra).unwrap().contains("hand-written"));
+        assert_eq!(std::fs::read_to_string(dir.join("extra.yaml.bak")).unwrap(), text);
+        format_settings(&settings, true, false).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,338 @@
+// Snippet import (`hotkeys import espanso|autokey <path>`).
+// Converts espanso match files and AutoKey phrases into boards and padsets of an include file, printed to stdout
+
+use crate::app::config::{BoardConfig, ConfigFormat, PadConfig, PadSetConfig};
+use crate::core::Action;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Tools `import` converts from
+pub const IMPORT_SOURCES: &[&str] = &["espanso", "autokey"];
+
+/// Pads of a board, the last one leads to the next page when the matches do not fit
+const PAGE_PADS: usize = 9;
+/// Characters of the replacement shown as pad text
+const PREVIEW_CHARS: usize = 40;
+/// Cursor position marker of espanso replacements, HotKeys leaves the cursor at the end
+const CURSOR_HINT: &str = "$|$";
+/// Cursor position macro of AutoKey phrases
+const AUTOKEY_CURSOR: &str = "<cursor>";
+/// AutoKey directory holding the top level folders, which are named without it
+const AUTOKEY_DATA_DIR: &str = "data";
+/// AutoKey phrase macros that need AutoKey to expand them
+const AUTOKEY_MACROS: &[&str] = &["<script", "<file", "<system", "<date", "<clipboard", "<selection"];
+
+/// Pad typing the text (`Line` when it ends with a newline), showing its first line
+fn text_pad(header: String, replace: &str) -> PadConfig {
+    let action = match replace.strip_suffix('\n') {
+        Some(line) => Action::Line(line.to_string()),
+        None => Action::Text(replace.to_string()),
+    };
+    let first_line = replace.lines().next().unwrap_or_default();
+    let mut text: String = first_line.chars().take(PREVIEW_CHARS).collect();
+    if text.len() < first_line.len() || first_line.len() < replace.trim_end().len() {
+        text.push('…');
+    }
+
+    PadConfig {
+        header,
+        text,
+        actions: vec![action],
+        ..Default::default()
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct MatchFile {
+    #[serde(default)]
+    matches: Vec<EspansoMatch>,
+}
+
+/// Espanso match, only plain text replacements are imported
+#[derive(Deserialize, Debug, Default)]
+struct EspansoMatch {
+    trigger: Option<String>,
+    #[serde(default)]
+    triggers: Vec<String>,
+    label: Option<String>,
+    replace: Option<String>,
+    #[serde(default)]
+    vars: Vec<serde_yaml::Value>,
+}
+
+impl EspansoMatch {
+    fn name(&self) -> String {
+        self.label.clone()
+            .or_else(|| self.trigger.clone())
+            .or_else(|| self.triggers.first().cloned())
+            .unwrap_or_default()
+    }
+
+    /// Pad typing the replacement, or why the match cannot be imported
+    fn to_pad(&self) -> Result<PadConfig, &'static str> {
+        let replace = self.replace.as_deref().ok_or("only text replacements are supported")?;
+        if !self.vars.is_empty() || replace.contains("{{") {
+            return Err("variables are not supported");
+        }
+
+        Ok(text_pad(self.name(), &replace.replace(CURSOR_HINT, "")))
+    }
+}
+
+/// AutoKey item metadata, stored next to the item as ".<name>.json"
+#[derive(Deserialize, Debug, Default)]
+struct AutoKeyItem {
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+struct Include {
+    boards: Vec<BoardConfig>,
+    padsets: Vec<PadSetConfig>,
+}
+
+/// Match files of an espanso match directory (in name order), or the given file
+fn match_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| matches!(ConfigFormat::from_path(file), ConfigFormat::Yaml))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn board_config(name: &str, title: String) -> BoardConfig {
+    BoardConfig {
+        kind: Default::default(),
+        title: Some(title),
+        name: name.to_string(),
+        icon: None,
+        color_scheme: None,
+        color_scheme_dark: None,
+        color_scheme_light: None,
+        text_style: None,
+        background_image: None,
+        detection: Default::default(),
+        base_pads: Some(name.to_string()),
+        modifier_pads: Default::default(),
+        double_pads: None,
+        refresh: 0,
+        only_on_hosts: Vec::new(),
+        except_hosts: Vec::new(),
+    }
+}
+
+/// Boards and padsets of one match file or folder, named "<source>-<name>" with a suffix for further pages
+fn convert(source: &str, stem: &str, pads: Vec<PadConfig>, include: &mut Include) {
+    let per_page = if pads.len() > PAGE_PADS { PAGE_PADS - 1 } else { PAGE_PADS };
+    let pages: Vec<&[PadConfig]> = pads.chunks(per_page).collect();
+    let page_name = |page: usize| match page {
+        0 => format!("{}-{}", source, stem),
+        _ => format!("{}-{}-{}", source, stem, page + 1),
+    };
+
+    for (page, page_pads) in pages.iter().enumerate() {
+        let name = page_name(page);
+        let mut items = page_pads.to_vec();
+        if pages.len() > 1 {
+            items.resize_with(PAGE_PADS - 1, PadConfig::default);
+            items.push(PadConfig {
+                header: "More".to_string(),
+                text: format!("{}/{}", page + 1, pages.len()),
+                board: Some(page_name((page + 1) % pages.len())),
+                ..Default::default()
+            });
+        }
+        let title = match pages.len() {
+            1 => format!("{} {}", source, stem),
+            _ => format!("{} {} ({}/{})", source, stem, page + 1, pages.len()),
+        };
+        include.boards.push(board_config(&name, title));
+        include.padsets.push(PadSetConfig { name, items, ..Default::default() });
+    }
+}
+
+fn import_espanso(path: &Path) -> Result<Include> {
+    let mut include = Include::default();
+    for file in match_files(path)? {
+        let contents = std::fs::read_to_string(&file)
+            .map_err(|e| anyhow::anyhow!("Failed to read {:?}: {}", file, e))?;
+        let match_file: MatchFile = serde_yaml::from_str::<Option<MatchFile>>(&contents)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {:?}: {}", file, e))?
+            .unwrap_or_default();
+
+        let mut pads = Vec::new();
+        for espanso_match in &match_file.matches {
+            match espanso_match.to_pad() {
+                Ok(pad) => pads.push(pad),
+                Err(reason) => eprintln!("Skipped '{}' of {:?}: {}", espanso_match.name(), file, reason),
+            }
+        }
+        if pads.is_empty() {
+            continue;
+        }
+        let stem = file.file_stem().and_then(|stem| stem.to_str()).unwrap_or("matches");
+        convert("espanso", stem, pads, &mut include);
+    }
+    Ok(include)
+}
+
+/// Pad of an AutoKey phrase (.txt) or script (.py), scripts are run through `autokey-run`
+fn autokey_pad(file: &Path) -> Result<Option<PadConfig>, String> {
+    let (Some(stem), Some(extension)) = (file.file_stem().and_then(|s| s.to_str()), file.extension().and_then(|e| e.to_str())) else {
+        return Ok(None);
+    };
+    if stem.starts_with('.') || !matches!(extension, "txt" | "py") {
+        return Ok(None);
+    }
+    let metadata = file.with_file_name(format!(".{}.json", stem));
+    let description = std::fs::read_to_string(&metadata).ok()
+        .and_then(|json| serde_json::from_str::<AutoKeyItem>(&json).ok())
+        .map(|item| item.description)
+        .filter(|description| !description.is_empty())
+        .unwrap_or_else(|| stem.to_string());
+
+    if extension == "py" {
+        return Ok(Some(PadConfig {
+            header: description.clone(),
+            text: "script".to_string(),
+            actions: vec![Action::Command(format!("autokey-run -s '{}'", description.replace('\'', r"'\''")))],
+            ..Default::default()
+        }));
+    }
+
+    let phrase = std::fs::read_to_string(file).map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+    if AUTOKEY_MACROS.iter().any(|autokey_macro| phrase.contains(autokey_macro)) {
+        return Err(format!("Skipped '{}' of {:?}: macros are not supported", description, file));
+    }
+    Ok(Some(text_pad(description, &phrase.replace(AUTOKEY_CURSOR, ""))))
+}
+
+/// Boards of an AutoKey folder and its subfolders, named "autokey-<folder>" ("autokey-<folder>-<subfolder>")
+fn import_autokey_folder(folder: &Path, name: &str, include: &mut Include) -> Result<()> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(folder)
+        .map_err(|e| anyhow::anyhow!("Failed to read {:?}: {}", folder, e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+
+    let mut pads = Vec::new();
+    for entry in entries.iter().filter(|entry| entry.is_file()) {
+        match autokey_pad(entry) {
+            Ok(pad) => pads.extend(pad),
+            Err(message) => eprintln!("{}", message),
+        }
+    }
+    if !pads.is_empty() {
+        convert("autokey", name, pads, include);
+    }
+
+    for subfolder in entries.iter().filter(|entry| entry.is_dir()) {
+        let Some(subfolder_name) = subfolder.file_name().and_then(|n| n.to_str()).filter(|n| !n.starts_with('.')) else {
+            continue;
+        };
+        let subfolder_name = match name {
+            AUTOKEY_DATA_DIR => subfolder_name.to_string(),
+            _ => format!("{}-{}", name, subfolder_name),
+        };
+        import_autokey_folder(subfolder, &subfolder_name, include)?;
+    }
+    Ok(())
+}
+
+/// AutoKey data directory (every folder becomes a board) or a single folder
+fn import_autokey(path: &Path) -> Result<Include> {
+    let mut include = Include::default();
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or(AUTOKEY_DATA_DIR);
+    import_autokey_folder(path, name, &mut include)?;
+    Ok(include)
+}
+
+/// Print the include file converted from the snippets of another tool
+pub fn import(source: &str, path: &str) -> Result<()> {
+    let include = match source {
+        "espanso" => import_espanso(Path::new(path))?,
+        "autokey" => import_autokey(Path::new(path))?,
+        _ => anyhow::bail!("Unknown import source '{}', supported: {}", source, IMPORT_SOURCES.join(", ")),
+    };
+    if include.boards.is_empty() {
+        anyhow::bail!("No text replacements found in {}", path);
+    }
+    print!("{}", ConfigFormat::Json.to_string(&include)?);
+    eprintln!("Imported {} boards, save the output as an include file and add it to \"includes\"", include.boards.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_espanso() {
+        let match_file: MatchFile = serde_yaml::from_str(r#"
+matches:
+  - trigger: ":br"
+    replace: "Best Regards,\nJon Snow"
+  - triggers: [":ls", ":list"]
+    label: "List files"
+    replace: "ls -la $|$\n"
+  - trigger: ":date"
+    replace: "{{mydate}}"
+    vars:
+      - name: mydate
+        type: date
+  - trigger: ":cat"
+    image_path: "~/cat.png"
+"#).unwrap();
+        let pads: Vec<_> = match_file.matches.iter().map(EspansoMatch::to_pad).collect();
+        let br = pads[0].as_ref().unwrap();
+        assert_eq!(br.header, ":br");
+        assert_eq!(br.text, "Best Regards,…");
+        assert!(matches!(&br.actions[..], [Action::Text(text)] if text == "Best Regards,\nJon Snow"));
+        let ls = pads[1].as_ref().unwrap();
+        assert_eq!(ls.header, "List files");
+        assert!(matches!(&ls.actions[..], [Action::Line(text)] if text == "ls -la "));
+        assert!(pads[2].is_err() && pads[3].is_err());
+
+        let mut include = Include::default();
+        let pad = PadConfig { header: ":x".to_string(), ..Default::default() };
+        convert("espanso", "base", vec![pad; 10], &mut include);
+        let names: Vec<_> = include.boards.iter().map(|board| board.name.as_str()).collect();
+        assert_eq!(names, vec!["espanso-base", "espanso-base-2"]);
+        assert_eq!(include.padsets[0].items.len(), 9);
+        assert_eq!(include.padsets[0].items[8].board.as_deref(), Some("espanso-base-2"));
+        assert_eq!(include.padsets[1].items[2].header, "");
+        assert_eq!(include.padsets[1].items[8].board.as_deref(), Some("espanso-base"));
+    }
+
+    #[test]
+    fn test_import_autokey() {
+        let data = std::env::temp_dir().join(format!("hotkeys-autokey-{}", std::process::id())).join("data");
+        let folder = data.join("My Phrases");
+        std::fs::create_dir_all(folder.join("Git")).unwrap();
+        std::fs::write(folder.join("Address.txt"), "Main Street 1<cursor>").unwrap();
+        std::fs::write(folder.join(".Address.json"), r#"{"type": "phrase", "description": "Home address"}"#).unwrap();
+        std::fs::write(folder.join("Today.txt"), "<script name='date' args=''>").unwrap();
+        std::fs::write(folder.join("Git").join("Status.txt"), "git status\n").unwrap();
+        std::fs::write(data.join("Notify.py"), "system.exec_command('notify-send hi')").unwrap();
+        std::fs::write(data.join(".Notify.json"), r#"{"type": "script", "description": "Say 'hi'"}"#).unwrap();
+
+        let include = import_autokey(&data).unwrap();
+        std::fs::remove_dir_all(data.parent().unwrap()).unwrap();
+
+        let names: Vec<_> = include.boards.iter().map(|board| board.name.as_str()).collect();
+        assert_eq!(names, vec!["autokey-data", "autokey-My Phrases", "autokey-My Phrases-Git"]);
+        let script = &include.padsets[0].items[0];
+        assert!(matches!(&script.actions[..], [Action::Command(command)] if command == r#"autokey-run -s 'Say '\''hi'\'''"#));
+        let phrases = &include.padsets[1].items;
+        assert_eq!(phrases.len(), 1);
+        assert_eq!(phrases[0].header, "Home address");
+        assert!(matches!(&phrases[0].actions[..], [Action::Text(text)] if text == "Main Street 1"));
+        assert!(matches!(&include.padsets[2].items[0].actions[..], [Action::Line(text)] if text == "git status"));
+    }
+}
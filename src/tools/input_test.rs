@@ -21,7 +21,7 @@ pub fn test_direct_uinput(keyboard_layout: KeyboardLayout) -> Result<()> {
 
     let script1 = for_shortcut("Ctrl T".to_owned());
     let script2 = for_pause(500);
-    let script3 = for_line("https://www.example.com".to_owned(), keyboard_layout.mappings.clone());
+    let script3 = for_line("https://www.example.com".to_owned(), &keyboard_layout.character_keys());
 
     let combined_script = InputScript{
         steps: script1.steps
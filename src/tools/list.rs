@@ -0,0 +1,142 @@
+// Machine-readable configuration inventory (`hotkeys list`).
+// Prints profiles, boards with their pads, and padsets as JSON for external tooling
+
+use crate::app::config::{AppSettings, BoardConfig, BoardKind, Detection, PadConfig, Profile};
+use crate::core::Action;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize)]
+struct Inventory<'a> {
+    profiles: &'a [Profile],
+    boards: Vec<BoardEntry<'a>>,
+    padsets: Vec<PadSetEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct BoardEntry<'a> {
+    name: &'a str,
+    title: &'a str,
+    kind: &'a BoardKind,
+    detection: &'a Detection,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<&'a str>,
+    /// Pads per modifier combination, "" holds the base pads
+    pads: BTreeMap<&'a str, Vec<PadEntry<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    double_pads: Option<Vec<PadEntry<'a>>>,
+}
+
+#[derive(Serialize)]
+struct PadSetEntry<'a> {
+    name: &'a str,
+    pads: Vec<PadEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct PadEntry<'a> {
+    id: usize,
+    header: &'a str,
+    /// Text and actions of sensitive pads are left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actions: Option<&'a [Action]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    long_actions: Option<&'a [Action]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    board: Option<&'a str>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    sensitive: bool,
+}
+
+fn pad_entries<'a>(settings: &'a AppSettings, padset_name: &str) -> Vec<PadEntry<'a>> {
+    settings.get_padset_config(padset_name)
+        .map(|padset| padset.items.iter().enumerate().map(|(index, pad)| pad_entry(index, pad)).collect())
+        .unwrap_or_default()
+}
+
+fn pad_entry(index: usize, pad: &PadConfig) -> PadEntry<'_> {
+    PadEntry {
+        id: index + 1,
+        header: &pad.header,
+        text: (!pad.sensitive).then_some(pad.text.as_str()),
+        actions: (!pad.sensitive).then_some(pad.actions.as_slice()),
+        long_actions: (!pad.sensitive && !pad.long_actions.is_empty()).then_some(pad.long_actions.as_slice()),
+        board: pad.board.as_deref(),
+        sensitive: pad.sensitive,
+    }
+}
+
+fn board_entry<'a>(settings: &'a AppSettings, board: &'a BoardConfig) -> BoardEntry<'a> {
+    let mut pads = BTreeMap::new();
+    if let Some(base_pads) = &board.base_pads {
+        pads.insert("", pad_entries(settings, base_pads));
+    }
+    for (modifier, padset_name) in &board.modifier_pads {
+        pads.insert(modifier.as_str(), pad_entries(settings, padset_name));
+    }
+
+    BoardEntry {
+        name: &board.name,
+        title: board.title.as_deref().unwrap_or(&board.name),
+        kind: &board.kind,
+        detection: &board.detection,
+        icon: board.icon.as_deref(),
+        pads,
+        double_pads: board.double_pads.as_deref().map(|padset_name| pad_entries(settings, padset_name)),
+    }
+}
+
+/// Build the inventory JSON document
+pub fn inventory(settings: &AppSettings) -> Result<String> {
+    let inventory = Inventory {
+        profiles: &settings.profiles,
+        boards: settings.board_configs.iter().map(|board| board_entry(settings, board)).collect(),
+        padsets: settings.padset_configs.iter()
+            .map(|padset| PadSetEntry {
+                name: &padset.name,
+                pads: padset.items.iter().enumerate().map(|(index, pad)| pad_entry(index, pad)).collect(),
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_string_pretty(&inventory)?)
+}
+
+pub fn print_inventory(settings: &AppSettings) -> Result<()> {
+    println!("{}", inventory(settings)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::tests::{json, test_settings};
+
+    #[test]
+    fn test_inventory() {
+        let settings = test_settings(json!({
+            "boards": [{ "name": "code", "detection": { "ps": "code" }, "base_pads": "code", "modifier_pads": { "Ctrl": "code/ctrl" } }],
+            "padsets": [
+                { "name": "code", "items": [{ "header": "F12", "actions": [{ "Shortcut": "F12" }] }] },
+                { "name": "code/ctrl", "items": [{}, { "text": "Next", "board": "code" }, { "text": "KEY-1234", "sensitive": true, "actions": [{ "Text": "KEY-1234" }] }] }
+            ],
+            "profiles": [{ "name": "default", "boards": ["code"], "default": "code" }]
+        }));
+
+        let json: serde_json::Value = serde_json::from_str(&inventory(&settings).unwrap()).unwrap();
+        let board = &json["boards"][0];
+        assert_eq!(board["title"], "code");
+        assert_eq!(board["detection"]["ps"], "code");
+        assert_eq!(board["pads"][""][0]["actions"][0]["Shortcut"], "F12");
+        assert_eq!(board["pads"]["Ctrl"][1]["id"], 2);
+        assert_eq!(board["pads"]["Ctrl"][1]["board"], "code");
+        assert_eq!(board["pads"]["Ctrl"][1]["text"], "Next");
+        let sensitive = board["pads"]["Ctrl"][2].as_object().unwrap();
+        assert_eq!(sensitive["sensitive"], true);
+        assert!(!sensitive.contains_key("text") && !sensitive.contains_key("actions"));
+        assert_eq!(json["padsets"].as_array().unwrap().len(), 2);
+        assert_eq!(json["profiles"][0]["default"], "code");
+    }
+}
@@ -0,0 +1,40 @@
+// Settings migration (`hotkeys migrate`).
+// Upgrades the settings file in place to the current version, keeping a backup of the original file
+
+use crate::{app::{config::{self, ConfigFormat}, migration}, core::Resources};
+use anyhow::Result;
+
+pub fn migrate_settings(resources: &Resources) -> Result<()> {
+    let settings_path = resources.settings_file()
+        .ok_or_else(|| anyhow::anyhow!("Settings file not found"))?;
+
+    let contents = std::fs::read_to_string(&settings_path)?;
+    let format = ConfigFormat::from_path(&settings_path);
+    let mut document: serde_json::Value = format.parse(&contents)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {:?}: {}", settings_path, e))?;
+
+    let version = migration::settings_version(&document);
+    let transformations = migration::migrate(&mut document)?;
+    if transformations.is_empty() {
+        println!("Settings {:?} are up to date (version {})", settings_path, version);
+        return Ok(());
+    }
+
+    for transformation in &transformations {
+        log::info!("Migrating {:?}, {}", settings_path, transformation);
+        println!("{}", transformation);
+    }
+
+    let extension = settings_path.extension().and_then(|ext| ext.to_str()).unwrap_or("json");
+    let backup_path = settings_path.with_extension(format!("{}.v{}.bak", extension, version));
+    std::fs::copy(&settings_path, &backup_path)
+        .map_err(|e| anyhow::anyhow!("Failed to back up {:?}: {}", settings_path, e))?;
+    std::fs::write(&settings_path, format.to_string(&document)?)
+        .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", settings_path, e))?;
+
+    println!("Migrated {:?} to version {} (backup: {:?})", settings_path, migration::SETTINGS_VERSION, backup_path);
+
+    config::load_settings(resources)
+        .map_err(|e| anyhow::anyhow!("Migrated settings are invalid: {}", e))?;
+    Ok(())
+}
@@ -1 +1,18 @@
-pub mod input_test;
\ No newline at end of file
+pub mod input_test;
+pub mod exec;
+pub mod run;
+pub mod list;
+pub mod doctor;
+pub mod detect;
+pub mod record;
+pub mod edit;
+pub mod migrate;
+pub mod batch;
+pub mod fmt;
+pub mod import;
+pub mod cheatsheet;
+pub mod render;
+pub mod split;
+pub mod backup;
+pub mod settings;
+pub mod validate;
\ No newline at end of file
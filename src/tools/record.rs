@@ -0,0 +1,123 @@
+// Shortcut recorder (`hotkeys record`).
+// Listens to the physical keyboards and prints pressed key combinations in Shortcut syntax
+
+use crate::{app::config::AppSettings, input::{evdev, keys::*}};
+use anyhow::Result;
+use std::sync::mpsc;
+
+/// Modifiers in the order they are written, (left key, right key, title)
+const MODIFIERS: [(u16, u16, &str); 4] = [
+    (VK_LCTRL.linux_key, VK_RCTRL.linux_key, "Ctrl"),
+    (VK_LSHIFT.linux_key, VK_RSHIFT.linux_key, "Shift"),
+    (VK_LALT.linux_key, VK_RALT.linux_key, "Alt"),
+    (VK_LWIN.linux_key, VK_RWIN.linux_key, "Lwin"),
+];
+
+fn modifier_index(code: u16) -> Option<usize> {
+    MODIFIERS.iter().position(|(left, right, _)| code == *left || code == *right)
+}
+
+/// Key title as written in shortcuts, e.g. "p" -> "P", "pgdown" -> "Pgdown"
+fn key_title(code: u16) -> Option<String> {
+    let vkey = find_vkey_by_linux_key(code).ok()?;
+    let mut chars = vkey.title.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Turns key events into Shortcut strings like "Ctrl Shift P"
+#[derive(Debug, Default)]
+pub struct ShortcutRecorder {
+    held: [bool; 4],
+    /// Held modifiers were already used in a printed combination
+    used: bool,
+}
+
+impl ShortcutRecorder {
+    /// Feed a key event (value: 0 = up, 1 = down, 2 = repeat).
+    /// Returns the combination completed by this event, if any.
+    /// Modifiers pressed and released on their own are reported as well.
+    pub fn on_key(&mut self, code: u16, value: i32) -> Option<String> {
+        match (modifier_index(code), value) {
+            (Some(index), 1) => {
+                self.held[index] = true;
+                self.used = false;
+                None
+            },
+            (Some(index), 0) => {
+                let combination = (!self.used).then(|| self.combination(None)).flatten();
+                self.held[index] = false;
+                self.used = true;
+                combination
+            },
+            (None, 1) => {
+                self.used = true;
+                self.combination(key_title(code))
+            },
+            _ => None,
+        }
+    }
+
+    fn combination(&self, key: Option<String>) -> Option<String> {
+        let parts: Vec<String> = MODIFIERS.iter()
+            .zip(self.held)
+            .filter(|(_, held)| *held)
+            .map(|((_, _, title), _)| title.to_string())
+            .chain(key)
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+}
+
+pub fn record_shortcuts(settings: &AppSettings) -> Result<()> {
+    let filters = settings.listener().as_ref().map(|l| l.devices.clone()).unwrap_or_default();
+    let devices = evdev::find_keyboards(&filters, &[VK_A.linux_key, VK_SPACE.linux_key])?;
+    if devices.is_empty() {
+        anyhow::bail!("No keyboards found. Make sure you are a member of the 'input' group");
+    }
+
+    for device in &devices {
+        println!("Reading from {} ({:?})", device.name(), device.path());
+    }
+    println!("Press key combinations to print them in Shortcut syntax, Ctrl+C to quit");
+    println!();
+
+    let (sender, receiver) = mpsc::channel();
+    evdev::spawn_readers(devices, move |code, value| {
+        let _ = sender.send((code, value));
+    });
+
+    let mut recorder = ShortcutRecorder::default();
+    for (code, value) in receiver {
+        if let Some(shortcut) = recorder.on_key(code, value) {
+            println!("{}", shortcut);
+        }
+    }
+
+    anyhow::bail!("All keyboards stopped sending events")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(events: &[(u16, i32)]) -> Vec<String> {
+        let mut recorder = ShortcutRecorder::default();
+        events.iter().filter_map(|(code, value)| recorder.on_key(*code, *value)).collect()
+    }
+
+    #[test]
+    fn test_record_shortcuts() {
+        assert_eq!(record(&[(VK_RSHIFT.linux_key, 1), (VK_LCTRL.linux_key, 1), (VK_P.linux_key, 1), (VK_P.linux_key, 0), (VK_LCTRL.linux_key, 0), (VK_RSHIFT.linux_key, 0)]),
+            vec!["Ctrl Shift P"]);
+        assert_eq!(record(&[(VK_F5.linux_key, 1), (VK_F5.linux_key, 2), (VK_F5.linux_key, 0)]), vec!["F5"]);
+        assert_eq!(record(&[(VK_LCTRL.linux_key, 1), (VK_K.linux_key, 1), (VK_K.linux_key, 0), (VK_B.linux_key, 1), (VK_B.linux_key, 0), (VK_LCTRL.linux_key, 0)]),
+            vec!["Ctrl K", "Ctrl B"]);
+        assert_eq!(record(&[(VK_LALT.linux_key, 1), (VK_PGDOWN.linux_key, 1)]), vec!["Alt Pgdown"]);
+    }
+
+    #[test]
+    fn test_record_modifiers_alone() {
+        assert_eq!(record(&[(VK_LWIN.linux_key, 1), (VK_LWIN.linux_key, 0)]), vec!["Lwin"]);
+        assert_eq!(record(&[(VK_LCTRL.linux_key, 1), (VK_LSHIFT.linux_key, 1), (VK_LSHIFT.linux_key, 0), (VK_LCTRL.linux_key, 0)]), vec!["Ctrl Shift"]);
+    }
+}
@@ -0,0 +1,35 @@
+// Board screenshots (`hotkeys render <board> --out board.png`).
+// Draws a configured board with the board window renderer into a PNG file, without opening a window
+
+use crate::{app::{config::AppSettings, HotKeysApp}, core::Resources};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// PNG file of the board when no --out is given, e.g. "code.png"
+pub fn default_output(board: &str) -> PathBuf {
+    PathBuf::from(format!("{}.png", board.replace('/', "_")))
+}
+
+pub fn render_board(resources: Resources, profile: Option<String>, settings: AppSettings, board: &str, out: Option<&str>) -> Result<()> {
+    // Icon theme icons are only found with a display, boards render without them otherwise
+    if gtk4::init().is_err() {
+        log::warn!("No display available, icon theme icons are left out");
+    }
+
+    let path = out.map(PathBuf::from).unwrap_or_else(|| default_output(board));
+    let app = HotKeysApp::new(resources, profile, settings)?;
+    app.render_board(board, &path)?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_output() {
+        assert_eq!(default_output("code"), PathBuf::from("code.png"));
+        assert_eq!(default_output("ides/code"), PathBuf::from("ides_code.png"));
+    }
+}
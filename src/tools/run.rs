@@ -0,0 +1,44 @@
+// Headless pad execution (`hotkeys run <board> <pad>`).
+// Resolves a pad of a configured board and executes its actions without any GTK window
+
+use crate::{app::{config::AppSettings, HotKeysApp}, core::{ModifierState, Resources}};
+use anyhow::Result;
+use std::str::FromStr;
+
+/// Parse pad references like "5" or "Ctrl+Shift+5" into pad id and modifiers
+pub fn parse_pad(text: &str) -> Result<(u8, ModifierState), String> {
+    let (modifiers, pad) = match text.rsplit_once('+') {
+        Some((modifiers, pad)) => (modifiers, pad),
+        None => ("", text),
+    };
+
+    let pad_id = pad.trim().parse::<u8>()
+        .ok()
+        .filter(|id| (1..=9).contains(id))
+        .ok_or_else(|| format!("Invalid pad '{}', expected a number from 1 to 9", pad))?;
+
+    Ok((pad_id, ModifierState::from_str(modifiers)?))
+}
+
+pub fn run_pad(resources: Resources, profile: Option<String>, settings: AppSettings, board: &str, pad: &str) -> Result<()> {
+    let (pad_id, modifier_state) = parse_pad(pad).map_err(anyhow::Error::msg)?;
+
+    let app = HotKeysApp::new(resources, profile, settings)?;
+    app.run_pad(board, pad_id, modifier_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pad() {
+        assert_eq!(parse_pad("5"), Ok((5, ModifierState::default())));
+        assert_eq!(parse_pad("Ctrl+Shift+9"), Ok((9, ModifierState { ctrl: true, shift: true, ..Default::default() })));
+        assert_eq!(parse_pad("super+1"), Ok((1, ModifierState { super_key: true, ..Default::default() })));
+        assert!(parse_pad("0").is_err());
+        assert!(parse_pad("10").is_err());
+        assert!(parse_pad("Hyper+1").is_err());
+        assert!(parse_pad("Ctrl").is_err());
+    }
+}
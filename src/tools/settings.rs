@@ -0,0 +1,22 @@
+// Graphical settings editor (`hotkeys settings`).
+// Opens the settings window, settings with validation errors are opened too so they can be fixed there
+
+use crate::{app::config, core::Resources, windows::settings_window};
+use anyhow::Result;
+use gtk4::prelude::*;
+
+/// Separate from the board application, so the editor can stay open while boards are shown
+const APPLICATION_ID: &str = "com.github.ivicakukic.hotkeys.settings";
+
+pub fn edit_settings(resources: Resources) -> Result<()> {
+    let settings = config::load_settings_unvalidated(&resources)?;
+
+    let app = gtk4::Application::builder()
+        .application_id(APPLICATION_ID)
+        .build();
+    app.connect_activate(move |app| settings_window::show(app, resources.clone(), settings.clone()));
+
+    let empty_args: Vec<String> = vec![];
+    app.run_with_args(&empty_args);
+    Ok(())
+}
@@ -0,0 +1,173 @@
+// Settings split (`hotkeys split`).
+// Moves the boards and padsets of the settings file into one components file per board (with the
+// padsets only it uses) and per shared padset, and includes them, for diffable, git-friendly configs
+
+use crate::{app::config::{self, ConfigFormat}, core::Resources};
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+const BOARDS_DIR: &str = "boards";
+const PADSETS_DIR: &str = "padsets";
+
+/// File name for a board or padset name, e.g. "code/ctrl" becomes "code_ctrl.json"
+fn file_name(name: &str, extension: &str) -> String {
+    let stem: String = name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}.{}", stem, extension)
+}
+
+fn name_of(item: &Value) -> &str {
+    item.get("name").and_then(Value::as_str).unwrap_or_default()
+}
+
+/// Padsets a board shows, its base pads, modifier pads and double tap pads
+fn board_padsets(board: &Value) -> Vec<&str> {
+    let base_pads = board.get("base_pads").and_then(Value::as_str);
+    let modifier_pads = board.get("modifier_pads").and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|pads| pads.values().filter_map(Value::as_str));
+    let double_pads = board.get("double_pads").and_then(Value::as_str);
+    base_pads.into_iter().chain(modifier_pads).chain(double_pads).collect()
+}
+
+/// Take the boards and padsets out of the settings document, returning the components files
+/// (relative to the settings directory) in include order
+fn split_document(document: &mut Value, extension: &str) -> Result<Vec<(String, Value)>> {
+    let settings = document.as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("Settings are not an object"))?;
+    let boards = match settings.remove("boards") { Some(Value::Array(boards)) => boards, _ => Vec::new() };
+    let mut padsets = match settings.remove("padsets") { Some(Value::Array(padsets)) => padsets, _ => Vec::new() };
+
+    // A padset moves into the file of a board when no other board or padset refers to it
+    let users = |padset: &str| {
+        let boards_using = boards.iter().filter(|board| board_padsets(board).contains(&padset)).count();
+        let padsets_using = padsets.iter().filter(|other| other.get("base").and_then(Value::as_str) == Some(padset)).count();
+        (boards_using, padsets_using)
+    };
+    let owned: Vec<Vec<String>> = boards.iter()
+        .map(|board| board_padsets(board).into_iter()
+            .filter(|padset| users(padset) == (1, 0))
+            .map(str::to_string)
+            .collect())
+        .collect();
+
+    let mut files = Vec::new();
+    for (board, owned) in boards.into_iter().zip(owned) {
+        let path = format!("{}/{}", BOARDS_DIR, file_name(name_of(&board), extension));
+        let (board_padsets, rest): (Vec<Value>, Vec<Value>) = padsets.into_iter()
+            .partition(|padset| owned.iter().any(|name| name == name_of(padset)));
+        padsets = rest;
+
+        let mut components = Map::new();
+        components.insert("boards".to_string(), json!([board]));
+        if !board_padsets.is_empty() {
+            components.insert("padsets".to_string(), Value::Array(board_padsets));
+        }
+        files.push((path, Value::Object(components)));
+    }
+    for padset in padsets {
+        let path = format!("{}/{}", PADSETS_DIR, file_name(name_of(&padset), extension));
+        files.push((path, json!({ "padsets": [padset] })));
+    }
+
+    let mut paths: Vec<&String> = files.iter().map(|(path, _)| path).collect();
+    paths.sort();
+    if let Some(duplicate) = paths.windows(2).find(|pair| pair[0] == pair[1]) {
+        anyhow::bail!("Two components would be written to {}, rename one of them first", duplicate[0]);
+    }
+
+    let includes = settings.entry("includes").or_insert_with(|| json!([]));
+    let includes = includes.as_array_mut()
+        .ok_or_else(|| anyhow::anyhow!("Settings includes are not a list"))?;
+    includes.extend(files.iter().map(|(path, _)| Value::String(path.clone())));
+    Ok(files)
+}
+
+pub fn split_settings(resources: &Resources) -> Result<()> {
+    let settings_path = resources.settings_file()
+        .ok_or_else(|| anyhow::anyhow!("Settings file not found"))?;
+    let directory = settings_path.parent().unwrap_or(Path::new(""));
+
+    let format = ConfigFormat::from_path(&settings_path);
+    let mut document: Value = format.parse(&std::fs::read_to_string(&settings_path)?)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {:?}: {}", settings_path, e))?;
+    let extension = settings_path.extension().and_then(|ext| ext.to_str()).unwrap_or("json");
+
+    let files = split_document(&mut document, extension)?;
+    if files.is_empty() {
+        println!("Settings {:?} have no boards or padsets to split", settings_path);
+        return Ok(());
+    }
+    if let Some((path, _)) = files.iter().find(|(path, _)| directory.join(path).exists()) {
+        anyhow::bail!("{:?} already exists, move it away first", directory.join(path));
+    }
+
+    for (path, components) in &files {
+        let path = directory.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, format.to_string(components)?)
+            .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", path, e))?;
+        println!("Wrote {:?}", path);
+    }
+
+    let backup_path = settings_path.with_extension(format!("{}.bak", extension));
+    std::fs::copy(&settings_path, &backup_path)
+        .map_err(|e| anyhow::anyhow!("Failed to back up {:?}: {}", settings_path, e))?;
+    std::fs::write(&settings_path, format.to_string(&document)?)
+        .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", settings_path, e))?;
+    log::info!("Split {:?} into {} components files", settings_path, files.len());
+    println!("Split {:?} into {} components files (backup: {:?})", settings_path, files.len(), backup_path);
+
+    config::load_settings(resources)
+        .map_err(|e| anyhow::anyhow!("Split settings are invalid: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_document() {
+        let mut document = json!({
+            "timeout": 4,
+            "includes": ["themes.json"],
+            "boards": [
+                { "name": "code", "base_pads": "code", "modifier_pads": { "Ctrl": "code/ctrl" }, "double_pads": "code/double" },
+                { "name": "term", "base_pads": "common" }
+            ],
+            "padsets": [
+                { "name": "code", "items": [] },
+                { "name": "code/ctrl", "items": [] },
+                { "name": "code/double", "items": [] },
+                { "name": "common", "items": [] },
+                { "name": "base", "items": [] },
+                { "name": "derived", "base": "base" }
+            ]
+        });
+        // "common" would be owned by "term", but "code" uses it too
+        document["boards"][0]["modifier_pads"]["Shift"] = json!("common");
+
+        let files = split_document(&mut document, "json").unwrap();
+        let paths: Vec<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["boards/code.json", "boards/term.json", "padsets/common.json", "padsets/base.json", "padsets/derived.json"]);
+        assert_eq!(files[0].1["padsets"].as_array().unwrap().iter().map(name_of).collect::<Vec<_>>(), vec!["code", "code/ctrl", "code/double"]);
+        assert!(files[1].1.get("padsets").is_none());
+
+        assert!(document.get("boards").is_none());
+        assert!(document.get("padsets").is_none());
+        assert_eq!(document["includes"][0], "themes.json");
+        assert_eq!(document["includes"][1], "boards/code.json");
+        assert_eq!(document["includes"].as_array().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_split_document_name_clash() {
+        let mut document = json!({ "padsets": [{ "name": "a/b" }, { "name": "a_b" }] });
+        assert!(split_document(&mut document, "json").is_err());
+    }
+}
@@ -0,0 +1,41 @@
+// Settings validation (`hotkeys validate-settings`).
+// Prints every error and warning with the file and location it comes from, failing when there are errors.
+// In strict mode missing icons and unknown shortcut keys are errors, at runtime they are drawn as a placeholder or skipped
+
+use crate::{app::{config, validation::ValidationReport}, core::Resources};
+use anyhow::Result;
+use std::path::PathBuf;
+
+pub const OUTPUT_FORMATS: &[&str] = &["text", "json"];
+
+pub fn validate_settings(resources: &Resources, format: &str, strict: bool) -> Result<()> {
+    let report = if strict { ValidationReport::strict() } else { ValidationReport::default() };
+
+    // Parse errors and broken includes stop loading, so they are the only finding
+    let (report, file_count) = match config::load_settings_unvalidated(resources) {
+        Ok(settings) => (settings.validation_report(resources, report), settings.source_files().len()),
+        Err(e) if format == "json" => {
+            let mut report = report;
+            let file = resources.settings_file().unwrap_or_else(|| PathBuf::from("settings.json"));
+            report.error(&file, "", format!("Failed to load settings: {}", e));
+            (report, 1)
+        },
+        Err(e) => anyhow::bail!("Failed to load settings: {}", e),
+    };
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report.to_json())?);
+    } else {
+        for finding in &report.findings {
+            println!("{}", finding);
+        }
+        let (errors, warnings) = (report.errors().count(), report.warnings().count());
+        println!("{} error(s), {} warning(s) in {} file(s)", errors, warnings, file_count);
+    }
+
+    if report.has_errors() {
+        anyhow::bail!("Settings validation failed");
+    }
+    log::info!("Validation SUCCESSFUL!");
+    Ok(())
+}
@@ -0,0 +1,71 @@
+/// Pseudo-transparency on X11 without a compositor: a screenshot of the screen taken before
+/// the board window is shown is painted behind the board, offset by the window position.
+/// The screenshot is taken with ImageMagick's import
+
+use super::placement;
+use gtk4::cairo::{self, ImageSurface};
+use gtk4::glib;
+use gtk4::prelude::*;
+use std::cell::Cell;
+use std::io::Cursor;
+use std::process::Command;
+use std::rc::Rc;
+
+/// Screenshot of the whole X11 screen, None on other display servers or when it fails
+pub fn capture_screen() -> Option<ImageSurface> {
+    placement::x11_display()?;
+    let output = match Command::new("import").args(["-silent", "-window", "root", "png:-"]).output() {
+        Ok(output) if output.status.success() => output.stdout,
+        Ok(output) => {
+            log::warn!("Screenshot failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+            return None;
+        },
+        Err(e) => {
+            log::warn!("Failed to run import (ImageMagick, required for transparency without a compositor): {}", e);
+            return None;
+        },
+    };
+    match ImageSurface::create_from_png(&mut Cursor::new(output)) {
+        Ok(surface) => Some(surface),
+        Err(e) => {
+            log::warn!("Failed to read the screenshot: {}", e);
+            None
+        },
+    }
+}
+
+/// Put the board over a drawing area painting the part of the screenshot behind the window.
+/// The window position is read once the window is active, after the placement handlers moved it
+pub fn set_child_with_backdrop(window: &gtk4::ApplicationWindow, board: &gtk4::DrawingArea, screenshot: ImageSurface) {
+    let position: Rc<Cell<Option<(i32, i32)>>> = Rc::new(Cell::new(None));
+    let backdrop = gtk4::DrawingArea::new();
+
+    let draw_position = position.clone();
+    backdrop.set_draw_func(move |_area, ctx, width, height| {
+        // Nothing to show through until the window position is known
+        let Some((x, y)) = draw_position.get() else {
+            return;
+        };
+        ctx.rectangle(0.0, 0.0, width as f64, height as f64);
+        if ctx.set_source_surface(&screenshot, -x as f64, -y as f64).is_ok() {
+            ctx.set_operator(cairo::Operator::Source);
+            let _ = ctx.fill();
+        }
+    });
+
+    let backdrop_area = backdrop.clone();
+    window.connect_is_active_notify(move |window| {
+        if window.is_active() && position.get().is_none() {
+            let (window, position, backdrop_area) = (window.clone(), position.clone(), backdrop_area.clone());
+            glib::idle_add_local_once(move || {
+                position.set(placement::window_position(&window));
+                backdrop_area.queue_draw();
+            });
+        }
+    });
+
+    let overlay = gtk4::Overlay::new();
+    overlay.set_child(Some(&backdrop));
+    overlay.add_overlay(board);
+    window.set_child(Some(&overlay));
+}
@@ -1,50 +1,308 @@
 /// GTK4-based 3x3 board window for Linux
 /// Provides pixel-perfect recreation of Windows HotKeys UI
 
-use crate::core::{Board, ModifierState, Resources};
-use super::layout::{WindowLayout, BoardLayout};
-use super::renderer;
-use super::modifier_handler::ModifierHandler;
+use crate::app::config::PadConfig;
+use crate::core::{perf, Board, ModifierState, Resources};
+use super::layout::{WindowGeometry, WindowLayout, WindowStyle, BoardLayout, Rect};
+use super::renderer::{self, Countdown, RenderCache, Selection};
+use super::modifier_handler::{BoardModifiers, ModifierHandler};
+use super::placement;
+use super::layer_shell;
+use super::backdrop;
+use super::keyboard_grab;
+use super::evdev_keys::{BoardKey, EvdevKeys};
+use super::settings_window;
 use anyhow::Result;
 use gdk4::Key;
 use gtk4::prelude::*;
 use gtk4::{glib, gdk, GestureClick};
 use cairo;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::sync::Arc;
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
 
+/// How often the keys read from the keyboards are handled
+const EVDEV_POLL_INTERVAL: Duration = Duration::from_millis(20);
 
-/// Main 3x3 board window for Linux with GTK4
-pub struct BoardWindow {}
+/// CSS class of the drawing area in edit mode (Ctrl+E), selecting a pad then edits it
+const EDITING_CLASS: &str = "editing";
+
+/// Pad id selected with the 0 key, the configured zero key action runs instead of a pad of the board
+pub const ZERO_KEY_PAD: u8 = 0;
+
+
+/// Main 3x3 board window for Linux with GTK4, another board can be shown in the same window
+pub struct BoardWindow {
+    window: gtk4::ApplicationWindow,
+    drawing_area: gtk4::DrawingArea,
+    board: Rc<RefCell<ShownBoard>>,
+    modifiers: Rc<RefCell<BoardModifiers>>,
+    result: Rc<RefCell<BoardResult>>,
+    countdown: Rc<RefCell<Countdown>>,
+    feedback_progress: Rc<Cell<f64>>,
+    events: async_channel::Receiver<WindowEvent>,
+    destroyed: Cell<bool>,
+}
+
+/// Board shown in the window with its rendered image, drawn again when the size, selection
+/// or modifiers change. The countdown is drawn over it
+struct ShownBoard {
+    board: Arc<dyn Board>,
+    /// Pad selected last time on this board, highlighted while its modifiers are held and selected again with Enter
+    last_pad: Option<(u8, ModifierState)>,
+    image: RenderCache<(Option<Selection>, ModifierState)>,
+}
+
+impl ShownBoard {
+    fn new(board: Arc<dyn Board>, last_pad: Option<(u8, ModifierState)>) -> Self {
+        Self { board, last_pad, image: RenderCache::default() }
+    }
+}
+
+/// How a pad was selected with its number key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PadPress {
+    /// Pressed, or released before the long press time
+    #[default]
+    Press,
+    /// Held past the long press time, the long press actions of the pad run
+    Long,
+    /// Tapped twice, the pad of the double tap pads of the board is selected
+    Double,
+}
+
+/// Pad selection function
+type PadSelector = Rc<dyn Fn(u8, ModifierState, PadPress)>;
+
+/// Pad key waited on by [PadTaps] with its modifiers
+#[derive(Debug)]
+struct PendingPad {
+    pad: u8,
+    modifier_state: ModifierState,
+    /// Waiting for a second tap when released
+    double: bool,
+    /// Released and waiting for a second tap
+    released: bool,
+}
+
+/// Timer of a pad key waited on
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PadTimer {
+    LongPress,
+    DoubleTap,
+}
+
+/// What [PadKeys] does after a key event
+#[derive(Debug, PartialEq)]
+enum PadKeyStep {
+    /// Select the pad, the timer of a pad waited on stops
+    Select(u8, ModifierState, PadPress),
+    /// (Re)start the timer, see [PadTaps::expire]
+    Wait(PadTimer),
+}
+
+/// Transitions of the pad keys without the timers: pads are selected when their key is pressed, a pad with
+/// long press actions when its key is released, or for its long press actions once the key is held past the
+/// long press time. With a double tap pad for the key and no modifiers held the pad is selected once no
+/// second tap follows in time
+#[derive(Debug, Default)]
+struct PadTaps {
+    pending: Option<PendingPad>,
+}
+
+impl PadTaps {
+    /// Pad key pressed: `long` if the pad has long press actions, `double` if the board has a double tap pad for the key
+    fn press(&mut self, pad: u8, modifier_state: ModifierState, long: bool, double: bool) -> Vec<PadKeyStep> {
+        let mut steps = Vec::new();
+        match self.pending.as_ref().filter(|pending| pending.pad == pad).map(|pending| pending.released) {
+            // Key repeat
+            Some(false) => return steps,
+            Some(true) => return self.select(PadPress::Double).into_iter().collect(),
+            None => steps.extend(self.select(PadPress::Press)),
+        }
+
+        let double = double && modifier_state.is_none();
+        if !long && !double {
+            steps.push(PadKeyStep::Select(pad, modifier_state, PadPress::Press));
+            return steps;
+        }
+        self.pending = Some(PendingPad { pad, modifier_state, double, released: false });
+        if long {
+            steps.push(PadKeyStep::Wait(PadTimer::LongPress));
+        }
+        steps
+    }
+
+    fn release(&mut self, pad: u8) -> Vec<PadKeyStep> {
+        match self.pending.as_mut().filter(|pending| pending.pad == pad && !pending.released) {
+            Some(pending) if pending.double => {
+                pending.released = true;
+                vec![PadKeyStep::Wait(PadTimer::DoubleTap)]
+            },
+            Some(_) => self.select(PadPress::Press).into_iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The timer ran out: a pad still held is long pressed, a released one was not tapped again
+    fn expire(&mut self) -> Option<PadKeyStep> {
+        let press = match self.pending.as_ref()?.released {
+            true => PadPress::Press,
+            false => PadPress::Long,
+        };
+        self.select(press)
+    }
+
+    /// Select the pad waited on
+    fn select(&mut self, press: PadPress) -> Option<PadKeyStep> {
+        self.pending.take().map(|pending| PadKeyStep::Select(pending.pad, pending.modifier_state, press))
+    }
+}
+
+/// Pad keys pressed in the window or read from the keyboards, see [PadTaps]
+struct PadKeys {
+    board: Rc<RefCell<ShownBoard>>,
+    select_pad: PadSelector,
+    long_press: Duration,
+    double_tap: Duration,
+    /// The 0 key selects [ZERO_KEY_PAD]
+    zero_key: bool,
+    taps: RefCell<PadTaps>,
+    /// Long press or double tap timer of the pad waited on
+    timer: RefCell<Option<glib::SourceId>>,
+}
+
+impl PadKeys {
+    fn new(board: Rc<RefCell<ShownBoard>>, select_pad: PadSelector, long_press: Duration, double_tap: Duration, zero_key: bool) -> Rc<Self> {
+        Rc::new(Self { board, select_pad, long_press, double_tap, zero_key, taps: RefCell::default(), timer: RefCell::new(None) })
+    }
+
+    /// The 0 key, a pad waited on is selected first
+    fn press_zero(self: &Rc<Self>) {
+        if !self.zero_key {
+            return log::info!("0 pressed - no zero_key action configured, ignoring");
+        }
+        let pending = self.taps.borrow_mut().select(PadPress::Press);
+        self.apply(pending.into_iter().chain([PadKeyStep::Select(ZERO_KEY_PAD, ModifierState::default(), PadPress::Press)]).collect());
+    }
+
+    fn press(self: &Rc<Self>, pad: u8, modifier_state: ModifierState) {
+        let (long, double) = {
+            let board = &self.board.borrow().board;
+            let long = board.pads(Some(&modifier_state)).get((pad - 1) as usize).is_some_and(|pad| !pad.long_actions.is_empty());
+            let double = board.double_pads().is_some_and(|pads| pads.get((pad - 1) as usize).is_some_and(|pad| !pad.actions.is_empty() || pad.board.is_some()));
+            (long, double)
+        };
+        let steps = self.taps.borrow_mut().press(pad, modifier_state, long, double);
+        self.apply(steps);
+    }
+
+    fn release(self: &Rc<Self>, pad: u8) {
+        let steps = self.taps.borrow_mut().release(pad);
+        self.apply(steps);
+    }
+
+    fn apply(self: &Rc<Self>, steps: Vec<PadKeyStep>) {
+        for step in steps {
+            if let Some(timer) = self.timer.take() {
+                timer.remove();
+            }
+            match step {
+                PadKeyStep::Select(pad, modifier_state, press) => (self.select_pad)(pad, modifier_state, press),
+                PadKeyStep::Wait(timer) => {
+                    let duration = match timer {
+                        PadTimer::LongPress => self.long_press,
+                        PadTimer::DoubleTap => self.double_tap,
+                    };
+                    *self.timer.borrow_mut() = Some(self.start_timer(duration));
+                },
+            }
+        }
+    }
+
+    /// Timer selecting the pad waited on when it runs out
+    fn start_timer(self: &Rc<Self>, duration: Duration) -> glib::SourceId {
+        let keys = self.clone();
+        glib::timeout_add_local_once(duration, move || {
+            // The source is gone once it ran, it must not be removed again
+            keys.timer.take();
+            let step = keys.taps.borrow_mut().expire();
+            if let Some(PadKeyStep::Select(pad, _, press)) = &step {
+                log::info!("Pad {} key waited on for {:?} - {:?}", pad, duration, press);
+            }
+            keys.apply(step.into_iter().collect());
+        })
+    }
+}
+
+/// Board window events awaited by [BoardWindow::wait]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WindowEvent {
+    /// A pad was selected and its feedback shown
+    Selected,
+    /// A pad change was requested, see [BoardResult::edit]
+    Edit,
+    Destroyed,
+}
+
+/// Change of the pads requested in the window, with the modifiers held (their pads are changed)
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditRequest {
+    /// Edit the pad in a dialog: right-click, or a selection in edit mode
+    Pad(u8, ModifierState),
+    /// Swap two pads: a pad dragged onto another in edit mode
+    Swap(u8, u8, ModifierState),
+}
+
+/// Outcome of a board window, filled in while the window is shown
+#[derive(Debug, Clone, Default)]
+pub struct BoardResult {
+    /// Selected pad and the modifiers held when it was selected
+    pub selection: Option<(u8, ModifierState)>,
+    /// The window closed itself when the countdown ran out
+    pub timed_out: bool,
+    /// Window size and position when it was closed
+    pub geometry: Option<WindowGeometry>,
+    /// Pad change requested, see [BoardWindow::take_edit]
+    pub edit: Option<EditRequest>,
+    /// How the selected pad was selected
+    pub press: PadPress,
+    /// Board requested by another invocation while the window was open (`hotkeys --board <name>`)
+    pub requested_board: Option<String>,
+}
 
 impl BoardWindow {
-    /// Show board window using the new app.connect_activate approach
+    /// Show board window of the application
     pub fn show_with_app(
         app: &gtk4::Application,
-        board: &dyn Board,
+        board: Arc<dyn Board>,
+        last_pad: Option<(u8, ModifierState)>,
         timeout: u64,
         feedback: u64,
         layout: WindowLayout,
         resources: Resources,
-        result_receiver: Rc<RefCell<Option<(u8, ModifierState)>>>,
-    ) -> Result<()> {
+    ) -> Result<Self> {
+        let result_receiver: Rc<RefCell<BoardResult>> = Rc::new(RefCell::new(BoardResult::default()));
         // Create GTK4 window and associate with application
         let window = gtk4::ApplicationWindow::builder()
             .application(app)
             .title(&format!("HotKeys - {}", board.title()))
             .width_request(600)
             .height_request(450)
-            .default_width(layout.size.width as i32)
-            .default_height(layout.size.height as i32)
+            .default_width(layout.geometry.map_or(layout.size.width as i32, |geometry| geometry.width))
+            .default_height(layout.geometry.map_or(layout.size.height as i32, |geometry| geometry.height))
             .decorated(layout.style.has_decorations())
             .resizable(layout.style.has_decorations())
             .build();
 
         // Set window properties for overlay behavior
+        window.add_css_class("hotkeys");
         window.set_modal(false);
         window.set_deletable(true);
 
-        // Enable transparency support
+        // Enable transparency support, without a compositor the screen behind the window is painted under the board
+        let mut screenshot = None;
         if let Some(display) = gdk::Display::default() {
             if display.is_composited() {
                 log::info!("Display supports compositing - enabling transparency");
@@ -59,28 +317,88 @@ impl BoardWindow {
                     gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
                 );
             } else {
-                log::warn!("Display does not support compositing - transparency may not work");
+                log::warn!("Display does not support compositing - painting a screenshot behind the board");
+                screenshot = backdrop::capture_screen();
             }
+
+            Self::load_user_style(&window, &display, &resources);
         }
 
         // Create drawing area for custom rendering
         let drawing_area = gtk4::DrawingArea::new();
-        window.set_child(Some(&drawing_area));
+        drawing_area.add_css_class("board");
+        match screenshot {
+            Some(screenshot) => backdrop::set_child_with_backdrop(&window, &drawing_area, screenshot),
+            None => window.set_child(Some(&drawing_area)),
+        }
 
-        let timeout_ref = Rc::new(RefCell::new(timeout));
-        let modifier_state = Rc::new(RefCell::new(ModifierState::default()));
+        // Without focus the board keys are read from the keyboards, or grabbed on X11 when they cannot be read
+        let evdev_keys = match layout.no_focus {
+            true => EvdevKeys::start().inspect_err(|e| log::warn!("Cannot read the keyboards: {}", e)).ok(),
+            false => None,
+        };
+
+        let board = Rc::new(RefCell::new(ShownBoard::new(board, last_pad)));
+        let timeout_ref = Rc::new(RefCell::new(Countdown { remaining: timeout, total: timeout }));
+        let modifiers = Rc::new(RefCell::new(BoardModifiers::new(layout.sticky_modifiers)));
+        let feedback_progress = Rc::new(Cell::new(0.0));
+        let (events_sender, events) = async_channel::unbounded();
+
+        let events_sender_clone = events_sender.clone();
+        window.connect_destroy(move |_| {
+            let _ = events_sender_clone.try_send(WindowEvent::Destroyed);
+        });
 
-        // Create shared timeout cancellation function
+        // Create shared timeout cancellation and pad selection functions
         let cancel_timeout = Self::create_timeout_canceller(timeout_ref.clone(), drawing_area.clone());
+        let request_edit = Self::create_edit_requester(result_receiver.clone(), events_sender.clone());
+        let select_pad = Self::create_pad_selector(&drawing_area, feedback, feedback_progress.clone(), result_receiver.clone(), events_sender, request_edit.clone());
+        let select_last_pad = Self::create_last_pad_selector(board.clone(), select_pad.clone());
+        let pad_keys = PadKeys::new(board.clone(), select_pad, Duration::from_millis(layout.long_press), Duration::from_millis(layout.double_tap), layout.zero_key);
 
         // Setup all the handlers and show the window
-        Self::setup_drawing(&drawing_area, board, timeout_ref.clone(), result_receiver.clone(), modifier_state.clone(), resources)?;
-        Self::setup_input_handling(&window, &drawing_area, feedback, result_receiver.clone(), modifier_state.clone(), cancel_timeout.clone())?;
-        Self::setup_mouse_handling(&drawing_area, cancel_timeout)?;
+        Self::setup_drawing(&drawing_area, board.clone(), timeout_ref.clone(), result_receiver.clone(), feedback_progress.clone(), modifiers.clone(), resources)?;
+        Self::setup_input_handling(&window, &drawing_area, pad_keys.clone(), select_last_pad.clone(), modifiers.clone(), cancel_timeout.clone())?;
+        let take_keyboard = evdev_keys.is_none();
+        if let Some(evdev_keys) = evdev_keys {
+            Self::setup_evdev_input(&window, &drawing_area, evdev_keys, pad_keys, select_last_pad, modifiers.clone(), cancel_timeout.clone());
+        }
+        Self::setup_mouse_handling(&drawing_area, request_edit, modifiers.clone(), cancel_timeout)?;
 
         // Setup timeout for auto-close (only if timeout > 0)
         if timeout > 0 {
-            Self::setup_auto_close_timer(&window, &drawing_area, timeout_ref.clone());
+            Self::setup_auto_close_timer(&window, &drawing_area, timeout_ref.clone(), result_receiver.clone());
+        }
+
+        // Recorded before fading out, the close request handlers run in order until one stops it
+        Self::setup_geometry_capture(&window, result_receiver.clone());
+
+        if layout.fade > 0 {
+            Self::setup_fade(&window, layout.fade);
+        }
+
+        // Layer surfaces have to be set up before the window is shown
+        let overlay = layout.style == WindowStyle::Overlay && layer_shell::init_overlay(&window, layout.placement.as_ref(), take_keyboard);
+
+        // A configured placement takes precedence over the last position
+        if overlay {
+            // Placed by the compositor from the anchor and margins
+        } else if let Some(placement) = &layout.placement {
+            placement::place_window(&window, placement, &layout.size);
+        } else if let Some((x, y)) = layout.geometry.and_then(|geometry| geometry.position) {
+            placement::move_window(&window, x, y);
+        }
+
+        // Layer surfaces already take the keyboard exclusively
+        if !overlay {
+            placement::set_window_state(&window, layout.keep_above, layout.skip_taskbar);
+            if layout.grab_keyboard || (layout.no_focus && take_keyboard) {
+                keyboard_grab::grab_keyboard(&window);
+            }
+        }
+
+        if layout.no_focus {
+            placement::show_without_focus(&window);
         }
 
         // Connect unrealize signal - only for debugging purposes for now, to confirm window destruction order
@@ -90,8 +408,10 @@ impl BoardWindow {
 
         // Show window
         window.set_visible(true);
-        window.present();
-        window.grab_focus();
+        if !layout.no_focus {
+            window.present();
+            window.grab_focus();
+        }
 
         // Set icon name after window is shown for proper taskbar grouping
         window.set_icon_name(Some("hotkeys"));
@@ -99,57 +419,141 @@ impl BoardWindow {
         // Force initial draw
         drawing_area.queue_draw();
 
-        Ok(())
+        Ok(Self {
+            window,
+            drawing_area,
+            board,
+            modifiers,
+            result: result_receiver,
+            countdown: timeout_ref,
+            feedback_progress,
+            events,
+            destroyed: Cell::new(false),
+        })
+    }
+
+    /// Wait until a pad is selected (and its feedback shown), a pad edit is requested or the window is closed
+    pub async fn wait(&self) -> BoardResult {
+        if !self.destroyed.get() {
+            let event = self.events.recv().await.unwrap_or(WindowEvent::Destroyed);
+            self.destroyed.set(event == WindowEvent::Destroyed);
+        }
+        self.result.borrow().clone()
+    }
+
+    /// Show another board in the window, keeping its size and position. Auto-close is stopped
+    /// and the latched modifiers are released
+    pub fn show_board(&self, board: Arc<dyn Board>, last_pad: Option<(u8, ModifierState)>) {
+        self.window.set_title(Some(&format!("HotKeys - {}", board.title())));
+        *self.board.borrow_mut() = ShownBoard::new(board, last_pad);
+        self.modifiers.borrow_mut().release_latched();
+        self.result.borrow_mut().selection = None;
+        self.result.borrow_mut().edit = None;
+        self.result.borrow_mut().press = PadPress::Press;
+        self.drawing_area.remove_css_class(EDITING_CLASS);
+        self.countdown.borrow_mut().remaining = 0;
+        self.feedback_progress.set(0.0);
+        self.drawing_area.queue_draw();
+    }
+
+    /// Show the board again with new pad contents, keeping the selection and countdown
+    pub fn refresh_board(&self, board: Arc<dyn Board>) {
+        let last_pad = self.board.borrow().last_pad.clone();
+        *self.board.borrow_mut() = ShownBoard::new(board, last_pad);
+        self.drawing_area.queue_draw();
+    }
+
+    /// Raise the window and give it the focus, e.g. when `hotkeys` is invoked again while it is open
+    pub fn present(&self) {
+        if !self.destroyed.get() {
+            self.window.present();
+        }
+    }
+
+    /// Whether a pad was selected in the window and the selection has not been waited for yet
+    pub fn has_selection(&self) -> bool {
+        self.result.borrow().selection.is_some()
+    }
+
+    /// Take the pad change requested in the window, another change can be requested afterwards
+    pub fn take_edit(&self) -> Option<EditRequest> {
+        self.result.borrow_mut().edit.take()
+    }
+
+    /// Edit a pad in a dialog over the window, `None` when the dialog is cancelled. Edit mode stays on
+    pub async fn edit_pad(&self, title: &str, pad: &PadConfig, check: impl Fn(&PadConfig) -> Vec<String> + 'static) -> Option<PadConfig> {
+        if self.destroyed.get() {
+            return None;
+        }
+        settings_window::pad_dialog(&self.window, title, pad, check).await
+    }
+
+    /// Close the window and wait until it is destroyed
+    pub async fn close(&self) -> BoardResult {
+        if !self.destroyed.get() {
+            self.window.close();
+            while self.events.recv().await.is_ok_and(|event| event != WindowEvent::Destroyed) {}
+            self.destroyed.set(true);
+        }
+        self.result.borrow().clone()
     }
 
     /// Setup Cairo drawing for the board
     fn setup_drawing(
         drawing_area: &gtk4::DrawingArea,
-        board: &dyn Board,
-        timeout: Rc<RefCell<u64>>,
-        selected_pad: Rc<RefCell<Option<(u8, ModifierState)>>>,
-        modifier_state: Rc<RefCell<ModifierState>>,
+        board: Rc<RefCell<ShownBoard>>,
+        countdown: Rc<RefCell<Countdown>>,
+        result: Rc<RefCell<BoardResult>>,
+        feedback_progress: Rc<Cell<f64>>,
+        modifiers: Rc<RefCell<BoardModifiers>>,
         resources: Resources,
     ) -> Result<()> {
-        let cloned_board = board.clone_box();
+        // From the window set up until the board is painted the first time
+        let created = Instant::now();
+        drawing_area.set_draw_func(move |area, ctx, width, height| {
+            let mut shown = board.borrow_mut();
+            let ShownBoard { board, last_pad, image } = &mut *shown;
 
-        drawing_area.set_draw_func(move |_area, ctx, width, height| {
-            let (width, height) = (width as f64, height as f64);
+            // Create layout for current dimensions
+            let board_layout = BoardLayout::new(width as f64, height as f64);
 
-            // Clear everything to transparent
-            ctx.set_operator(cairo::Operator::Clear);
-            ctx.rectangle(0.0, 0.0, width, height);
-            ctx.fill().unwrap();
+            let selection = result.borrow().selection.as_ref().map(|(pad, _)| Selection { pad: *pad, progress: feedback_progress.get() });
+            let current_modifiers = modifiers.borrow().state();
+            let last_pad = last_pad.as_ref().filter(|(_, modifiers)| *modifiers == board.modifiers(&current_modifiers)).map(|(pad, _)| *pad);
 
-            // Reset operator to normal
-            ctx.set_operator(cairo::Operator::Over);
+            // Draw the 3x3 board, rendered again only when the selection or modifiers change
+            image.paint(ctx, width, height, (selection, current_modifiers.clone()), |ctx| {
+                let (width, height) = (width as f64, height as f64);
 
-            // Draw background with color scheme background and opacity
-            let color_scheme = cloned_board.color_scheme();
+                // Clear everything to transparent
+                ctx.set_operator(cairo::Operator::Clear);
+                ctx.rectangle(0.0, 0.0, width, height);
+                ctx.fill().unwrap();
 
-            let bg_color = color_scheme.background().to_rgb();
-            ctx.set_source_rgba(bg_color.0, bg_color.1, bg_color.2, color_scheme.opacity);
-            ctx.paint().unwrap();
+                // Reset operator to normal
+                ctx.set_operator(cairo::Operator::Over);
 
-            // Create layout for current dimensions
-            let board_layout = BoardLayout::new(width, height);
+                // Draw background with color scheme background and opacity
+                let color_scheme = board.color_scheme();
 
-            // Get countdown time if timer is active (timeout > 0)
-            let timeout_value = *timeout.borrow();
-            let remaining_time = if timeout_value > 0 {
-                Some(timeout_value)
-            } else {
-                None
-            };
+                renderer::draw_background(ctx, color_scheme, color_scheme.opacity(), Rect::new(0.0, 0.0, width, height));
 
-            // Draw the 3x3 board with optional countdown using the new Board renderer
-            let selected_pad_num = selected_pad.borrow().as_ref().map(|(pad, _)| *pad);
-            let current_modifiers = modifier_state.borrow().clone();
+                renderer::draw_board(ctx, board.as_ref(), &board_layout, &resources, selection, last_pad, &current_modifiers);
+            });
 
-            // Use the new Board renderer
-            renderer::draw_board(ctx, cloned_board.as_ref(), &board_layout, &resources,
-                selected_pad_num, remaining_time, &current_modifiers
-            );
+            // Draw countdown over the board if timer is active (timeout > 0)
+            let countdown = *countdown.borrow();
+            if countdown.remaining > 0 {
+                renderer::draw_countdown(ctx, board.as_ref(), &board_layout, &resources, countdown);
+            }
+            if area.has_css_class(EDITING_CLASS) {
+                renderer::draw_edit_mode(ctx, board.as_ref(), &board_layout, &resources);
+            }
+            let latched = modifiers.borrow().latched().clone();
+            if !latched.is_none() {
+                renderer::draw_latched_modifiers(ctx, board.as_ref(), &board_layout, &resources, &latched);
+            }
+            perf::record("first draw", created);
         });
 
         Ok(())
@@ -159,9 +563,9 @@ impl BoardWindow {
     fn setup_input_handling(
         window: &gtk4::ApplicationWindow,
         drawing_area: &gtk4::DrawingArea,
-        feedback: u64,
-        selected_pad: Rc<RefCell<Option<(u8, ModifierState)>>>,
-        modifier_state: Rc<RefCell<ModifierState>>,
+        pad_keys: Rc<PadKeys>,
+        select_last_pad: Rc<dyn Fn()>,
+        modifiers: Rc<RefCell<BoardModifiers>>,
         cancel_timeout: Rc<dyn Fn()>,
     ) -> Result<()> {
         // Enable key events and make window focusable
@@ -174,15 +578,14 @@ impl BoardWindow {
         // Helper function for modifier handling
         let handle_modifier_event = |handler_fn: fn(&mut ModifierHandler, gdk::Key) -> bool,
                                      keyval: gdk::Key,
-                                     modifier_state: &Rc<RefCell<ModifierState>>,
+                                     modifiers: &Rc<RefCell<BoardModifiers>>,
                                      drawing_area: &gtk4::DrawingArea| -> bool {
-            let old_state = modifier_state.borrow().clone();
-            let mut handler = ModifierHandler::new(old_state.clone());
+            let mut handler = ModifierHandler::new(modifiers.borrow().held().clone());
 
             if handler_fn(&mut handler, keyval) {
-                let new_state = handler.state().clone();
-                if new_state != old_state {
-                    *modifier_state.borrow_mut() = new_state.clone();
+                let (old_state, old_latched) = (modifiers.borrow().state(), modifiers.borrow().latched().clone());
+                modifiers.borrow_mut().set_held(handler.state().clone());
+                if modifiers.borrow().state() != old_state || *modifiers.borrow().latched() != old_latched {
                     drawing_area.queue_draw();
                 }
                 true
@@ -195,7 +598,8 @@ impl BoardWindow {
         let cancel_timeout_clone = cancel_timeout.clone();
         let window_clone = window.clone();
         let drawing_area_clone = drawing_area.clone();
-        let modifier_state_clone = modifier_state.clone();
+        let modifiers_clone = modifiers.clone();
+        let pad_keys_clone = pad_keys.clone();
 
         // Handle key presses with result capture (no action execution)
         key_controller.connect_key_pressed(move |_controller, keyval, keycode, state| {
@@ -203,17 +607,21 @@ impl BoardWindow {
             cancel_timeout_clone();
 
             // Handle modifier key presses using helper function
-            if handle_modifier_event(ModifierHandler::handle_key_press, keyval, &modifier_state_clone, &drawing_area_clone) {
+            if handle_modifier_event(ModifierHandler::handle_key_press, keyval, &modifiers_clone, &drawing_area_clone) {
                 return glib::Propagation::Proceed;
             }
+            modifiers_clone.borrow_mut().other_key();
 
-            // Extract modifier state from GTK state for number key selection
-            let modifier_state = ModifierState {
+            // Extract modifier state from GTK state for number key selection, on the sides tracked from the modifier keys,
+            // with the latched modifiers
+            let held = ModifierState {
                 ctrl: state.contains(gdk::ModifierType::CONTROL_MASK),
                 shift: state.contains(gdk::ModifierType::SHIFT_MASK),
                 alt: state.contains(gdk::ModifierType::ALT_MASK),
                 super_key: state.contains(gdk::ModifierType::SUPER_MASK),
-            };
+                ..Default::default()
+            }.with_sides_from(modifiers_clone.borrow().held());
+            let modifier_state = modifiers_clone.borrow().with_latched(&held);
 
             match keyval {
                 // Numpad keys (preferred)
@@ -225,10 +633,18 @@ impl BoardWindow {
                 gdk::Key::KP_6 | gdk::Key::_6 | gdk::Key::KP_Right |
                 gdk::Key::KP_7 | gdk::Key::_7 | gdk::Key::KP_Home |
                 gdk::Key::KP_8 | gdk::Key::_8 | gdk::Key::KP_Up |
-                gdk::Key::KP_9 | gdk::Key::_9 | gdk::Key::KP_Page_Up => {
-                    log::info!("Number pressed: selecting pad {} with modifiers: {}", keyval.pad_id(), modifier_state.to_string());
-                    *selected_pad.borrow_mut() = Some((keyval.pad_id(), modifier_state));
-                    Self::on_key_selected(window_clone.clone(), feedback, drawing_area_clone.clone())
+                gdk::Key::KP_9 | gdk::Key::_9 | gdk::Key::KP_Page_Up => pad_keys_clone.press(keyval.pad_id(), modifier_state),
+                // Without modifiers, a held trigger such as Ctrl+Alt+KP_0 repeats into the board
+                gdk::Key::KP_0 | gdk::Key::_0 | gdk::Key::KP_Insert if held.is_none() => pad_keys_clone.press_zero(),
+                gdk::Key::Return | gdk::Key::KP_Enter => select_last_pad(),
+                gdk::Key::e | gdk::Key::E if held.ctrl => {
+                    let editing = !drawing_area_clone.has_css_class(EDITING_CLASS);
+                    log::info!("Ctrl+E pressed - edit mode {}", if editing { "on" } else { "off" });
+                    match editing {
+                        true => drawing_area_clone.add_css_class(EDITING_CLASS),
+                        false => drawing_area_clone.remove_css_class(EDITING_CLASS),
+                    }
+                    drawing_area_clone.queue_draw();
                 },
                 gdk::Key::Escape => {
                     log::info!("Escape pressed - cancelling selection");
@@ -243,45 +659,139 @@ impl BoardWindow {
         });
 
         // Handle key releases to detect modifier changes
-        let modifier_state_clone = modifier_state.clone();
         let drawing_area_clone = drawing_area.clone();
         key_controller.connect_key_released(move |_controller, keyval, _keycode, _state| {
             // Handle modifier key releases using helper function
-            handle_modifier_event(ModifierHandler::handle_key_release, keyval, &modifier_state_clone, &drawing_area_clone);
+            if !handle_modifier_event(ModifierHandler::handle_key_release, keyval, &modifiers, &drawing_area_clone) && keyval.pad_id() != 0 {
+                pad_keys.release(keyval.pad_id());
+            }
         });
 
         Ok(())
     }
 
-    /// Setup mouse input handling to cancel timeout on any click
-    fn setup_mouse_handling(drawing_area: &gtk4::DrawingArea, cancel_timeout: Rc<dyn Fn()>) -> Result<()> {
+    /// Handle the board keys read from the keyboards while the window has no focus,
+    /// the keyboards are released once the window is hidden
+    fn setup_evdev_input(
+        window: &gtk4::ApplicationWindow,
+        drawing_area: &gtk4::DrawingArea,
+        evdev_keys: EvdevKeys,
+        pad_keys: Rc<PadKeys>,
+        select_last_pad: Rc<dyn Fn()>,
+        modifiers: Rc<RefCell<BoardModifiers>>,
+        cancel_timeout: Rc<dyn Fn()>,
+    ) {
+        let window = window.clone();
+        let drawing_area = drawing_area.clone();
+        glib::timeout_add_local(EVDEV_POLL_INTERVAL, move || {
+            if !window.is_visible() {
+                return glib::ControlFlow::Break;
+            }
+            while let Some(key) = evdev_keys.try_recv() {
+                if !matches!(key, BoardKey::Modifiers(_)) {
+                    modifiers.borrow_mut().other_key();
+                }
+                match key {
+                    BoardKey::Pad(pad, held) => {
+                        cancel_timeout();
+                        let modifier_state = modifiers.borrow().with_latched(&held);
+                        pad_keys.press(pad, modifier_state);
+                    },
+                    BoardKey::PadReleased(pad) => pad_keys.release(pad),
+                    BoardKey::Enter => {
+                        cancel_timeout();
+                        select_last_pad();
+                    },
+                    BoardKey::Zero => {
+                        cancel_timeout();
+                        pad_keys.press_zero();
+                    },
+                    BoardKey::Modifiers(held) => {
+                        modifiers.borrow_mut().set_held(held);
+                        drawing_area.queue_draw();
+                    },
+                    BoardKey::Escape => {
+                        log::info!("Escape pressed - cancelling selection");
+                        window.close();
+                    },
+                }
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+
+    /// Setup mouse input handling to cancel timeout on any click. A right-click on a pad edits it,
+    /// in edit mode a click edits the pad and dragging a pad onto another swaps them
+    fn setup_mouse_handling(
+        drawing_area: &gtk4::DrawingArea,
+        request_edit: Rc<dyn Fn(EditRequest)>,
+        modifiers: Rc<RefCell<BoardModifiers>>,
+        cancel_timeout: Rc<dyn Fn()>,
+    ) -> Result<()> {
         let gesture = GestureClick::new();
         gesture.set_button(0); // Accept all buttons
 
-        gesture.connect_pressed(move |_gesture, _n_press, _x, _y| {
+        let (area, edit, shown_modifiers) = (drawing_area.clone(), request_edit.clone(), modifiers.clone());
+        gesture.connect_pressed(move |gesture, _n_press, x, y| {
             // Cancel timeout on any mouse click
             cancel_timeout();
+
+            if let Some(pad) = Self::pad_at(&area, x, y).filter(|_| gesture.current_button() == gdk::BUTTON_SECONDARY) {
+                edit(EditRequest::Pad(pad, shown_modifiers.borrow().state()));
+            }
+        });
+
+        // Released only when the pointer stayed in place, drags are handled below
+        let (area, edit, shown_modifiers) = (drawing_area.clone(), request_edit.clone(), modifiers.clone());
+        gesture.connect_released(move |gesture, _n_press, x, y| {
+            let editing = gesture.current_button() == gdk::BUTTON_PRIMARY && area.has_css_class(EDITING_CLASS);
+            if let Some(pad) = Self::pad_at(&area, x, y).filter(|_| editing) {
+                edit(EditRequest::Pad(pad, shown_modifiers.borrow().state()));
+            }
         });
 
         drawing_area.add_controller(gesture);
 
+        let drag = gtk4::GestureDrag::new();
+        drag.set_button(gdk::BUTTON_PRIMARY);
+        let area = drawing_area.clone();
+        drag.connect_drag_end(move |drag, offset_x, offset_y| {
+            let Some((x, y)) = drag.start_point().filter(|_| area.has_css_class(EDITING_CLASS)) else {
+                return;
+            };
+            let from = Self::pad_at(&area, x, y);
+            let to = Self::pad_at(&area, x + offset_x, y + offset_y);
+            if let Some((from, to)) = from.zip(to).filter(|(from, to)| from != to) {
+                request_edit(EditRequest::Swap(from, to, modifiers.borrow().state()));
+            }
+        });
+
+        drawing_area.add_controller(drag);
+
         Ok(())
     }
 
+    /// Pad (1-9) at a point of the drawing area
+    fn pad_at(drawing_area: &gtk4::DrawingArea, x: f64, y: f64) -> Option<u8> {
+        let layout = BoardLayout::new(drawing_area.width() as f64, drawing_area.height() as f64);
+        (1..=9).find(|id| layout.get_tile_rect(*id).is_some_and(|rect| rect.contains(x, y)))
+    }
+
     /// Setup auto close timer for the window
-    fn setup_auto_close_timer(window: &gtk4::ApplicationWindow, drawing_area: &gtk4::DrawingArea, timeout: Rc<RefCell<u64>>) {
+    fn setup_auto_close_timer(window: &gtk4::ApplicationWindow, drawing_area: &gtk4::DrawingArea, countdown: Rc<RefCell<Countdown>>, result: Rc<RefCell<BoardResult>>) {
         let drawing_area_for_countdown = drawing_area.clone();
         let window_for_timeout = window.clone();
 
         // Single timer that decrements timeout every second
         glib::timeout_add_seconds_local(1, move || {
-            let mut time_left = timeout.borrow_mut();
+            let time_left = &mut countdown.borrow_mut().remaining;
             if *time_left > 0 {
                 *time_left -= 1;
                 drawing_area_for_countdown.queue_draw(); // Trigger redraw to update visual cue
 
                 if *time_left == 0 {
                     log::info!("Board timeout reached - auto-closing");
+                    result.borrow_mut().timed_out = true;
                     window_for_timeout.close();
                 }
 
@@ -292,31 +802,182 @@ impl BoardWindow {
         });
     }
 
+    /// Record the window size and position in the result when it is closed, to restore them next time
+    fn setup_geometry_capture(window: &gtk4::ApplicationWindow, result: Rc<RefCell<BoardResult>>) {
+        window.connect_close_request(move |window| {
+            if result.borrow().geometry.is_none() {
+                let (width, height) = window.default_size();
+                let position = placement::window_position(window);
+                result.borrow_mut().geometry = Some(WindowGeometry { width, height, position });
+            }
+            glib::Propagation::Proceed
+        });
+    }
+
+    /// Fade the window in when it is shown and out before it closes, whatever closes it
+    fn setup_fade(window: &gtk4::ApplicationWindow, duration: u64) {
+        let closing = Rc::new(Cell::new(false));
+
+        window.set_opacity(0.0);
+        let closing_clone = closing.clone();
+        let started = Cell::new(None);
+        window.add_tick_callback(move |window, clock| {
+            if closing_clone.get() {
+                return glib::ControlFlow::Break; // Fading out already
+            }
+            let progress = Self::animation_progress(&started, clock, duration);
+            window.set_opacity(progress);
+
+            match progress < 1.0 {
+                true => glib::ControlFlow::Continue,
+                false => glib::ControlFlow::Break,
+            }
+        });
+
+        window.connect_close_request(move |window| {
+            if closing.get() {
+                return glib::Propagation::Proceed; // Faded out
+            }
+            closing.set(true);
+
+            let from = window.opacity();
+            let started = Cell::new(None);
+            window.add_tick_callback(move |window, clock| {
+                let progress = Self::animation_progress(&started, clock, duration);
+                window.set_opacity(from * (1.0 - progress));
+
+                match progress < 1.0 {
+                    true => glib::ControlFlow::Continue,
+                    false => {
+                        window.close();
+                        glib::ControlFlow::Break
+                    },
+                }
+            });
+            glib::Propagation::Stop
+        });
+    }
+
+    /// Progress (0 to 1) of an animation lasting `duration` milliseconds from its first frame
+    fn animation_progress(started: &Cell<Option<i64>>, clock: &gdk::FrameClock, duration: u64) -> f64 {
+        let start = started.get().unwrap_or_else(|| clock.frame_time());
+        started.set(Some(start));
+        ((clock.frame_time() - start) as f64 / (duration * 1000) as f64).min(1.0)
+    }
+
+    /// Load the user stylesheet (style.css in the config directory) over the built-in rules,
+    /// it is read again for every window and removed when the window is closed
+    fn load_user_style(window: &gtk4::ApplicationWindow, display: &gdk::Display, resources: &Resources) {
+        let Some(style_path) = resources.style_css() else {
+            return;
+        };
+        log::info!("Loading style: {:?}", style_path);
+
+        let css_provider = gtk4::CssProvider::new();
+        css_provider.connect_parsing_error(|_, section, error| {
+            log::warn!("Style error at {}: {}", section, error);
+        });
+        css_provider.load_from_path(&style_path);
+        gtk4::style_context_add_provider_for_display(display, &css_provider, gtk4::STYLE_PROVIDER_PRIORITY_USER);
+
+        let display = display.clone();
+        window.connect_destroy(move |_| {
+            gtk4::style_context_remove_provider_for_display(&display, &css_provider);
+        });
+    }
+
     /// Create a shared timeout cancellation function
     fn create_timeout_canceller(
-        timeout: Rc<RefCell<u64>>,
+        countdown: Rc<RefCell<Countdown>>,
         drawing_area: gtk4::DrawingArea,
     ) -> Rc<dyn Fn()> {
         Rc::new(move || {
-            if *timeout.borrow() > 0 {
-                *timeout.borrow_mut() = 0;
+            if countdown.borrow().remaining > 0 {
+                countdown.borrow_mut().remaining = 0;
                 drawing_area.queue_draw(); // Redraw to remove visual cue
             }
         })
     }
 
+    /// Create the pad selection function, shared by the window keys and the keys read from the keyboards.
+    /// Further selections are ignored until another board is shown, in edit mode the pads are edited instead
+    /// (the 0 key has no pad to edit)
+    fn create_pad_selector(
+        drawing_area: &gtk4::DrawingArea,
+        feedback: u64,
+        feedback_progress: Rc<Cell<f64>>,
+        result: Rc<RefCell<BoardResult>>,
+        events: async_channel::Sender<WindowEvent>,
+        request_edit: Rc<dyn Fn(EditRequest)>,
+    ) -> PadSelector {
+        let drawing_area = drawing_area.clone();
+        Rc::new(move |pad, modifier_state, press| {
+            if drawing_area.has_css_class(EDITING_CLASS) && pad == ZERO_KEY_PAD {
+                return;
+            }
+            if drawing_area.has_css_class(EDITING_CLASS) {
+                return request_edit(EditRequest::Pad(pad, modifier_state));
+            }
+            if result.borrow().selection.is_some() {
+                return;
+            }
+            log::info!("Number pressed: selecting pad {} with modifiers: {}", pad, modifier_state.to_string());
+            result.borrow_mut().selection = Some((pad, modifier_state));
+            result.borrow_mut().press = press;
+            Self::on_key_selected(events.clone(), feedback, feedback_progress.clone(), drawing_area.clone())
+        })
+    }
+
+    /// Create the function requesting a change of the pads, awaited like a selection.
+    /// Further requests are ignored until the request is taken with [BoardWindow::take_edit]
+    fn create_edit_requester(result: Rc<RefCell<BoardResult>>, events: async_channel::Sender<WindowEvent>) -> Rc<dyn Fn(EditRequest)> {
+        Rc::new(move |request| {
+            if result.borrow().selection.is_some() || result.borrow().edit.is_some() {
+                return;
+            }
+            log::info!("Pad change requested: {:?}", request);
+            result.borrow_mut().edit = Some(request);
+            let _ = events.try_send(WindowEvent::Edit);
+        })
+    }
+
+    /// Create the function selecting the pad selected last time on the shown board (Enter), with the same modifiers
+    fn create_last_pad_selector(board: Rc<RefCell<ShownBoard>>, select_pad: PadSelector) -> Rc<dyn Fn()> {
+        Rc::new(move || {
+            let last_pad = board.borrow().last_pad.clone();
+            match last_pad {
+                Some((pad, modifier_state)) => select_pad(pad, modifier_state, PadPress::Press),
+                None => log::info!("Enter pressed - no pad was selected on this board before"),
+            }
+        })
+    }
+
     /// Handle key selection and provide visual feedback if configured
-    fn on_key_selected(window: gtk4::ApplicationWindow, feedback: u64, drawing_area: gtk4::DrawingArea) {
+    fn on_key_selected(events: async_channel::Sender<WindowEvent>, feedback: u64, feedback_progress: Rc<Cell<f64>>, drawing_area: gtk4::DrawingArea) {
         if feedback > 0 {
             drawing_area.queue_draw(); // Trigger immediate redraw for visual feedback
+
+            // Animate the selection highlight on every frame until the feedback window ends
+            let started = Cell::new(None);
+            drawing_area.add_tick_callback(move |area, clock| {
+                let progress = Self::animation_progress(&started, clock, feedback);
+                feedback_progress.set(progress);
+                area.queue_draw();
+
+                match progress < 1.0 {
+                    true => glib::ControlFlow::Continue,
+                    false => glib::ControlFlow::Break,
+                }
+            });
+
             glib::timeout_add_local(std::time::Duration::from_millis(feedback), move || {
-                 log::info!("Feedback timer expired - closing window");
-                 window.close();
+                 log::info!("Feedback timer expired - pad selected");
+                 let _ = events.try_send(WindowEvent::Selected);
                  glib::ControlFlow::Break
             });
         } else {
-            log::info!("No feedback configured - closing window immediately");
-            window.close();
+            log::info!("No feedback configured - pad selected immediately");
+            let _ = events.try_send(WindowEvent::Selected);
         }
     }
 
@@ -346,4 +1007,49 @@ impl NumberPad for Key {
             _ => 0, // Invalid key for pad selection
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(pad: u8, modifier_state: &ModifierState, press: PadPress) -> PadKeyStep {
+        PadKeyStep::Select(pad, modifier_state.clone(), press)
+    }
+
+    #[test]
+    fn test_pad_taps() {
+        let (none, ctrl) = (ModifierState::default(), ModifierState { ctrl: true, ..Default::default() });
+        let mut taps = PadTaps::default();
+
+        // Plain pads are selected when pressed
+        assert_eq!(taps.press(1, none.clone(), false, false), vec![select(1, &none, PadPress::Press)]);
+        assert!(taps.release(1).is_empty());
+
+        // Long press pads on release, or when held until the timer runs out
+        assert_eq!(taps.press(2, none.clone(), true, false), vec![PadKeyStep::Wait(PadTimer::LongPress)]);
+        assert!(taps.press(2, none.clone(), true, false).is_empty());
+        assert_eq!(taps.release(2), vec![select(2, &none, PadPress::Press)]);
+        assert_eq!(taps.press(2, none.clone(), true, false), vec![PadKeyStep::Wait(PadTimer::LongPress)]);
+        assert_eq!(taps.expire(), Some(select(2, &none, PadPress::Long)));
+        assert!(taps.release(2).is_empty());
+
+        // Double tap pads wait for a second tap after the release
+        assert!(taps.press(3, none.clone(), false, true).is_empty());
+        assert_eq!(taps.release(3), vec![PadKeyStep::Wait(PadTimer::DoubleTap)]);
+        assert_eq!(taps.press(3, none.clone(), false, true), vec![select(3, &none, PadPress::Double)]);
+        assert!(taps.press(3, none.clone(), false, true).is_empty());
+        assert_eq!(taps.release(3), vec![PadKeyStep::Wait(PadTimer::DoubleTap)]);
+        assert_eq!(taps.expire(), Some(select(3, &none, PadPress::Press)));
+        assert_eq!(taps.expire(), None);
+
+        // Another key selects the pad waited on first
+        taps.press(3, none.clone(), false, true);
+        taps.release(3);
+        assert_eq!(taps.press(4, none.clone(), false, false), vec![select(3, &none, PadPress::Press), select(4, &none, PadPress::Press)]);
+
+        // With modifiers held there is no double tap
+        assert_eq!(taps.press(3, ctrl.clone(), false, true), vec![select(3, &ctrl, PadPress::Press)]);
+        assert_eq!(taps.press(3, ctrl.clone(), true, true), vec![PadKeyStep::Wait(PadTimer::LongPress)]);
+        assert_eq!(taps.release(3), vec![select(3, &ctrl, PadPress::Press)]);
+    }
+}
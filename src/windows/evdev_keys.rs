@@ -0,0 +1,198 @@
+/// Board keys read from the physical keyboards (evdev) while the board window has no keyboard focus.
+/// The keyboards are grabbed so the keys don't reach the focused application, until the board closes
+
+use crate::core::{Modifier, ModifierSide, ModifierState};
+use crate::input::evdev::{self, EvdevDevice};
+use crate::input::keys::{
+    VirtualKey, VK_0, VK_1, VK_2, VK_3, VK_4, VK_5, VK_6, VK_7, VK_8, VK_9, VK_ENTER, VK_ESC,
+    VK_LALT, VK_LCTRL, VK_LSHIFT, VK_LWIN, VK_RALT, VK_RCTRL, VK_RSHIFT, VK_RWIN,
+    VK_NUMPAD0, VK_NUMPAD1, VK_NUMPAD2, VK_NUMPAD3, VK_NUMPAD4, VK_NUMPAD5, VK_NUMPAD6, VK_NUMPAD7, VK_NUMPAD8, VK_NUMPAD9,
+};
+use anyhow::{Result, anyhow};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+/// Keys selecting pads 1 to 9, numpad and number row
+const PAD_KEYS: [[&VirtualKey; 2]; 9] = [
+    [&VK_NUMPAD1, &VK_1], [&VK_NUMPAD2, &VK_2], [&VK_NUMPAD3, &VK_3],
+    [&VK_NUMPAD4, &VK_4], [&VK_NUMPAD5, &VK_5], [&VK_NUMPAD6, &VK_6],
+    [&VK_NUMPAD7, &VK_7], [&VK_NUMPAD8, &VK_8], [&VK_NUMPAD9, &VK_9],
+];
+
+/// Keys selecting the zero key pad, numpad and number row
+const ZERO_KEYS: [&VirtualKey; 2] = [&VK_NUMPAD0, &VK_0];
+
+/// Modifier keys and the modifier and side they hold
+const MODIFIER_KEYS: [(&VirtualKey, Modifier, ModifierSide); 8] = [
+    (&VK_LCTRL, Modifier::Ctrl, ModifierSide::Left), (&VK_RCTRL, Modifier::Ctrl, ModifierSide::Right),
+    (&VK_LSHIFT, Modifier::Shift, ModifierSide::Left), (&VK_RSHIFT, Modifier::Shift, ModifierSide::Right),
+    (&VK_LALT, Modifier::Alt, ModifierSide::Left), (&VK_RALT, Modifier::Alt, ModifierSide::Right),
+    (&VK_LWIN, Modifier::Super, ModifierSide::Left), (&VK_RWIN, Modifier::Super, ModifierSide::Right),
+];
+
+/// Numpad Enter, which has no virtual key
+const KEY_KPENTER: u16 = 104;
+
+/// How often the reader threads check whether the board closed
+const POLL_TIMEOUT_MS: i32 = 100;
+/// Keys held when the board opens (e.g. the trigger) are released before the keyboard is grabbed,
+/// otherwise their release never reaches the application
+const RELEASE_WAIT: Duration = Duration::from_secs(2);
+
+/// Board key pressed on a physical keyboard
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoardKey {
+    Pad(u8, ModifierState),
+    /// A pad key was released, for long presses
+    PadReleased(u8),
+    Modifiers(ModifierState),
+    /// Select the pad selected last time
+    Enter,
+    /// The 0 key without modifiers, for the configured zero key action
+    Zero,
+    Escape,
+}
+
+/// Tracks the modifiers held on one keyboard and translates its key events to board keys
+#[derive(Debug, Default)]
+struct KeyTracker {
+    modifiers: ModifierState,
+}
+
+impl KeyTracker {
+    /// Feed a key event (value: 0 = up, 1 = down, 2 = repeat)
+    fn on_key(&mut self, code: u16, value: i32) -> Option<BoardKey> {
+        let pressed = value != 0;
+        let modifier = MODIFIER_KEYS.iter().find(|(key, _, _)| key.linux_key == code);
+        if let Some((_, modifier, side)) = modifier {
+            let previous = self.modifiers.clone();
+            match pressed {
+                true => self.modifiers.press(*modifier, *side),
+                false => self.modifiers.release(*modifier, *side),
+            }
+            return (self.modifiers != previous).then(|| BoardKey::Modifiers(self.modifiers.clone()));
+        }
+
+        let pad = PAD_KEYS.iter()
+            .position(|keys| keys.iter().any(|key| key.linux_key == code))
+            .map(|index| index as u8 + 1);
+        if value == 0 {
+            return pad.map(BoardKey::PadReleased);
+        }
+        if value != 1 {
+            return None;
+        }
+        if code == VK_ESC.linux_key {
+            return Some(BoardKey::Escape);
+        }
+        if code == VK_ENTER.linux_key || code == KEY_KPENTER {
+            return Some(BoardKey::Enter);
+        }
+        if ZERO_KEYS.iter().any(|key| key.linux_key == code) && self.modifiers.is_none() {
+            return Some(BoardKey::Zero);
+        }
+        pad.map(|pad| BoardKey::Pad(pad, self.modifiers.clone()))
+    }
+}
+
+/// Grabbed keyboards read in background threads, released when dropped
+pub struct EvdevKeys {
+    stop: Arc<AtomicBool>,
+    receiver: mpsc::Receiver<BoardKey>,
+}
+
+impl EvdevKeys {
+    /// Start reading the keyboards with pad keys, fails without access to /dev/input
+    pub fn start() -> Result<Self> {
+        let keys: Vec<u16> = PAD_KEYS.iter().flatten().map(|key| key.linux_key).collect();
+        let devices = evdev::find_keyboards(&[], &keys)?;
+        if devices.is_empty() {
+            return Err(anyhow!("No keyboards found. Make sure you are a member of the 'input' group"));
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        for device in devices {
+            let (stop, sender) = (stop.clone(), sender.clone());
+            std::thread::spawn(move || read_keys(device, stop, sender));
+        }
+        Ok(Self { stop, receiver })
+    }
+
+    /// Next key pressed since the last call, without blocking
+    pub fn try_recv(&self) -> Option<BoardKey> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Drop for EvdevKeys {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn read_keys(mut device: EvdevDevice, stop: Arc<AtomicBool>, sender: mpsc::Sender<BoardKey>) {
+    let started = Instant::now();
+    while device.has_pressed_keys() && started.elapsed() < RELEASE_WAIT && !stop.load(Ordering::Relaxed) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    if let Err(e) = device.grab() {
+        log::warn!("{}", e);
+        return;
+    }
+
+    let mut tracker = KeyTracker::default();
+    while !stop.load(Ordering::Relaxed) {
+        if !device.wait_readable(POLL_TIMEOUT_MS) {
+            continue;
+        }
+        match device.read_key_event() {
+            Ok(Some((code, value))) => {
+                if let Some(key) = tracker.on_key(code, value) {
+                    let _ = sender.send(key);
+                }
+            },
+            Ok(None) => {},
+            Err(e) => {
+                log::warn!("Stopped reading from {}: {}", device.name(), e);
+                break;
+            }
+        }
+    }
+    // Closing the device releases the grab
+    log::debug!("Released {}", device.name());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_tracker() {
+        let mut tracker = KeyTracker::default();
+        assert_eq!(tracker.on_key(VK_NUMPAD7.linux_key, 1), Some(BoardKey::Pad(7, ModifierState::default())));
+        assert_eq!(tracker.on_key(VK_NUMPAD7.linux_key, 2), None); // auto-repeat
+        assert_eq!(tracker.on_key(VK_NUMPAD7.linux_key, 0), Some(BoardKey::PadReleased(7)));
+        assert_eq!(tracker.on_key(VK_ESC.linux_key, 0), None);
+
+        let ctrl: ModifierState = "RCtrl".parse().unwrap();
+        assert_eq!(tracker.on_key(VK_RCTRL.linux_key, 1), Some(BoardKey::Modifiers(ctrl.clone())));
+        assert_eq!(tracker.on_key(VK_RCTRL.linux_key, 2), None);
+        assert_eq!(tracker.on_key(VK_3.linux_key, 1), Some(BoardKey::Pad(3, ctrl)));
+        assert_eq!(tracker.on_key(VK_0.linux_key, 1), None);
+        assert_eq!(tracker.on_key(VK_RCTRL.linux_key, 0), Some(BoardKey::Modifiers(ModifierState::default())));
+
+        // Held on both sides, then on the left side only
+        tracker.on_key(VK_LCTRL.linux_key, 1);
+        assert_eq!(tracker.on_key(VK_RCTRL.linux_key, 1), Some(BoardKey::Modifiers("Ctrl".parse().unwrap())));
+        assert_eq!(tracker.on_key(VK_RCTRL.linux_key, 0), Some(BoardKey::Modifiers("LCtrl".parse().unwrap())));
+        tracker.on_key(VK_LCTRL.linux_key, 0);
+
+        assert_eq!(tracker.on_key(VK_ESC.linux_key, 1), Some(BoardKey::Escape));
+        assert_eq!(tracker.on_key(KEY_KPENTER, 1), Some(BoardKey::Enter));
+        assert_eq!(tracker.on_key(VK_NUMPAD0.linux_key, 1), Some(BoardKey::Zero));
+        assert_eq!(tracker.on_key(VK_0.linux_key, 0), None);
+        assert_eq!(tracker.on_key(VK_NUMPAD1.linux_key - 1, 1), None);
+    }
+}
@@ -0,0 +1,96 @@
+/// Exclusive keyboard grab on X11 while the board is open, so the keys reach the board even when
+/// the window manager moves the focus (focus-follows-mouse, focus stealing prevention).
+/// libX11 is loaded at runtime, GDK's own X connection is used so GTK receives the grabbed keys
+
+use gtk4::glib::{self, translate::ToGlibPtr};
+use gtk4::prelude::*;
+use std::ffi::{c_int, c_ulong, c_void, CStr};
+use std::rc::Rc;
+use std::cell::Cell;
+use std::time::Duration;
+use super::placement;
+
+const LIBRARY: &CStr = c"libX11.so.6";
+
+/// XGrabKeyboard arguments and result
+const GRAB_MODE_ASYNC: c_int = 1;
+const CURRENT_TIME: c_ulong = 0;
+const GRAB_SUCCESS: c_int = 0;
+
+/// The window may not be viewable yet when it is mapped, the grab is retried a few times
+const GRAB_ATTEMPTS: u32 = 20;
+const GRAB_RETRY: Duration = Duration::from_millis(50);
+
+/// X11 functions from libX11 and GDK, displays are Display pointers and windows XIDs
+struct XGrab {
+    get_xdisplay: unsafe extern "C" fn(*mut c_void) -> *mut c_void,
+    get_xid: unsafe extern "C" fn(*mut c_void) -> c_ulong,
+    grab_keyboard: unsafe extern "C" fn(*mut c_void, c_ulong, c_int, c_int, c_int, c_ulong) -> c_int,
+    flush: unsafe extern "C" fn(*mut c_void) -> c_int,
+}
+
+impl XGrab {
+    fn load() -> Option<Self> {
+        unsafe {
+            let library = libc::dlopen(LIBRARY.as_ptr(), libc::RTLD_NOW);
+            if library.is_null() {
+                return None;
+            }
+            let symbol = |handle: *mut c_void, name: &CStr| Some(libc::dlsym(handle, name.as_ptr())).filter(|symbol| !symbol.is_null());
+            Some(Self {
+                get_xdisplay: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void) -> *mut c_void>(symbol(libc::RTLD_DEFAULT, c"gdk_x11_display_get_xdisplay")?),
+                get_xid: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void) -> c_ulong>(symbol(libc::RTLD_DEFAULT, c"gdk_x11_surface_get_xid")?),
+                grab_keyboard: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void, c_ulong, c_int, c_int, c_int, c_ulong) -> c_int>(symbol(library, c"XGrabKeyboard")?),
+                flush: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void) -> c_int>(symbol(library, c"XFlush")?),
+            })
+        }
+    }
+
+    /// Grab the keyboard for the window surface, the X server releases it when the window is unmapped
+    fn grab(&self, display: &gtk4::gdk::Display, surface: &gtk4::gdk::Surface) -> c_int {
+        let display: *mut gtk4::gdk::ffi::GdkDisplay = display.to_glib_none().0;
+        let surface: *mut gtk4::gdk::ffi::GdkSurface = surface.to_glib_none().0;
+        unsafe {
+            let xdisplay = (self.get_xdisplay)(display as *mut c_void);
+            let xid = (self.get_xid)(surface as *mut c_void);
+            let result = (self.grab_keyboard)(xdisplay, xid, 1, GRAB_MODE_ASYNC, GRAB_MODE_ASYNC, CURRENT_TIME);
+            (self.flush)(xdisplay);
+            result
+        }
+    }
+}
+
+/// Grab the keyboard once the window is mapped, only supported on X11
+/// (on Wayland the Overlay window style takes the keyboard exclusively)
+pub fn grab_keyboard(window: &gtk4::ApplicationWindow) {
+    let Some(display) = placement::x11_display() else {
+        log::info!("Keyboard grab is only supported on X11, use the Overlay window style on Wayland");
+        return;
+    };
+    let Some(xgrab) = XGrab::load() else {
+        log::warn!("{} not found, the keyboard is not grabbed", LIBRARY.to_string_lossy());
+        return;
+    };
+    let xgrab = Rc::new(xgrab);
+
+    window.connect_map(move |window| {
+        let Some(surface) = window.surface() else {
+            return;
+        };
+        let (xgrab, display) = (xgrab.clone(), display.clone());
+        let attempts = Cell::new(0);
+        glib::timeout_add_local(GRAB_RETRY, move || {
+            let result = xgrab.grab(&display, &surface);
+            attempts.set(attempts.get() + 1);
+            if result == GRAB_SUCCESS {
+                log::info!("Keyboard grabbed");
+                glib::ControlFlow::Break
+            } else if attempts.get() >= GRAB_ATTEMPTS {
+                log::warn!("Failed to grab the keyboard (status {})", result);
+                glib::ControlFlow::Break
+            } else {
+                glib::ControlFlow::Continue
+            }
+        });
+    });
+}
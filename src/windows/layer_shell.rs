@@ -0,0 +1,145 @@
+/// Wayland layer-shell surfaces for the Overlay window style, so the board appears above
+/// fullscreen windows and gets exclusive keyboard focus on wlroots compositors.
+/// gtk4-layer-shell stays an optional dependency, but it has to be loaded before libwayland-client:
+/// hotkeys does not link it, it is preloaded with LD_PRELOAD and looked up at runtime
+
+use crate::core::{Anchor, MonitorSelection, WindowPlacement};
+use gtk4::glib::translate::ToGlibPtr;
+use gtk4::prelude::*;
+use std::ffi::{c_char, c_int, c_void, CStr};
+
+const LIBRARY: &CStr = c"libgtk4-layer-shell.so.0";
+const NAMESPACE: &CStr = c"hotkeys";
+
+/// GtkLayerShellLayer
+const LAYER_OVERLAY: c_int = 3;
+/// GtkLayerShellKeyboardMode
+const KEYBOARD_MODE_NONE: c_int = 0;
+const KEYBOARD_MODE_EXCLUSIVE: c_int = 1;
+/// GtkLayerShellEdge
+const EDGE_LEFT: c_int = 0;
+const EDGE_RIGHT: c_int = 1;
+const EDGE_TOP: c_int = 2;
+const EDGE_BOTTOM: c_int = 3;
+
+/// gtk4-layer-shell functions, the window arguments are GtkWindow pointers
+struct LayerShell {
+    is_supported: unsafe extern "C" fn() -> c_int,
+    init_for_window: unsafe extern "C" fn(*mut c_void),
+    set_namespace: unsafe extern "C" fn(*mut c_void, *const c_char),
+    set_layer: unsafe extern "C" fn(*mut c_void, c_int),
+    set_keyboard_mode: unsafe extern "C" fn(*mut c_void, c_int),
+    set_anchor: unsafe extern "C" fn(*mut c_void, c_int, c_int),
+    set_margin: unsafe extern "C" fn(*mut c_void, c_int, c_int),
+    set_monitor: unsafe extern "C" fn(*mut c_void, *mut c_void),
+}
+
+impl LayerShell {
+    /// The functions of the preloaded library, None when it is not loaded in the process.
+    /// Loading it now would be too late, GTK is already connected to the compositor
+    fn load() -> Option<Self> {
+        unsafe {
+            let library = libc::dlopen(LIBRARY.as_ptr(), libc::RTLD_NOW | libc::RTLD_GLOBAL | libc::RTLD_NOLOAD);
+            if library.is_null() {
+                return None;
+            }
+            let symbol = |name: &CStr| Some(libc::dlsym(library, name.as_ptr())).filter(|symbol| !symbol.is_null());
+            Some(Self {
+                is_supported: std::mem::transmute::<*mut c_void, unsafe extern "C" fn() -> c_int>(symbol(c"gtk_layer_is_supported")?),
+                init_for_window: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void)>(symbol(c"gtk_layer_init_for_window")?),
+                set_namespace: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void, *const c_char)>(symbol(c"gtk_layer_set_namespace")?),
+                set_layer: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void, c_int)>(symbol(c"gtk_layer_set_layer")?),
+                set_keyboard_mode: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void, c_int)>(symbol(c"gtk_layer_set_keyboard_mode")?),
+                set_anchor: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void, c_int, c_int)>(symbol(c"gtk_layer_set_anchor")?),
+                set_margin: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void, c_int, c_int)>(symbol(c"gtk_layer_set_margin")?),
+                set_monitor: std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void, *mut c_void)>(symbol(c"gtk_layer_set_monitor")?),
+            })
+        }
+    }
+}
+
+/// Whether gtk4-layer-shell was preloaded into the process
+pub fn is_preloaded() -> bool {
+    LayerShell::load().is_some()
+}
+
+/// Make the window an overlay layer surface with exclusive keyboard focus (or none without `keyboard`),
+/// placed by the anchor, offsets and monitor index of the placement. Has to be called before the window is shown.
+/// Returns false when layer-shell is not available: library not preloaded, X11, or a compositor
+/// without the wlr-layer-shell protocol (e.g. GNOME)
+pub fn init_overlay(window: &gtk4::ApplicationWindow, placement: Option<&WindowPlacement>, keyboard: bool) -> bool {
+    let Some(layer_shell) = LayerShell::load() else {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            log::error!("{0} is not preloaded, Overlay style requires starting hotkeys with LD_PRELOAD={0} (package gtk4-layer-shell), falling back to a borderless window",
+                LIBRARY.to_string_lossy());
+        } else {
+            log::warn!("Overlay style requires Wayland, falling back to a borderless window");
+        }
+        return false;
+    };
+    if unsafe { (layer_shell.is_supported)() } == 0 {
+        log::warn!("Layer shell is not supported by the display server, Overlay style falls back to a borderless window");
+        return false;
+    }
+
+    let gtk_window: *mut gtk4::ffi::GtkWindow = window.upcast_ref::<gtk4::Window>().to_glib_none().0;
+    let gtk_window = gtk_window as *mut c_void;
+    let placement = placement.cloned().unwrap_or_default();
+
+    unsafe {
+        (layer_shell.init_for_window)(gtk_window);
+        (layer_shell.set_namespace)(gtk_window, NAMESPACE.as_ptr());
+        (layer_shell.set_layer)(gtk_window, LAYER_OVERLAY);
+        (layer_shell.set_keyboard_mode)(gtk_window, if keyboard { KEYBOARD_MODE_EXCLUSIVE } else { KEYBOARD_MODE_NONE });
+
+        for (edge, margin) in anchor_margins(placement.anchor, placement.x, placement.y) {
+            (layer_shell.set_anchor)(gtk_window, edge, 1);
+            (layer_shell.set_margin)(gtk_window, edge, margin);
+        }
+
+        if let Some(MonitorSelection::Index(index)) = placement.monitor {
+            let monitor = gtk4::gdk::Display::default()
+                .and_then(|display| display.monitors().item(index as u32))
+                .and_then(|monitor| monitor.downcast::<gtk4::gdk::Monitor>().ok());
+            match monitor {
+                Some(monitor) => {
+                    let gdk_monitor: *mut gtk4::gdk::ffi::GdkMonitor = monitor.to_glib_none().0;
+                    (layer_shell.set_monitor)(gtk_window, gdk_monitor as *mut c_void);
+                },
+                None => log::warn!("Monitor {} not found, the compositor chooses the output", index),
+            }
+        }
+    }
+    log::info!("Board window is an overlay layer surface");
+    true
+}
+
+/// Edges a layer surface is anchored to with their margins, unanchored axes are centered.
+/// Offsets move the window right and down like on X11, margins push it away from the edge
+fn anchor_margins(anchor: Anchor, x: i32, y: i32) -> Vec<(c_int, c_int)> {
+    let (fx, fy) = anchor.factors();
+    let horizontal = match fx {
+        0.0 => Some((EDGE_LEFT, x)),
+        1.0 => Some((EDGE_RIGHT, -x)),
+        _ => None,
+    };
+    let vertical = match fy {
+        0.0 => Some((EDGE_TOP, y)),
+        1.0 => Some((EDGE_BOTTOM, -y)),
+        _ => None,
+    };
+    horizontal.into_iter().chain(vertical).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchor_margins() {
+        assert_eq!(anchor_margins(Anchor::Center, 10, 10), vec![]);
+        assert_eq!(anchor_margins(Anchor::TopLeft, 20, 30), vec![(EDGE_LEFT, 20), (EDGE_TOP, 30)]);
+        assert_eq!(anchor_margins(Anchor::BottomRight, -20, -30), vec![(EDGE_RIGHT, 20), (EDGE_BOTTOM, 30)]);
+        assert_eq!(anchor_margins(Anchor::Top, 0, 40), vec![(EDGE_TOP, 40)]);
+    }
+}
@@ -1,7 +1,9 @@
 /// Linux layout system for HotKeys UI
 /// Provides window positioning and styling abstractions
 
+use crate::core::{Anchor, ImageFit, WindowPlacement};
 use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 
 
 /// Cross-platform rectangle structure
@@ -33,6 +35,39 @@ impl Rect {
     pub fn y(&self) -> f64 {
         self.top
     }
+
+    /// Rectangle of an image with the given size scaled into this rectangle, centered
+    pub fn fit(&self, width: f64, height: f64, fit: ImageFit) -> Rect {
+        let (scale_x, scale_y) = (self.width() / width, self.height() / height);
+        let (width, height) = match fit {
+            ImageFit::Cover => (width * scale_x.max(scale_y), height * scale_x.max(scale_y)),
+            ImageFit::Contain => (width * scale_x.min(scale_y), height * scale_x.min(scale_y)),
+            ImageFit::Stretch => (self.width(), self.height()),
+        };
+        let left = self.left + (self.width() - width) / 2.0;
+        let top = self.top + (self.height() - height) / 2.0;
+        Rect::new(left, top, left + width, top + height)
+    }
+
+    /// Rectangle with the given size whose anchor point is at the same point of this rectangle,
+    /// for an empty rectangle (a point) the anchor point of the result is at that point
+    pub fn anchored(&self, width: f64, height: f64, anchor: Anchor) -> Rect {
+        let (fx, fy) = anchor.factors();
+        let left = self.left + (self.width() - width) * fx;
+        let top = self.top + (self.height() - height) * fy;
+        Rect::new(left, top, left + width, top + height)
+    }
+
+    /// This rectangle moved into the bounds, as far as it fits
+    pub fn moved_into(&self, bounds: Rect) -> Rect {
+        let left = self.left.min(bounds.right - self.width()).max(bounds.left);
+        let top = self.top.min(bounds.bottom - self.height()).max(bounds.top);
+        Rect::new(left, top, left + self.width(), top + self.height())
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.left && x < self.right && y >= self.top && y < self.bottom
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -42,11 +77,38 @@ pub struct Size {
 }
 
 
+/// Milliseconds a pad key is held to select the long press actions of the pad, when not configured
+pub const DEFAULT_LONG_PRESS: u64 = 500;
+
+/// Milliseconds a second tap of a number key selects from the double tap pads, when not configured
+pub const DEFAULT_DOUBLE_TAP: u64 = 300;
+
 /// Window layout configuration
 #[derive(Clone, Debug, PartialEq)]
 pub struct WindowLayout {
     pub style: WindowStyle,
     pub size: Size,
+    /// Fade in/out duration in milliseconds
+    pub fade: u64,
+    /// Window position, left to the window manager when not set
+    pub placement: Option<WindowPlacement>,
+    /// Window manager hints, keep the window above others and out of the taskbar and pager
+    pub keep_above: bool,
+    pub skip_taskbar: bool,
+    /// Grab the keyboard while the window is open
+    pub grab_keyboard: bool,
+    /// Show the window without taking the keyboard focus
+    pub no_focus: bool,
+    /// Latch a modifier tapped alone until another board is shown or it is tapped again
+    pub sticky_modifiers: bool,
+    /// Milliseconds a pad key is held to select the long press actions of the pad
+    pub long_press: u64,
+    /// Milliseconds a second tap of a number key selects from the double tap pads of the board
+    pub double_tap: u64,
+    /// The 0 key selects the zero key pad, set when an action is configured for it
+    pub zero_key: bool,
+    /// Size and position the window had when it was last closed
+    pub geometry: Option<WindowGeometry>,
 }
 
 impl Default for WindowLayout {
@@ -54,10 +116,63 @@ impl Default for WindowLayout {
         WindowLayout {
             style: WindowStyle::default(),
             size: Size { width: 800.0, height: 600.0 },
+            fade: 0,
+            placement: None,
+            keep_above: false,
+            skip_taskbar: false,
+            grab_keyboard: false,
+            no_focus: false,
+            sticky_modifiers: false,
+            long_press: DEFAULT_LONG_PRESS,
+            double_tap: DEFAULT_DOUBLE_TAP,
+            zero_key: false,
+            geometry: None,
+        }
+    }
+}
+
+
+/// Window size and position on screen (unknown on Wayland), written as X geometry "800x600+100+50"
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowGeometry {
+    pub width: i32,
+    pub height: i32,
+    pub position: Option<(i32, i32)>,
+}
+
+impl Display for WindowGeometry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)?;
+        match self.position {
+            Some((x, y)) => write!(f, "{:+}{:+}", x, y),
+            None => Ok(()),
         }
     }
 }
 
+impl FromStr for WindowGeometry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid window geometry '{}'", s);
+        let (size, position) = match s.find(['+', '-']) {
+            Some(index) => s.split_at(index),
+            None => (s, ""),
+        };
+        let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+        let (width, height) = (width.parse().map_err(|_| invalid())?, height.parse().map_err(|_| invalid())?);
+
+        let position = match position.rfind(['+', '-']) {
+            Some(index) if index > 0 => {
+                let (x, y) = position.split_at(index);
+                Some((x.parse().map_err(|_| invalid())?, y.parse().map_err(|_| invalid())?))
+            },
+            Some(_) => return Err(invalid()),
+            None => None,
+        };
+        Ok(WindowGeometry { width, height, position })
+    }
+}
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum WindowStyle {
@@ -65,6 +180,9 @@ pub enum WindowStyle {
     Window,
     /// Borderless window that shows in taskbar
     Taskbar,
+    /// Wayland layer-shell surface above all windows with exclusive keyboard focus,
+    /// a borderless window where layer-shell is not available
+    Overlay,
 }
 
 impl Default for WindowStyle {
@@ -78,6 +196,7 @@ impl WindowStyle {
         match s {
             "Taskbar" => WindowStyle::Taskbar,
             "Window" => WindowStyle::Window,
+            "Overlay" => WindowStyle::Overlay,
             _ => WindowStyle::Window, // Fallback variant
         }
     }
@@ -86,7 +205,7 @@ impl WindowStyle {
     pub fn has_decorations(&self) -> bool {
         match self {
             WindowStyle::Window => true,
-            WindowStyle::Taskbar => false,
+            WindowStyle::Taskbar | WindowStyle::Overlay => false,
         }
     }
 
@@ -185,6 +304,39 @@ mod tests {
         assert_eq!(rect.height(), 100.0);
     }
 
+    #[test]
+    fn test_rect_fit() {
+        let area = Rect::new(0.0, 60.0, 900.0, 600.0);
+        assert_eq!(area.fit(100.0, 100.0, ImageFit::Cover), Rect::new(0.0, -120.0, 900.0, 780.0));
+        assert_eq!(area.fit(100.0, 100.0, ImageFit::Contain), Rect::new(180.0, 60.0, 720.0, 600.0));
+        assert_eq!(area.fit(100.0, 100.0, ImageFit::Stretch), area);
+    }
+
+    #[test]
+    fn test_rect_anchored() {
+        let monitor = Rect::new(1920.0, 0.0, 3840.0, 1080.0);
+        assert_eq!(monitor.anchored(800.0, 600.0, Anchor::Center), Rect::new(2480.0, 240.0, 3280.0, 840.0));
+        assert_eq!(monitor.anchored(800.0, 600.0, Anchor::BottomRight), Rect::new(3040.0, 480.0, 3840.0, 1080.0));
+
+        let cursor = Rect::new(100.0, 50.0, 100.0, 50.0);
+        assert_eq!(cursor.anchored(800.0, 600.0, Anchor::TopLeft), Rect::new(100.0, 50.0, 900.0, 650.0));
+        assert_eq!(cursor.anchored(800.0, 600.0, Anchor::Center).moved_into(monitor), Rect::new(1920.0, 0.0, 2720.0, 600.0));
+        assert!(monitor.contains(1920.0, 0.0) && !monitor.contains(3840.0, 0.0));
+    }
+
+    #[test]
+    fn test_window_geometry() {
+        let geometry: WindowGeometry = "800x600+100-20".parse().unwrap();
+        assert_eq!(geometry, WindowGeometry { width: 800, height: 600, position: Some((100, -20)) });
+        assert_eq!(geometry.to_string(), "800x600+100-20");
+
+        let geometry: WindowGeometry = "1024x768".parse().unwrap();
+        assert_eq!((geometry.position, geometry.to_string().as_str()), (None, "1024x768"));
+
+        assert!("800x".parse::<WindowGeometry>().is_err());
+        assert!("800x600+100".parse::<WindowGeometry>().is_err());
+    }
+
     #[test]
     fn test_window_layout_default() {
         let layout = WindowLayout::default();
@@ -1,4 +1,11 @@
 pub mod board;
 pub mod renderer;
+pub mod settings_window;
+pub mod scheme_designer;
 pub mod layout;
-pub mod modifier_handler;
\ No newline at end of file
+pub mod modifier_handler;
+pub mod placement;
+pub mod layer_shell;
+pub mod backdrop;
+pub mod keyboard_grab;
+pub mod evdev_keys;
\ No newline at end of file
@@ -1,6 +1,8 @@
 /// Handler for modifier keys (Ctrl, Shift, Alt, Super)
+/// Tracks the side each modifier is held on, for modifier pads bound to one side (e.g. "RAlt")
+/// and latches the modifiers tapped with sticky modifiers
 
-use crate::core::ModifierState;
+use crate::core::{Modifier, ModifierSide, ModifierState};
 use gtk4::gdk;
 
 pub struct ModifierHandler {
@@ -21,51 +23,164 @@ impl ModifierHandler {
         &self.state
     }
 
+    /// Modifier and side of a modifier key
+    fn modifier_key(keyval: gdk::Key) -> Option<(Modifier, ModifierSide)> {
+        match keyval {
+            gdk::Key::Control_L => Some((Modifier::Ctrl, ModifierSide::Left)),
+            gdk::Key::Control_R => Some((Modifier::Ctrl, ModifierSide::Right)),
+            gdk::Key::Shift_L => Some((Modifier::Shift, ModifierSide::Left)),
+            gdk::Key::Shift_R => Some((Modifier::Shift, ModifierSide::Right)),
+            gdk::Key::Alt_L => Some((Modifier::Alt, ModifierSide::Left)),
+            gdk::Key::Alt_R => Some((Modifier::Alt, ModifierSide::Right)),
+            gdk::Key::Super_L => Some((Modifier::Super, ModifierSide::Left)),
+            gdk::Key::Super_R => Some((Modifier::Super, ModifierSide::Right)),
+            _ => None,
+        }
+    }
+
     /// Handle key press event
     /// Returns true if this was a modifier key we handle, false otherwise
     pub fn handle_key_press(&mut self, keyval: gdk::Key) -> bool {
-        match keyval {
-            gdk::Key::Control_L | gdk::Key::Control_R => {
-                self.state.ctrl = true;
-                true
-            },
-            gdk::Key::Shift_L | gdk::Key::Shift_R => {
-                self.state.shift = true;
-                true
-            },
-            gdk::Key::Alt_L | gdk::Key::Alt_R => {
-                self.state.alt = true;
-                true
-            },
-            gdk::Key::Super_L | gdk::Key::Super_R => {
-                self.state.super_key = true;
-                true
-            },
-            _ => false,
-        }
+        let Some((modifier, side)) = Self::modifier_key(keyval) else {
+            return false;
+        };
+        self.state.press(modifier, side);
+        true
     }
 
     /// Handle key release event
     /// Returns true if this was a modifier key we handle, false otherwise
     pub fn handle_key_release(&mut self, keyval: gdk::Key) -> bool {
-        match keyval {
-            gdk::Key::Control_L | gdk::Key::Control_R => {
-                self.state.ctrl = false;
-                true
-            },
-            gdk::Key::Shift_L | gdk::Key::Shift_R => {
-                self.state.shift = false;
-                true
-            },
-            gdk::Key::Alt_L | gdk::Key::Alt_R => {
-                self.state.alt = false;
-                true
-            },
-            gdk::Key::Super_L | gdk::Key::Super_R => {
-                self.state.super_key = false;
-                true
-            },
-            _ => false,
+        let Some((modifier, side)) = Self::modifier_key(keyval) else {
+            return false;
+        };
+        self.state.release(modifier, side);
+        true
+    }
+}
+
+/// Modifiers of the board window: the modifiers held and, with sticky modifiers, the modifiers latched by
+/// tapping them (pressed and released with no other key in between) until another board is shown or they are tapped again
+#[derive(Debug, Default)]
+pub struct BoardModifiers {
+    sticky: bool,
+    held: ModifierState,
+    latched: ModifierState,
+    /// Modifier pressed alone with no other key since, latched when it is released
+    tapped: Option<(Modifier, ModifierSide)>,
+}
+
+impl BoardModifiers {
+    pub fn new(sticky: bool) -> Self {
+        Self { sticky, ..Default::default() }
+    }
+
+    /// Modifiers held on the keyboard
+    pub fn held(&self) -> &ModifierState {
+        &self.held
+    }
+
+    pub fn latched(&self) -> &ModifierState {
+        &self.latched
+    }
+
+    /// Modifiers in effect, held or latched
+    pub fn state(&self) -> ModifierState {
+        self.with_latched(&self.held)
+    }
+
+    /// `held` and the latched modifiers it does not hold, on the side they were latched on
+    pub fn with_latched(&self, held: &ModifierState) -> ModifierState {
+        let mut state = held.clone();
+        for modifier in Modifier::ALL.into_iter().filter(|modifier| self.latched.is_held(*modifier) && !held.is_held(*modifier)) {
+            state.press(modifier, self.latched.side(modifier));
         }
+        state
+    }
+
+    /// Modifier keys pressed or released, a modifier tapped alone is latched, or released when it is latched
+    pub fn set_held(&mut self, held: ModifierState) {
+        if self.sticky {
+            let changed = |pressed: bool| -> Vec<Modifier> {
+                Modifier::ALL.into_iter()
+                    .filter(|modifier| held.is_held(*modifier) == pressed && self.held.is_held(*modifier) != pressed)
+                    .collect()
+            };
+            let (pressed, released) = (changed(true), changed(false));
+            self.tapped = match (self.tapped, pressed.as_slice(), released.as_slice()) {
+                (_, [modifier], []) if self.held.is_none() => Some((*modifier, held.side(*modifier))),
+                (Some((modifier, side)), [], [released]) if modifier == *released => {
+                    self.toggle(modifier, side);
+                    None
+                },
+                // Key repeat
+                (tapped, [], []) if held == self.held => tapped,
+                _ => None,
+            };
+        }
+        self.held = held;
+    }
+
+    /// Another key pressed, the modifier pressed before is not tapped alone
+    pub fn other_key(&mut self) {
+        self.tapped = None;
+    }
+
+    /// Release the latched modifiers, once another board is shown
+    pub fn release_latched(&mut self) {
+        self.latched = ModifierState::default();
+        self.tapped = None;
+    }
+
+    fn toggle(&mut self, modifier: Modifier, side: ModifierSide) {
+        match self.latched.is_held(modifier) {
+            true => self.latched.release(modifier, ModifierSide::Any),
+            false => self.latched.press(modifier, side),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn held(modifiers: &str) -> ModifierState {
+        modifiers.parse().unwrap()
+    }
+
+    #[test]
+    fn test_sticky_modifiers() {
+        let mut modifiers = BoardModifiers::new(true);
+        modifiers.set_held(held("LCtrl"));
+        modifiers.set_held(held("LCtrl"));
+        modifiers.set_held(ModifierState::default());
+        assert_eq!(modifiers.latched(), &held("LCtrl"));
+        assert_eq!(modifiers.with_latched(&held("Shift")), held("LCtrl+Shift"));
+
+        // A modifier held with a key or another modifier is not latched
+        modifiers.set_held(held("Shift"));
+        modifiers.other_key();
+        modifiers.set_held(ModifierState::default());
+        modifiers.set_held(held("Alt"));
+        modifiers.set_held(held("Alt+Super"));
+        modifiers.set_held(held("Alt"));
+        modifiers.set_held(ModifierState::default());
+        assert_eq!(modifiers.state(), held("LCtrl"));
+
+        // Tapped again it is released
+        modifiers.set_held(held("RCtrl"));
+        assert_eq!(modifiers.state(), held("RCtrl"));
+        modifiers.set_held(ModifierState::default());
+        assert!(modifiers.state().is_none());
+
+        modifiers.set_held(held("Super"));
+        modifiers.set_held(ModifierState::default());
+        modifiers.release_latched();
+        assert!(modifiers.latched().is_none());
+
+        let mut modifiers = BoardModifiers::new(false);
+        modifiers.set_held(held("Ctrl"));
+        modifiers.set_held(ModifierState::default());
+        assert!(modifiers.state().is_none());
     }
 }
@@ -0,0 +1,218 @@
+/// Board window placement on X11: a monitor, an anchor and offsets, or the mouse cursor.
+/// GTK4 cannot move toplevel windows, they are moved with xdotool once they are active
+
+use crate::core::{MonitorRule, MonitorSelection, WindowPlacement};
+use super::layout::{Rect, Size};
+use gtk4::glib::translate::ToGlibPtr;
+use gtk4::prelude::*;
+use gtk4::gdk;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::process::Command;
+
+/// Move the window to its placement when it first becomes active, only supported on X11
+pub fn place_window(window: &gtk4::ApplicationWindow, placement: &WindowPlacement, size: &Size) {
+    let Some(display) = x11_display() else {
+        log::info!("Window placement is only supported on X11, leaving it to the compositor");
+        return;
+    };
+
+    // Read before the board window takes the focus from the previously focused window
+    let target = target_rect(&monitor_areas(&display), placement, size);
+    log::debug!("Placing window at {:?}", target);
+    move_window(window, target.x().round() as i32, target.y().round() as i32);
+}
+
+/// Move the window to a position on screen when it first becomes active, only supported on X11
+pub fn move_window(window: &gtk4::ApplicationWindow, x: i32, y: i32) {
+    if x11_display().is_none() {
+        return;
+    }
+    let moved = Cell::new(false);
+    window.connect_is_active_notify(move |window| {
+        if window.is_active() && !moved.replace(true) {
+            move_active_window(x, y);
+        }
+    });
+}
+
+/// Set window manager state hints when the window first becomes active, only supported on X11
+pub fn set_window_state(window: &gtk4::ApplicationWindow, keep_above: bool, skip_taskbar: bool) {
+    let args = window_state_args(keep_above, skip_taskbar);
+    if args.is_empty() || x11_display().is_none() {
+        return;
+    }
+    let applied = Cell::new(false);
+    window.connect_is_active_notify(move |window| {
+        if window.is_active() && !applied.replace(true) && xdotool(&args).is_none() {
+            log::warn!("Failed to set the board window state");
+        }
+    });
+}
+
+/// Ask the window manager not to focus the window when it is shown (_NET_WM_USER_TIME 0), only supported on X11
+pub fn show_without_focus(window: &gtk4::ApplicationWindow) {
+    if x11_display().is_none() {
+        log::info!("Showing the board without focus is up to the compositor on Wayland");
+        return;
+    }
+    // Part of GDK, looked up at runtime as there are no bindings for the X11 backend
+    let set_user_time = unsafe { libc::dlsym(libc::RTLD_DEFAULT, c"gdk_x11_surface_set_user_time".as_ptr()) };
+    if set_user_time.is_null() {
+        log::warn!("gdk_x11_surface_set_user_time not found, the board window may take the focus");
+        return;
+    }
+    let set_user_time = unsafe { std::mem::transmute::<*mut c_void, unsafe extern "C" fn(*mut c_void, u32)>(set_user_time) };
+    window.connect_realize(move |window| {
+        if let Some(surface) = window.surface() {
+            let surface: *mut gdk::ffi::GdkSurface = surface.to_glib_none().0;
+            unsafe { set_user_time(surface as *mut c_void, 0) };
+        }
+    });
+}
+
+/// Position of the window on screen if it is the active window, only known on X11
+pub fn window_position(window: &gtk4::ApplicationWindow) -> Option<(i32, i32)> {
+    x11_display().filter(|_| window.is_active())?;
+    let values = xdotool(&["getactivewindow", "getwindowgeometry", "--shell"])?;
+    Some((*values.get("X")? as i32, *values.get("Y")? as i32))
+}
+
+pub fn x11_display() -> Option<gdk::Display> {
+    gdk::Display::default().filter(|display| display.type_().name() == "GdkX11Display")
+}
+
+/// Window rectangle on screen, kept inside its monitor
+fn target_rect(monitors: &[Rect], placement: &WindowPlacement, size: &Size) -> Rect {
+    let cursor = placement.at_cursor.then(cursor_position).flatten();
+    let monitor_at = |x: f64, y: f64| monitors.iter().find(|monitor| monitor.contains(x, y)).copied();
+    let first_monitor = monitors.first().copied().unwrap_or(Rect::new(0.0, 0.0, size.width, size.height));
+
+    let monitor = match (cursor, placement.monitor) {
+        (Some((x, y)), _) => monitor_at(x, y),
+        (None, Some(MonitorSelection::Index(index))) => {
+            let monitor = monitors.get(index).copied();
+            if monitor.is_none() {
+                log::warn!("Monitor {} not found, {} monitor(s) connected", index, monitors.len());
+            }
+            monitor
+        },
+        (None, Some(MonitorSelection::Rule(MonitorRule::WithFocusedWindow))) => active_window_geometry()
+            .and_then(|window| monitor_at(window.x() + window.width() / 2.0, window.y() + window.height() / 2.0)),
+        (None, None) => None,
+    }.unwrap_or(first_monitor);
+
+    let reference = match cursor {
+        Some((x, y)) => Rect::new(x, y, x, y),
+        None => monitor,
+    };
+    let rect = reference.anchored(size.width, size.height, placement.anchor);
+    let (x, y) = (rect.x() + placement.x as f64, rect.y() + placement.y as f64);
+    Rect::new(x, y, x + size.width, y + size.height).moved_into(monitor)
+}
+
+fn monitor_areas(display: &gdk::Display) -> Vec<Rect> {
+    display.monitors().iter::<gdk::Monitor>()
+        .filter_map(|monitor| monitor.ok())
+        .map(|monitor| {
+            let geometry = monitor.geometry();
+            let (x, y) = (geometry.x() as f64, geometry.y() as f64);
+            Rect::new(x, y, x + geometry.width() as f64, y + geometry.height() as f64)
+        })
+        .collect()
+}
+
+/// Mouse cursor position on screen
+fn cursor_position() -> Option<(f64, f64)> {
+    let values = xdotool(&["getmouselocation", "--shell"])?;
+    Some((*values.get("X")? as f64, *values.get("Y")? as f64))
+}
+
+/// Geometry of the focused window
+fn active_window_geometry() -> Option<Rect> {
+    let values = xdotool(&["getactivewindow", "getwindowgeometry", "--shell"])?;
+    let (x, y) = (*values.get("X")? as f64, *values.get("Y")? as f64);
+    Some(Rect::new(x, y, x + *values.get("WIDTH")? as f64, y + *values.get("HEIGHT")? as f64))
+}
+
+fn move_active_window(x: i32, y: i32) {
+    if xdotool(&["getactivewindow", "windowmove", &x.to_string(), &y.to_string()]).is_none() {
+        log::warn!("Failed to move the board window to {},{}", x, y);
+    }
+}
+
+/// xdotool arguments adding _NET_WM_STATE hints to the active window, empty when there are none
+fn window_state_args(keep_above: bool, skip_taskbar: bool) -> Vec<&'static str> {
+    let mut states = Vec::new();
+    if keep_above {
+        states.extend(["--add", "ABOVE"]);
+    }
+    if skip_taskbar {
+        states.extend(["--add", "SKIP_TASKBAR", "--add", "SKIP_PAGER"]);
+    }
+    if states.is_empty() {
+        return states;
+    }
+    ["getactivewindow", "windowstate"].into_iter().chain(states).collect()
+}
+
+/// Run xdotool and parse its `--shell` output, failures are logged
+fn xdotool(args: &[&str]) -> Option<HashMap<String, i64>> {
+    match Command::new("xdotool").args(args).output() {
+        Ok(output) if output.status.success() => Some(parse_shell_values(&String::from_utf8_lossy(&output.stdout))),
+        Ok(output) => {
+            log::warn!("xdotool {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+            None
+        },
+        Err(e) => {
+            log::warn!("Failed to run xdotool (required for window placement): {}", e);
+            None
+        },
+    }
+}
+
+/// Parse numeric NAME=value lines
+/// Example input: "X=1204\nY=533\nSCREEN=0\nWINDOW=48234503"
+fn parse_shell_values(output: &str) -> HashMap<String, i64> {
+    output.lines()
+        .filter_map(|line| line.split_once('='))
+        .filter_map(|(name, value)| value.trim().parse().ok().map(|value| (name.trim().to_string(), value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Anchor;
+
+    #[test]
+    fn test_parse_shell_values() {
+        let values = parse_shell_values("WINDOW=48234503\nX=1204\nY=-20\nWIDTH=800\nHEIGHT=600\nSCREEN=0\n");
+        assert_eq!((values["X"], values["Y"], values["WIDTH"]), (1204, -20, 800));
+        assert!(parse_shell_values("").is_empty());
+    }
+
+    #[test]
+    fn test_window_state_args() {
+        assert!(window_state_args(false, false).is_empty());
+        assert_eq!(window_state_args(true, false), vec!["getactivewindow", "windowstate", "--add", "ABOVE"]);
+        assert_eq!(window_state_args(true, true), vec!["getactivewindow", "windowstate", "--add", "ABOVE", "--add", "SKIP_TASKBAR", "--add", "SKIP_PAGER"]);
+    }
+
+    #[test]
+    fn test_target_rect() {
+        let monitors = [Rect::new(0.0, 0.0, 1920.0, 1080.0), Rect::new(1920.0, 0.0, 3840.0, 1080.0)];
+        let size = Size { width: 800.0, height: 600.0 };
+
+        let placement = WindowPlacement { monitor: Some(MonitorSelection::Index(1)), anchor: Anchor::TopRight, x: -20, y: 20, ..Default::default() };
+        assert_eq!(target_rect(&monitors, &placement, &size), Rect::new(3020.0, 20.0, 3820.0, 620.0));
+
+        let placement = WindowPlacement { monitor: Some(MonitorSelection::Index(5)), x: -2000, ..Default::default() };
+        assert_eq!(target_rect(&monitors, &placement, &size), Rect::new(0.0, 240.0, 800.0, 840.0));
+
+        let placement: WindowPlacement = serde_json::from_str(r#"{ "monitor": "with-focused-window", "anchor": "Bottom" }"#).unwrap();
+        assert_eq!(placement.monitor, Some(MonitorSelection::Rule(MonitorRule::WithFocusedWindow)));
+        assert_eq!(placement.anchor, Anchor::Bottom);
+    }
+}
@@ -1,24 +1,220 @@
 /// Cairo-based rendering for the 3x3 board window
 /// Handles all drawing operations for board display
 
-use crate::core::{Board, ColorScheme, ModifierState, Pad, TextStyle, Resources};
+use crate::core::{BackgroundImage, Board, ColorScheme, CountdownStyle, GradientDirection, IconKind, IconPlacement, ModifierState, Pad, TextStyle, Resources};
 use super::layout::{BoardLayout, Rect};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::fs::File;
+use std::sync::Mutex;
 
 // use gtk4::prelude::*;
-use gtk4::cairo::{Context, FontSlant, FontWeight, ImageSurface};
+use gtk4::cairo::{Context, FontSlant, FontWeight, ImageSurface, LinearGradient};
 use pango::{FontDescription, Weight};
 use pangocairo::functions as pangocairo;
+use gtk4::prelude::*;
 
 
-pub fn draw_board(ctx: &Context, board: &dyn Board, layout: &BoardLayout, resources: &Resources, selected_pad: Option<u8>, remaining_time: Option<u64>, current_modifiers: &ModifierState) {
+/// Draw the board without its countdown, see [draw_countdown]
+pub fn draw_board(ctx: &Context, board: &dyn Board, layout: &BoardLayout, resources: &Resources, selection: Option<Selection>, last_pad: Option<u8>, current_modifiers: &ModifierState) {
     BoardRenderer::new(
         board.color_scheme(), board.text_style(), layout, resources
-    ).draw_board(ctx, board, selected_pad, remaining_time, current_modifiers);
+    ).draw_board(ctx, board, selection, last_pad, current_modifiers);
 }
 
+/// Draw the countdown over the board header, in the countdown style of the board
+pub fn draw_countdown(ctx: &Context, board: &dyn Board, layout: &BoardLayout, resources: &Resources, countdown: Countdown) {
+    BoardRenderer::new(
+        board.color_scheme(), board.text_style(), layout, resources
+    ).draw_countdown_timer(ctx, countdown);
+}
+
+/// Draw the edit mode marks over the board: a dashed frame around the pads and "EDIT" in the header
+pub fn draw_edit_mode(ctx: &Context, board: &dyn Board, layout: &BoardLayout, resources: &Resources) {
+    BoardRenderer::new(
+        board.color_scheme(), board.text_style(), layout, resources
+    ).draw_edit_mode(ctx);
+}
+
+/// Draw the latched modifiers (sticky modifiers) left-aligned in the header, e.g. "Ctrl+Shift"
+pub fn draw_latched_modifiers(ctx: &Context, board: &dyn Board, layout: &BoardLayout, resources: &Resources, latched: &ModifierState) {
+    BoardRenderer::new(
+        board.color_scheme(), board.text_style(), layout, resources
+    ).draw_latched_modifiers(ctx, latched);
+}
+
+/// Render the board as shown when opened, without a countdown, into a PNG file of the given size
+pub fn write_png(board: &dyn Board, width: i32, height: i32, resources: &Resources, path: &Path) -> anyhow::Result<()> {
+    let image = ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+    let ctx = Context::new(&image)?;
+    let layout = BoardLayout::new(width as f64, height as f64);
+    let color_scheme = board.color_scheme();
+
+    draw_background(&ctx, color_scheme, color_scheme.opacity(), Rect::new(0.0, 0.0, width as f64, height as f64));
+    draw_board(&ctx, board, &layout, resources, None, None, &ModifierState::default());
+    drop(ctx);
+
+    image.write_to_png(&mut File::create(path)?)?;
+    Ok(())
+}
+
+/// Offscreen image of a drawing, rendered again only when its key changes.
+/// Keeps repeated draws (e.g. every countdown tick) from laying out text and rendering icons again
+pub struct RenderCache<K> {
+    image: Option<(K, ImageSurface)>,
+}
+
+impl<K: PartialEq> Default for RenderCache<K> {
+    fn default() -> Self {
+        Self { image: None }
+    }
+}
+
+impl<K: PartialEq> RenderCache<K> {
+    /// Paint the cached image of the given size, calling `render` to draw it first when the key or size changed
+    pub fn paint(&mut self, ctx: &Context, width: i32, height: i32, key: K, render: impl FnOnce(&Context)) {
+        let (scale_x, scale_y) = ctx.target().device_scale();
+        let size = ((width as f64 * scale_x).ceil() as i32, (height as f64 * scale_y).ceil() as i32);
+
+        let cached = self.image.as_ref()
+            .is_some_and(|(cached_key, image)| *cached_key == key && (image.width(), image.height()) == size);
+        if !cached {
+            self.image = ImageSurface::create(cairo::Format::ARgb32, size.0, size.1).ok()
+                .and_then(|image| {
+                    image.set_device_scale(scale_x, scale_y);
+                    render(&Context::new(&image).ok()?);
+                    image.flush();
+                    Some((key, image))
+                });
+        }
+
+        if let Some((_, image)) = &self.image {
+            ctx.save().ok();
+            ctx.set_operator(cairo::Operator::Source);
+            ctx.set_source_surface(image, 0.0, 0.0).ok();
+            ctx.paint().ok();
+            ctx.restore().ok();
+        }
+    }
+}
+
+/// Selected pad and how far the feedback window has progressed (0 to 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Selection {
+    pub pad: u8,
+    pub progress: f64,
+}
+
+impl Selection {
+    /// Highlight pulse: brightens quickly to SELECTION_FLASH_ALPHA, then fades back to SELECTION_ALPHA
+    fn highlight_alpha(&self) -> f64 {
+        let progress = self.progress.clamp(0.0, 1.0);
+        let flash = match progress < SELECTION_FLASH_PEAK {
+            true => progress / SELECTION_FLASH_PEAK,
+            false => (1.0 - (progress - SELECTION_FLASH_PEAK) / (1.0 - SELECTION_FLASH_PEAK)).powi(2),
+        };
+        SELECTION_ALPHA + (SELECTION_FLASH_ALPHA - SELECTION_ALPHA) * flash
+    }
+}
+
+/// Seconds left until the board closes, out of the configured timeout
+#[derive(Debug, Clone, Copy)]
+pub struct Countdown {
+    pub remaining: u64,
+    pub total: u64,
+}
+
+impl Countdown {
+    /// Part of the timeout left, from 1 down to 0
+    fn fraction(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            total => (self.remaining as f64 / total as f64).min(1.0),
+        }
+    }
+}
 
 
+/// Fill the area with the color scheme background, a linear gradient when `background2` is set
+pub fn draw_background(ctx: &Context, color_scheme: &ColorScheme, opacity: f64, area: Rect) {
+    let bg_color = color_scheme.background().to_rgb();
+    let (x, y, width, height) = (area.x(), area.y(), area.width(), area.height());
+
+    ctx.save().unwrap();
+    ctx.rectangle(x, y, width, height);
+    match color_scheme.background2() {
+        Some(bg_color2) => {
+            let bg_color2 = bg_color2.to_rgb();
+            let (end_x, end_y) = match color_scheme.gradient {
+                GradientDirection::Vertical => (x, y + height),
+                GradientDirection::Horizontal => (x + width, y),
+                GradientDirection::Diagonal => (x + width, y + height),
+            };
+            let gradient = LinearGradient::new(x, y, end_x, end_y);
+            gradient.add_color_stop_rgba(0.0, bg_color.0, bg_color.1, bg_color.2, opacity);
+            gradient.add_color_stop_rgba(1.0, bg_color2.0, bg_color2.1, bg_color2.2, opacity);
+            ctx.set_source(&gradient).unwrap();
+        },
+        None => ctx.set_source_rgba(bg_color.0, bg_color.1, bg_color.2, opacity),
+    }
+    ctx.fill().unwrap();
+    ctx.restore().unwrap();
+}
+
+/// Size requested from the icon theme, icons are scaled when drawn
+const THEME_ICON_SIZE: i32 = 64;
+
+/// Space between the tile edges and its content
+const TILE_PADDING: f64 = 10.0;
+
+/// Smallest font size of shrunk pad texts, relative to the text style
+const MIN_FONT_SCALE: f64 = 0.5;
+
+/// Fontconfig alias of the color emoji font
+const EMOJI_FONT_FAMILY: &str = "emoji";
+
+/// Pad icon size when neither the pad nor its text style set one
+const DEFAULT_ICON_SIZE: f64 = 32.0;
+
+/// Space between a pad icon and the text next to it
+const ICON_SPACING: f64 = 5.0;
+
+/// Watermark icon size relative to the shorter tile side, when no icon size is set
+const WATERMARK_SCALE: f64 = 0.7;
+
+/// Opacity of watermark icons behind the pad text
+const WATERMARK_OPACITY: f64 = 0.2;
+
+/// Height of the layer hint badges relative to the header height
+const LAYER_HINT_SCALE: f64 = 0.32;
+
+/// Space between the board title and the layer hint badges, and between the badges
+const LAYER_HINT_SPACING: f64 = 6.0;
+
+/// Label of the layer hint badge of double tap pads
+const DOUBLE_TAP_HINT: &str = "2x";
+
+/// Countdown ring radius relative to the header height
+const COUNTDOWN_RING_SCALE: f64 = 0.25;
+
+/// Thickness of the countdown bar
+const COUNTDOWN_BAR_HEIGHT: f64 = 4.0;
+
+/// Opacity of the selected tile highlight before and after the flash
+const SELECTION_ALPHA: f64 = 0.3;
+
+/// Opacity of the selected tile highlight at the peak of the flash
+const SELECTION_FLASH_ALPHA: f64 = 0.8;
+
+/// Part of the feedback window until the flash peaks
+const SELECTION_FLASH_PEAK: f64 = 0.15;
+
+/// Opacity of the highlight of the pad selected last time, until a pad is selected
+const LAST_PAD_ALPHA: f64 = 0.12;
+
+/// Shown in the header while the board is in edit mode
+const EDIT_MODE_LABEL: &str = "EDIT";
+
 struct BoardRenderer<'a> {
     color_scheme: &'a ColorScheme,
     text_style: &'a TextStyle,
@@ -37,41 +233,51 @@ impl<'a> BoardRenderer<'a> {
         }
     }
 
-    /// Draw the complete 3x3 board using Board interface
-    fn draw_board(&self, ctx: &Context, board: &dyn Board, selected_pad: Option<u8>, remaining_time: Option<u64>, current_modifiers: &ModifierState) {
+    /// Draw the complete 3x3 board using Board interface, `last_pad` is the pad selected last time
+    fn draw_board(&self, ctx: &Context, board: &dyn Board, selection: Option<Selection>, last_pad: Option<u8>, current_modifiers: &ModifierState) {
         let fg1_color = self.color_scheme.foreground1().to_rgb();
         let fg2_color = self.color_scheme.foreground2().to_rgb();
 
-        // Draw header using layout dimensions
-        self.draw_header(ctx, board.title(), &fg2_color, board.icon());
+        // Draw background image behind the grid
+        if let Some(background) = board.background_image() {
+            self.draw_background_image(ctx, background, self.layout.get_grid_rect());
+        }
 
-        // Draw countdown timer if active
-        if let Some(time_left) = remaining_time {
-            if time_left > 0 {
-                self.draw_countdown(ctx, time_left, &fg2_color);
-            }
+        // Draw header using layout dimensions
+        let title_end = self.draw_header(ctx, board.title(), &fg2_color, board.icon());
+        if self.text_style.layer_hints {
+            let labels: Vec<String> = board.modifier_layers().iter()
+                .map(ModifierState::abbreviation)
+                .chain(board.double_pads().map(|_| DOUBLE_TAP_HINT.to_string()))
+                .collect();
+            self.draw_layer_hints(ctx, &labels, title_end);
         }
 
         // Draw grid lines using layout calculations
         self.draw_grid_lines(ctx, &fg1_color);
 
+        // Determine which pads to use based on current modifier state - using Board interface
+        let pads = board.pads(Some(current_modifiers));
+        let empty_pad = Pad::default();
+
         // Draw tiles
         for tile_id in 1..=9 {
-            let is_selected = selected_pad == Some(tile_id);
-
-            // Determine which pad to use based on current modifier state - using Board interface
-            let pad = board.pads(Some(current_modifiers.clone())).get_or_default((tile_id - 1) as usize);
+            let highlight = match selection {
+                Some(selection) => (selection.pad == tile_id).then(|| selection.highlight_alpha()),
+                None => (last_pad == Some(tile_id)).then_some(LAST_PAD_ALPHA),
+            };
+            let pad = pads.get((tile_id - 1) as usize).unwrap_or(&empty_pad);
 
             // Get tile rectangle from layout
             if let Some(tile_rect) = self.layout.get_tile_rect(tile_id) {
-                self.draw_tile(ctx, &pad, tile_id, tile_rect, is_selected);
+                self.draw_tile(ctx, pad, tile_id, tile_rect, highlight);
             }
         }
     }
 
 
-    /// Draw header with board name using layout dimensions
-    fn draw_header(&self, ctx: &Context, name: &str, color: &(f64, f64, f64), icon: Option<&str>) {
+    /// Draw header with board name using layout dimensions, returns where the name ends
+    fn draw_header(&self, ctx: &Context, name: &str, color: &(f64, f64, f64), icon: Option<&str>) -> f64 {
         let header_rect = self.layout.get_header_rect();
 
         ctx.set_source_rgba(color.0, color.1, color.2, 1.0);
@@ -98,7 +304,8 @@ impl<'a> BoardRenderer<'a> {
 
         // Draw icon if configured
         if let Some(icon) = icon {
-            self.draw_icon(ctx, icon, start_x, text_y - h_extents.height() - icon_size_addition / 2.0, icon_size, color.0, color.1, color.2);
+            let icon_y = text_y - h_extents.height() - icon_size_addition / 2.0;
+            self.draw_icon(ctx, icon, Rect::new(start_x, icon_y, start_x + icon_size, icon_y + icon_size), self.color_scheme, &self.text_style.svg_style);
             // Draw text after icon
             ctx.move_to(start_x + icon_size + icon_spacing, text_y);
         } else {
@@ -107,6 +314,87 @@ impl<'a> BoardRenderer<'a> {
         }
 
         ctx.show_text(name).unwrap();
+        start_x + total_width
+    }
+
+    /// Badges after the board title naming the layers of pads the board has besides its base pads,
+    /// e.g. "C" for Ctrl pads and "2x" for double tap pads, lines in the first and labels in the second foreground color
+    fn draw_layer_hints(&self, ctx: &Context, labels: &[String], title_end: f64) {
+        let header_rect = self.layout.get_header_rect();
+        let (fg1_color, fg2_color) = (self.color_scheme.foreground1().to_rgb(), self.color_scheme.foreground2().to_rgb());
+        let height = header_rect.height() * LAYER_HINT_SCALE;
+        let (top, radius) = (header_rect.y() + (header_rect.height() - height) / 2.0, height / 2.0);
+
+        ctx.save().unwrap();
+        apply_text_style(ctx, &self.text_style.pad_id_font, "Impact");
+        ctx.set_font_size(height * 0.7);
+        ctx.set_line_width(1.5);
+        let mut x = title_end + LAYER_HINT_SPACING;
+        for label in labels {
+            let extents = ctx.text_extents(label).unwrap();
+            let width = (extents.width() + height * 0.6).max(height);
+
+            // Rounded badge, a circle for single letters
+            ctx.new_sub_path();
+            ctx.arc(x + width - radius, top + radius, radius, -std::f64::consts::FRAC_PI_2, std::f64::consts::FRAC_PI_2);
+            ctx.arc(x + radius, top + radius, radius, std::f64::consts::FRAC_PI_2, 3.0 * std::f64::consts::FRAC_PI_2);
+            ctx.close_path();
+            ctx.set_source_rgba(fg1_color.0, fg1_color.1, fg1_color.2, 1.0);
+            ctx.stroke().unwrap();
+
+            ctx.set_source_rgba(fg2_color.0, fg2_color.1, fg2_color.2, 1.0);
+            ctx.move_to(x + (width - extents.width()) / 2.0 - extents.x_bearing(), top + radius - extents.y_bearing() - extents.height() / 2.0);
+            ctx.show_text(label).unwrap();
+            x += width + LAYER_HINT_SPACING;
+        }
+        ctx.restore().unwrap();
+    }
+
+    /// Edit mode marks in the second foreground color, right-aligned in the header like the countdown dots
+    fn draw_edit_mode(&self, ctx: &Context) {
+        let color = self.color_scheme.foreground2().to_rgb();
+        let (header_rect, grid_rect) = (self.layout.get_header_rect(), self.layout.get_grid_rect());
+
+        ctx.save().unwrap();
+        ctx.set_source_rgba(color.0, color.1, color.2, 1.0);
+        apply_text_style(ctx, &self.text_style.header_font, "Impact");
+        let extents = ctx.text_extents(EDIT_MODE_LABEL).unwrap();
+        ctx.move_to(header_rect.width() - extents.width() - 10.0, header_rect.height() / 2.0 + extents.height() / 2.0);
+        ctx.show_text(EDIT_MODE_LABEL).unwrap();
+
+        ctx.set_line_width(2.0);
+        ctx.set_dash(&[8.0, 6.0], 0.0);
+        ctx.rectangle(grid_rect.x() + 1.0, grid_rect.y() + 1.0, grid_rect.width() - 2.0, grid_rect.height() - 2.0);
+        ctx.stroke().unwrap();
+        ctx.restore().unwrap();
+    }
+
+    /// Latched modifiers in the second foreground color, left-aligned in the header opposite the edit mode label
+    fn draw_latched_modifiers(&self, ctx: &Context, latched: &ModifierState) {
+        let color = self.color_scheme.foreground2().to_rgb();
+        let header_rect = self.layout.get_header_rect();
+        let label = latched.to_string();
+
+        ctx.save().unwrap();
+        ctx.set_source_rgba(color.0, color.1, color.2, 1.0);
+        apply_text_style(ctx, &self.text_style.header_font, "Impact");
+        let extents = ctx.text_extents(&label).unwrap();
+        ctx.move_to(10.0, header_rect.height() / 2.0 + extents.height() / 2.0);
+        ctx.show_text(&label).unwrap();
+        ctx.restore().unwrap();
+    }
+
+    /// Draw countdown timer if active
+    fn draw_countdown_timer(&self, ctx: &Context, countdown: Countdown) {
+        if countdown.remaining == 0 {
+            return;
+        }
+        let fg2_color = self.color_scheme.foreground2().to_rgb();
+        match self.text_style.countdown {
+            CountdownStyle::Dots => self.draw_countdown(ctx, countdown.remaining, &fg2_color),
+            CountdownStyle::Ring => self.draw_countdown_ring(ctx, countdown, &fg2_color),
+            CountdownStyle::Bar => self.draw_countdown_bar(ctx, countdown, &fg2_color),
+        }
     }
 
     /// Draw countdown timer as dotted string in header area (right-aligned, vertically aligned as continuation of header text)
@@ -132,6 +420,39 @@ impl<'a> BoardRenderer<'a> {
         ctx.show_text(&dots).unwrap();
     }
 
+    /// Ring at the right edge of the header, the arc of the time left empties clockwise from the top
+    fn draw_countdown_ring(&self, ctx: &Context, countdown: Countdown, color: &(f64, f64, f64)) {
+        let header_rect = self.layout.get_header_rect();
+        let radius = header_rect.height() * COUNTDOWN_RING_SCALE;
+        let (center_x, center_y) = (header_rect.x() + header_rect.width() - radius - 2.0 * TILE_PADDING, header_rect.y() + header_rect.height() / 2.0);
+        let start = -std::f64::consts::FRAC_PI_2;
+
+        ctx.save().unwrap();
+        ctx.set_line_width((radius / 3.0).max(2.0));
+        ctx.new_path();
+        ctx.set_source_rgba(color.0, color.1, color.2, 0.3);
+        ctx.arc(center_x, center_y, radius, 0.0, 2.0 * std::f64::consts::PI);
+        ctx.stroke().unwrap();
+
+        ctx.set_source_rgba(color.0, color.1, color.2, 1.0);
+        ctx.arc(center_x, center_y, radius, start, start + countdown.fraction() * 2.0 * std::f64::consts::PI);
+        ctx.stroke().unwrap();
+        ctx.restore().unwrap();
+    }
+
+    /// Bar along the bottom of the header, shrinking towards the left edge
+    fn draw_countdown_bar(&self, ctx: &Context, countdown: Countdown, color: &(f64, f64, f64)) {
+        let header_rect = self.layout.get_header_rect();
+        let width = (header_rect.width() - 2.0 * TILE_PADDING) * countdown.fraction();
+        let y = header_rect.y() + header_rect.height() - COUNTDOWN_BAR_HEIGHT - TILE_PADDING / 2.0;
+
+        ctx.save().unwrap();
+        ctx.set_source_rgba(color.0, color.1, color.2, 1.0);
+        ctx.rectangle(header_rect.x() + TILE_PADDING, y, width, COUNTDOWN_BAR_HEIGHT);
+        ctx.fill().unwrap();
+        ctx.restore().unwrap();
+    }
+
     /// Draw grid lines using layout calculations
     fn draw_grid_lines(&self, ctx: &Context, color: &(f64, f64, f64)) {
         let window_rect = self.layout.get_window_rect();
@@ -164,8 +485,8 @@ impl<'a> BoardRenderer<'a> {
         ctx.stroke().unwrap();
     }
 
-    /// Draw individual tile with content
-    fn draw_tile(&self, ctx: &Context, pad: &Pad, tile_id: u8, rect: Rect, selected: bool) {
+    /// Draw individual tile with content, `highlight` is the selection highlight opacity of the selected tile
+    fn draw_tile(&self, ctx: &Context, pad: &Pad, tile_id: u8, rect: Rect, highlight: Option<f64>) {
         // Resolve color scheme: pad-specific or board default
         let color_scheme = pad.color_scheme.as_ref().unwrap_or(self.color_scheme);
         let text_style = pad.text_style.as_ref().unwrap_or(self.text_style);
@@ -173,14 +494,16 @@ impl<'a> BoardRenderer<'a> {
         let fg2_color = color_scheme.foreground2().to_rgb();
         let bg_color = color_scheme.background().to_rgb();
 
+        // Draw tile background if different from board default, or always for pads filling it
+        let differs = bg_color != self.color_scheme.background().to_rgb() || color_scheme.background2 != self.color_scheme.background2;
+        if pad.fill_background || (differs && highlight.is_none()) {
+            let opacity = pad.opacity.unwrap_or(color_scheme.opacity()).clamp(0.0, 1.0);
+            draw_background(ctx, color_scheme, opacity, rect);
+        }
+
         // Highlight selected tile
-        if selected {
-            ctx.set_source_rgba(fg2_color.0, fg2_color.1, fg2_color.2, 0.3);
-            ctx.rectangle(rect.x(), rect.y(), rect.width(), rect.height());
-            ctx.fill().unwrap();
-        } else if bg_color != self.color_scheme.background().to_rgb() {
-            // Draw tile background if different from board default
-            ctx.set_source_rgba(bg_color.0, bg_color.1, bg_color.2, color_scheme.opacity);
+        if let Some(alpha) = highlight {
+            ctx.set_source_rgba(fg2_color.0, fg2_color.1, fg2_color.2, alpha);
             ctx.rectangle(rect.x(), rect.y(), rect.width(), rect.height());
             ctx.fill().unwrap();
         }
@@ -204,48 +527,114 @@ impl<'a> BoardRenderer<'a> {
             pangocairo::show_layout(ctx, &id_layout);
         }
 
+        // Content stays inside the tile, clear of its edges and the tile ID
+        let content_width = rect.width() - 2.0 * TILE_PADDING;
+
+        let text = pad.display_text();
+
         // Draw header (top center)
+        let mut header_height = 0.0;
         if !pad.header.is_empty() {
-            let layout = pangocairo::create_layout(ctx);
-            layout.set_font_description(Some(&FontDescription::from_string(&text_style.pad_header_font)));
-            layout.set_text(&pad.header);
-            layout.set_alignment(pango::Alignment::Center);
-
-            let (header_width, _) = layout.size().scaled();
+            let max_height = match text.is_empty() && pad.icon.is_empty() {
+                true => rect.height() - 2.0 * TILE_PADDING,
+                false => (rect.height() - 2.0 * TILE_PADDING) / 3.0,
+            };
+            let layout = fitted_layout(ctx, &pad.header, &text_style.pad_header_font, content_width, max_height, text_style.shrink_to_fit);
+            let (header_width, height) = text_size(&layout);
+            header_height = height;
 
             // Center header horizontally, position near top
             let header_x = rect.x() + (rect.width() - header_width) / 2.0;
-            let header_y = rect.y() + 10.0;
+            let header_y = rect.y() + TILE_PADDING;
 
-            ctx.move_to(header_x, header_y);
-            pangocairo::show_layout(ctx, &layout);
+            draw_layout(ctx, &layout, header_x, header_y);
         }
 
-        // Draw text (center)
-        if !pad.icon.is_empty() {
-            // Draw icon if configured
-            self.draw_icon(ctx, &pad.icon, rect.x() + rect.width() / 2.0 - 16.0, rect.y() + rect.height() / 2.0 - 16.0, 32.0, fg2_color.0, fg2_color.1, fg2_color.2);
-        }
-        else if !pad.text.is_empty() {
-            let layout = pangocairo::create_layout(ctx);
-            layout.set_font_description(Some(&FontDescription::from_string(&text_style.pad_text_font)));
-            layout.set_text(&pad.text);
-            layout.set_alignment(pango::Alignment::Center);
-
-            let (text_width, text_height) = layout.size().scaled();
-
-            // center text in header area
-            let x = rect.x() + (rect.width() - text_width) / 2.0;
-            let y = rect.y() + (rect.height() - text_height) / 2.0;
+        // Icon and text are centered, keeping the same distance from the header at the top and the bottom edge
+        let max_height = (rect.height() - 2.0 * (TILE_PADDING + header_height + TILE_PADDING / 2.0)).max(0.0);
+        let (center_x, center_y) = (rect.x() + rect.width() / 2.0, rect.y() + rect.height() / 2.0);
+        let text_layout = |width: f64, height: f64| (!text.is_empty())
+            .then(|| fitted_layout(ctx, text, &text_style.pad_text_font, width, height.max(0.0), text_style.shrink_to_fit));
+
+        let icon_size = pad.icon_size.or(text_style.icon_size);
+        let placement = pad.icon_placement.or(text_style.icon_placement).unwrap_or_default();
+        let svg_style = format!("{} {}", text_style.svg_style, pad.svg_style);
+
+        match placement {
+            _ if pad.icon.is_empty() => {
+                if let Some(layout) = text_layout(content_width, max_height) {
+                    let (text_width, text_height) = text_size(&layout);
+                    draw_layout(ctx, &layout, center_x - text_width / 2.0, center_y - text_height / 2.0);
+                }
+            },
+            IconPlacement::Center => {
+                let size = icon_size.unwrap_or(DEFAULT_ICON_SIZE);
+                self.draw_icon(ctx, &pad.icon, Rect::new(center_x - size / 2.0, center_y - size / 2.0, center_x + size / 2.0, center_y + size / 2.0), color_scheme, &svg_style);
+            },
+            IconPlacement::Above => {
+                let size = icon_size.unwrap_or(DEFAULT_ICON_SIZE).min(max_height);
+                let layout = text_layout(content_width, max_height - size - ICON_SPACING);
+                let (text_width, text_height) = layout.as_ref().map(text_size).unwrap_or_default();
+                let spacing = if layout.is_some() { ICON_SPACING } else { 0.0 };
+
+                let top = center_y - (size + spacing + text_height) / 2.0;
+                self.draw_icon(ctx, &pad.icon, Rect::new(center_x - size / 2.0, top, center_x + size / 2.0, top + size), color_scheme, &svg_style);
+                if let Some(layout) = layout {
+                    draw_layout(ctx, &layout, center_x - text_width / 2.0, top + size + spacing);
+                }
+            },
+            IconPlacement::Left => {
+                let size = icon_size.unwrap_or(DEFAULT_ICON_SIZE).min(max_height).min(content_width / 2.0);
+                let layout = text_layout(content_width - size - ICON_SPACING, max_height);
+                if let Some(layout) = &layout {
+                    layout.set_alignment(pango::Alignment::Left);
+                }
+                let (text_width, text_height) = layout.as_ref().map(text_size).unwrap_or_default();
+                let spacing = if layout.is_some() { ICON_SPACING } else { 0.0 };
 
-            ctx.move_to(x, y);
-            pangocairo::show_layout(ctx, &layout);
+                let left = center_x - (size + spacing + text_width) / 2.0;
+                self.draw_icon(ctx, &pad.icon, Rect::new(left, center_y - size / 2.0, left + size, center_y + size / 2.0), color_scheme, &svg_style);
+                if let Some(layout) = layout {
+                    draw_layout(ctx, &layout, left + size + spacing, center_y - text_height / 2.0);
+                }
+            },
+            IconPlacement::Watermark => {
+                let size = icon_size.unwrap_or(rect.width().min(rect.height()) * WATERMARK_SCALE);
+                ctx.push_group();
+                self.draw_icon(ctx, &pad.icon, Rect::new(center_x - size / 2.0, center_y - size / 2.0, center_x + size / 2.0, center_y + size / 2.0), color_scheme, &svg_style);
+                ctx.pop_group_to_source().unwrap();
+                ctx.paint_with_alpha(WATERMARK_OPACITY).unwrap();
+                ctx.set_source_rgba(fg2_color.0, fg2_color.1, fg2_color.2, 1.0);
+
+                if let Some(layout) = text_layout(content_width, max_height) {
+                    let (text_width, text_height) = text_size(&layout);
+                    draw_layout(ctx, &layout, center_x - text_width / 2.0, center_y - text_height / 2.0);
+                }
+            },
         }
     }
 
-    /// Draw icon in header area based on board configuration
-    fn draw_icon(&self, ctx: &Context, icon: &str, x: f64, y: f64, size: f64, red: f64, green: f64, blue: f64) {
-        if let Some(icon_path) = self.resources.icon(icon) {
+    /// Icon file from the config directories, or for names like "firefox" from the desktop icon theme
+    fn icon_path(&self, icon: &str) -> Option<PathBuf> {
+        self.resources.icon(icon)
+            .or_else(|| Resources::is_theme_icon(icon).then(|| theme_icon(icon)).flatten())
+    }
+
+    /// Draw an icon into the square, SVG icons are styled with the color scheme and the custom `svg_style` CSS
+    fn draw_icon(&self, ctx: &Context, icon: &str, square: Rect, color_scheme: &ColorScheme, svg_style: &str) {
+        let (x, y, size) = (square.x(), square.y(), square.height());
+        let color = color_scheme.foreground2().to_rgb();
+        let icon = match IconKind::parse(icon) {
+            IconKind::Emoji(text) => return self.draw_text_icon(ctx, text, EMOJI_FONT_FAMILY, square, color),
+            IconKind::Glyph(text) => {
+                let font = FontDescription::from_string(&self.text_style.pad_text_font);
+                let family = font.family().map(|family| family.to_string()).unwrap_or_default();
+                return self.draw_text_icon(ctx, text, &family, square, color);
+            },
+            IconKind::Image(icon) => icon,
+        };
+
+        if let Some(icon_path) = self.icon_path(icon) {
             let icon_path = icon_path.to_str().unwrap();
 
             if icon_path.ends_with(".png") {
@@ -267,8 +656,7 @@ impl<'a> BoardRenderer<'a> {
             } else if icon_path.ends_with(".svg") {
                 // Load SVG icon using resvg
                 if let Ok(svg_data) = std::fs::read(&icon_path) {
-                    let color_str = format!("rgb({}, {}, {})", (red * 255.0) as u8, (green * 255.0) as u8, (blue * 255.0) as u8);
-                    let stylesheet = format!(".board-s {{ stroke: {}; }}  .board-f {{ fill: {}; }}  .board-sf {{ stroke: {}; fill: {}; }} ", color_str, color_str, color_str, color_str);
+                    let stylesheet = color_scheme.svg_stylesheet(svg_style);
 
                     // Use usvg's built-in stylesheet injection
                     let mut options = resvg::usvg::Options::default();
@@ -310,9 +698,168 @@ impl<'a> BoardRenderer<'a> {
                 }
             }
 
+        } else {
+            // Missing icons are reported by settings validation, mark their place instead of leaving a gap
+            self.draw_icon_placeholder(ctx, x, y, size, color);
         }
     }
 
+    /// Draw a background image scaled into the area, missing images are reported by settings validation
+    fn draw_background_image(&self, ctx: &Context, background: &BackgroundImage, area: Rect) {
+        let Some(image_path) = self.icon_path(&background.image) else {
+            return;
+        };
+        let Some((surface, target)) = load_image(&image_path, area, background) else {
+            log::warn!("Failed to load background image: {:?}", image_path);
+            return;
+        };
+
+        ctx.save().unwrap();
+        ctx.rectangle(area.x(), area.y(), area.width(), area.height());
+        ctx.clip();
+        ctx.translate(target.x(), target.y());
+        ctx.scale(target.width() / surface.width() as f64, target.height() / surface.height() as f64);
+        ctx.set_source_surface(&surface, 0.0, 0.0).unwrap();
+        ctx.paint_with_alpha(background.opacity.clamp(0.0, 1.0)).unwrap();
+        ctx.restore().unwrap();
+    }
+
+    /// Draw an emoji or glyph centered in the icon square, missing glyphs fall back to other fonts
+    fn draw_text_icon(&self, ctx: &Context, text: &str, family: &str, square: Rect, color: (f64, f64, f64)) {
+        let mut font = FontDescription::new();
+        font.set_family(family);
+        font.set_absolute_size(square.height() * pango::SCALE as f64);
+
+        let layout = pangocairo::create_layout(ctx);
+        layout.set_font_description(Some(&font));
+        layout.set_text(text);
+        let (width, height) = layout.pixel_size();
+
+        ctx.save().unwrap();
+        ctx.set_source_rgb(color.0, color.1, color.2);
+        ctx.move_to(square.x() + (square.width() - width as f64) / 2.0, square.y() + (square.height() - height as f64) / 2.0);
+        pangocairo::show_layout(ctx, &layout);
+        ctx.restore().unwrap();
+    }
+
+    /// Crossed-out square in place of an icon that was not found
+    fn draw_icon_placeholder(&self, ctx: &Context, x: f64, y: f64, size: f64, color: (f64, f64, f64)) {
+        let inset = size * 0.15;
+        let (left, top, side) = (x + inset, y + inset, size - 2.0 * inset);
+
+        ctx.save().unwrap();
+        ctx.set_source_rgb(color.0, color.1, color.2);
+        ctx.set_line_width((size / 16.0).max(1.0));
+        ctx.rectangle(left, top, side, side);
+        ctx.move_to(left, top);
+        ctx.line_to(left + side, top + side);
+        ctx.move_to(left + side, top);
+        ctx.line_to(left, top + side);
+        ctx.stroke().unwrap();
+        ctx.restore().unwrap();
+    }
+
+}
+
+/// Theme icon names already reported as missing, so a board redrawn every frame logs them once
+static MISSING_THEME_ICONS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Whether the desktop icon theme has the icon, see [Resources::with_icon_theme].
+/// Without GTK or a display it cannot be checked and is assumed to exist
+pub fn has_theme_icon(icon_name: &str) -> bool {
+    if !gtk4::is_initialized_main_thread() {
+        return true;
+    }
+    gtk4::gdk::Display::default()
+        .is_none_or(|display| gtk4::IconTheme::for_display(&display).has_icon(icon_name))
+}
+
+/// File of a freedesktop icon theme icon, preferring scalable icons
+fn theme_icon(icon_name: &str) -> Option<PathBuf> {
+    let theme = gtk4::IconTheme::for_display(&gtk4::gdk::Display::default()?);
+    if !theme.has_icon(icon_name) {
+        if MISSING_THEME_ICONS.lock().is_ok_and(|mut missing| missing.insert(icon_name.to_string())) {
+            log::warn!("Icon '{}' is neither an icon file nor in icon theme '{}'", icon_name, theme.theme_name());
+        }
+        return None;
+    }
+    theme.lookup_icon(icon_name, &[], THEME_ICON_SIZE, 1, gtk4::TextDirection::None, gtk4::IconLookupFlags::empty())
+        .file()?
+        .path()
+}
+
+/// Load a PNG or SVG image and the rectangle it is drawn in, SVG images are rendered at that size
+fn load_image(path: &Path, area: Rect, background: &BackgroundImage) -> Option<(ImageSurface, Rect)> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("png") => {
+            let surface = ImageSurface::create_from_png(&mut File::open(path).ok()?).ok()?;
+            let target = area.fit(surface.width() as f64, surface.height() as f64, background.fit);
+            Some((surface, target))
+        },
+        Some("svg") => {
+            let tree = resvg::usvg::Tree::from_data(&std::fs::read(path).ok()?, &resvg::usvg::Options::default()).ok()?;
+            let size = tree.size();
+            let target = area.fit(size.width() as f64, size.height() as f64, background.fit);
+
+            let (width, height) = (target.width().round().max(1.0) as u32, target.height().round().max(1.0) as u32);
+            let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)?;
+            let transform = resvg::tiny_skia::Transform::from_scale(width as f32 / size.width(), height as f32 / size.height());
+            resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+            // tiny-skia pixels are RGBA, cairo ARGB32 is BGRA in memory on little-endian
+            let mut data = pixmap.take();
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+            let stride = cairo::Format::ARgb32.stride_for_width(width).ok()?;
+            let surface = ImageSurface::create_for_data(data, cairo::Format::ARgb32, width as i32, height as i32, stride).ok()?;
+            Some((surface, target))
+        },
+        _ => None,
+    }
+}
+
+/// Centered layout wrapped to `width`, text higher than `height` is ellipsized.
+/// With `shrink` the font size is first reduced (down to MIN_FONT_SCALE of its size) until the text fits.
+fn fitted_layout(ctx: &Context, text: &str, font: &str, width: f64, height: f64, shrink: bool) -> pango::Layout {
+    let layout = pangocairo::create_layout(ctx);
+    layout.set_text(text);
+    layout.set_alignment(pango::Alignment::Center);
+    layout.set_wrap(pango::WrapMode::WordChar);
+    layout.set_width((width.max(1.0) * pango::SCALE as f64) as i32);
+
+    let mut font = FontDescription::from_string(font);
+    layout.set_font_description(Some(&font));
+
+    if shrink {
+        let size = font.size() as f64;
+        let mut scale = 1.0;
+        while text_size(&layout).1 > height && scale > MIN_FONT_SCALE {
+            scale -= 0.1;
+            match font.is_size_absolute() {
+                true => font.set_absolute_size(size * scale),
+                false => font.set_size((size * scale) as i32),
+            }
+            layout.set_font_description(Some(&font));
+        }
+    }
+
+    layout.set_height((height * pango::SCALE as f64) as i32);
+    layout.set_ellipsize(pango::EllipsizeMode::End);
+    layout
+}
+
+/// Size of the text of a layout, narrower than the layout width for wrapped layouts
+fn text_size(layout: &pango::Layout) -> (f64, f64) {
+    let (_, logical) = layout.pixel_extents();
+    (logical.width() as f64, logical.height() as f64)
+}
+
+/// Draw a layout with its text (not the layout width) starting at x, y
+fn draw_layout(ctx: &Context, layout: &pango::Layout, x: f64, y: f64) {
+    let (_, logical) = layout.pixel_extents();
+    ctx.move_to(x - logical.x() as f64, y - logical.y() as f64);
+    pangocairo::show_layout(ctx, layout);
 }
 
 fn apply_text_style(ctx: &Context, font: &str, default_family: &str) {
@@ -351,4 +898,19 @@ impl ScaledSize for (i32, i32) {
         let (width, height) = *self;
         ((width as f64) / pango::SCALE as f64, (height as f64) / pango::SCALE as f64)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_highlight() {
+        let alpha = |progress| Selection { pad: 5, progress }.highlight_alpha();
+        assert_eq!(alpha(0.0), SELECTION_ALPHA);
+        assert_eq!(alpha(SELECTION_FLASH_PEAK), SELECTION_FLASH_ALPHA);
+        assert!(alpha(0.5) > SELECTION_ALPHA && alpha(0.5) < SELECTION_FLASH_ALPHA);
+        assert_eq!(alpha(1.0), SELECTION_ALPHA);
+        assert_eq!(alpha(2.0), SELECTION_ALPHA);
+    }
+}
@@ -0,0 +1,211 @@
+/// Color scheme designer of the settings window
+/// Picks the colors and opacity of a new color scheme with GTK color pickers, previewed on a sample board
+
+use crate::components::boards::StaticBoard;
+use crate::core::{Color, ColorScheme, GradientDirection, ModifierState, Pad, Resources, TextStyle};
+use super::layout::{BoardLayout, Rect};
+use super::renderer;
+
+use gtk4::{gdk, glib};
+use gtk4::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+const GRADIENTS: [(&str, Option<GradientDirection>); 4] = [
+    ("Flat", None),
+    ("Vertical", Some(GradientDirection::Vertical)),
+    ("Horizontal", Some(GradientDirection::Horizontal)),
+    ("Diagonal", Some(GradientDirection::Diagonal)),
+];
+
+/// Headers and texts of the preview pads, by pad id (1-9)
+const SAMPLE_PADS: [(&str, &str); 9] = [
+    ("Undo", "Ctrl Z"), ("Find", "Ctrl F"), ("Save", "Ctrl S"),
+    ("Copy", "Ctrl C"), ("Paste", "Ctrl V"), ("Cut", "Ctrl X"),
+    ("Terminal", "make test"), ("Build", "make"), ("Deploy", "make deploy"),
+];
+
+/// Pad of the sample board highlighted as selected last time, to preview the highlight
+const SAMPLE_LAST_PAD: u8 = 5;
+
+/// The scheme with every value set (colors it leaves out take the built-in defaults) and no base,
+/// the designer writes complete schemes
+fn complete(color_scheme: &ColorScheme) -> ColorScheme {
+    ColorScheme {
+        name: color_scheme.name.clone(),
+        base: None,
+        opacity: Some(color_scheme.opacity()),
+        background: color_scheme.background().to_hex(),
+        foreground1: color_scheme.foreground1().to_hex(),
+        foreground2: color_scheme.foreground2().to_hex(),
+        background2: color_scheme.background2().map(|color| color.to_hex()).unwrap_or_default(),
+        gradient: color_scheme.gradient,
+        transform: Default::default(),
+    }
+}
+
+fn to_rgba(color: &Color) -> gdk::RGBA {
+    let (r, g, b) = color.to_rgb();
+    gdk::RGBA::new(r as f32, g as f32, b as f32, 1.0)
+}
+
+/// Hex color of a picked color, e.g. "#1e1e2e", its alpha is left out (see the opacity)
+fn to_hex(rgba: &gdk::RGBA) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color { r: channel(rgba.red()), g: channel(rgba.green()), b: channel(rgba.blue()) }.to_hex()
+}
+
+/// Board with sample pads drawn in the scheme and style, for previews
+pub fn sample_board(color_scheme: &ColorScheme, text_style: &TextStyle) -> StaticBoard {
+    let pads: Vec<Pad> = SAMPLE_PADS.iter()
+        .map(|(header, text)| Pad { header: header.to_string(), text: text.to_string(), ..Default::default() })
+        .collect();
+    StaticBoard::new(color_scheme.name.clone(), None, color_scheme.clone(), text_style.clone(), Arc::new(pads), HashMap::new())
+}
+
+/// Picker of one color of the scheme, the preview is drawn again when a color is picked
+fn color_button(
+    color_scheme: &Rc<RefCell<ColorScheme>>,
+    preview: &gtk4::DrawingArea,
+    color: Color,
+    apply: fn(&mut ColorScheme, String),
+) -> gtk4::ColorButton {
+    let button = gtk4::ColorButton::with_rgba(&to_rgba(&color));
+    button.set_use_alpha(false);
+    let (color_scheme, preview) = (color_scheme.clone(), preview.clone());
+    button.connect_color_set(move |button| {
+        apply(&mut color_scheme.borrow_mut(), to_hex(&button.rgba()));
+        preview.queue_draw();
+    });
+    button
+}
+
+fn add_row(grid: &gtk4::Grid, row: i32, label: &str, widget: &impl IsA<gtk4::Widget>) {
+    grid.attach(&gtk4::Label::builder().label(label).xalign(0.0).build(), 0, row, 1, 1);
+    grid.attach(widget, 1, row, 1, 1);
+}
+
+/// Modal dialog over the window designing a color scheme from the colors of `color_scheme`,
+/// resolves to the complete new scheme or `None` when cancelled
+pub async fn design(parent: &impl IsA<gtk4::Window>, resources: Resources, text_style: TextStyle, color_scheme: &ColorScheme) -> Option<ColorScheme> {
+    let dialog = gtk4::Window::builder()
+        .title("Design color scheme")
+        .transient_for(parent)
+        .modal(true)
+        .build();
+
+    let initial = complete(color_scheme);
+    let color_scheme = Rc::new(RefCell::new(initial.clone()));
+
+    let preview = gtk4::DrawingArea::builder().content_width(420).content_height(315).hexpand(true).vexpand(true).build();
+    let preview_scheme = color_scheme.clone();
+    preview.set_draw_func(move |_, ctx, width, height| {
+        let board = sample_board(&preview_scheme.borrow(), &text_style);
+        let color_scheme = preview_scheme.borrow();
+        let (width, height) = (width as f64, height as f64);
+        renderer::draw_background(ctx, &color_scheme, color_scheme.opacity(), Rect::new(0.0, 0.0, width, height));
+        renderer::draw_board(ctx, &board, &BoardLayout::new(width, height), &resources, None, Some(SAMPLE_LAST_PAD), &ModifierState::default());
+    });
+
+    let grid = gtk4::Grid::builder().row_spacing(6).column_spacing(12).build();
+    let name = gtk4::Entry::new();
+    name.set_text(&initial.name);
+    let name_scheme = color_scheme.clone();
+    name.connect_changed(move |name| name_scheme.borrow_mut().name = name.text().to_string());
+    add_row(&grid, 0, "Name", &name);
+    add_row(&grid, 1, "Background", &color_button(&color_scheme, &preview, initial.background(), |scheme, color| scheme.background = color));
+
+    let background2 = color_button(&color_scheme, &preview, initial.background2().unwrap_or(initial.background()), |scheme, color| scheme.background2 = color);
+    background2.set_sensitive(!initial.background2.is_empty());
+    let gradients: Vec<&str> = GRADIENTS.iter().map(|(name, _)| *name).collect();
+    let gradient = gtk4::DropDown::from_strings(&gradients);
+    let selected = GRADIENTS.iter().position(|(_, direction)| match initial.background2.is_empty() {
+        true => direction.is_none(),
+        false => *direction == Some(initial.gradient),
+    });
+    gradient.set_selected(selected.unwrap_or_default() as u32);
+    let (gradient_scheme, gradient_preview, gradient_background2) = (color_scheme.clone(), preview.clone(), background2.clone());
+    gradient.connect_selected_notify(move |gradient| {
+        let direction = GRADIENTS.get(gradient.selected() as usize).and_then(|(_, direction)| *direction);
+        let mut color_scheme = gradient_scheme.borrow_mut();
+        color_scheme.gradient = direction.unwrap_or_default();
+        color_scheme.background2 = direction.map(|_| to_hex(&gradient_background2.rgba())).unwrap_or_default();
+        gradient_background2.set_sensitive(direction.is_some());
+        gradient_preview.queue_draw();
+    });
+    add_row(&grid, 2, "Gradient", &gradient);
+    add_row(&grid, 3, "Background 2", &background2);
+    add_row(&grid, 4, "Lines", &color_button(&color_scheme, &preview, initial.foreground1(), |scheme, color| scheme.foreground1 = color));
+    add_row(&grid, 5, "Text", &color_button(&color_scheme, &preview, initial.foreground2(), |scheme, color| scheme.foreground2 = color));
+
+    let opacity = gtk4::SpinButton::with_range(0.0, 1.0, 0.05);
+    opacity.set_value(initial.opacity());
+    let (opacity_scheme, opacity_preview) = (color_scheme.clone(), preview.clone());
+    opacity.connect_value_changed(move |opacity| {
+        opacity_scheme.borrow_mut().opacity = Some(opacity.value());
+        opacity_preview.queue_draw();
+    });
+    add_row(&grid, 6, "Opacity", &opacity);
+
+    let add = gtk4::Button::with_label("Add");
+    add.add_css_class("suggested-action");
+    let cancel = gtk4::Button::with_label("Cancel");
+    let buttons = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    buttons.set_halign(gtk4::Align::End);
+    buttons.append(&cancel);
+    buttons.append(&add);
+
+    let columns = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+    columns.append(&grid);
+    columns.append(&preview);
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    content.append(&columns);
+    content.append(&buttons);
+    dialog.set_child(Some(&content));
+
+    // The first answer wins, closing the dialog after adding answers `None` too late
+    let (sender, receiver) = async_channel::bounded(1);
+    let (add_sender, add_dialog) = (sender.clone(), dialog.clone());
+    add.connect_clicked(move |_| {
+        let _ = add_sender.try_send(Some(color_scheme.borrow().clone()));
+        add_dialog.close();
+    });
+    let cancel_dialog = dialog.clone();
+    cancel.connect_clicked(move |_| cancel_dialog.close());
+    dialog.connect_close_request(move |_| {
+        let _ = sender.try_send(None);
+        glib::Propagation::Proceed
+    });
+
+    dialog.present();
+    receiver.recv().await.ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete() {
+        let derived = ColorScheme { name: "mine".to_string(), base: Some("dark".to_string()), background: "#102030".to_string(), ..Default::default() };
+        let scheme = complete(&derived);
+        assert!(scheme.is_complete());
+        assert_eq!(scheme.base, None);
+        assert_eq!(scheme.background, "#102030");
+        assert_eq!(scheme.foreground2, ColorScheme::default().foreground2().to_hex());
+        assert!(scheme.background2.is_empty());
+    }
+
+    #[test]
+    fn test_to_hex() {
+        let color = Color::from_hex("#1e90ff").unwrap();
+        assert_eq!(to_hex(&to_rgba(&color)), "#1e90ff");
+        assert_eq!(to_hex(&gdk::RGBA::new(1.5, 0.0, 0.5, 0.3)), "#ff0080");
+    }
+}
@@ -0,0 +1,1080 @@
+/// Settings editor window (`hotkeys settings`)
+/// Lists the boards, padsets, color schemes and text styles, edits them in forms validated on every change
+/// and saves them into the files they were loaded from. The pad dialog of the board edit mode reuses the pad form
+
+use crate::app::config::{self, AppSettings, BoardConfig, PadConfig, PadSetConfig};
+use crate::app::validation::ValidationReport;
+use crate::core::{Action, ColorScheme, CountdownStyle, GradientDirection, ModifierState, Resources, TextStyle};
+use crate::tools::batch;
+use super::layout::{BoardLayout, Rect};
+use super::renderer;
+use super::scheme_designer;
+
+use anyhow::Result;
+use gtk4::glib;
+use gtk4::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const TITLE: &str = "HotKeys Settings";
+
+/// Pad ids in the rows of the pad grid, as on the numpad
+const PAD_ROWS: [[usize; 3]; 3] = [[7, 8, 9], [4, 5, 6], [1, 2, 3]];
+
+const ACTIONS_HINT: &str = "Actions, one per line: a shortcut like \"Ctrl C\" or a JSON action like {\"Line\": \"ls\"}";
+
+const GRADIENTS: [(&str, GradientDirection); 3] = [
+    ("Vertical", GradientDirection::Vertical),
+    ("Horizontal", GradientDirection::Horizontal),
+    ("Diagonal", GradientDirection::Diagonal),
+];
+
+/// A component as written in its settings file
+#[derive(Clone, Debug)]
+enum Component {
+    Board(BoardConfig),
+    Padset(PadSetConfig),
+    ColorScheme(ColorScheme),
+    TextStyle(TextStyle),
+}
+
+impl Component {
+    fn collection(&self) -> &'static str {
+        match self {
+            Component::Board(_) => "boards",
+            Component::Padset(_) => "padsets",
+            Component::ColorScheme(_) => "color_schemes",
+            Component::TextStyle(_) => "text_styles",
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Component::Board(board) => &board.name,
+            Component::Padset(padset) => &padset.name,
+            Component::ColorScheme(color_scheme) => &color_scheme.name,
+            Component::TextStyle(text_style) => &text_style.name,
+        }
+    }
+
+    fn read(file: &Path, collection: &str, name: &str) -> Result<Option<Component>> {
+        Ok(match collection {
+            "boards" => config::read_component(file, collection, name)?.map(Component::Board),
+            "padsets" => config::read_component(file, collection, name)?.map(Component::Padset),
+            "text_styles" => config::read_component(file, collection, name)?.map(Component::TextStyle),
+            _ => config::read_component(file, collection, name)?.map(Component::ColorScheme),
+        })
+    }
+
+    fn write(&self, file: &Path, previous_name: &str) -> Result<()> {
+        match self {
+            Component::Board(board) => config::write_component(file, self.collection(), previous_name, board),
+            Component::Padset(padset) => config::write_component(file, self.collection(), previous_name, padset),
+            Component::ColorScheme(color_scheme) => config::write_component(file, self.collection(), previous_name, color_scheme),
+            Component::TextStyle(text_style) => config::write_component(file, self.collection(), previous_name, text_style),
+        }
+    }
+
+    /// Settings with this component in place of the one called `previous_name`
+    fn apply(&self, settings: AppSettings, previous_name: Option<&str>) -> AppSettings {
+        match self {
+            Component::Board(board) => settings.with_board(previous_name, board.clone()),
+            Component::Padset(padset) => settings.with_padset(previous_name, padset.clone()),
+            Component::ColorScheme(color_scheme) => settings.with_color_scheme(previous_name, color_scheme.clone()),
+            Component::TextStyle(text_style) => settings.with_text_style(previous_name, text_style.clone()),
+        }
+    }
+}
+
+/// Names of a collection of the settings
+fn component_names<'a>(settings: &'a AppSettings, collection: &str) -> Vec<&'a str> {
+    match collection {
+        "boards" => settings.board_configs.iter().map(|b| b.name.as_str()).collect(),
+        "padsets" => settings.padset_configs.iter().map(|p| p.name.as_str()).collect(),
+        "text_styles" => settings.text_styles().iter().map(|s| s.name.as_str()).collect(),
+        _ => settings.color_schemes().iter().map(|c| c.name.as_str()).collect(),
+    }
+}
+
+/// A component opened in the window, saved into `file` in place of `previous_name` (new components have none)
+#[derive(Clone, Debug)]
+struct Edit {
+    file: PathBuf,
+    previous_name: Option<String>,
+    component: Component,
+    changed: bool,
+    /// Form values that could not be applied (e.g. invalid actions), by field
+    problems: BTreeMap<String, String>,
+}
+
+/// Warning for a file whose comments or YAML anchors are lost when a component is saved into it
+fn rewrite_warning(file: &Path) -> Option<String> {
+    let losses = config::rewrite_losses(file);
+    (!losses.is_empty())
+        .then(|| format!("warning: {:?} is written in canonical form when saved, its {} are lost", file, losses.join(" and ")))
+}
+
+/// Messages of the edited settings and whether they prevent saving: form problems and names first,
+/// then the validation report of the settings with all edits applied. Only errors the edits
+/// introduce prevent saving, so a fix can be saved while other components still have errors
+fn check(settings: &AppSettings, resources: &Resources, edits: &[Edit]) -> (Vec<String>, bool) {
+    let mut messages = Vec::new();
+    let edited = edits.iter()
+        .fold(settings.clone(), |settings, edit| edit.component.apply(settings, edit.previous_name.as_deref()));
+
+    for edit in edits {
+        let (collection, name) = (edit.component.collection(), edit.component.name());
+        for (field, problem) in &edit.problems {
+            messages.push(format!("error: {} '{}': {}: {}", collection, name, field, problem));
+        }
+        if name.trim().is_empty() {
+            messages.push(format!("error: {}: A name is required", collection));
+        } else if component_names(&edited, collection).iter().filter(|other| **other == name).count() > 1 {
+            messages.push(format!("error: {} '{}': The name is already used", collection, name));
+        }
+    }
+    let has_problems = !messages.is_empty();
+
+    let mut files: Vec<&Path> = edits.iter().filter(|edit| edit.changed).map(|edit| edit.file.as_path()).collect();
+    files.sort();
+    files.dedup();
+    messages.extend(files.into_iter().filter_map(rewrite_warning));
+
+    let before = settings.validation_report(resources, ValidationReport::default());
+    let report = edited.validation_report(resources, ValidationReport::default());
+    let new_errors = report.errors().any(|finding| !before.findings.contains(finding));
+    messages.extend(report.findings.iter().map(ToString::to_string));
+    (messages, has_problems || new_errors)
+}
+
+/// Actions as lines of `hotkeys batch` input, shortcuts as keys and other actions as JSON
+pub fn action_lines(actions: &[Action]) -> String {
+    actions.iter()
+        .map(|action| match action {
+            Action::Shortcut(keys) => keys.clone(),
+            action => serde_json::to_string(action).unwrap_or_default(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Actions of `hotkeys batch` lines, with the line number of the first invalid one
+pub fn parse_action_lines(text: &str) -> Result<Vec<Action>, String> {
+    let mut actions = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let parsed = batch::parse_line(line).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        actions.extend(parsed.unwrap_or_default());
+    }
+    Ok(actions)
+}
+
+/// Modifier pads as "Ctrl = code/ctrl" lines, sorted by modifier
+fn modifier_pad_lines(modifier_pads: &HashMap<String, String>) -> String {
+    let mut lines: Vec<String> = modifier_pads.iter().map(|(modifier, padset)| format!("{} = {}", modifier, padset)).collect();
+    lines.sort();
+    lines.join("\n")
+}
+
+fn parse_modifier_pad_lines(text: &str) -> Result<HashMap<String, String>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| match line.split_once('=') {
+            Some((modifier, padset)) if !modifier.trim().is_empty() && !padset.trim().is_empty() =>
+                Ok((modifier.trim().to_string(), padset.trim().to_string())),
+            _ => Err(format!("line {}: expected \"<modifiers> = <padset>\"", index + 1)),
+        })
+        .collect()
+}
+
+fn optional(text: String) -> Option<String> {
+    (!text.trim().is_empty()).then_some(text)
+}
+
+fn entry(value: &str, placeholder: &str, on_change: impl Fn(String) + 'static) -> gtk4::Entry {
+    let entry = gtk4::Entry::new();
+    entry.set_text(value);
+    entry.set_placeholder_text(Some(placeholder));
+    entry.set_hexpand(true);
+    entry.connect_changed(move |entry| on_change(entry.text().to_string()));
+    entry
+}
+
+fn text_view(value: &str, on_change: impl Fn(String) + 'static) -> gtk4::ScrolledWindow {
+    let view = gtk4::TextView::new();
+    view.set_monospace(true);
+    view.buffer().set_text(value);
+    view.buffer().connect_changed(move |buffer| on_change(buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).to_string()));
+
+    let scrolled = gtk4::ScrolledWindow::builder().child(&view).min_content_height(60).hexpand(true).build();
+    scrolled.add_css_class("frame");
+    scrolled
+}
+
+/// Drop down of the options, the first option stands for "not set"
+fn drop_down(options: &[String], selected: Option<&str>, on_change: impl Fn(Option<String>) + 'static) -> gtk4::DropDown {
+    let strings: Vec<&str> = options.iter().map(String::as_str).collect();
+    let drop_down = gtk4::DropDown::from_strings(&strings);
+    let index = selected.and_then(|selected| options.iter().skip(1).position(|option| option == selected)).map_or(0, |index| index + 1);
+    drop_down.set_selected(index as u32);
+
+    let options = options.to_vec();
+    drop_down.connect_selected_notify(move |drop_down| {
+        let index = drop_down.selected() as usize;
+        on_change((index > 0).then(|| options.get(index).cloned()).flatten());
+    });
+    drop_down
+}
+
+fn form_grid() -> gtk4::Grid {
+    let grid = gtk4::Grid::builder().row_spacing(6).column_spacing(12).margin_top(12).margin_bottom(12).margin_start(12).margin_end(12).build();
+    grid.set_hexpand(true);
+    grid
+}
+
+fn add_row(grid: &gtk4::Grid, label: &str, widget: &impl IsA<gtk4::Widget>) {
+    let row = grid_rows(grid);
+    let label = gtk4::Label::builder().label(label).xalign(0.0).valign(gtk4::Align::Start).build();
+    grid.attach(&label, 0, row, 1, 1);
+    grid.attach(widget, 1, row, 1, 1);
+}
+
+fn grid_rows(grid: &gtk4::Grid) -> i32 {
+    let mut rows = 0;
+    let mut child = grid.first_child();
+    while let Some(widget) = child {
+        let (_, row, _, height) = grid.query_child(&widget);
+        rows = rows.max(row + height);
+        child = widget.next_sibling();
+    }
+    rows
+}
+
+fn hint(text: &str) -> gtk4::Label {
+    let label = gtk4::Label::builder().label(text).xalign(0.0).wrap(true).build();
+    label.add_css_class("dim-label");
+    label
+}
+
+/// Form editing the header, text, icon, target board and actions of a pad.
+/// `on_change` gets the edited pad, or why the actions cannot be used
+pub fn pad_form(pad: &PadConfig, on_change: impl Fn(Result<PadConfig, String>) + 'static) -> gtk4::Box {
+    let state = Rc::new(RefCell::new((pad.clone(), Ok(()))));
+    let on_change = Rc::new(on_change);
+    let field = |apply: fn(&mut PadConfig, String)| {
+        let (state, on_change) = (state.clone(), on_change.clone());
+        move |text: String| {
+            let result = {
+                let mut state = state.borrow_mut();
+                apply(&mut state.0, text);
+                state.1.clone().map(|_| state.0.clone())
+            };
+            on_change(result);
+        }
+    };
+
+    let form = gtk4::Box::new(gtk4::Orientation::Vertical, 4);
+    form.append(&entry(&pad.header, "Header", field(|pad, text| pad.header = text)));
+    form.append(&entry(&pad.text, "Text", field(|pad, text| pad.text = text)));
+    form.append(&entry(&pad.icon, "Icon", field(|pad, text| pad.icon = text)));
+    form.append(&entry(pad.board.as_deref().unwrap_or_default(), "Opens board", field(|pad, text| pad.board = optional(text))));
+    form.append(&text_view(&action_lines(&pad.actions), move |text| {
+        let result = {
+            let mut state = state.borrow_mut();
+            state.1 = parse_action_lines(&text).map(|actions| state.0.actions = actions);
+            state.1.clone().map(|_| state.0.clone())
+        };
+        on_change(result);
+    }));
+    form
+}
+
+struct Editor {
+    resources: Resources,
+    settings: RefCell<AppSettings>,
+    edits: RefCell<Vec<Edit>>,
+    /// Index of the edit shown in the form
+    current: Cell<Option<usize>>,
+    /// Collection and name of the list rows, `None` for section headers
+    entries: RefCell<Vec<Option<(&'static str, String)>>>,
+    /// Set while the list is filled, row selections then do not open components
+    filling: Cell<bool>,
+    window: gtk4::ApplicationWindow,
+    list: gtk4::ListBox,
+    form: gtk4::ScrolledWindow,
+    status: gtk4::Label,
+    save: gtk4::Button,
+}
+
+impl Editor {
+    /// Fill the component list, selecting (and opening) the given component
+    fn fill_list(self: &Rc<Self>, select: Option<(&'static str, String)>) {
+        self.filling.set(true);
+        while let Some(row) = self.list.first_child() {
+            self.list.remove(&row);
+        }
+
+        let mut entries = Vec::new();
+        {
+            let settings = self.settings.borrow();
+            let edits = self.edits.borrow();
+            for (collection, title) in [("boards", "Boards"), ("padsets", "Padsets"), ("color_schemes", "Color schemes"), ("text_styles", "Text styles")] {
+                entries.push(None);
+                let header = gtk4::Label::builder().label(title).xalign(0.0).margin_top(8).build();
+                header.add_css_class("heading");
+                let row = gtk4::ListBoxRow::builder().child(&header).selectable(false).activatable(false).build();
+                self.list.append(&row);
+
+                let added = edits.iter()
+                    .filter(|edit| edit.previous_name.is_none() && edit.component.collection() == collection)
+                    .map(|edit| edit.component.name());
+                for name in component_names(&settings, collection).into_iter().chain(added) {
+                    let label = gtk4::Label::builder().label(name).xalign(0.0).margin_start(12).build();
+                    let file = settings.component_file(collection, name);
+                    label.set_tooltip_text(Some(&file.to_string_lossy()));
+                    self.list.append(&label);
+                    entries.push(Some((collection, name.to_string())));
+                }
+            }
+        }
+
+        let index = select.and_then(|select| entries.iter().position(|entry| entry.as_ref() == Some(&select)));
+        *self.entries.borrow_mut() = entries;
+        if let Some(row) = index.and_then(|index| self.list.row_at_index(index as i32)) {
+            self.list.select_row(Some(&row));
+        }
+        self.filling.set(false);
+
+        match index {
+            Some(index) => self.open(index),
+            None => {
+                self.current.set(None);
+                self.form.set_child(Some(&hint("Select a board, padset or color scheme to edit it")));
+            }
+        }
+    }
+
+    /// Show the form of the component in the list row, loading it as written in its file
+    fn open(self: &Rc<Self>, row: usize) {
+        let Some((collection, name)) = self.entries.borrow().get(row).cloned().flatten() else {
+            return;
+        };
+
+        let existing = self.edits.borrow().iter().position(|edit| match &edit.previous_name {
+            Some(previous_name) => edit.component.collection() == collection && *previous_name == name,
+            None => edit.component.collection() == collection && edit.component.name() == name,
+        });
+        let index = match existing {
+            Some(index) => index,
+            None => {
+                let file = self.settings.borrow().component_file(collection, &name);
+                let component = match Component::read(&file, collection, &name) {
+                    Ok(Some(component)) => component,
+                    Ok(None) => return self.show_error(&format!("'{}' was not found in {:?}, it may be generated", name, file)),
+                    Err(e) => return self.show_error(&format!("Failed to read '{}' from {:?}: {}", name, file, e)),
+                };
+                let mut edits = self.edits.borrow_mut();
+                edits.push(Edit { file, previous_name: Some(name), component, changed: false, problems: BTreeMap::new() });
+                edits.len() - 1
+            }
+        };
+
+        self.current.set(Some(index));
+        let (component, file) = {
+            let edit = &self.edits.borrow()[index];
+            (edit.component.clone(), edit.file.clone())
+        };
+        let form = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+        let location = hint(&format!("Stored in {}. Settings not shown here are kept as they are.", file.display()));
+        location.set_margin_start(12);
+        location.set_margin_top(12);
+        form.append(&location);
+        match component {
+            Component::Board(board) => form.append(&self.board_form(&board)),
+            Component::Padset(padset) => form.append(&self.padset_form(&padset)),
+            Component::ColorScheme(color_scheme) => form.append(&self.color_scheme_form(&color_scheme)),
+            Component::TextStyle(text_style) => form.append(&self.text_style_form(&text_style)),
+        }
+        self.form.set_child(Some(&form));
+        self.refresh_status();
+    }
+
+    fn show_error(&self, message: &str) {
+        self.current.set(None);
+        self.form.set_child(Some(&hint(message)));
+    }
+
+    /// Change the shown component and validate the settings again
+    fn update(&self, apply: impl FnOnce(&mut Edit)) {
+        if let Some(index) = self.current.get() {
+            let mut edits = self.edits.borrow_mut();
+            edits[index].changed = true;
+            apply(&mut edits[index]);
+        }
+        self.refresh_status();
+    }
+
+    fn update_board(&self, apply: impl FnOnce(&mut BoardConfig)) {
+        self.update(|edit| if let Component::Board(board) = &mut edit.component { apply(board) });
+    }
+
+    fn update_padset(&self, apply: impl FnOnce(&mut PadSetConfig)) {
+        self.update(|edit| if let Component::Padset(padset) = &mut edit.component { apply(padset) });
+    }
+
+    fn update_color_scheme(&self, apply: impl FnOnce(&mut ColorScheme)) {
+        self.update(|edit| if let Component::ColorScheme(color_scheme) = &mut edit.component { apply(color_scheme) });
+    }
+
+    fn update_text_style(&self, apply: impl FnOnce(&mut TextStyle)) {
+        self.update(|edit| if let Component::TextStyle(text_style) = &mut edit.component { apply(text_style) });
+    }
+
+    /// Record or clear a form problem of the shown component
+    fn set_problem(&self, field: &str, problem: Option<String>) {
+        self.update(|edit| match problem {
+            Some(problem) => { edit.problems.insert(field.to_string(), problem); },
+            None => { edit.problems.remove(field); },
+        });
+    }
+
+    fn refresh_status(&self) {
+        let edits = self.edits.borrow();
+        let (messages, has_errors) = check(&self.settings.borrow(), &self.resources, &edits);
+        let changed = edits.iter().any(|edit| edit.changed);
+
+        self.status.set_text(&match messages.is_empty() {
+            true => "No problems found".to_string(),
+            false => messages.join("\n"),
+        });
+        self.save.set_sensitive(changed && !has_errors);
+        self.window.set_title(Some(&match changed {
+            true => format!("{} (unsaved changes)", TITLE),
+            false => TITLE.to_string()
+        }));
+    }
+
+    /// Write the changed components into their files and load the settings again
+    fn save_changes(self: &Rc<Self>) {
+        let selected = self.current.get().map(|index| {
+            let edit = &self.edits.borrow()[index];
+            (edit.component.collection(), edit.component.name().to_string())
+        });
+
+        for edit in self.edits.borrow().iter().filter(|edit| edit.changed) {
+            let previous_name = edit.previous_name.as_deref().unwrap_or(edit.component.name());
+            if let Err(e) = edit.component.write(&edit.file, previous_name) {
+                self.status.set_text(&format!("error: {:#}", e));
+                return;
+            }
+            log::info!("Saved {} '{}' to {:?}", edit.component.collection(), edit.component.name(), edit.file);
+        }
+
+        match config::load_settings_unvalidated(&self.resources) {
+            Ok(settings) => *self.settings.borrow_mut() = settings,
+            Err(e) => {
+                self.status.set_text(&format!("error: Saved settings cannot be loaded: {:#}", e));
+                return;
+            }
+        }
+        self.edits.borrow_mut().clear();
+        self.fill_list(selected);
+        self.refresh_status();
+    }
+
+    /// Add a new component to the settings file, named "new-board", "new-board-2", ...
+    /// Unused name for a new component, e.g. "new-board-2"
+    fn new_name(&self, collection: &str) -> String {
+        let kind = match collection { "boards" => "board", "padsets" => "padset", "text_styles" => "text-style", _ => "color-scheme" };
+        let settings = self.settings.borrow();
+        let edits = self.edits.borrow();
+        let used = |name: &str| component_names(&settings, collection).contains(&name)
+            || edits.iter().any(|edit| edit.component.collection() == collection && edit.component.name() == name);
+        (1..).map(|n| match n { 1 => format!("new-{}", kind), n => format!("new-{}-{}", kind, n) })
+            .find(|name| !used(name))
+            .unwrap_or_default()
+    }
+
+    fn add(self: &Rc<Self>, collection: &'static str) {
+        let name = self.new_name(collection);
+        let component = match collection {
+            "boards" => match serde_json::from_value(serde_json::json!({ "name": name })) {
+                Ok(board) => Component::Board(board),
+                Err(e) => return self.show_error(&format!("Failed to create a board: {}", e)),
+            },
+            "padsets" => Component::Padset(PadSetConfig { name: name.clone(), ..Default::default() }),
+            "text_styles" => Component::TextStyle(TextStyle { name: name.clone(), ..Default::default() }),
+            _ => Component::ColorScheme(ColorScheme { name: name.clone(), ..Default::default() }),
+        };
+        self.add_component(component);
+    }
+
+    /// Add a new component, saved into the settings file
+    fn add_component(self: &Rc<Self>, component: Component) {
+        let select = (component.collection(), component.name().to_string());
+        let file = PathBuf::from(self.settings.borrow().file_path());
+        self.edits.borrow_mut().push(Edit { file, previous_name: None, component, changed: true, problems: BTreeMap::new() });
+        self.fill_list(Some(select));
+    }
+
+    /// Design a new color scheme in the designer dialog, starting from the colors of the open color scheme
+    fn design_color_scheme(self: &Rc<Self>) {
+        let (start, text_style) = {
+            let settings = self.settings.borrow();
+            let open = self.current.get().and_then(|index| match &self.edits.borrow()[index].component {
+                Component::ColorScheme(color_scheme) => settings.get_color_scheme(&color_scheme.name).cloned(),
+                _ => None,
+            });
+            let text_style = settings.get_text_style(&TextStyle::default().name).cloned().unwrap_or_default();
+            (open.unwrap_or_default(), text_style)
+        };
+        let start = ColorScheme { name: self.new_name("color_schemes"), ..start };
+
+        let editor = self.clone();
+        glib::spawn_future_local(async move {
+            let designed = scheme_designer::design(&editor.window, editor.resources.clone(), text_style, &start).await;
+            if let Some(color_scheme) = designed {
+                editor.add_component(Component::ColorScheme(color_scheme));
+            }
+        });
+    }
+
+    fn board_form(self: &Rc<Self>, board: &BoardConfig) -> gtk4::Grid {
+        let (color_schemes, padsets) = {
+            let settings = self.settings.borrow();
+            let names = |collection, none: &str| std::iter::once(none.to_string())
+                .chain(component_names(&settings, collection).into_iter().map(str::to_string))
+                .collect::<Vec<_>>();
+            (names("color_schemes", "(default)"), names("padsets", "(none)"))
+        };
+
+        let grid = form_grid();
+        let editor = self.clone();
+        add_row(&grid, "Name", &entry(&board.name, "Board name", move |text| editor.update_board(|board| board.name = text)));
+        let editor = self.clone();
+        add_row(&grid, "Title", &entry(board.title.as_deref().unwrap_or_default(), "Shown in the board header, the name when empty",
+            move |text| editor.update_board(|board| board.title = optional(text))));
+        let editor = self.clone();
+        add_row(&grid, "Icon", &entry(board.icon.as_deref().unwrap_or_default(), "Icon file or icon theme name",
+            move |text| editor.update_board(|board| board.icon = optional(text))));
+        let editor = self.clone();
+        add_row(&grid, "Color scheme", &drop_down(&color_schemes, board.color_scheme.as_deref(),
+            move |name| editor.update_board(|board| board.color_scheme = name)));
+        let editor = self.clone();
+        add_row(&grid, "Pads", &drop_down(&padsets, board.base_pads.as_deref(),
+            move |name| editor.update_board(|board| board.base_pads = name)));
+        let editor = self.clone();
+        add_row(&grid, "Double tap pads", &drop_down(&padsets, board.double_pads.as_deref(),
+            move |name| editor.update_board(|board| board.double_pads = name)));
+        let editor = self.clone();
+        add_row(&grid, "Modifier pads", &text_view(&modifier_pad_lines(&board.modifier_pads), move |text| {
+            match parse_modifier_pad_lines(&text) {
+                Ok(modifier_pads) => {
+                    editor.update_board(|board| board.modifier_pads = modifier_pads);
+                    editor.set_problem("modifier_pads", None);
+                },
+                Err(e) => editor.set_problem("modifier_pads", Some(e)),
+            }
+        }));
+        grid.attach(&hint("Modifier pads, one per line: the held modifiers and the padset shown, e.g. \"Ctrl = code/ctrl\""), 1, grid_rows(&grid), 1, 1);
+        grid
+    }
+
+    fn padset_form(self: &Rc<Self>, padset: &PadSetConfig) -> gtk4::Grid {
+        let grid = form_grid();
+        let editor = self.clone();
+        add_row(&grid, "Name", &entry(&padset.name, "Padset name", move |text| editor.update_padset(|padset| padset.name = text)));
+        if let Some(base) = &padset.base {
+            grid.attach(&hint(&format!("Derived from '{}': edited pads replace the pads of the base, cleared ones hide them", base)), 1, grid_rows(&grid), 1, 1);
+        }
+        grid.attach(&hint(ACTIONS_HINT), 1, grid_rows(&grid), 1, 1);
+
+        let pads = gtk4::Grid::builder().row_spacing(6).column_spacing(6).column_homogeneous(true).build();
+        for (row, ids) in PAD_ROWS.iter().enumerate() {
+            for (column, &id) in ids.iter().enumerate() {
+                let editor = self.clone();
+                let form = pad_form(&padset.pad(id), move |pad| {
+                    let field = format!("pad {}", id);
+                    match pad {
+                        Ok(pad) => {
+                            editor.update_padset(|padset| padset.set_pad(id, pad));
+                            editor.set_problem(&field, None);
+                        },
+                        Err(e) => editor.set_problem(&field, Some(e)),
+                    }
+                });
+                form.set_margin_start(6);
+                form.set_margin_end(6);
+                form.set_margin_bottom(6);
+                let frame = gtk4::Frame::builder().label(format!("Pad {}", id)).child(&form).build();
+                pads.attach(&frame, column as i32, row as i32, 1, 1);
+            }
+        }
+        grid.attach(&pads, 0, grid_rows(&grid), 2, 1);
+        grid
+    }
+
+    fn text_style_form(self: &Rc<Self>, text_style: &TextStyle) -> gtk4::Grid {
+        let grid = form_grid();
+        let preview = gtk4::DrawingArea::builder().content_height(240).hexpand(true).build();
+        let editor = self.clone();
+        preview.set_draw_func(move |_, ctx, width, height| {
+            let Some(index) = editor.current.get() else { return };
+            if let Component::TextStyle(text_style) = &editor.edits.borrow()[index].component {
+                let color_scheme = editor.settings.borrow().get_color_scheme(&ColorScheme::default().name).cloned().unwrap_or_default();
+                let board = scheme_designer::sample_board(&color_scheme, text_style);
+                let (width, height) = (width as f64, height as f64);
+                renderer::draw_background(ctx, &color_scheme, color_scheme.opacity(), Rect::new(0.0, 0.0, width, height));
+                renderer::draw_board(ctx, &board, &BoardLayout::new(width, height), &editor.resources, None, None, &ModifierState::default());
+            }
+        });
+
+        let editor = self.clone();
+        add_row(&grid, "Name", &entry(&text_style.name, "Text style name", move |text| editor.update_text_style(|style| style.name = text)));
+        for (label, font, set_font) in TEXT_STYLE_FONTS {
+            let button = gtk4::FontButton::with_font(font(text_style));
+            button.set_use_font(true);
+            let (editor, preview) = (self.clone(), preview.clone());
+            button.connect_font_set(move |button| {
+                let font = button.font().map(|font| font.to_string()).unwrap_or_default();
+                editor.update_text_style(|style| set_font(style, font));
+                preview.queue_draw();
+            });
+            add_row(&grid, label, &button);
+        }
+
+        let shrink = gtk4::CheckButton::with_label("Shrink pad headers and texts that do not fit");
+        shrink.set_active(text_style.shrink_to_fit);
+        let (editor, shrink_preview) = (self.clone(), preview.clone());
+        shrink.connect_toggled(move |shrink| {
+            editor.update_text_style(|style| style.shrink_to_fit = shrink.is_active());
+            shrink_preview.queue_draw();
+        });
+        add_row(&grid, "Shrink to fit", &shrink);
+
+        let layer_hints = gtk4::CheckButton::with_label("Mark the modifier pads and double tap pads of boards after their title");
+        layer_hints.set_active(text_style.layer_hints);
+        let editor = self.clone();
+        layer_hints.connect_toggled(move |layer_hints| editor.update_text_style(|style| style.layer_hints = layer_hints.is_active()));
+        add_row(&grid, "Layer hints", &layer_hints);
+
+        let countdown_styles: Vec<&str> = COUNTDOWN_STYLES.iter().map(|(name, _)| *name).collect();
+        let countdown = gtk4::DropDown::from_strings(&countdown_styles);
+        countdown.set_selected(COUNTDOWN_STYLES.iter().position(|(_, style)| *style == text_style.countdown).unwrap_or_default() as u32);
+        let editor = self.clone();
+        countdown.connect_selected_notify(move |countdown| {
+            let style = COUNTDOWN_STYLES.get(countdown.selected() as usize).map(|(_, style)| *style).unwrap_or_default();
+            editor.update_text_style(|text_style| text_style.countdown = style);
+        });
+        add_row(&grid, "Countdown", &countdown);
+        add_row(&grid, "Preview", &preview);
+        grid.attach(&hint("Drawn with the default color scheme. Settings not shown here (icons, SVG style) are kept"), 1, grid_rows(&grid), 1, 1);
+        grid
+    }
+
+    fn color_scheme_form(self: &Rc<Self>, color_scheme: &ColorScheme) -> gtk4::Grid {
+        let grid = form_grid();
+        let preview = gtk4::DrawingArea::builder().content_height(60).hexpand(true).build();
+        let editor = self.clone();
+        preview.set_draw_func(move |_, ctx, width, height| {
+            let Some(index) = editor.current.get() else { return };
+            if let Component::ColorScheme(color_scheme) = &editor.edits.borrow()[index].component {
+                renderer::draw_background(ctx, color_scheme, color_scheme.opacity(), Rect::new(0.0, 0.0, width as f64, height as f64));
+            }
+        });
+
+        let color = |apply: fn(&mut ColorScheme, String)| {
+            let (editor, preview) = (self.clone(), preview.clone());
+            move |text: String| {
+                editor.update_color_scheme(|color_scheme| apply(color_scheme, text));
+                preview.queue_draw();
+            }
+        };
+        let editor = self.clone();
+        add_row(&grid, "Name", &entry(&color_scheme.name, "Color scheme name", move |text| editor.update_color_scheme(|scheme| scheme.name = text)));
+        add_row(&grid, "Base", &entry(color_scheme.base.as_deref().unwrap_or_default(), "Color scheme the colors not set here come from",
+            color(|scheme, text| scheme.base = optional(text))));
+        add_row(&grid, "Background", &entry(&color_scheme.background, "#RRGGBB", color(|scheme, text| scheme.background = text)));
+        add_row(&grid, "Background 2", &entry(&color_scheme.background2, "Gradient end color, e.g. #RRGGBB", color(|scheme, text| scheme.background2 = text)));
+        add_row(&grid, "Lines", &entry(&color_scheme.foreground1, "#RRGGBB", color(|scheme, text| scheme.foreground1 = text)));
+        add_row(&grid, "Text", &entry(&color_scheme.foreground2, "#RRGGBB", color(|scheme, text| scheme.foreground2 = text)));
+
+        let gradients: Vec<String> = GRADIENTS.iter().map(|(name, _)| name.to_string()).collect();
+        let gradient = gtk4::DropDown::from_strings(&gradients.iter().map(String::as_str).collect::<Vec<_>>());
+        gradient.set_selected(GRADIENTS.iter().position(|(_, direction)| *direction == color_scheme.gradient).unwrap_or_default() as u32);
+        let (editor, gradient_preview) = (self.clone(), preview.clone());
+        gradient.connect_selected_notify(move |gradient| {
+            let direction = GRADIENTS.get(gradient.selected() as usize).map(|(_, direction)| *direction).unwrap_or_default();
+            editor.update_color_scheme(|scheme| scheme.gradient = direction);
+            gradient_preview.queue_draw();
+        });
+        add_row(&grid, "Gradient", &gradient);
+
+        let opacity = gtk4::SpinButton::with_range(0.0, 1.0, 0.05);
+        opacity.set_value(color_scheme.opacity());
+        let (editor, opacity_preview) = (self.clone(), preview.clone());
+        opacity.connect_value_changed(move |opacity| {
+            editor.update_color_scheme(|scheme| scheme.opacity = Some(opacity.value()));
+            opacity_preview.queue_draw();
+        });
+        add_row(&grid, "Opacity", &opacity);
+        add_row(&grid, "Preview", &preview);
+        grid
+    }
+}
+
+/// Pad of a padset edited from a board (edit mode), saved into the file the padset was loaded from
+#[derive(Clone, Debug)]
+pub struct PadEdit {
+    file: PathBuf,
+    padset: PadSetConfig,
+    id: usize,
+}
+
+impl PadEdit {
+    /// Pad `id` (1-9) of the padset as written in its file, before includes and variables are resolved
+    pub fn open(settings: &AppSettings, padset_name: &str, id: usize) -> Result<Self> {
+        let file = settings.component_file("padsets", padset_name);
+        let padset = config::read_component(&file, "padsets", padset_name)?
+            .ok_or_else(|| anyhow::anyhow!("Padset '{}' not found in {:?}", padset_name, file))?;
+        Ok(Self { file, padset, id })
+    }
+
+    /// Pad `id` as the padset shows it, from the (written) base padsets where a derived padset has no override
+    pub fn shown_pad(&self, settings: &AppSettings, id: usize) -> Result<PadConfig> {
+        let mut padset = self.padset.clone();
+        for _ in 0..=settings.padset_configs.len() {
+            match padset.base.clone().filter(|_| !padset.overrides.contains_key(&id)) {
+                Some(base) => padset = Self::open(settings, &base, id)?.padset,
+                None => return Ok(padset.pad(id)),
+            }
+        }
+        anyhow::bail!("Padset '{}' has a cyclic base", self.padset.name)
+    }
+
+    /// Swap the pad with pad `other` and write the padset into its file. Derived padsets get
+    /// overrides for both pads, empty ones included, so the pads of the base swap places too
+    pub fn swap(&self, settings: &AppSettings, other: usize) -> Result<()> {
+        let (pad, other_pad) = (self.shown_pad(settings, self.id)?, self.shown_pad(settings, other)?);
+        let mut padset = self.padset.clone();
+        padset.set_pad(self.id, other_pad);
+        padset.set_pad(other, pad);
+        config::write_component(&self.file, "padsets", &self.padset.name, &padset)?;
+        log::info!("Swapped pads {} and {} of padset '{}' in {:?}", self.id, other, self.padset.name, self.file);
+        Ok(())
+    }
+
+    fn edit(&self, pad: &PadConfig) -> Edit {
+        let mut padset = self.padset.clone();
+        padset.set_pad(self.id, pad.clone());
+        Edit {
+            file: self.file.clone(),
+            previous_name: Some(self.padset.name.clone()),
+            component: Component::Padset(padset),
+            changed: true,
+            problems: BTreeMap::new(),
+        }
+    }
+
+    /// Errors of the settings with the pad replaced when the pad introduces errors, none when it can be saved,
+    /// and a warning when saving loses comments or anchors of the file
+    pub fn check(&self, settings: &AppSettings, resources: &Resources, pad: &PadConfig) -> Vec<String> {
+        let mut messages = match check(settings, resources, &[self.edit(pad)]) {
+            (messages, true) => messages.into_iter().filter(|message| message.starts_with("error")).collect(),
+            (_, false) => Vec::new(),
+        };
+        messages.extend(rewrite_warning(&self.file));
+        messages
+    }
+
+    /// Write the padset with the pad replaced into its file
+    pub fn save(&self, pad: &PadConfig) -> Result<()> {
+        let edit = self.edit(pad);
+        edit.component.write(&edit.file, &self.padset.name)?;
+        log::info!("Saved pad {} of padset '{}' into {:?}", self.id, self.padset.name, self.file);
+        Ok(())
+    }
+}
+
+/// Modal dialog over the window editing a pad, resolves to the edited pad or `None` when cancelled.
+/// Saving is blocked while `check` returns errors for the edited pad, warnings are only shown
+pub async fn pad_dialog(parent: &impl IsA<gtk4::Window>, title: &str, pad: &PadConfig, check: impl Fn(&PadConfig) -> Vec<String> + 'static) -> Option<PadConfig> {
+    let dialog = gtk4::Window::builder()
+        .title(title)
+        .transient_for(parent)
+        .modal(true)
+        .default_width(520)
+        .build();
+
+    let status = gtk4::Label::builder().xalign(0.0).wrap(true).selectable(true).build();
+    let save = gtk4::Button::with_label("Save");
+    save.add_css_class("suggested-action");
+    let cancel = gtk4::Button::with_label("Cancel");
+
+    let edited = Rc::new(RefCell::new(pad.clone()));
+    let (form_edited, form_status, form_save) = (edited.clone(), status.clone(), save.clone());
+    let form = pad_form(pad, move |pad| {
+        let problems = match &pad {
+            Ok(pad) => check(pad),
+            Err(e) => vec![format!("error: actions: {}", e)],
+        };
+        form_status.set_label(&problems.join("\n"));
+        form_save.set_sensitive(!problems.iter().any(|problem| problem.starts_with("error")));
+        if let Ok(pad) = pad {
+            *form_edited.borrow_mut() = pad;
+        }
+    });
+
+    let buttons = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    buttons.set_halign(gtk4::Align::End);
+    buttons.append(&cancel);
+    buttons.append(&save);
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 8);
+    content.set_margin_top(12);
+    content.set_margin_bottom(12);
+    content.set_margin_start(12);
+    content.set_margin_end(12);
+    content.append(&hint(ACTIONS_HINT));
+    content.append(&form);
+    content.append(&status);
+    content.append(&buttons);
+    dialog.set_child(Some(&content));
+
+    // The first answer wins, closing the dialog after saving answers `None` too late
+    let (sender, receiver) = async_channel::bounded(1);
+    let (save_sender, save_dialog) = (sender.clone(), dialog.clone());
+    save.connect_clicked(move |_| {
+        let _ = save_sender.try_send(Some(edited.borrow().clone()));
+        save_dialog.close();
+    });
+    let cancel_dialog = dialog.clone();
+    cancel.connect_clicked(move |_| cancel_dialog.close());
+    dialog.connect_close_request(move |_| {
+        let _ = sender.try_send(None);
+        glib::Propagation::Proceed
+    });
+
+    dialog.present();
+    receiver.recv().await.ok().flatten()
+}
+
+/// Font field of a text style: label, the font of the style and how to set it
+type FontField = (&'static str, fn(&TextStyle) -> &str, fn(&mut TextStyle, String));
+
+const TEXT_STYLE_FONTS: [FontField; 4] = [
+    ("Board title", |style| &style.header_font, |style, font| style.header_font = font),
+    ("Pad header", |style| &style.pad_header_font, |style, font| style.pad_header_font = font),
+    ("Pad text", |style| &style.pad_text_font, |style, font| style.pad_text_font = font),
+    ("Pad number", |style| &style.pad_id_font, |style, font| style.pad_id_font = font),
+];
+
+const COUNTDOWN_STYLES: [(&str, CountdownStyle); 3] = [
+    ("Dots", CountdownStyle::Dots),
+    ("Ring", CountdownStyle::Ring),
+    ("Bar", CountdownStyle::Bar),
+];
+
+/// Open the settings window of the application
+pub fn show(app: &gtk4::Application, resources: Resources, settings: AppSettings) {
+    let resources = resources.with_icon_theme(renderer::has_theme_icon);
+    let window = gtk4::ApplicationWindow::builder()
+        .application(app)
+        .title(TITLE)
+        .default_width(1100)
+        .default_height(760)
+        .build();
+
+    let header = gtk4::HeaderBar::new();
+    let save = gtk4::Button::with_label("Save");
+    save.add_css_class("suggested-action");
+    save.set_sensitive(false);
+    header.pack_end(&save);
+    window.set_titlebar(Some(&header));
+
+    let list = gtk4::ListBox::new();
+    let buttons = gtk4::FlowBox::builder().selection_mode(gtk4::SelectionMode::None).column_spacing(6).row_spacing(6).build();
+    let sidebar = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    sidebar.append(&gtk4::ScrolledWindow::builder().child(&list).vexpand(true).min_content_width(240).build());
+    sidebar.append(&buttons);
+
+    let form = gtk4::ScrolledWindow::builder().vexpand(true).hexpand(true).build();
+    let status = gtk4::Label::builder().xalign(0.0).yalign(0.0).wrap(true).selectable(true)
+        .margin_top(6).margin_bottom(6).margin_start(12).margin_end(12).build();
+    let content = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+    content.append(&form);
+    content.append(&gtk4::Separator::new(gtk4::Orientation::Horizontal));
+    content.append(&gtk4::ScrolledWindow::builder().child(&status).min_content_height(110).build());
+
+    let paned = gtk4::Paned::new(gtk4::Orientation::Horizontal);
+    paned.set_start_child(Some(&sidebar));
+    paned.set_end_child(Some(&content));
+    paned.set_position(260);
+    window.set_child(Some(&paned));
+
+    let editor = Rc::new(Editor {
+        resources,
+        settings: RefCell::new(settings),
+        edits: RefCell::new(Vec::new()),
+        current: Cell::new(None),
+        entries: RefCell::new(Vec::new()),
+        filling: Cell::new(false),
+        window: window.clone(),
+        list: list.clone(),
+        form,
+        status,
+        save: save.clone(),
+    });
+
+    for (collection, label) in [("boards", "New board"), ("padsets", "New padset"), ("color_schemes", "New scheme"), ("text_styles", "New style")] {
+        let button = gtk4::Button::with_label(label);
+        let editor = editor.clone();
+        button.connect_clicked(move |_| editor.add(collection));
+        buttons.insert(&button, -1);
+    }
+    let design = gtk4::Button::with_label("Design scheme");
+    design.set_tooltip_text(Some("Pick the colors of a new color scheme on a preview board"));
+    let design_editor = editor.clone();
+    design.connect_clicked(move |_| design_editor.design_color_scheme());
+    buttons.insert(&design, -1);
+    let list_editor = editor.clone();
+    list.connect_row_selected(move |_, row| {
+        if let Some(row) = row.filter(|_| !list_editor.filling.get()) {
+            list_editor.open(row.index() as usize);
+        }
+    });
+    let save_editor = editor.clone();
+    save.connect_clicked(move |_| save_editor.save_changes());
+
+    editor.fill_list(None);
+    editor.refresh_status();
+    window.present();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::tests::{json, test_settings};
+
+    #[test]
+    fn test_action_lines() {
+        let actions = vec![
+            Action::Shortcut("Ctrl Shift T".to_string()),
+            Action::Line("cd ~/projects".to_string()),
+            Action::Pause(200),
+        ];
+        let text = action_lines(&actions);
+        assert_eq!(text, "Ctrl Shift T\n{\"Line\":\"cd ~/projects\"}\n{\"Pause\":200}");
+        assert_eq!(format!("{:?}", parse_action_lines(&format!("{}\n\n", text)).unwrap()), format!("{:?}", actions));
+        assert!(parse_action_lines("Ctrl C\n{\"Line\": ").unwrap_err().starts_with("line 2:"));
+
+        assert_eq!(parse_modifier_pad_lines("Ctrl = code/ctrl\n\n").unwrap(), HashMap::from([("Ctrl".to_string(), "code/ctrl".to_string())]));
+        assert!(parse_modifier_pad_lines("Ctrl code").is_err());
+    }
+
+    #[test]
+    fn test_pad_edit() {
+        let dir = std::env::temp_dir().join(format!("hotkeys-pad-edit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("settings.json");
+        std::fs::write(&file, serde_json::to_string(&test_settings(json!({
+            "boards": [{ "name": "code", "base_pads": "code" }],
+            "padsets": [{ "name": "code", "items": [{ "header": "Copy" }] }],
+            "profiles": [{ "name": "default", "boards": ["code"], "default": "code" }]
+        }))).unwrap()).unwrap();
+        let resources = Resources::new(vec![dir.clone()]);
+        let settings = config::load_settings_unvalidated(&resources).unwrap();
+
+        let edit = PadEdit::open(&settings, "code", 2).unwrap();
+        assert!(edit.shown_pad(&settings, 2).unwrap().header.is_empty());
+        assert!(PadEdit::open(&settings, "missing", 2).is_err());
+
+        let pad = PadConfig { header: "Paste".to_string(), ..Default::default() };
+        assert!(edit.check(&settings, &resources, &pad).is_empty());
+        let broken = PadConfig { board: Some("missing".to_string()), ..pad.clone() };
+        assert!(!edit.check(&settings, &resources, &broken).is_empty());
+
+        edit.save(&pad).unwrap();
+        let headers = |name: &str| {
+            let padset: PadSetConfig = config::read_component(&file, "padsets", name).unwrap().unwrap();
+            (1..=9).map(|id| padset.pad(id).header).collect::<Vec<_>>().join(",")
+        };
+        assert_eq!(headers("code"), "Copy,Paste,,,,,,,");
+
+        PadEdit::open(&settings, "code", 2).unwrap().swap(&settings, 3).unwrap();
+        assert_eq!(headers("code"), "Copy,,Paste,,,,,,");
+
+        // The pads of derived padsets swap with the pads they show from their base
+        let settings = settings.with_padset(None, PadSetConfig { name: "code/ctrl".to_string(), base: Some("code".to_string()), ..Default::default() });
+        config::write_component(&file, "padsets", "code/ctrl", &settings.padset_configs[1]).unwrap();
+        PadEdit::open(&settings, "code/ctrl", 1).unwrap().swap(&settings, 9).unwrap();
+        assert_eq!(headers("code/ctrl"), ",,,,,,,,Copy");
+        assert_eq!(headers("code"), "Copy,,Paste,,,,,,");
+
+        // Editing a pad a derived padset shows from its base starts from the base pad, saving it unchanged keeps it
+        let edit = PadEdit::open(&settings, "code/ctrl", 3).unwrap();
+        let pad = edit.shown_pad(&settings, 3).unwrap();
+        assert_eq!(pad.header, "Paste");
+        edit.save(&pad).unwrap();
+        assert_eq!(headers("code/ctrl"), ",,Paste,,,,,,Copy");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_text_style_fonts() {
+        let mut text_style = TextStyle::default();
+        for (label, font, set_font) in TEXT_STYLE_FONTS {
+            set_font(&mut text_style, format!("{} 12", label));
+            assert_eq!(font(&text_style), format!("{} 12", label));
+        }
+        assert_eq!(text_style.header_font, "Board title 12");
+        assert_eq!(text_style.pad_id_font, "Pad number 12");
+
+        let settings = test_settings(json!({}));
+        let settings = settings.with_text_style(None, text_style);
+        assert_eq!(component_names(&settings, "text_styles"), vec![TextStyle::default().name]);
+    }
+
+    #[test]
+    fn test_check_edits() {
+        let settings = test_settings(json!({
+            "boards": [{ "name": "code", "base_pads": "code" }, { "name": "term", "base_pads": "code" }],
+            "padsets": [{ "name": "code", "items": [] }],
+            "profiles": [{ "name": "default", "boards": ["code", "term"], "default": "code" }]
+        }));
+        let resources = Resources::new(vec![]);
+        let board = |json: &str| Component::Board(serde_json::from_str(json).unwrap());
+        let edit = |previous_name: Option<&str>, component| Edit {
+            file: PathBuf::from("settings.json"),
+            previous_name: previous_name.map(str::to_string),
+            component,
+            changed: true,
+            problems: BTreeMap::new(),
+        };
+
+        assert_eq!(check(&settings, &resources, &[]), (vec![], false));
+
+        // Renaming a board onto another one, and a broken padset reference
+        let (messages, has_errors) = check(&settings, &resources, &[edit(Some("code"), board(r#"{ "name": "term", "base_pads": "code" }"#))]);
+        assert!(has_errors);
+        assert!(messages[0].contains("boards 'term': The name is already used"), "{:?}", messages);
+
+        let (messages, has_errors) = check(&settings, &resources, &[edit(None, board(r#"{ "name": "git", "base_pads": "git" }"#))]);
+        assert!(has_errors);
+        assert!(messages.iter().any(|message| message.contains("'git'")), "{:?}", messages);
+
+        let mut invalid = edit(Some("code"), board(r#"{ "name": "code", "base_pads": "code" }"#));
+        invalid.problems.insert("modifier_pads".to_string(), "line 1: expected".to_string());
+        let (messages, has_errors) = check(&settings, &resources, &[invalid]);
+        assert!(has_errors);
+        assert_eq!(messages[0], "error: boards 'code': modifier_pads: line 1: expected");
+
+        // Errors the settings had before do not prevent saving
+        let broken = settings.clone().with_board(Some("term"), serde_json::from_str(r#"{ "name": "term", "base_pads": "gone" }"#).unwrap());
+        let (messages, has_errors) = check(&broken, &resources, &[edit(Some("code"), board(r#"{ "name": "code", "title": "Code" }"#))]);
+        assert!(!has_errors);
+        assert!(messages.iter().any(|message| message.contains("'gone'")), "{:?}", messages);
+    }
+}